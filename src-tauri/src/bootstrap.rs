@@ -1,14 +1,26 @@
+use crate::constants::PRICE_CHANGE_CHECK_INTERVAL_SECS;
 use crate::infrastructure::sqlite::Database;
 use crate::modules::accounts_receivable::adapters::inbound::tauri::*;
 use crate::modules::cash_register::adapters::inbound::tauri::*;
+use crate::modules::cash_register::adapters::outbound::sqlite::SqliteCashRegisterRepository;
+use crate::modules::cash_register::application::handle_stale_sessions;
 use crate::modules::catalog::categories::adapters::inbound::tauri::*;
 use crate::modules::catalog::products::adapters::inbound::tauri::*;
+use crate::modules::catalog::products::adapters::outbound::sqlite::SqliteProductRepository;
+use crate::modules::catalog::products::repricing::apply_due_price_changes;
+use crate::modules::catalog::units::adapters::inbound::tauri::*;
 use crate::modules::identity::adapters::inbound::auth_tauri::*;
 use crate::modules::identity::adapters::inbound::users_tauri::*;
 use crate::modules::inventory::adapters::inbound::tauri::*;
+use crate::modules::loyalty::adapters::inbound::tauri::*;
+use crate::modules::pricing::adapters::inbound::tauri::*;
 use crate::modules::printing::adapters::inbound::tauri::*;
+use crate::modules::registers::adapters::inbound::tauri::*;
 use crate::modules::sales::adapters::inbound::tauri::*;
 use crate::modules::settings::adapters::inbound::tauri::*;
+use crate::modules::stored_value::adapters::inbound::tauri::*;
+use std::thread;
+use std::time::Duration;
 use tauri::Manager;
 
 pub fn run() {
@@ -23,7 +35,30 @@ pub fn run() {
                     error
                 })
                 .expect("Failed to initialize database");
+            match handle_stale_sessions(&SqliteCashRegisterRepository::new(&db)) {
+                Ok(check) => {
+                    for session in &check.sessions {
+                        eprintln!(
+                            "Cash register session {} open since {}",
+                            session.id, session.opened_at
+                        );
+                    }
+                    for session in &check.auto_closed {
+                        eprintln!(
+                            "Cash register session {} auto-closed with its count pending",
+                            session.id
+                        );
+                    }
+                }
+                Err(error) => eprintln!("Failed to check stale cash sessions: {error}"),
+            }
+            apply_scheduled_prices(&db);
             app.manage(db);
+            let handle = app.handle().clone();
+            thread::spawn(move || loop {
+                thread::sleep(Duration::from_secs(PRICE_CHANGE_CHECK_INTERVAL_SECS));
+                apply_scheduled_prices(&handle.state::<Database>());
+            });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -39,19 +74,45 @@ pub fn run() {
             create_category,
             update_category,
             delete_category,
+            get_units,
+            create_unit,
+            update_unit,
+            delete_unit,
+            move_category,
+            merge_categories,
             get_products,
             get_active_products,
             get_product,
             find_product_by_barcode,
             search_products,
+            search_products_grouped,
             create_product,
             update_product,
             delete_product,
+            get_product_codes,
+            add_product_code,
+            delete_product_code,
+            get_product_attributes,
+            get_product_variants,
+            generate_product_variants,
+            get_kit_components,
+            set_kit_components,
+            get_product_price_history,
+            schedule_price_changes,
+            get_scheduled_price_changes,
+            cancel_scheduled_price_change,
+            preview_bulk_reprice,
+            apply_bulk_reprice,
+            import_products,
+            export_products,
             get_printer_config,
             save_printer_config,
             detect_usb_printers,
             test_printer,
             print_sale_ticket,
+            print_cash_movement_voucher,
+            print_cash_register_report,
+            print_cash_handover_report,
             get_cash_register_sessions,
             get_cash_register_sessions_by_date_range,
             get_cash_register_session,
@@ -59,7 +120,26 @@ pub fn run() {
             get_open_cash_register_by_user,
             open_cash_register,
             close_cash_register,
+            submit_blind_cash_register_close,
+            approve_cash_register_close,
+            get_pending_cash_register_reviews,
+            get_stale_cash_register_sessions,
+            reopen_cash_register_session,
+            get_cash_session_reopenings,
+            get_cash_over_short_report,
+            record_cash_shortage,
+            handover_cash_register,
+            get_cash_handovers,
             get_cash_register_summary,
+            get_cash_movements,
+            record_cash_movement,
+            get_cash_denominations,
+            generate_cash_register_report,
+            get_cash_register_reports,
+            get_registers,
+            get_register,
+            create_register,
+            update_register,
             get_customers,
             get_active_customers,
             get_customer,
@@ -67,14 +147,32 @@ pub fn run() {
             update_customer,
             get_customer_movements,
             register_customer_payment,
+            get_price_lists,
+            get_price_list,
+            create_price_list,
+            update_price_list,
+            delete_price_list,
+            assign_customer_price_list,
+            get_customer_points,
+            expire_loyalty_points,
+            get_loyalty_category_rules,
+            set_loyalty_category_rule,
+            find_gift_card,
+            get_customer_store_credit,
+            get_stored_value_movements,
+            issue_gift_card,
             create_sale,
             get_sale,
             get_sales,
             get_sales_by_session,
             get_sales_by_date_range,
+            search_sales,
             get_sales_report,
             get_top_products,
+            get_sales_by_category,
             cancel_sale,
+            amend_sale,
+            get_sale_revisions,
             get_inventory_adjustments,
             get_inventory_adjustments_by_date_range,
             get_inventory_adjustments_by_product,
@@ -89,3 +187,23 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+fn apply_scheduled_prices(db: &Database) {
+    match apply_due_price_changes(&SqliteProductRepository::new(db)) {
+        Ok(outcome) => {
+            for change in &outcome.applied {
+                eprintln!(
+                    "Scheduled price {:.2} applied to product {}",
+                    change.new_price, change.product_id
+                );
+            }
+            for (change, error) in &outcome.failed {
+                eprintln!(
+                    "Scheduled price change {} for product {} cancelled: {error}",
+                    change.id, change.product_id
+                );
+            }
+        }
+        Err(error) => eprintln!("Failed to apply scheduled price changes: {error}"),
+    }
+}
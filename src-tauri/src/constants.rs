@@ -6,3 +6,6 @@ pub const MAX_PAGE_SIZE: i64 = 200;
 
 /// Maximum number of products returned by a POS search query.
 pub const SEARCH_RESULT_LIMIT: i64 = 20;
+
+/// How often the app checks for scheduled price changes that became due.
+pub const PRICE_CHANGE_CHECK_INTERVAL_SECS: u64 = 60;
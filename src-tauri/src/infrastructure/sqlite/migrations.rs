@@ -31,6 +31,98 @@ const MIGRATIONS: &[Migration] = &[
         version: 6,
         sql: include_str!("migrations/0006_customer_movement_session_required.sql"),
     },
+    Migration {
+        version: 7,
+        sql: include_str!("migrations/0007_price_lists.sql"),
+    },
+    Migration {
+        version: 8,
+        sql: include_str!("migrations/0008_loyalty_points.sql"),
+    },
+    Migration {
+        version: 9,
+        sql: include_str!("migrations/0009_stored_value.sql"),
+    },
+    Migration {
+        version: 10,
+        sql: include_str!("migrations/0010_sale_revisions.sql"),
+    },
+    Migration {
+        version: 11,
+        sql: include_str!("migrations/0011_cash_movements.sql"),
+    },
+    Migration {
+        version: 12,
+        sql: include_str!("migrations/0012_cash_denomination_counts.sql"),
+    },
+    Migration {
+        version: 13,
+        sql: include_str!("migrations/0013_cash_register_reports.sql"),
+    },
+    Migration {
+        version: 14,
+        sql: include_str!("migrations/0014_registers.sql"),
+    },
+    Migration {
+        version: 15,
+        sql: include_str!("migrations/0015_blind_close_review.sql"),
+    },
+    Migration {
+        version: 16,
+        sql: include_str!("migrations/0016_cash_handovers.sql"),
+    },
+    Migration {
+        version: 17,
+        sql: include_str!("migrations/0017_session_close_snapshot.sql"),
+    },
+    Migration {
+        version: 18,
+        sql: include_str!("migrations/0018_cash_session_reopenings.sql"),
+    },
+    Migration {
+        version: 19,
+        sql: include_str!("migrations/0019_cash_shortages.sql"),
+    },
+    Migration {
+        version: 20,
+        sql: include_str!("migrations/0020_stale_sessions.sql"),
+    },
+    Migration {
+        version: 21,
+        sql: include_str!("migrations/0021_product_codes.sql"),
+    },
+    Migration {
+        version: 22,
+        sql: include_str!("migrations/0022_product_variants.sql"),
+    },
+    Migration {
+        version: 23,
+        sql: include_str!("migrations/0023_product_cost.sql"),
+    },
+    Migration {
+        version: 24,
+        sql: include_str!("migrations/0024_scheduled_price_changes.sql"),
+    },
+    Migration {
+        version: 25,
+        sql: include_str!("migrations/0025_product_search.sql"),
+    },
+    Migration {
+        version: 26,
+        sql: include_str!("migrations/0026_category_hierarchy.sql"),
+    },
+    Migration {
+        version: 27,
+        sql: include_str!("migrations/0027_product_kits.sql"),
+    },
+    Migration {
+        version: 28,
+        sql: include_str!("migrations/0028_units.sql"),
+    },
+    Migration {
+        version: 29,
+        sql: include_str!("migrations/0029_sale_item_catalog_price.sql"),
+    },
 ];
 
 pub fn initialize(db: &Database) -> Result<(), String> {
@@ -86,7 +178,25 @@ fn apply(conn: &Connection) -> Result<(), String> {
     Ok(())
 }
 
+/// Foreign keys are switched off while a migration runs so parent tables can be
+/// rebuilt; the pragma is ignored inside a transaction, so it wraps the whole step.
 fn apply_migration(conn: &Connection, migration: &Migration, checksum: &str) -> Result<(), String> {
+    let foreign_keys: bool = conn
+        .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+        .map_err(|error| format!("No se pudo consultar las llaves foráneas: {error}"))?;
+    conn.execute_batch("PRAGMA foreign_keys = OFF")
+        .map_err(|error| format!("No se pudieron desactivar las llaves foráneas: {error}"))?;
+
+    let result = run_migration(conn, migration, checksum);
+
+    if foreign_keys {
+        conn.execute_batch("PRAGMA foreign_keys = ON")
+            .map_err(|error| format!("No se pudieron reactivar las llaves foráneas: {error}"))?;
+    }
+    result
+}
+
+fn run_migration(conn: &Connection, migration: &Migration, checksum: &str) -> Result<(), String> {
     conn.execute_batch("BEGIN IMMEDIATE").map_err(|error| {
         format!(
             "No se pudo iniciar la migración {:04}: {error}",
@@ -95,12 +205,19 @@ fn apply_migration(conn: &Connection, migration: &Migration, checksum: &str) ->
     })?;
 
     let result = (|| {
+        let violations_before = foreign_key_violations(conn, migration)?;
         conn.execute_batch(migration.sql).map_err(|error| {
             format!(
                 "Falló la migración {:04}; no se registrará como aplicada: {error}",
                 migration.version
             )
         })?;
+        if foreign_key_violations(conn, migration)? > violations_before {
+            return Err(format!(
+                "La migración {:04} dejó referencias inválidas; no se registrará como aplicada",
+                migration.version
+            ));
+        }
         conn.execute(
             "INSERT INTO schema_migrations (version, checksum) VALUES (?1, ?2)",
             (migration.version, checksum),
@@ -128,6 +245,18 @@ fn apply_migration(conn: &Connection, migration: &Migration, checksum: &str) ->
     }
 }
 
+fn foreign_key_violations(conn: &Connection, migration: &Migration) -> Result<i64, String> {
+    conn.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| {
+        row.get(0)
+    })
+    .map_err(|error| {
+        format!(
+            "No se pudo verificar la migración {:04}: {error}",
+            migration.version
+        )
+    })
+}
+
 fn register_legacy_baseline(conn: &Connection, version: i64) -> Result<(), String> {
     conn.execute_batch("BEGIN IMMEDIATE")
         .map_err(|error| format!("No se pudo iniciar el registro de la base existente: {error}"))?;
@@ -228,7 +357,103 @@ fn detect_legacy_version(conn: &Connection) -> Result<i64, String> {
         return Ok(5);
     }
 
-    Ok(6)
+    if !table_exists(conn, "price_lists")? {
+        return Ok(6);
+    }
+
+    if !table_exists(conn, "customer_points_movements")? {
+        return Ok(7);
+    }
+
+    if !table_exists(conn, "stored_value_movements")? {
+        return Ok(8);
+    }
+
+    if !table_exists(conn, "sale_revisions")? {
+        return Ok(9);
+    }
+
+    if !table_exists(conn, "cash_movements")? {
+        return Ok(10);
+    }
+
+    if !table_exists(conn, "cash_denomination_counts")? {
+        return Ok(11);
+    }
+
+    if !table_exists(conn, "cash_register_reports")? {
+        return Ok(12);
+    }
+
+    if !table_exists(conn, "registers")? {
+        return Ok(13);
+    }
+
+    if !has_columns(
+        conn,
+        "cash_register_sessions",
+        ["reviewed_by", "review_notes", "reviewed_at"],
+    )? {
+        return Ok(14);
+    }
+
+    if !table_exists(conn, "cash_handovers")? {
+        return Ok(15);
+    }
+
+    if !has_columns(conn, "cash_register_sessions", ["closing_summary"])? {
+        return Ok(16);
+    }
+
+    if !table_exists(conn, "cash_session_reopenings")? {
+        return Ok(17);
+    }
+
+    if !table_exists(conn, "cash_shortages")? {
+        return Ok(18);
+    }
+
+    if !has_columns(conn, "cash_register_sessions", ["count_pending"])? {
+        return Ok(19);
+    }
+
+    if !table_exists(conn, "product_codes")? {
+        return Ok(20);
+    }
+
+    if !table_exists(conn, "product_attributes")? {
+        return Ok(21);
+    }
+
+    if !table_exists(conn, "product_price_history")? {
+        return Ok(22);
+    }
+
+    if !table_exists(conn, "scheduled_price_changes")? {
+        return Ok(23);
+    }
+
+    if !table_exists(conn, "product_search")? {
+        return Ok(24);
+    }
+
+    if !has_columns(conn, "categories", ["parent_id"])? {
+        return Ok(25);
+    }
+
+    if !table_exists(conn, "product_kit_components")? {
+        return Ok(26);
+    }
+
+    if !table_exists(conn, "units")? {
+        return Ok(27);
+    }
+
+    if !has_columns(conn, "sale_items", ["catalog_price"])? {
+        return Ok(28);
+    }
+
+    Ok(29)
 }
 
 fn migration_count(conn: &Connection) -> Result<i64, String> {
@@ -436,6 +661,118 @@ fn seed_default_settings(conn: &Connection) -> Result<(), String> {
             "fiados",
             10,
         ),
+        (
+            "loyalty_enabled",
+            "false",
+            "boolean",
+            "Programa de puntos habilitado",
+            "lealtad",
+            10,
+        ),
+        (
+            "loyalty_points_per_mxn",
+            "0.1",
+            "number",
+            "Puntos ganados por cada peso",
+            "lealtad",
+            20,
+        ),
+        (
+            "loyalty_point_value_mxn",
+            "0.1",
+            "number",
+            "Valor de cada punto en pesos",
+            "lealtad",
+            30,
+        ),
+        (
+            "loyalty_points_expiry_days",
+            "365",
+            "number",
+            "Días de vigencia de los puntos (0 = sin vencimiento)",
+            "lealtad",
+            40,
+        ),
+        (
+            "cash_denominations_mxn",
+            "1000,500,200,100,50,20,10,5,2,1,0.5",
+            "string",
+            "Denominaciones MXN para arqueo (separadas por coma)",
+            "caja",
+            10,
+        ),
+        (
+            "cash_denominations_usd",
+            "100,50,20,10,5,1",
+            "string",
+            "Denominaciones USD para arqueo (separadas por coma)",
+            "caja",
+            20,
+        ),
+        (
+            "cash_blind_close",
+            "false",
+            "boolean",
+            "Cierre ciego (el cajero no ve el efectivo esperado)",
+            "caja",
+            30,
+        ),
+        (
+            "cash_reopen_window_hours",
+            "12",
+            "number",
+            "Horas para reabrir un corte cerrado (0 lo desactiva)",
+            "caja",
+            40,
+        ),
+        (
+            "cash_over_short_tolerance_mxn",
+            "20",
+            "number",
+            "Tolerancia de sobrante/faltante MXN",
+            "caja",
+            50,
+        ),
+        (
+            "cash_over_short_tolerance_usd",
+            "2",
+            "number",
+            "Tolerancia de sobrante/faltante USD",
+            "caja",
+            60,
+        ),
+        (
+            "scale_barcode_formats",
+            "20:5:weight:3,22:5:price:2",
+            "string",
+            "Etiquetas de báscula (prefijo:dígitos PLU:weight|price:decimales, separadas por coma)",
+            "productos",
+            10,
+        ),
+        (
+            "products_below_cost_policy",
+            "warn",
+            "string",
+            "Precio por debajo del costo: warn (pedir confirmación) o refuse (rechazar)",
+            "productos",
+            20,
+        ),
+        (
+            "cash_stale_session_hours",
+            "18",
+            "number",
+            "Horas tras las que una sesión abierta se considera olvidada",
+            "caja",
+            70,
+        ),
+        (
+            "cash_stale_session_policy",
+            "warn",
+            "string",
+            "Sesión olvidada: warn (avisar), block (bloquear ventas) o auto_close (cerrar con arqueo pendiente)",
+            "caja",
+            80,
+        ),
     ];
 
     for (key, value, value_type, label, group_name, sort_order) in DEFAULTS {
@@ -527,4 +864,120 @@ mod tests {
             .unwrap();
         assert_eq!(count, 0);
     }
+
+    #[test]
+    fn z_reports_cannot_be_modified_or_deleted() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (id, username, password_hash, full_name, role)
+                VALUES (1, 'root', 'x', 'Administrador', 'admin');
+            INSERT INTO cash_register_sessions (id, user_id, status) VALUES (1, 1, 'closed');
+            INSERT INTO cash_register_reports
+                (cash_register_session_id, report_type, z_number, user_id, payload)
+                VALUES (1, 'z', 1, 1, '{}');",
+        )
+        .unwrap();
+
+        assert!(conn
+            .execute("UPDATE cash_register_reports SET payload = '[]'", [])
+            .is_err());
+        assert!(conn
+            .execute("DELETE FROM cash_register_reports", [])
+            .is_err());
+    }
+
+    #[test]
+    fn rebuilding_sessions_keeps_rows_that_reference_them() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON;
+            CREATE TABLE schema_migrations (version INTEGER PRIMARY KEY, checksum TEXT NOT NULL);",
+        )
+        .unwrap();
+        for migration in MIGRATIONS.iter().filter(|migration| migration.version < 15) {
+            apply_migration(&conn, migration, &checksum(migration.sql)).unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO users (id, username, password_hash, full_name, role)
+                VALUES (1, 'root', 'x', 'Administrador', 'admin');
+            INSERT INTO cash_register_sessions (id, user_id, register_id, status)
+                VALUES (1, 1, 1, 'closed');
+            INSERT INTO sales (cash_register_session_id, user_id, total, payment_amount)
+                VALUES (1, 1, 10.0, 10.0);",
+        )
+        .unwrap();
+
+        apply(&conn).unwrap();
+
+        let foreign_keys: bool = conn
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert!(foreign_keys);
+        assert!(conn
+            .execute("DELETE FROM cash_register_sessions WHERE id = 1", [])
+            .is_err());
+        conn.execute(
+            "UPDATE cash_register_sessions SET status = 'pending_review' WHERE id = 1",
+            [],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn extra_open_sessions_wait_for_their_count() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE schema_migrations (version INTEGER PRIMARY KEY, checksum TEXT NOT NULL);",
+        )
+        .unwrap();
+        for migration in MIGRATIONS.iter().filter(|migration| migration.version < 17) {
+            apply_migration(&conn, migration, &checksum(migration.sql)).unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO users (id, username, password_hash, full_name, role)
+                VALUES (1, 'root', 'x', 'Administrador', 'admin');
+            INSERT INTO cash_register_sessions (id, user_id, register_id, status)
+                VALUES (1, 1, 1, 'open'), (2, 1, 1, 'open');",
+        )
+        .unwrap();
+
+        apply(&conn).unwrap();
+
+        let sessions: Vec<(String, bool)> = conn
+            .prepare("SELECT status, count_pending FROM cash_register_sessions ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            sessions,
+            vec![
+                ("pending_review".to_string(), true),
+                ("open".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn only_one_session_per_register_can_be_open() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (id, username, password_hash, full_name, role)
+                VALUES (1, 'root', 'x', 'Administrador', 'admin');
+            INSERT INTO registers (id, name) VALUES (2, 'Caja 2');
+            INSERT INTO cash_register_sessions (user_id, register_id, status)
+                VALUES (1, 1, 'open'), (1, 2, 'open'), (1, 1, 'closed');",
+        )
+        .unwrap();
+
+        assert!(conn
+            .execute(
+                "INSERT INTO cash_register_sessions (user_id, register_id, status) VALUES (1, 1, 'open')",
+                [],
+            )
+            .is_err());
+    }
 }
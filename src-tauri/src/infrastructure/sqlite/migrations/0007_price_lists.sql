@@ -0,0 +1,28 @@
+CREATE TABLE price_lists (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE NOT NULL,
+    description TEXT,
+    default_adjustment_percent REAL NOT NULL DEFAULT 0,
+    active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT DEFAULT (datetime('now', 'localtime')),
+    updated_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+CREATE TABLE price_list_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    price_list_id INTEGER NOT NULL REFERENCES price_lists(id) ON DELETE CASCADE,
+    product_id INTEGER REFERENCES products(id) ON DELETE CASCADE,
+    category_id INTEGER REFERENCES categories(id) ON DELETE CASCADE,
+    min_quantity REAL NOT NULL DEFAULT 1 CHECK(min_quantity > 0),
+    fixed_price REAL CHECK(fixed_price IS NULL OR fixed_price >= 0),
+    percent_adjustment REAL,
+    CHECK(product_id IS NULL OR category_id IS NULL),
+    CHECK((fixed_price IS NULL) <> (percent_adjustment IS NULL))
+);
+ALTER TABLE customers ADD COLUMN price_list_id INTEGER REFERENCES price_lists(id) ON DELETE SET NULL;
+ALTER TABLE sale_items ADD COLUMN price_list_id INTEGER REFERENCES price_lists(id);
+CREATE INDEX idx_price_list_rules_list ON price_list_rules(price_list_id);
+CREATE INDEX idx_customers_price_list ON customers(price_list_id);
+INSERT INTO price_lists (name, description) VALUES
+    ('Menudeo', 'Precio de lista del catálogo'),
+    ('Mayoreo', 'Precios para clientes de mayoreo'),
+    ('Distribuidor', 'Precios para distribuidores');
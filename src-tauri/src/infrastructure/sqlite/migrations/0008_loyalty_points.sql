@@ -0,0 +1,21 @@
+CREATE TABLE loyalty_category_rules (
+    category_id INTEGER PRIMARY KEY REFERENCES categories(id) ON DELETE CASCADE,
+    points_per_mxn REAL NOT NULL CHECK(points_per_mxn >= 0)
+);
+CREATE TABLE customer_points_movements (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    customer_id INTEGER NOT NULL REFERENCES customers(id),
+    sale_id INTEGER REFERENCES sales(id),
+    user_id INTEGER REFERENCES users(id),
+    movement_type TEXT NOT NULL CHECK(movement_type IN ('earn', 'redeem', 'expire', 'reversal')),
+    points REAL NOT NULL,
+    amount_mxn REAL NOT NULL DEFAULT 0,
+    expires_at TEXT,
+    notes TEXT,
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+ALTER TABLE sales ADD COLUMN payment_points REAL NOT NULL DEFAULT 0;
+ALTER TABLE sales ADD COLUMN payment_points_mxn REAL NOT NULL DEFAULT 0;
+ALTER TABLE sales ADD COLUMN points_earned REAL NOT NULL DEFAULT 0;
+CREATE INDEX idx_points_movements_customer ON customer_points_movements(customer_id);
+CREATE INDEX idx_points_movements_sale ON customer_points_movements(sale_id);
@@ -0,0 +1,31 @@
+CREATE TABLE stored_value_accounts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL CHECK(kind IN ('gift_card', 'store_credit')),
+    code TEXT UNIQUE COLLATE NOCASE,
+    customer_id INTEGER REFERENCES customers(id),
+    active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT DEFAULT (datetime('now', 'localtime')),
+    updated_at TEXT DEFAULT (datetime('now', 'localtime')),
+    CHECK((kind = 'gift_card' AND code IS NOT NULL) OR (kind = 'store_credit' AND customer_id IS NOT NULL))
+);
+CREATE TABLE stored_value_movements (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES stored_value_accounts(id),
+    sale_id INTEGER REFERENCES sales(id),
+    cash_register_session_id INTEGER NOT NULL REFERENCES cash_register_sessions(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    movement_type TEXT NOT NULL CHECK(movement_type IN ('issue', 'redeem', 'refund', 'reversal')),
+    amount REAL NOT NULL,
+    payment_cash_mxn REAL NOT NULL DEFAULT 0,
+    payment_cash_usd REAL NOT NULL DEFAULT 0,
+    payment_transfer REAL NOT NULL DEFAULT 0,
+    exchange_rate REAL,
+    notes TEXT,
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+ALTER TABLE sales ADD COLUMN payment_stored_value REAL NOT NULL DEFAULT 0;
+ALTER TABLE sales ADD COLUMN stored_value_account_id INTEGER REFERENCES stored_value_accounts(id);
+ALTER TABLE sales ADD COLUMN stored_value_balance REAL;
+CREATE UNIQUE INDEX idx_store_credit_customer ON stored_value_accounts(customer_id) WHERE kind = 'store_credit';
+CREATE INDEX idx_stored_value_movements_account ON stored_value_movements(account_id);
+CREATE INDEX idx_stored_value_movements_session ON stored_value_movements(cash_register_session_id);
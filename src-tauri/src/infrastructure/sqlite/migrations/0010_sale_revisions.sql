@@ -0,0 +1,10 @@
+CREATE TABLE sale_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sale_id INTEGER NOT NULL REFERENCES sales(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    reason TEXT NOT NULL,
+    before_snapshot TEXT NOT NULL,
+    after_snapshot TEXT NOT NULL,
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+CREATE INDEX idx_sale_revisions_sale ON sale_revisions(sale_id);
@@ -0,0 +1,11 @@
+CREATE TABLE cash_movements (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cash_register_session_id INTEGER NOT NULL REFERENCES cash_register_sessions(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    movement_type TEXT NOT NULL CHECK(movement_type IN ('pay_in', 'pay_out', 'safe_drop')),
+    amount_mxn REAL NOT NULL DEFAULT 0 CHECK(amount_mxn >= 0),
+    amount_usd REAL NOT NULL DEFAULT 0 CHECK(amount_usd >= 0),
+    reason TEXT NOT NULL,
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+CREATE INDEX idx_cash_movements_session ON cash_movements(cash_register_session_id);
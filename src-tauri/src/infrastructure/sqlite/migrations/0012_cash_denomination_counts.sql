@@ -0,0 +1,10 @@
+ALTER TABLE cash_register_sessions ADD COLUMN opening_cash_usd REAL NOT NULL DEFAULT 0;
+CREATE TABLE cash_denomination_counts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cash_register_session_id INTEGER NOT NULL REFERENCES cash_register_sessions(id),
+    count_type TEXT NOT NULL CHECK(count_type IN ('opening', 'closing')),
+    currency TEXT NOT NULL CHECK(currency IN ('MXN', 'USD')),
+    denomination REAL NOT NULL CHECK(denomination > 0),
+    quantity INTEGER NOT NULL CHECK(quantity >= 0),
+    UNIQUE(cash_register_session_id, count_type, currency, denomination)
+);
@@ -0,0 +1,22 @@
+CREATE TABLE cash_register_reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cash_register_session_id INTEGER NOT NULL REFERENCES cash_register_sessions(id),
+    report_type TEXT NOT NULL CHECK(report_type IN ('x', 'z')),
+    z_number INTEGER UNIQUE,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    payload TEXT NOT NULL,
+    created_at TEXT DEFAULT (datetime('now', 'localtime')),
+    CHECK((report_type = 'z') = (z_number IS NOT NULL))
+);
+CREATE UNIQUE INDEX idx_cash_register_reports_z_session ON cash_register_reports(cash_register_session_id) WHERE report_type = 'z';
+CREATE INDEX idx_cash_register_reports_session ON cash_register_reports(cash_register_session_id);
+CREATE TRIGGER cash_register_reports_z_immutable_update
+BEFORE UPDATE ON cash_register_reports WHEN OLD.report_type = 'z'
+BEGIN
+    SELECT RAISE(ABORT, 'Z reports are immutable');
+END;
+CREATE TRIGGER cash_register_reports_z_immutable_delete
+BEFORE DELETE ON cash_register_reports WHEN OLD.report_type = 'z'
+BEGIN
+    SELECT RAISE(ABORT, 'Z reports are immutable');
+END;
@@ -0,0 +1,18 @@
+CREATE TABLE registers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    location TEXT,
+    printer_settings TEXT,
+    active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT DEFAULT (datetime('now', 'localtime')),
+    updated_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+INSERT INTO registers (id, name) VALUES (1, 'Caja principal');
+ALTER TABLE cash_register_sessions ADD COLUMN register_id INTEGER REFERENCES registers(id);
+UPDATE cash_register_sessions SET register_id = 1;
+ALTER TABLE sales ADD COLUMN register_id INTEGER REFERENCES registers(id);
+UPDATE sales SET register_id = (
+    SELECT cr.register_id FROM cash_register_sessions cr WHERE cr.id = sales.cash_register_session_id
+);
+CREATE INDEX idx_cash_register_sessions_register ON cash_register_sessions(register_id, status);
+CREATE INDEX idx_sales_register ON sales(register_id);
@@ -0,0 +1,23 @@
+CREATE TABLE cash_register_sessions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    opening_amount REAL NOT NULL DEFAULT 0,
+    closing_amount REAL,
+    exchange_rate REAL,
+    status TEXT NOT NULL CHECK(status IN ('open', 'pending_review', 'closed')) DEFAULT 'open',
+    opened_at TEXT DEFAULT (datetime('now', 'localtime')),
+    closed_at TEXT,
+    closing_cash_mxn REAL,
+    closing_cash_usd REAL,
+    opening_cash_usd REAL NOT NULL DEFAULT 0,
+    register_id INTEGER REFERENCES registers(id),
+    reviewed_by INTEGER REFERENCES users(id),
+    review_notes TEXT,
+    reviewed_at TEXT
+);
+INSERT INTO cash_register_sessions_new (id, user_id, opening_amount, closing_amount, exchange_rate, status, opened_at, closed_at, closing_cash_mxn, closing_cash_usd, opening_cash_usd, register_id)
+    SELECT id, user_id, opening_amount, closing_amount, exchange_rate, status, opened_at, closed_at, closing_cash_mxn, closing_cash_usd, opening_cash_usd, register_id
+    FROM cash_register_sessions;
+DROP TABLE cash_register_sessions;
+ALTER TABLE cash_register_sessions_new RENAME TO cash_register_sessions;
+CREATE INDEX idx_cash_register_sessions_register ON cash_register_sessions(register_id, status);
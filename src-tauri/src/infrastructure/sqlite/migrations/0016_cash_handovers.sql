@@ -0,0 +1,25 @@
+CREATE TABLE cash_handovers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cash_register_session_id INTEGER NOT NULL REFERENCES cash_register_sessions(id),
+    from_user_id INTEGER NOT NULL REFERENCES users(id),
+    to_user_id INTEGER NOT NULL REFERENCES users(id),
+    started_at TEXT NOT NULL,
+    last_sale_id INTEGER NOT NULL DEFAULT 0,
+    sales_total REAL NOT NULL DEFAULT 0,
+    sales_count INTEGER NOT NULL DEFAULT 0,
+    expected_cash_mxn REAL NOT NULL,
+    expected_cash_usd REAL NOT NULL,
+    counted_cash_mxn REAL NOT NULL CHECK(counted_cash_mxn >= 0),
+    counted_cash_usd REAL NOT NULL CHECK(counted_cash_usd >= 0),
+    notes TEXT,
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+CREATE TABLE cash_handover_counts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    handover_id INTEGER NOT NULL REFERENCES cash_handovers(id),
+    currency TEXT NOT NULL CHECK(currency IN ('MXN', 'USD')),
+    denomination REAL NOT NULL CHECK(denomination > 0),
+    quantity INTEGER NOT NULL CHECK(quantity >= 0),
+    UNIQUE(handover_id, currency, denomination)
+);
+CREATE INDEX idx_cash_handovers_session ON cash_handovers(cash_register_session_id);
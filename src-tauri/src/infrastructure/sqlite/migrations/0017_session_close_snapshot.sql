@@ -0,0 +1,9 @@
+UPDATE cash_register_sessions
+    SET status = 'pending_review', closed_at = datetime('now', 'localtime')
+    WHERE status = 'open'
+      AND id NOT IN (
+        SELECT MAX(id) FROM cash_register_sessions WHERE status = 'open' GROUP BY register_id
+      );
+ALTER TABLE cash_register_sessions ADD COLUMN closing_summary TEXT;
+CREATE UNIQUE INDEX idx_cash_register_sessions_one_open
+    ON cash_register_sessions(register_id) WHERE status = 'open';
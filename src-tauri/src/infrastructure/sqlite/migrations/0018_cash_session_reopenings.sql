@@ -0,0 +1,11 @@
+CREATE TABLE cash_session_reopenings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cash_register_session_id INTEGER NOT NULL REFERENCES cash_register_sessions(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    reason TEXT NOT NULL,
+    previous_status TEXT NOT NULL,
+    previous_closed_at TEXT,
+    previous_summary TEXT,
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+CREATE INDEX idx_cash_session_reopenings_session ON cash_session_reopenings(cash_register_session_id);
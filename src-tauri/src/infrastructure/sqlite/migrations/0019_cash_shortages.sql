@@ -0,0 +1,11 @@
+CREATE TABLE cash_shortages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cash_register_session_id INTEGER NOT NULL REFERENCES cash_register_sessions(id),
+    cashier_id INTEGER NOT NULL REFERENCES users(id),
+    amount_mxn REAL NOT NULL CHECK(amount_mxn > 0),
+    notes TEXT,
+    recorded_by INTEGER NOT NULL REFERENCES users(id),
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+CREATE UNIQUE INDEX idx_cash_shortages_session_cashier ON cash_shortages(cash_register_session_id, cashier_id);
+CREATE INDEX idx_cash_shortages_cashier ON cash_shortages(cashier_id);
@@ -0,0 +1,3 @@
+ALTER TABLE cash_register_sessions ADD COLUMN count_pending INTEGER NOT NULL DEFAULT 0;
+UPDATE cash_register_sessions SET count_pending = 1
+    WHERE status = 'pending_review' AND closing_cash_mxn IS NULL;
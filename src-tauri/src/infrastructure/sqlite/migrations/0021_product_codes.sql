@@ -0,0 +1,14 @@
+CREATE TABLE product_codes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_id INTEGER NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    code TEXT NOT NULL UNIQUE,
+    code_type TEXT NOT NULL CHECK(code_type IN ('barcode', 'sku', 'supplier')),
+    supplier_name TEXT,
+    is_primary INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+CREATE INDEX idx_product_codes_product ON product_codes(product_id);
+CREATE UNIQUE INDEX idx_product_codes_primary ON product_codes(product_id) WHERE is_primary = 1;
+INSERT OR IGNORE INTO product_codes (product_id, code, code_type, is_primary)
+    SELECT id, TRIM(barcode), 'barcode', 1 FROM products
+    WHERE barcode IS NOT NULL AND TRIM(barcode) <> '';
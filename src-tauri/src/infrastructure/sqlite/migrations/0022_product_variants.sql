@@ -0,0 +1,21 @@
+ALTER TABLE products ADD COLUMN parent_id INTEGER REFERENCES products(id);
+CREATE INDEX idx_products_parent ON products(parent_id);
+CREATE TABLE product_attributes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_id INTEGER NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    UNIQUE(product_id, name)
+);
+CREATE TABLE product_attribute_values (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    attribute_id INTEGER NOT NULL REFERENCES product_attributes(id) ON DELETE CASCADE,
+    value TEXT NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    UNIQUE(attribute_id, value)
+);
+CREATE TABLE product_variant_values (
+    variant_id INTEGER NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    attribute_value_id INTEGER NOT NULL REFERENCES product_attribute_values(id),
+    PRIMARY KEY (variant_id, attribute_value_id)
+);
@@ -0,0 +1,13 @@
+ALTER TABLE products ADD COLUMN cost REAL NOT NULL DEFAULT 0;
+ALTER TABLE sale_items ADD COLUMN unit_cost REAL;
+CREATE TABLE product_price_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_id INTEGER NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    price REAL NOT NULL,
+    cost REAL NOT NULL,
+    previous_price REAL,
+    previous_cost REAL,
+    changed_by INTEGER REFERENCES users(id),
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+CREATE INDEX idx_product_price_history_product ON product_price_history(product_id, id);
@@ -0,0 +1,11 @@
+CREATE TABLE scheduled_price_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_id INTEGER NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    new_price REAL NOT NULL CHECK(new_price >= 0),
+    effective_at TEXT NOT NULL,
+    status TEXT NOT NULL CHECK(status IN ('pending', 'applied', 'cancelled')) DEFAULT 'pending',
+    created_by INTEGER REFERENCES users(id),
+    created_at TEXT DEFAULT (datetime('now', 'localtime')),
+    applied_at TEXT
+);
+CREATE INDEX idx_scheduled_price_changes_due ON scheduled_price_changes(status, effective_at);
@@ -0,0 +1,70 @@
+CREATE VIEW product_search_source AS
+SELECT p.id,
+       p.name,
+       COALESCE(p.description, '') AS description,
+       COALESCE((SELECT group_concat(pc.code, ' ') FROM product_codes pc WHERE pc.product_id = p.id), '') AS codes,
+       COALESCE(c.name, '') AS category
+FROM products p
+LEFT JOIN categories c ON c.id = p.category_id;
+
+CREATE VIRTUAL TABLE product_search USING fts5(
+    name,
+    description,
+    codes,
+    category,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO product_search (rowid, name, description, codes, category)
+SELECT id, name, description, codes, category FROM product_search_source;
+
+CREATE TRIGGER product_search_product_insert
+AFTER INSERT ON products
+BEGIN
+    INSERT INTO product_search (rowid, name, description, codes, category)
+    SELECT id, name, description, codes, category FROM product_search_source WHERE id = NEW.id;
+END;
+CREATE TRIGGER product_search_product_update
+AFTER UPDATE OF name, description, category_id ON products
+BEGIN
+    DELETE FROM product_search WHERE rowid = OLD.id;
+    INSERT INTO product_search (rowid, name, description, codes, category)
+    SELECT id, name, description, codes, category FROM product_search_source WHERE id = NEW.id;
+END;
+CREATE TRIGGER product_search_product_delete
+AFTER DELETE ON products
+BEGIN
+    DELETE FROM product_search WHERE rowid = OLD.id;
+END;
+
+CREATE TRIGGER product_search_code_insert
+AFTER INSERT ON product_codes
+BEGIN
+    DELETE FROM product_search WHERE rowid = NEW.product_id;
+    INSERT INTO product_search (rowid, name, description, codes, category)
+    SELECT id, name, description, codes, category FROM product_search_source WHERE id = NEW.product_id;
+END;
+CREATE TRIGGER product_search_code_update
+AFTER UPDATE ON product_codes
+BEGIN
+    DELETE FROM product_search WHERE rowid IN (OLD.product_id, NEW.product_id);
+    INSERT INTO product_search (rowid, name, description, codes, category)
+    SELECT id, name, description, codes, category FROM product_search_source
+    WHERE id IN (OLD.product_id, NEW.product_id);
+END;
+CREATE TRIGGER product_search_code_delete
+AFTER DELETE ON product_codes
+BEGIN
+    DELETE FROM product_search WHERE rowid = OLD.product_id;
+    INSERT INTO product_search (rowid, name, description, codes, category)
+    SELECT id, name, description, codes, category FROM product_search_source WHERE id = OLD.product_id;
+END;
+
+CREATE TRIGGER product_search_category_update
+AFTER UPDATE OF name ON categories
+BEGIN
+    DELETE FROM product_search WHERE rowid IN (SELECT id FROM products WHERE category_id = NEW.id);
+    INSERT INTO product_search (rowid, name, description, codes, category)
+    SELECT id, name, description, codes, category FROM product_search_source
+    WHERE id IN (SELECT id FROM products WHERE category_id = NEW.id);
+END;
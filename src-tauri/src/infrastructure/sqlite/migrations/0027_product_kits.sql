@@ -0,0 +1,18 @@
+CREATE TABLE product_kit_components (
+    kit_id INTEGER NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    component_id INTEGER NOT NULL REFERENCES products(id),
+    quantity REAL NOT NULL CHECK(quantity > 0),
+    PRIMARY KEY (kit_id, component_id)
+);
+CREATE INDEX idx_product_kit_components_component ON product_kit_components(component_id);
+CREATE VIEW kit_availability AS
+    SELECT k.kit_id,
+        MAX(0, MIN(CAST(ROUND(c.stock / k.quantity, 6) AS INTEGER))) AS available
+    FROM product_kit_components k JOIN products c ON c.id = k.component_id
+    GROUP BY k.kit_id;
+CREATE TABLE sale_item_components (
+    sale_item_id INTEGER NOT NULL REFERENCES sale_items(id) ON DELETE CASCADE,
+    product_id INTEGER NOT NULL REFERENCES products(id),
+    quantity REAL NOT NULL,
+    PRIMARY KEY (sale_item_id, product_id)
+);
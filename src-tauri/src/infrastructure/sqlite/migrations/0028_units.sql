@@ -0,0 +1,18 @@
+CREATE TABLE units (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    base_unit_id INTEGER REFERENCES units(id),
+    factor REAL NOT NULL DEFAULT 1 CHECK(factor > 0),
+    fractional INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT (datetime('now', 'localtime'))
+);
+INSERT INTO units (name, fractional) VALUES ('pieza', 0), ('kg', 1), ('litro', 1), ('metro', 1);
+INSERT INTO units (name, base_unit_id, factor)
+    SELECT 'g', id, 0.001 FROM units WHERE name = 'kg'
+    UNION ALL SELECT 'ml', id, 0.001 FROM units WHERE name = 'litro'
+    UNION ALL SELECT 'cm', id, 0.01 FROM units WHERE name = 'metro';
+UPDATE products SET unit = TRIM(unit) WHERE unit <> TRIM(unit);
+INSERT OR IGNORE INTO units (name)
+    SELECT DISTINCT unit FROM products WHERE unit <> '';
+ALTER TABLE inventory_adjustments ADD COLUMN input_value REAL;
+ALTER TABLE inventory_adjustments ADD COLUMN input_unit TEXT;
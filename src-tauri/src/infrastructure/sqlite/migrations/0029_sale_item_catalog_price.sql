@@ -0,0 +1,5 @@
+ALTER TABLE sale_items ADD COLUMN catalog_price REAL;
+UPDATE sale_items SET catalog_price = CASE
+    WHEN price_list_id IS NULL THEN unit_price
+    ELSE (SELECT price FROM products WHERE products.id = sale_items.product_id)
+END;
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// Over/short of one cashier's shift in a closed session: the whole session, or the
+/// stretch between handovers. Positive differences are overages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionOverShort {
+    pub session_id: i64,
+    pub register_name: Option<String>,
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    /// When the shift ended: the handover, or the session close for the last cashier.
+    pub closed_at: Option<String>,
+    pub expected_cash_mxn: f64,
+    pub expected_cash_usd: f64,
+    pub difference_mxn: f64,
+    pub difference_usd: f64,
+    /// Difference beyond the configured tolerance in either currency.
+    pub flagged: bool,
+    pub shortage_owed: Option<CashShortage>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CashierOverShort {
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub sessions: i64,
+    pub flagged_sessions: i64,
+    pub over_mxn: f64,
+    pub short_mxn: f64,
+    pub net_mxn: f64,
+    pub over_usd: f64,
+    pub short_usd: f64,
+    pub net_usd: f64,
+    pub shortage_owed_mxn: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashOverShortReport {
+    pub tolerance_mxn: f64,
+    pub tolerance_usd: f64,
+    pub cashiers: Vec<CashierOverShort>,
+    pub sessions: Vec<SessionOverShort>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CashOverShortReportRequest {
+    pub start_date: String,
+    pub end_date: String,
+    #[serde(default)]
+    pub register_id: Option<i64>,
+    #[serde(default)]
+    pub cashier_id: Option<i64>,
+}
+
+/// Shortage a cashier owes back for their shifts in a closed session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashShortage {
+    pub id: i64,
+    pub cash_register_session_id: i64,
+    pub cashier_id: i64,
+    pub cashier_name: Option<String>,
+    pub amount_mxn: f64,
+    pub notes: Option<String>,
+    pub recorded_by: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordCashShortageRequest {
+    pub session_id: i64,
+    /// Administrator recording the shortage.
+    pub user_id: i64,
+    /// Cashier charged; required when the drawer changed hands during the session.
+    #[serde(default)]
+    pub cashier_id: Option<i64>,
+    pub amount_mxn: f64,
+    pub notes: Option<String>,
+}
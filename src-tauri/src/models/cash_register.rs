@@ -1,11 +1,13 @@
 use serde::{Deserialize, Serialize};
 
 /// Lifecycle state of a cash-register session.
-/// The `status` column stores the lowercase string form.
+/// The `status` column stores the snake_case string form.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub enum SessionStatus {
     Open,
+    /// Blind close submitted; waits for a supervisor to approve the count.
+    PendingReview,
     Closed,
 }
 
@@ -13,6 +15,7 @@ impl SessionStatus {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Open => "open",
+            Self::PendingReview => "pending_review",
             Self::Closed => "closed",
         }
     }
@@ -20,6 +23,7 @@ impl SessionStatus {
     pub fn parse(s: &str) -> Option<Self> {
         match s {
             "open" => Some(Self::Open),
+            "pending_review" => Some(Self::PendingReview),
             "closed" => Some(Self::Closed),
             _ => None,
         }
@@ -43,12 +47,176 @@ impl rusqlite::types::ToSql for SessionStatus {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CashMovementType {
+    /// Cash added to the drawer outside a sale, e.g. extra change.
+    PayIn,
+    /// Cash taken out to pay an expense.
+    PayOut,
+    /// Excess cash moved from the drawer to the safe.
+    SafeDrop,
+}
+
+impl CashMovementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PayIn => "pay_in",
+            Self::PayOut => "pay_out",
+            Self::SafeDrop => "safe_drop",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pay_in" => Some(Self::PayIn),
+            "pay_out" => Some(Self::PayOut),
+            "safe_drop" => Some(Self::SafeDrop),
+            _ => None,
+        }
+    }
+
+    /// Pay-ins add cash to the drawer; pay-outs and safe drops remove it.
+    pub fn is_inflow(&self) -> bool {
+        matches!(self, Self::PayIn)
+    }
+}
+
+impl rusqlite::types::FromSql for CashMovementType {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let s = String::column_result(value)?;
+        CashMovementType::parse(&s).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(
+                format!("invalid cash movement type: {}", s).into(),
+            )
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for CashMovementType {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CashCurrency {
+    #[serde(rename = "MXN")]
+    Mxn,
+    #[serde(rename = "USD")]
+    Usd,
+}
+
+impl CashCurrency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mxn => "MXN",
+            Self::Usd => "USD",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "MXN" => Some(Self::Mxn),
+            "USD" => Some(Self::Usd),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for CashCurrency {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let s = String::column_result(value)?;
+        CashCurrency::parse(&s).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(format!("invalid cash currency: {}", s).into())
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for CashCurrency {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+/// Moment of the session at which the drawer was counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CashCountType {
+    Opening,
+    Closing,
+}
+
+impl CashCountType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Opening => "opening",
+            Self::Closing => "closing",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "opening" => Some(Self::Opening),
+            "closing" => Some(Self::Closing),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for CashCountType {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let s = String::column_result(value)?;
+        CashCountType::parse(&s).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(format!("invalid cash count type: {}", s).into())
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for CashCountType {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+/// Bills and coins of one denomination found in the drawer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DenominationCount {
+    pub currency: CashCurrency,
+    pub denomination: f64,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashCountLine {
+    pub currency: CashCurrency,
+    pub denomination: f64,
+    pub quantity: i64,
+    pub subtotal: f64,
+}
+
+/// Denominations offered for counting, largest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashDenominations {
+    pub mxn: Vec<f64>,
+    pub usd: Vec<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CashRegisterSession {
     pub id: i64,
+    pub register_id: i64,
+    pub register_name: Option<String>,
     pub user_id: i64,
     pub user_name: Option<String>,
     pub opening_amount: f64,
+    pub opening_cash_usd: f64,
     pub closing_amount: Option<f64>,
     pub closing_cash_mxn: Option<f64>,
     pub closing_cash_usd: Option<f64>,
@@ -56,25 +224,195 @@ pub struct CashRegisterSession {
     pub status: SessionStatus,
     pub opened_at: String,
     pub closed_at: Option<String>,
+    /// Supervisor approval of a blind close.
+    pub review: Option<CashCloseReview>,
+    /// Auto-closed as stale; the drawer still has to be counted.
+    pub count_pending: bool,
     pub total_sales: Option<f64>,
     pub total_transactions: Option<i64>,
 }
 
+/// What to do with sessions left open past `cash_stale_session_hours`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleSessionPolicy {
+    #[default]
+    Warn,
+    /// New sales are rejected until the session is closed.
+    Block,
+    /// Closed at startup into review with the count left pending.
+    AutoClose,
+}
+
+impl StaleSessionPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "warn" => Some(Self::Warn),
+            "block" => Some(Self::Block),
+            "auto_close" => Some(Self::AutoClose),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleSessionCheck {
+    pub policy: StaleSessionPolicy,
+    pub cutoff_hours: i64,
+    /// Sessions still open past the cutoff.
+    pub sessions: Vec<CashRegisterSession>,
+    pub auto_closed: Vec<CashRegisterSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashCloseReview {
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub notes: String,
+    pub reviewed_at: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OpenCashRegisterRequest {
     pub user_id: i64,
+    /// May be omitted while only one register is active.
+    #[serde(default)]
+    pub register_id: Option<i64>,
+    #[serde(default)]
     pub opening_amount: f64,
+    #[serde(default)]
+    pub opening_cash_usd: f64,
     pub exchange_rate: Option<f64>,
+    /// When present, the opening amounts are computed from the count.
+    #[serde(default)]
+    pub opening_count: Option<Vec<DenominationCount>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CloseCashRegisterRequest {
     pub session_id: i64,
+    #[serde(default)]
     pub closing_cash_mxn: f64,
+    #[serde(default)]
     pub closing_cash_usd: f64,
+    /// When present, the closing amounts are computed from the count.
+    #[serde(default)]
+    pub closing_count: Option<Vec<DenominationCount>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Deserialize)]
+pub struct ApproveCashCloseRequest {
+    pub session_id: i64,
+    /// Supervisor approving the close; must be an administrator.
+    pub user_id: i64,
+    /// Explanation of the differences found in the count.
+    pub notes: String,
+}
+
+/// Drawer passed from one cashier to the next inside the same session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashHandover {
+    pub id: i64,
+    pub cash_register_session_id: i64,
+    pub register_name: Option<String>,
+    pub from_user_id: i64,
+    pub from_user_name: Option<String>,
+    pub to_user_id: i64,
+    pub to_user_name: Option<String>,
+    /// When the outgoing cashier took the drawer: session opening or the previous handover.
+    pub started_at: String,
+    /// Completed sales rung while the outgoing cashier held the drawer.
+    pub sales_total: f64,
+    pub sales_count: i64,
+    pub expected_cash_mxn: f64,
+    pub expected_cash_usd: f64,
+    pub counted_cash_mxn: f64,
+    pub counted_cash_usd: f64,
+    pub difference_mxn: f64,
+    pub difference_usd: f64,
+    /// Set when the expected cash and differences are withheld for a blind count.
+    #[serde(default)]
+    pub expectations_hidden: bool,
+    pub count: Vec<CashCountLine>,
+    pub notes: Option<String>,
+    pub created_at: String,
+}
+
+impl CashHandover {
+    pub fn hide_expectations(&mut self) {
+        self.expected_cash_mxn = 0.0;
+        self.expected_cash_usd = 0.0;
+        self.difference_mxn = 0.0;
+        self.difference_usd = 0.0;
+        self.expectations_hidden = true;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HandoverCashRegisterRequest {
+    pub session_id: i64,
+    /// Cashier currently holding the drawer.
+    pub from_user_id: i64,
+    pub to_user_id: i64,
+    #[serde(default)]
+    pub counted_cash_mxn: f64,
+    #[serde(default)]
+    pub counted_cash_usd: f64,
+    /// When present, the counted amounts are computed from the count.
+    #[serde(default)]
+    pub count: Option<Vec<DenominationCount>>,
+    pub notes: Option<String>,
+}
+
+/// Audit entry for a closed session put back into `Open`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashSessionReopening {
+    pub id: i64,
+    pub cash_register_session_id: i64,
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub reason: String,
+    pub previous_status: SessionStatus,
+    pub previous_closed_at: Option<String>,
+    /// Close snapshot that was discarded by the reopening, kept for comparison.
+    pub previous_summary: Option<CashRegisterSummary>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReopenCashRegisterSessionRequest {
+    pub session_id: i64,
+    /// Administrator reopening the session.
+    pub user_id: i64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashMovement {
+    pub id: i64,
+    pub cash_register_session_id: i64,
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub movement_type: CashMovementType,
+    pub amount_mxn: f64,
+    pub amount_usd: f64,
+    pub reason: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCashMovementRequest {
+    pub session_id: i64,
+    pub user_id: i64,
+    pub movement_type: CashMovementType,
+    #[serde(default)]
+    pub amount_mxn: f64,
+    #[serde(default)]
+    pub amount_usd: f64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CashRegisterSummary {
     pub session: CashRegisterSession,
     pub total_sales: f64,
@@ -85,6 +423,16 @@ pub struct CashRegisterSummary {
     pub account_payments_cash_mxn: f64,
     pub account_payments_cash_usd: f64,
     pub account_payments_transfer: f64,
+    /// Gift cards sold and sale refunds kept as store credit.
+    pub stored_value_cash_mxn: f64,
+    pub stored_value_cash_usd: f64,
+    pub stored_value_transfer: f64,
+    pub pay_ins_mxn: f64,
+    pub pay_ins_usd: f64,
+    pub pay_outs_mxn: f64,
+    pub pay_outs_usd: f64,
+    pub safe_drops_mxn: f64,
+    pub safe_drops_usd: f64,
     pub total_change_given: f64,
     pub expected_cash_mxn: f64,
     pub expected_cash_usd: f64,
@@ -92,10 +440,27 @@ pub struct CashRegisterSummary {
     pub actual_cash_usd: f64,
     pub difference_mxn: f64,
     pub difference_usd: f64,
+    /// Set when the expected cash and differences are withheld for a blind count.
+    #[serde(default)]
+    pub expectations_hidden: bool,
+    pub opening_count: Vec<CashCountLine>,
+    pub closing_count: Vec<CashCountLine>,
+}
+
+impl CashRegisterSummary {
+    pub fn hide_expectations(&mut self) {
+        self.expected_cash_mxn = 0.0;
+        self.expected_cash_usd = 0.0;
+        self.difference_mxn = 0.0;
+        self.difference_usd = 0.0;
+        self.expectations_hidden = true;
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DateRangeRequest {
     pub start_date: String,
     pub end_date: String,
+    #[serde(default)]
+    pub register_id: Option<i64>,
 }
@@ -0,0 +1,107 @@
+use crate::models::cash_register::CashRegisterSummary;
+use serde::{Deserialize, Serialize};
+
+/// X reports are informative snapshots that can be taken any number of times;
+/// a Z report closes out the session once and is numbered sequentially.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CashReportType {
+    X,
+    Z,
+}
+
+impl CashReportType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::X => "x",
+            Self::Z => "z",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "x" => Some(Self::X),
+            "z" => Some(Self::Z),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for CashReportType {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let s = String::column_result(value)?;
+        CashReportType::parse(&s).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(format!("invalid cash report type: {}", s).into())
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for CashReportType {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+/// Amount received through one tender; `tender` uses the sale payment method keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenderTotal {
+    pub tender: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySalesTotal {
+    pub category_id: Option<i64>,
+    pub category_name: Option<String>,
+    pub quantity: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashierSalesTotal {
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub transactions: i64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportTally {
+    pub count: i64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashRegisterReportData {
+    pub summary: CashRegisterSummary,
+    pub tenders: Vec<TenderTotal>,
+    pub categories: Vec<CategorySalesTotal>,
+    pub cashiers: Vec<CashierSalesTotal>,
+    pub cancellations: ReportTally,
+    /// Cancelled sales refunded to the customer's store credit.
+    pub returns: ReportTally,
+    /// Price list savings against the product list price.
+    pub discounts: f64,
+    pub tax: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashRegisterReport {
+    pub id: i64,
+    pub cash_register_session_id: i64,
+    pub report_type: CashReportType,
+    pub z_number: Option<i64>,
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub created_at: String,
+    pub data: CashRegisterReportData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateCashReportRequest {
+    pub session_id: i64,
+    pub user_id: i64,
+    pub report_type: CashReportType,
+}
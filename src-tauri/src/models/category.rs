@@ -6,12 +6,20 @@ pub struct Category {
     pub name: String,
     pub description: Option<String>,
     pub created_at: String,
+    /// `None` for a department (top-level category).
+    pub parent_id: Option<i64>,
+    /// Names from the department down, e.g. `Abarrotes / Enlatados`.
+    pub path: String,
+    /// 0 for departments.
+    pub depth: i64,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateCategoryRequest {
     pub name: String,
     pub description: Option<String>,
+    #[serde(default)]
+    pub parent_id: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,3 +28,17 @@ pub struct UpdateCategoryRequest {
     pub name: Option<String>,
     pub description: Option<String>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct MoveCategoryRequest {
+    pub id: i64,
+    /// `None` turns the category into a department.
+    pub parent_id: Option<i64>,
+}
+
+/// Moves the source's products and subcategories into the target, then deletes the source.
+#[derive(Debug, Deserialize)]
+pub struct MergeCategoriesRequest {
+    pub source_id: i64,
+    pub target_id: i64,
+}
@@ -50,6 +50,8 @@ pub struct Customer {
     pub phone: Option<String>,
     pub notes: Option<String>,
     pub credit_limit: f64,
+    pub price_list_id: Option<i64>,
+    pub price_list_name: Option<String>,
     pub active: bool,
     pub balance: f64,
     pub created_at: String,
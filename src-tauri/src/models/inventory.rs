@@ -67,6 +67,9 @@ pub struct InventoryAdjustment {
     pub new_stock: f64,
     pub reason: Option<String>,
     pub created_at: String,
+    /// What was captured when it was not in the product's unit, e.g. 3 `caja`.
+    pub input_value: Option<f64>,
+    pub input_unit: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -76,6 +79,9 @@ pub struct CreateInventoryAdjustmentRequest {
     pub adjustment_type: String,
     pub quantity: f64,
     pub reason: Option<String>,
+    /// Unit `quantity` is given in; defaults to the product's own unit.
+    #[serde(default)]
+    pub unit: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointsMovementType {
+    /// Points granted by a completed sale.
+    Earn,
+    /// Points used as a tender in a sale.
+    Redeem,
+    /// Earned points written off after their expiry date.
+    Expire,
+    /// Compensating entry created when a sale is cancelled.
+    Reversal,
+}
+
+impl PointsMovementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Earn => "earn",
+            Self::Redeem => "redeem",
+            Self::Expire => "expire",
+            Self::Reversal => "reversal",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "earn" => Some(Self::Earn),
+            "redeem" => Some(Self::Redeem),
+            "expire" => Some(Self::Expire),
+            "reversal" => Some(Self::Reversal),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for PointsMovementType {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let value = String::column_result(value)?;
+        Self::parse(&value).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(
+                format!("invalid points movement type: {}", value).into(),
+            )
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for PointsMovementType {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomerPointsMovement {
+    pub id: i64,
+    pub customer_id: i64,
+    pub sale_id: Option<i64>,
+    /// Empty for automatic expirations.
+    pub user_id: Option<i64>,
+    pub user_name: Option<String>,
+    pub movement_type: PointsMovementType,
+    /// Positive for earned or restored points, negative for consumed ones.
+    pub points: f64,
+    pub amount_mxn: f64,
+    pub expires_at: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomerPointsAccount {
+    pub customer_id: i64,
+    pub balance: f64,
+    pub balance_value_mxn: f64,
+    pub movements: Vec<CustomerPointsMovement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoyaltyCategoryRule {
+    pub category_id: i64,
+    pub category_name: Option<String>,
+    pub points_per_mxn: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetLoyaltyCategoryRuleRequest {
+    pub category_id: i64,
+    /// `None` removes the override so the general earn rate applies.
+    pub points_per_mxn: Option<f64>,
+}
@@ -1,8 +1,19 @@
+pub mod cash_over_short;
 pub mod cash_register;
+pub mod cash_register_report;
 pub mod category;
 pub mod customer;
 pub mod inventory;
+pub mod loyalty;
+pub mod price_change;
+pub mod price_list;
 pub mod product;
+pub mod product_import;
+pub mod product_kit;
+pub mod product_variant;
+pub mod register;
 pub mod sale;
 pub mod setting;
+pub mod stored_value;
+pub mod unit;
 pub mod user;
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+/// The `status` column stores the lowercase string form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduledPriceChangeStatus {
+    Pending,
+    Applied,
+    Cancelled,
+}
+
+impl ScheduledPriceChangeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Applied => "applied",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "applied" => Some(Self::Applied),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for ScheduledPriceChangeStatus {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let s = String::column_result(value)?;
+        ScheduledPriceChangeStatus::parse(&s).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(
+                format!("invalid scheduled price change status: {}", s).into(),
+            )
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for ScheduledPriceChangeStatus {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledPriceChange {
+    pub id: i64,
+    pub product_id: i64,
+    pub product_name: String,
+    pub current_price: f64,
+    pub new_price: f64,
+    /// Local time, `YYYY-MM-DD HH:MM:SS`.
+    pub effective_at: String,
+    pub status: ScheduledPriceChangeStatus,
+    pub created_by: Option<i64>,
+    pub created_at: String,
+    pub applied_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduledPriceInput {
+    pub product_id: i64,
+    pub new_price: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchedulePriceChangesRequest {
+    pub user_id: i64,
+    /// `YYYY-MM-DD HH:MM[:SS]`, local time; a `T` separator is accepted.
+    pub effective_at: String,
+    pub changes: Vec<ScheduledPriceInput>,
+    /// Confirms prices below cost under the `warn` policy.
+    #[serde(default)]
+    pub allow_below_cost: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkRepriceRequest {
+    /// Reprices this category and all of its subcategories.
+    pub category_id: i64,
+    /// Percentage applied to the current price; negative lowers it.
+    pub percent: f64,
+    /// Rounds each new price to the nearest multiple, e.g. `0.5`.
+    pub round_to: Option<f64>,
+    pub user_id: i64,
+    /// Confirms prices that end up below cost under the `warn` policy.
+    #[serde(default)]
+    pub allow_below_cost: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepriceLine {
+    pub product_id: i64,
+    pub product_name: String,
+    pub current_price: f64,
+    pub new_price: f64,
+    pub cost: f64,
+    pub below_cost: bool,
+}
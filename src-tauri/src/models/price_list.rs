@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceList {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    /// Percentage applied to the catalog price when no rule matches a line.
+    pub default_adjustment_percent: f64,
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    pub rules: Vec<PriceListRule>,
+}
+
+/// A price override inside a list. A rule targets a product, a category or
+/// (when both are empty) every product, and only applies from `min_quantity`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceListRule {
+    pub id: i64,
+    pub price_list_id: i64,
+    pub product_id: Option<i64>,
+    pub product_name: Option<String>,
+    pub category_id: Option<i64>,
+    pub category_name: Option<String>,
+    pub min_quantity: f64,
+    pub fixed_price: Option<f64>,
+    pub percent_adjustment: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PriceListRuleRequest {
+    pub product_id: Option<i64>,
+    pub category_id: Option<i64>,
+    pub min_quantity: Option<f64>,
+    pub fixed_price: Option<f64>,
+    pub percent_adjustment: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePriceListRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub default_adjustment_percent: Option<f64>,
+    #[serde(default)]
+    pub rules: Vec<PriceListRuleRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePriceListRequest {
+    pub id: i64,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub default_adjustment_percent: Option<f64>,
+    pub active: Option<bool>,
+    /// When present, replaces every rule of the list.
+    pub rules: Option<Vec<PriceListRuleRequest>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignCustomerPriceListRequest {
+    pub customer_id: i64,
+    pub price_list_id: Option<i64>,
+}
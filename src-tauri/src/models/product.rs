@@ -1,3 +1,4 @@
+use crate::models::sale::SaleInputMode;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +8,7 @@ pub struct Product {
     pub description: Option<String>,
     pub barcode: Option<String>,
     pub price: f64,
+    pub cost: f64,
     pub unit: String,
     pub is_bulk: bool,
     pub category_id: Option<i64>,
@@ -16,6 +18,30 @@ pub struct Product {
     pub active: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// Set on variants; points at the product they were generated from.
+    pub parent_id: Option<i64>,
+    /// Products with variants are not sold directly.
+    pub variant_count: i64,
+    /// Kits have no stock of their own: `stock` is how many complete kits the
+    /// components allow, and selling one deducts the components.
+    pub is_kit: bool,
+}
+
+/// Result of scanning a code at the register.
+#[derive(Debug, Serialize)]
+pub struct BarcodeLookup {
+    #[serde(flatten)]
+    pub product: Product,
+    /// Decoded from a scale label; absent for regular codes.
+    pub scale: Option<ScaleLabel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScaleLabel {
+    pub plu: String,
+    /// `Base` for a weight label, `Amount` for a price label.
+    pub input_mode: SaleInputMode,
+    pub input_value: f64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,12 +50,18 @@ pub struct CreateProductRequest {
     pub description: Option<String>,
     pub barcode: Option<String>,
     pub price: f64,
+    pub cost: Option<f64>,
     pub unit: String,
     #[serde(default)]
     pub is_bulk: bool,
     pub category_id: Option<i64>,
     pub stock: Option<f64>,
     pub min_stock: Option<f64>,
+    /// Recorded as the author of the initial price history entry.
+    pub user_id: Option<i64>,
+    /// Confirms a price below cost under the `warn` policy.
+    #[serde(default)]
+    pub allow_below_cost: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -39,9 +71,115 @@ pub struct UpdateProductRequest {
     pub description: Option<String>,
     pub barcode: Option<String>,
     pub price: Option<f64>,
+    pub cost: Option<f64>,
     pub unit: Option<String>,
     pub is_bulk: Option<bool>,
     pub category_id: Option<i64>,
     pub min_stock: Option<f64>,
     pub active: Option<bool>,
+    pub user_id: Option<i64>,
+    #[serde(default)]
+    pub allow_below_cost: bool,
+}
+
+/// What `create_product`/`update_product` do with a price below cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BelowCostPolicy {
+    /// Rejected until the request sets `allow_below_cost`.
+    #[default]
+    Warn,
+    Refuse,
+}
+
+impl BelowCostPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "warn" => Some(Self::Warn),
+            "refuse" => Some(Self::Refuse),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductPriceChange {
+    pub id: i64,
+    pub product_id: i64,
+    pub price: f64,
+    pub cost: f64,
+    pub previous_price: Option<f64>,
+    pub previous_cost: Option<f64>,
+    /// Gross margin over price, in percent; `None` without a price.
+    pub margin_percent: Option<f64>,
+    pub changed_by: Option<i64>,
+    pub changed_by_name: Option<String>,
+    pub created_at: String,
+}
+
+/// The `code_type` column stores the snake_case string form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductCodeType {
+    /// EAN/UPC printed on the package.
+    Barcode,
+    /// Internal short code typed at the register.
+    Sku,
+    /// Code a supplier uses on its invoices.
+    Supplier,
+}
+
+impl ProductCodeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Barcode => "barcode",
+            Self::Sku => "sku",
+            Self::Supplier => "supplier",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "barcode" => Some(Self::Barcode),
+            "sku" => Some(Self::Sku),
+            "supplier" => Some(Self::Supplier),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for ProductCodeType {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let s = String::column_result(value)?;
+        ProductCodeType::parse(&s).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(format!("invalid product code type: {}", s).into())
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for ProductCodeType {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductCode {
+    pub id: i64,
+    pub product_id: i64,
+    pub code: String,
+    pub code_type: ProductCodeType,
+    pub supplier_name: Option<String>,
+    /// Mirrors `products.barcode`; changed through the product itself.
+    pub is_primary: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddProductCodeRequest {
+    pub product_id: i64,
+    pub code: String,
+    pub code_type: ProductCodeType,
+    pub supplier_name: Option<String>,
 }
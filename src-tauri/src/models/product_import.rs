@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Product field a spreadsheet column can be mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductImportField {
+    Name,
+    Description,
+    Barcode,
+    Sku,
+    Price,
+    Cost,
+    Unit,
+    IsBulk,
+    Category,
+    Stock,
+    MinStock,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductImportRequest {
+    /// `.csv`, `.xlsx`, `.xls` or `.ods`; the first sheet is read.
+    pub path: String,
+    /// Header text to field. Headers left out fall back to the export's names.
+    #[serde(default)]
+    pub mapping: HashMap<String, ProductImportField>,
+    /// Validates every row and reports what would happen without saving.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Creates categories named in the file that do not exist yet.
+    #[serde(default)]
+    pub create_categories: bool,
+    pub user_id: Option<i64>,
+    /// Confirms prices below cost under the `warn` policy.
+    #[serde(default)]
+    pub allow_below_cost: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductImportRejection {
+    /// Spreadsheet row number, counting the header as row 1.
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub created_categories: Vec<String>,
+    pub rejected: Vec<ProductImportRejection>,
+}
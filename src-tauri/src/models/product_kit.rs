@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KitComponent {
+    pub component_id: i64,
+    pub name: String,
+    pub unit: String,
+    /// Units of the component used by one kit.
+    pub quantity: f64,
+    pub stock: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KitComponentInput {
+    pub component_id: i64,
+    pub quantity: f64,
+}
+
+/// Replaces the kit's components; an empty list turns it back into a regular product.
+#[derive(Debug, Deserialize)]
+pub struct SetKitComponentsRequest {
+    pub kit_id: i64,
+    pub components: Vec<KitComponentInput>,
+}
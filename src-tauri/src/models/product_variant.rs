@@ -0,0 +1,50 @@
+use crate::models::product::Product;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductAttribute {
+    pub id: i64,
+    pub product_id: i64,
+    pub name: String,
+    pub values: Vec<ProductAttributeValue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductAttributeValue {
+    pub id: i64,
+    pub value: String,
+}
+
+/// One attribute/value pair describing a variant, e.g. `Tamaño: 600 ml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VariantOption {
+    pub attribute: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductVariant {
+    #[serde(flatten)]
+    pub product: Product,
+    pub options: Vec<VariantOption>,
+}
+
+/// A search hit shown under its parent; products without variants come alone.
+#[derive(Debug, Serialize)]
+pub struct ProductGroup {
+    #[serde(flatten)]
+    pub product: Product,
+    pub variants: Vec<ProductVariant>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VariantAttributeInput {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateVariantsRequest {
+    pub product_id: i64,
+    pub attributes: Vec<VariantAttributeInput>,
+}
@@ -0,0 +1,36 @@
+use crate::printer::models::PrinterSettings;
+use serde::{Deserialize, Serialize};
+
+/// A checkout lane; each register runs its own cash sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Register {
+    pub id: i64,
+    pub name: String,
+    pub location: Option<String>,
+    /// Printer used for this lane; `None` falls back to the global printer settings.
+    pub printer: Option<PrinterSettings>,
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRegisterRequest {
+    pub user_id: i64,
+    pub name: String,
+    pub location: Option<String>,
+    pub printer: Option<PrinterSettings>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRegisterRequest {
+    pub user_id: i64,
+    pub id: i64,
+    pub name: Option<String>,
+    pub location: Option<String>,
+    pub printer: Option<PrinterSettings>,
+    /// Drops the lane printer so the global printer settings apply again.
+    #[serde(default)]
+    pub use_default_printer: bool,
+    pub active: Option<bool>,
+}
@@ -47,6 +47,7 @@ impl rusqlite::types::ToSql for SaleStatus {
 #[serde(rename_all = "lowercase")]
 pub enum SaleInputMode {
     Base,
+    /// Captured in a configured conversion of the product's unit, e.g. `g` or `caja`.
     Sub,
     Amount,
 }
@@ -91,6 +92,7 @@ impl rusqlite::types::ToSql for SaleInputMode {
 pub struct Sale {
     pub id: i64,
     pub cash_register_session_id: i64,
+    pub register_id: Option<i64>,
     pub user_id: i64,
     pub user_name: Option<String>,
     pub total: f64,
@@ -102,6 +104,17 @@ pub struct Sale {
     pub payment_cash_mxn: f64,
     pub payment_cash_usd: f64,
     pub payment_transfer: f64,
+    /// Loyalty points redeemed as a tender and their value in MXN.
+    pub payment_points: f64,
+    pub payment_points_mxn: f64,
+    pub points_earned: f64,
+    /// Amount paid with a gift card or store credit.
+    pub payment_stored_value: f64,
+    pub stored_value_account_id: Option<i64>,
+    /// Gift card code; `None` when the customer's store credit was used.
+    pub stored_value_code: Option<String>,
+    /// Balance left on the instrument right after the sale.
+    pub stored_value_balance: Option<f64>,
     pub exchange_rate: Option<f64>,
     pub change_amount: f64,
     pub status: SaleStatus,
@@ -121,7 +134,11 @@ pub struct SaleItem {
     pub input_value: Option<f64>,
     pub input_unit: Option<String>,
     pub unit_price: f64,
+    /// Price list that set `unit_price`; `None` when the catalog price applied.
+    pub price_list_id: Option<i64>,
     pub subtotal: f64,
+    /// Product cost when sold; `None` for sales made before costs were tracked.
+    pub unit_cost: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -131,6 +148,14 @@ pub struct CreateSaleRequest {
     pub payment_cash_mxn: f64,
     pub payment_cash_usd: f64,
     pub payment_transfer: f64,
+    /// Loyalty points of `customer_id` used to pay part of the sale.
+    #[serde(default)]
+    pub payment_points: f64,
+    #[serde(default)]
+    pub payment_stored_value: f64,
+    /// Gift card used for `payment_stored_value`; without a code the
+    /// customer's store credit is charged.
+    pub stored_value_code: Option<String>,
     pub customer_id: Option<i64>,
     pub items: Vec<CreateSaleItemRequest>,
 }
@@ -144,6 +169,53 @@ pub struct CreateSaleItemRequest {
     pub input_unit: String,
 }
 
+/// Fields of a sale that `amend_sale` may change, stored before and after
+/// every amendment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaleRevisionSnapshot {
+    pub customer_id: Option<i64>,
+    pub payment_method: String,
+    pub payment_amount: f64,
+    pub payment_cash_mxn: f64,
+    pub payment_cash_usd: f64,
+    pub payment_transfer: f64,
+    pub change_amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaleRevision {
+    pub id: i64,
+    pub sale_id: i64,
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub reason: String,
+    pub before: SaleRevisionSnapshot,
+    pub after: SaleRevisionSnapshot,
+    pub created_at: String,
+}
+
+/// Corrects the tender breakdown or the customer of a completed sale.
+/// Omitted fields keep their current value; items are never changed.
+#[derive(Debug, Deserialize)]
+pub struct AmendSaleRequest {
+    pub sale_id: i64,
+    /// Administrator making the change.
+    pub user_id: i64,
+    pub customer_id: Option<i64>,
+    pub payment_cash_mxn: Option<f64>,
+    pub payment_cash_usd: Option<f64>,
+    pub payment_transfer: Option<f64>,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SaleAmendment {
+    pub sale: Sale,
+    pub revision: SaleRevision,
+    /// Recomputed summary of the sale's session while it is still open.
+    pub session_summary: Option<crate::models::cash_register::CashRegisterSummary>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SalesReport {
     pub total_sales: f64,
@@ -153,9 +225,21 @@ pub struct SalesReport {
     pub total_account_collected: f64,
     pub outstanding_balance: f64,
     pub top_debtors: Vec<crate::models::customer::Customer>,
+    /// Completed sales per calendar day, even when a session spans several days.
+    pub days: Vec<SalesDay>,
+    /// Completed sales per department.
+    pub departments: Vec<CategorySales>,
     pub sales: Vec<Sale>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct SalesDay {
+    /// `YYYY-MM-DD` taken from the sale's `created_at`.
+    pub date: String,
+    pub total_sales: f64,
+    pub total_transactions: i64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct TopProduct {
     pub product_id: i64,
@@ -164,8 +248,60 @@ pub struct TopProduct {
     pub total_revenue: f64,
 }
 
+/// Sales rolled up to a category level; 0 groups by department.
+#[derive(Debug, Clone, Serialize)]
+pub struct CategorySales {
+    /// `None` groups products without a category.
+    pub category_id: Option<i64>,
+    pub category_name: Option<String>,
+    pub total_quantity: f64,
+    pub total_revenue: f64,
+    pub total_transactions: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaleSortField {
+    #[default]
+    CreatedAt,
+    Total,
+    Folio,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Filters for `search_sales`. Every field is optional and they combine with AND.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SaleSearchCriteria {
+    /// Free text matched against folio, customer, cashier and product names.
+    pub query: Option<String>,
+    /// Sale id as printed on the ticket.
+    pub folio: Option<i64>,
+    pub product_id: Option<i64>,
+    pub customer_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub register_id: Option<i64>,
+    pub payment_method: Option<String>,
+    pub status: Option<SaleStatus>,
+    pub min_total: Option<f64>,
+    pub max_total: Option<f64>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub sort_by: SaleSortField,
+    pub sort_direction: SortDirection,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DateRangeRequest {
     pub start_date: String,
     pub end_date: String,
+    #[serde(default)]
+    pub register_id: Option<i64>,
 }
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoredValueKind {
+    /// Card sold at the counter and identified by its code.
+    GiftCard,
+    /// Balance in favor of a customer, funded by refunds.
+    StoreCredit,
+}
+
+impl StoredValueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GiftCard => "gift_card",
+            Self::StoreCredit => "store_credit",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "gift_card" => Some(Self::GiftCard),
+            "store_credit" => Some(Self::StoreCredit),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for StoredValueKind {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let value = String::column_result(value)?;
+        Self::parse(&value).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(
+                format!("invalid stored value kind: {}", value).into(),
+            )
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for StoredValueKind {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoredValueMovementType {
+    /// Gift card sold; the tenders received are kept on the movement.
+    Issue,
+    /// Balance used as a tender in a sale.
+    Redeem,
+    /// Cancelled sale refunded to the customer's store credit.
+    Refund,
+    /// Balance given back to the instrument when its sale is cancelled.
+    Reversal,
+}
+
+impl StoredValueMovementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Issue => "issue",
+            Self::Redeem => "redeem",
+            Self::Refund => "refund",
+            Self::Reversal => "reversal",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "issue" => Some(Self::Issue),
+            "redeem" => Some(Self::Redeem),
+            "refund" => Some(Self::Refund),
+            "reversal" => Some(Self::Reversal),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for StoredValueMovementType {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let value = String::column_result(value)?;
+        Self::parse(&value).ok_or_else(|| {
+            rusqlite::types::FromSqlError::Other(
+                format!("invalid stored value movement type: {}", value).into(),
+            )
+        })
+    }
+}
+
+impl rusqlite::types::ToSql for StoredValueMovementType {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.as_str().to_string()),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredValueAccount {
+    pub id: i64,
+    pub kind: StoredValueKind,
+    /// Gift card code; store credit accounts are found by customer.
+    pub code: Option<String>,
+    pub customer_id: Option<i64>,
+    pub customer_name: Option<String>,
+    pub balance: f64,
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredValueMovement {
+    pub id: i64,
+    pub account_id: i64,
+    pub sale_id: Option<i64>,
+    pub cash_register_session_id: i64,
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub movement_type: StoredValueMovementType,
+    /// Positive when balance is added, negative when it is used.
+    pub amount: f64,
+    pub payment_cash_mxn: f64,
+    pub payment_cash_usd: f64,
+    pub payment_transfer: f64,
+    pub exchange_rate: Option<f64>,
+    pub notes: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssueGiftCardRequest {
+    pub code: String,
+    pub customer_id: Option<i64>,
+    pub cash_register_session_id: i64,
+    pub user_id: i64,
+    pub payment_cash_mxn: f64,
+    pub payment_cash_usd: f64,
+    pub payment_transfer: f64,
+    pub notes: Option<String>,
+}
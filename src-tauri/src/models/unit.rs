@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Unit {
+    pub id: i64,
+    pub name: String,
+    /// `None` for base units, the ones products keep their stock in.
+    pub base_unit_id: Option<i64>,
+    pub base_unit: Option<String>,
+    /// Base units in one of this unit: 0.001 for `g` of `kg`, 12 for a `caja`
+    /// of `pieza`. Always 1 for base units.
+    pub factor: f64,
+    /// Base units only: products in it may be sold in fractions (a granel).
+    pub fractional: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUnitRequest {
+    pub name: String,
+    /// Makes the new unit a conversion of an existing base unit.
+    pub base_unit_id: Option<i64>,
+    pub factor: Option<f64>,
+    #[serde(default)]
+    pub fractional: bool,
+}
+
+/// Names are fixed because products and sale lines store them as text.
+#[derive(Debug, Deserialize)]
+pub struct UpdateUnitRequest {
+    pub id: i64,
+    pub factor: Option<f64>,
+    pub fractional: Option<bool>,
+}
@@ -87,7 +87,8 @@ impl CustomerRepository for SqliteCustomerRepository<'_> {
 
 const CUSTOMER_SELECT: &str = "SELECT c.id, c.name, c.phone, c.notes, c.credit_limit, c.active, \
     COALESCE((SELECT SUM(m.amount) FROM customer_account_movements m WHERE m.customer_id = c.id), 0), \
-    c.created_at, c.updated_at FROM customers c";
+    c.created_at, c.updated_at, c.price_list_id, \
+    (SELECT pl.name FROM price_lists pl WHERE pl.id = c.price_list_id) FROM customers c";
 
 fn row_to_customer(row: &rusqlite::Row) -> rusqlite::Result<Customer> {
     Ok(Customer {
@@ -96,6 +97,8 @@ fn row_to_customer(row: &rusqlite::Row) -> rusqlite::Result<Customer> {
         phone: row.get(2)?,
         notes: row.get(3)?,
         credit_limit: money::round2(row.get(4)?),
+        price_list_id: row.get(9)?,
+        price_list_name: row.get(10)?,
         active: row.get::<_, i64>(5)? != 0,
         balance: money::round2(row.get(6)?),
         created_at: row.get(7)?,
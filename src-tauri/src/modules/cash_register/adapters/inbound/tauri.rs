@@ -1,11 +1,19 @@
 use crate::infrastructure::sqlite::Database;
+use crate::models::cash_over_short::{
+    CashOverShortReport, CashOverShortReportRequest, CashShortage, RecordCashShortageRequest,
+};
 use crate::models::cash_register::{
-    CashRegisterSession, CashRegisterSummary, CloseCashRegisterRequest, DateRangeRequest,
-    OpenCashRegisterRequest,
+    ApproveCashCloseRequest, CashDenominations, CashHandover, CashMovement, CashRegisterSession,
+    CashRegisterSummary, CashSessionReopening, CloseCashRegisterRequest, CreateCashMovementRequest,
+    DateRangeRequest, HandoverCashRegisterRequest, OpenCashRegisterRequest,
+    ReopenCashRegisterSessionRequest, StaleSessionCheck,
 };
+use crate::models::cash_register_report::{CashRegisterReport, GenerateCashReportRequest};
 use crate::modules::cash_register::{
     adapters::outbound::sqlite::SqliteCashRegisterRepository, application,
 };
+use crate::modules::identity::adapters::outbound::sqlite::SqliteUserRepository;
+use crate::modules::registers::adapters::outbound::sqlite::SqliteRegisterRepository;
 use crate::shared::error::AppResult;
 use crate::shared::pagination::PaginatedResult;
 use tauri::State;
@@ -33,8 +41,11 @@ pub fn get_cash_register_session(db: State<Database>, id: i64) -> AppResult<Cash
     application::get_session(&SqliteCashRegisterRepository::new(&db), id)
 }
 #[tauri::command]
-pub fn get_open_cash_register(db: State<Database>) -> AppResult<Option<CashRegisterSession>> {
-    application::get_open(&SqliteCashRegisterRepository::new(&db))
+pub fn get_open_cash_register(
+    db: State<Database>,
+    register_id: Option<i64>,
+) -> AppResult<Option<CashRegisterSession>> {
+    application::get_open(&SqliteCashRegisterRepository::new(&db), register_id)
 }
 #[tauri::command]
 pub fn get_open_cash_register_by_user(
@@ -48,7 +59,11 @@ pub fn open_cash_register(
     db: State<Database>,
     request: OpenCashRegisterRequest,
 ) -> AppResult<CashRegisterSession> {
-    application::open_session(&SqliteCashRegisterRepository::new(&db), request)
+    application::open_session(
+        &SqliteCashRegisterRepository::new(&db),
+        &SqliteRegisterRepository::new(&db),
+        request,
+    )
 }
 #[tauri::command]
 pub fn close_cash_register(
@@ -58,9 +73,122 @@ pub fn close_cash_register(
     application::close_session(&SqliteCashRegisterRepository::new(&db), request)
 }
 #[tauri::command]
+pub fn submit_blind_cash_register_close(
+    db: State<Database>,
+    request: CloseCashRegisterRequest,
+) -> AppResult<CashRegisterSession> {
+    application::submit_blind_close(&SqliteCashRegisterRepository::new(&db), request)
+}
+#[tauri::command]
+pub fn approve_cash_register_close(
+    db: State<Database>,
+    request: ApproveCashCloseRequest,
+) -> AppResult<CashRegisterSummary> {
+    application::approve_close(
+        &SqliteCashRegisterRepository::new(&db),
+        &SqliteUserRepository::new(&db),
+        request,
+    )
+}
+#[tauri::command]
+pub fn reopen_cash_register_session(
+    db: State<Database>,
+    request: ReopenCashRegisterSessionRequest,
+) -> AppResult<CashRegisterSession> {
+    application::reopen_session(
+        &SqliteCashRegisterRepository::new(&db),
+        &SqliteUserRepository::new(&db),
+        request,
+    )
+}
+#[tauri::command]
+pub fn get_cash_session_reopenings(
+    db: State<Database>,
+    session_id: i64,
+) -> AppResult<Vec<CashSessionReopening>> {
+    application::get_reopenings(&SqliteCashRegisterRepository::new(&db), session_id)
+}
+#[tauri::command]
+pub fn get_cash_over_short_report(
+    db: State<Database>,
+    request: CashOverShortReportRequest,
+) -> AppResult<CashOverShortReport> {
+    application::get_over_short_report(&SqliteCashRegisterRepository::new(&db), request)
+}
+#[tauri::command]
+pub fn record_cash_shortage(
+    db: State<Database>,
+    request: RecordCashShortageRequest,
+) -> AppResult<CashShortage> {
+    application::record_shortage(
+        &SqliteCashRegisterRepository::new(&db),
+        &SqliteUserRepository::new(&db),
+        request,
+    )
+}
+#[tauri::command]
+pub fn handover_cash_register(
+    db: State<Database>,
+    request: HandoverCashRegisterRequest,
+) -> AppResult<CashHandover> {
+    application::handover_session(
+        &SqliteCashRegisterRepository::new(&db),
+        &SqliteUserRepository::new(&db),
+        request,
+    )
+}
+#[tauri::command]
+pub fn get_cash_handovers(db: State<Database>, session_id: i64) -> AppResult<Vec<CashHandover>> {
+    application::get_handovers(&SqliteCashRegisterRepository::new(&db), session_id)
+}
+#[tauri::command]
+pub fn get_pending_cash_register_reviews(
+    db: State<Database>,
+) -> AppResult<Vec<CashRegisterSession>> {
+    application::get_pending_reviews(&SqliteCashRegisterRepository::new(&db))
+}
+#[tauri::command]
+pub fn get_stale_cash_register_sessions(db: State<Database>) -> AppResult<StaleSessionCheck> {
+    application::detect_stale_sessions(&SqliteCashRegisterRepository::new(&db))
+}
+#[tauri::command]
 pub fn get_cash_register_summary(
     db: State<Database>,
     session_id: i64,
 ) -> AppResult<CashRegisterSummary> {
     application::get_summary(&SqliteCashRegisterRepository::new(&db), session_id)
 }
+#[tauri::command]
+pub fn get_cash_movements(db: State<Database>, session_id: i64) -> AppResult<Vec<CashMovement>> {
+    application::get_movements(&SqliteCashRegisterRepository::new(&db), session_id)
+}
+#[tauri::command]
+pub fn record_cash_movement(
+    db: State<Database>,
+    request: CreateCashMovementRequest,
+) -> AppResult<CashMovement> {
+    application::record_movement(&SqliteCashRegisterRepository::new(&db), request)
+}
+#[tauri::command]
+pub fn get_cash_denominations(db: State<Database>) -> AppResult<CashDenominations> {
+    application::get_denominations(&SqliteCashRegisterRepository::new(&db))
+}
+#[tauri::command]
+pub fn generate_cash_register_report(
+    db: State<Database>,
+    request: GenerateCashReportRequest,
+) -> AppResult<CashRegisterReport> {
+    application::generate_report(&SqliteCashRegisterRepository::new(&db), request)
+}
+#[tauri::command]
+pub fn get_cash_register_reports(
+    db: State<Database>,
+    session_id: Option<i64>,
+    register_id: Option<i64>,
+) -> AppResult<Vec<CashRegisterReport>> {
+    application::get_reports(
+        &SqliteCashRegisterRepository::new(&db),
+        session_id,
+        register_id,
+    )
+}
@@ -1,13 +1,24 @@
 use crate::infrastructure::sqlite::Database;
-use crate::models::cash_register::{CashRegisterSession, CashRegisterSummary, SessionStatus};
+use crate::models::cash_over_short::{CashShortage, RecordCashShortageRequest};
+use crate::models::cash_register::{
+    CashCloseReview, CashCountLine, CashCountType, CashDenominations, CashHandover, CashMovement,
+    CashMovementType, CashRegisterSession, CashRegisterSummary, CashSessionReopening,
+    CreateCashMovementRequest, DenominationCount, SessionStatus, StaleSessionPolicy,
+};
+use crate::models::cash_register_report::{
+    CashRegisterReport, CashRegisterReportData, CashReportType, CashierSalesTotal,
+    CategorySalesTotal, ReportTally, TenderTotal,
+};
 use crate::models::customer::CustomerMovementType;
 use crate::models::sale::SaleStatus;
+use crate::models::stored_value::StoredValueMovementType;
 use crate::modules::cash_register::application::{
-    CashRegisterRepository, CashRegisterSessionLookup,
+    parse_denominations, CashRegisterRepository, CashRegisterSessionLookup, NewCashHandover,
+    NewCashRegisterSession,
 };
 use crate::shared::error::{AppError, AppResult};
 use crate::shared::money;
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 
 pub struct SqliteCashRegisterRepository<'db> {
     db: &'db Database,
@@ -32,34 +43,166 @@ impl CashRegisterRepository for SqliteCashRegisterRepository<'_> {
     fn find_any_open(&self) -> AppResult<Option<CashRegisterSession>> {
         find_any_open(self.db)
     }
+    fn find_open_by_register(&self, register_id: i64) -> AppResult<Option<CashRegisterSession>> {
+        let conn = self.db.conn.lock()?;
+        query_open_by_register(&conn, register_id)
+    }
     fn find_by_date_range_paginated(
         &self,
         start_date: &str,
         end_date: &str,
+        register_id: Option<i64>,
         page: i64,
         page_size: i64,
     ) -> AppResult<(Vec<CashRegisterSession>, i64)> {
-        find_by_date_range_paginated(self.db, start_date, end_date, page, page_size)
+        find_by_date_range_paginated(self.db, start_date, end_date, register_id, page, page_size)
     }
     fn open_session(
         &self,
-        user_id: i64,
-        opening_amount: f64,
-        exchange_rate: Option<f64>,
+        session: &NewCashRegisterSession,
+        count: &[DenominationCount],
     ) -> AppResult<CashRegisterSession> {
-        open_session(self.db, user_id, opening_amount, exchange_rate)
+        open_session(self.db, session, count)
     }
     fn close_session(
         &self,
         session_id: i64,
+        status: SessionStatus,
         closing_cash_mxn: f64,
         closing_cash_usd: f64,
+        count: &[DenominationCount],
     ) -> AppResult<CashRegisterSummary> {
-        close_session(self.db, session_id, closing_cash_mxn, closing_cash_usd)
+        close_session(
+            self.db,
+            session_id,
+            status,
+            closing_cash_mxn,
+            closing_cash_usd,
+            count,
+        )
+    }
+    fn approve_close(
+        &self,
+        session_id: i64,
+        reviewer_id: i64,
+        notes: &str,
+    ) -> AppResult<CashRegisterSummary> {
+        approve_close(self.db, session_id, reviewer_id, notes)
+    }
+    fn find_by_status(&self, status: SessionStatus) -> AppResult<Vec<CashRegisterSession>> {
+        find_by_status(self.db, status)
+    }
+    fn load_denominations(&self) -> AppResult<CashDenominations> {
+        let conn = self.db.conn.lock()?;
+        load_denominations(&conn)
+    }
+    fn blind_close_enabled(&self) -> AppResult<bool> {
+        let conn = self.db.conn.lock()?;
+        Ok(setting(&conn, "cash_blind_close")?.as_deref() == Some("true"))
+    }
+    fn load_stale_session_policy(&self) -> AppResult<(StaleSessionPolicy, i64)> {
+        let conn = self.db.conn.lock()?;
+        stale_session_policy(&conn)
+    }
+    fn find_stale_sessions(&self, cutoff_hours: i64) -> AppResult<Vec<CashRegisterSession>> {
+        find_stale_sessions(self.db, cutoff_hours)
+    }
+    fn auto_close_session(&self, session_id: i64) -> AppResult<CashRegisterSession> {
+        auto_close_session(self.db, session_id)
+    }
+    fn create_report(
+        &self,
+        session_id: i64,
+        user_id: i64,
+        report_type: CashReportType,
+        hide_expectations: bool,
+    ) -> AppResult<CashRegisterReport> {
+        create_report(self.db, session_id, user_id, report_type, hide_expectations)
+    }
+    fn create_handover(
+        &self,
+        handover: &NewCashHandover,
+        count: &[DenominationCount],
+    ) -> AppResult<CashHandover> {
+        create_handover(self.db, handover, count)
+    }
+    fn find_handovers(&self, session_id: i64) -> AppResult<Vec<CashHandover>> {
+        find_handovers(self.db, session_id)
+    }
+    fn find_handover(&self, id: i64) -> AppResult<Option<CashHandover>> {
+        find_handover(self.db, id)
+    }
+    fn reopen_session(
+        &self,
+        session_id: i64,
+        user_id: i64,
+        reason: &str,
+    ) -> AppResult<CashRegisterSession> {
+        reopen_session(self.db, session_id, user_id, reason)
+    }
+    fn find_reopenings(&self, session_id: i64) -> AppResult<Vec<CashSessionReopening>> {
+        find_reopenings(self.db, session_id)
+    }
+    fn find_closed_summaries(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+    ) -> AppResult<Vec<CashRegisterSummary>> {
+        find_closed_summaries(self.db, start, end, register_id)
+    }
+    fn load_over_short_tolerance(&self) -> AppResult<(f64, f64)> {
+        let conn = self.db.conn.lock()?;
+        let tolerance = |key: &str| -> AppResult<f64> {
+            Ok(setting(&conn, key)?
+                .and_then(|value| value.trim().parse::<f64>().ok())
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .unwrap_or(0.0))
+        };
+        Ok((
+            tolerance("cash_over_short_tolerance_mxn")?,
+            tolerance("cash_over_short_tolerance_usd")?,
+        ))
+    }
+    fn find_shortage(&self, session_id: i64, cashier_id: i64) -> AppResult<Option<CashShortage>> {
+        let conn = self.db.conn.lock()?;
+        query_shortage(&conn, session_id, cashier_id)
+    }
+    fn create_shortage(
+        &self,
+        request: &RecordCashShortageRequest,
+        cashier_id: i64,
+        notes: Option<&str>,
+    ) -> AppResult<CashShortage> {
+        create_shortage(self.db, request, cashier_id, notes)
+    }
+    fn find_reports(
+        &self,
+        session_id: Option<i64>,
+        register_id: Option<i64>,
+    ) -> AppResult<Vec<CashRegisterReport>> {
+        find_reports(self.db, session_id, register_id)
+    }
+    fn find_report(&self, id: i64) -> AppResult<Option<CashRegisterReport>> {
+        let conn = self.db.conn.lock()?;
+        query_report(&conn, id)
     }
     fn get_summary(&self, session_id: i64) -> AppResult<CashRegisterSummary> {
         get_summary(self.db, session_id)
     }
+    fn find_movements(&self, session_id: i64) -> AppResult<Vec<CashMovement>> {
+        find_movements(self.db, session_id)
+    }
+    fn find_movement(&self, id: i64) -> AppResult<Option<CashMovement>> {
+        find_movement(self.db, id)
+    }
+    fn create_movement(
+        &self,
+        request: &CreateCashMovementRequest,
+        reason: &str,
+    ) -> AppResult<CashMovement> {
+        create_movement(self.db, request, reason)
+    }
 }
 
 impl CashRegisterSessionLookup for SqliteCashRegisterRepository<'_> {
@@ -71,15 +214,31 @@ impl CashRegisterSessionLookup for SqliteCashRegisterRepository<'_> {
 const SELECT_QUERY: &str = "\
     SELECT cr.id, cr.user_id, u.full_name, cr.opening_amount, cr.closing_amount, \
             cr.closing_cash_mxn, cr.closing_cash_usd, cr.exchange_rate, \
-            cr.status, cr.opened_at, cr.closed_at \
-    FROM cash_register_sessions cr JOIN users u ON cr.user_id = u.id";
+            cr.status, cr.opened_at, cr.closed_at, cr.opening_cash_usd, \
+            cr.register_id, rg.name, cr.reviewed_by, rv.full_name, cr.review_notes, \
+            cr.reviewed_at, cr.count_pending \
+    FROM cash_register_sessions cr JOIN users u ON cr.user_id = u.id \
+    LEFT JOIN registers rg ON cr.register_id = rg.id \
+    LEFT JOIN users rv ON cr.reviewed_by = rv.id";
 
 fn row_to_session(row: &rusqlite::Row) -> rusqlite::Result<CashRegisterSession> {
+    let review = match row.get::<_, Option<i64>>(14)? {
+        Some(user_id) => Some(CashCloseReview {
+            user_id,
+            user_name: row.get(15)?,
+            notes: row.get::<_, Option<String>>(16)?.unwrap_or_default(),
+            reviewed_at: row.get::<_, Option<String>>(17)?.unwrap_or_default(),
+        }),
+        None => None,
+    };
     Ok(CashRegisterSession {
         id: row.get(0)?,
+        register_id: row.get(12)?,
+        register_name: row.get(13)?,
         user_id: row.get(1)?,
         user_name: row.get(2)?,
         opening_amount: row.get(3)?,
+        opening_cash_usd: row.get(11)?,
         closing_amount: row.get(4)?,
         closing_cash_mxn: row.get(5)?,
         closing_cash_usd: row.get(6)?,
@@ -87,6 +246,8 @@ fn row_to_session(row: &rusqlite::Row) -> rusqlite::Result<CashRegisterSession>
         status: row.get(8)?, // FromSql convierte TEXT → SessionStatus automáticamente
         opened_at: row.get(9)?,
         closed_at: row.get(10)?,
+        review,
+        count_pending: row.get(18)?,
         total_sales: None,
         total_transactions: None,
     })
@@ -106,9 +267,14 @@ pub fn find_all(db: &Database) -> AppResult<Vec<CashRegisterSession>> {
 
 pub fn find_by_id(db: &Database, id: i64) -> AppResult<Option<CashRegisterSession>> {
     let conn = db.conn.lock()?;
+    query_session(&conn, id)
+}
+
+fn query_session(conn: &Connection, id: i64) -> AppResult<Option<CashRegisterSession>> {
     let query = format!("{} WHERE cr.id = ?1", SELECT_QUERY);
-    let result = conn.query_row(&query, params![id], row_to_session).ok();
-    Ok(result)
+    Ok(conn
+        .query_row(&query, params![id], row_to_session)
+        .optional()?)
 }
 
 pub fn find_open_by_user(db: &Database, user_id: i64) -> AppResult<Option<CashRegisterSession>> {
@@ -128,6 +294,7 @@ pub fn find_by_date_range_paginated(
     db: &Database,
     start_date: &str,
     end_date: &str,
+    register_id: Option<i64>,
     page: i64,
     page_size: i64,
 ) -> AppResult<(Vec<CashRegisterSession>, i64)> {
@@ -136,13 +303,16 @@ pub fn find_by_date_range_paginated(
     let total: i64 = conn.query_row(
         "SELECT COUNT(*) FROM cash_register_sessions cr \
             WHERE cr.opened_at >= ?1 \
-            AND ((cr.status = ?3 AND cr.closed_at <= ?2) \
-            OR (cr.status = ?4 AND cr.opened_at  <= ?2))",
+            AND ((cr.status IN (?3, ?6) AND cr.closed_at <= ?2) \
+            OR (cr.status = ?4 AND cr.opened_at  <= ?2)) \
+            AND (?5 IS NULL OR cr.register_id = ?5)",
         params![
             start_date,
             end_date,
             SessionStatus::Closed,
-            SessionStatus::Open
+            SessionStatus::Open,
+            register_id,
+            SessionStatus::PendingReview
         ],
         |row| row.get(0),
     )?;
@@ -150,8 +320,9 @@ pub fn find_by_date_range_paginated(
     let offset = (page - 1) * page_size;
     let query = format!(
         "{} WHERE cr.opened_at >= ?1 \
-            AND ((cr.status = ?3 AND cr.closed_at <= ?2) \
+            AND ((cr.status IN (?3, ?8) AND cr.closed_at <= ?2) \
             OR (cr.status = ?4 AND cr.opened_at  <= ?2)) \
+            AND (?7 IS NULL OR cr.register_id = ?7) \
             ORDER BY cr.id DESC LIMIT ?5 OFFSET ?6",
         SELECT_QUERY
     );
@@ -165,7 +336,9 @@ pub fn find_by_date_range_paginated(
                 SessionStatus::Closed,
                 SessionStatus::Open,
                 page_size,
-                offset
+                offset,
+                register_id,
+                SessionStatus::PendingReview
             ],
             row_to_session,
         )?
@@ -174,6 +347,16 @@ pub fn find_by_date_range_paginated(
     Ok((sessions, total))
 }
 
+pub fn find_by_status(db: &Database, status: SessionStatus) -> AppResult<Vec<CashRegisterSession>> {
+    let conn = db.conn.lock()?;
+    let query = format!("{} WHERE cr.status = ?1 ORDER BY cr.id", SELECT_QUERY);
+    let mut stmt = conn.prepare(&query)?;
+    let sessions = stmt
+        .query_map(params![status], row_to_session)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(sessions)
+}
+
 pub fn find_any_open(db: &Database) -> AppResult<Option<CashRegisterSession>> {
     let conn = db.conn.lock()?;
     let query = format!("{} WHERE cr.status = ?1 LIMIT 1", SELECT_QUERY);
@@ -183,36 +366,208 @@ pub fn find_any_open(db: &Database) -> AppResult<Option<CashRegisterSession>> {
     Ok(result)
 }
 
+fn query_open_by_register(
+    conn: &Connection,
+    register_id: i64,
+) -> AppResult<Option<CashRegisterSession>> {
+    let query = format!(
+        "{} WHERE cr.register_id = ?1 AND cr.status = ?2",
+        SELECT_QUERY
+    );
+    Ok(conn
+        .query_row(
+            &query,
+            params![register_id, SessionStatus::Open],
+            row_to_session,
+        )
+        .optional()?)
+}
+
 pub fn open_session(
     db: &Database,
-    user_id: i64,
-    opening_amount: f64,
-    exchange_rate: Option<f64>,
+    session: &NewCashRegisterSession,
+    count: &[DenominationCount],
 ) -> AppResult<CashRegisterSession> {
-    if find_any_open(db)?.is_some() {
-        return Err(AppError::Conflict(
-            "Ya hay una caja abierta. Debe cerrarse antes de abrir otra.".to_string(),
-        ));
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    if query_open_by_register(&tx, session.register_id)?.is_some() {
+        return Err(register_already_open());
     }
-
-    let conn = db.conn.lock()?;
-    conn.execute(
-        "INSERT INTO cash_register_sessions (user_id, opening_amount, exchange_rate, status) \
-            VALUES (?1, ?2, ?3, ?4)",
+    // The partial unique index on open sessions backs the check above.
+    tx.execute(
+        "INSERT INTO cash_register_sessions \
+            (user_id, register_id, opening_amount, opening_cash_usd, exchange_rate, status) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         params![
-            user_id,
-            money::round2(opening_amount),
-            exchange_rate.map(money::round2),
+            session.user_id,
+            session.register_id,
+            money::round2(session.opening_amount),
+            money::round2(session.opening_cash_usd),
+            session.exchange_rate.map(money::round2),
             SessionStatus::Open,
         ],
-    )?;
+    )
+    .map_err(|error| match error.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ConstraintViolation) => register_already_open(),
+        _ => error.into(),
+    })?;
 
-    let id = conn.last_insert_rowid();
+    let id = tx.last_insert_rowid();
+    insert_count(&tx, id, CashCountType::Opening, count)?;
+    tx.commit()?;
     drop(conn);
     find_by_id(db, id)?
         .ok_or_else(|| AppError::NotFound("Failed to retrieve created session".to_string()))
 }
 
+fn setting(conn: &Connection, key: &str) -> AppResult<Option<String>> {
+    Ok(conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+            row.get::<_, Option<String>>(0)
+        })
+        .optional()?
+        .flatten())
+}
+
+fn stale_session_policy(conn: &Connection) -> AppResult<(StaleSessionPolicy, i64)> {
+    let policy = setting(conn, "cash_stale_session_policy")?
+        .and_then(|value| StaleSessionPolicy::parse(value.trim()))
+        .unwrap_or_default();
+    let cutoff_hours = setting(conn, "cash_stale_session_hours")?
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(0);
+    Ok((policy, cutoff_hours))
+}
+
+/// `opened_at` is local time, so the cutoff is compared in SQL against local now.
+const STALE_CONDITION: &str = "cr.status = 'open' \
+    AND cr.opened_at < datetime('now', 'localtime', '-' || ?1 || ' hours')";
+
+pub fn find_stale_sessions(
+    db: &Database,
+    cutoff_hours: i64,
+) -> AppResult<Vec<CashRegisterSession>> {
+    let conn = db.conn.lock()?;
+    let query = format!(
+        "{} WHERE {} ORDER BY cr.opened_at",
+        SELECT_QUERY, STALE_CONDITION
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let sessions = stmt
+        .query_map(params![cutoff_hours], row_to_session)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(sessions)
+}
+
+/// True when the `block` policy is active and the session is open past the cutoff.
+pub fn stale_session_blocks_sales(conn: &Connection, session_id: i64) -> AppResult<bool> {
+    let (policy, cutoff_hours) = stale_session_policy(conn)?;
+    if policy != StaleSessionPolicy::Block || cutoff_hours <= 0 {
+        return Ok(false);
+    }
+    Ok(conn.query_row(
+        &format!(
+            "SELECT EXISTS(SELECT 1 FROM cash_register_sessions cr WHERE {} AND cr.id = ?2)",
+            STALE_CONDITION
+        ),
+        params![cutoff_hours, session_id],
+        |row| row.get(0),
+    )?)
+}
+
+/// Moves a forgotten session into review without a count; it is counted later
+/// through the regular close.
+pub fn auto_close_session(db: &Database, session_id: i64) -> AppResult<CashRegisterSession> {
+    let conn = db.conn.lock()?;
+    conn.execute(
+        "UPDATE cash_register_sessions \
+        SET status = ?1, closed_at = datetime('now', 'localtime'), count_pending = 1 \
+        WHERE id = ?2 AND status = ?3",
+        params![
+            SessionStatus::PendingReview,
+            session_id,
+            SessionStatus::Open
+        ],
+    )?;
+    if conn.changes() == 0 {
+        return Err(AppError::NotFound(
+            "Sesión no encontrada o ya está cerrada".to_string(),
+        ));
+    }
+    query_session(&conn, session_id)?
+        .ok_or_else(|| AppError::NotFound("Sesión de caja no encontrada".to_string()))
+}
+
+fn register_already_open() -> AppError {
+    AppError::Conflict(
+        "Esta caja ya tiene una sesión abierta. Debe cerrarse antes de abrir otra.".to_string(),
+    )
+}
+
+pub fn load_denominations(conn: &Connection) -> AppResult<CashDenominations> {
+    let setting = |key: &str| -> AppResult<Vec<f64>> {
+        let value: Option<String> = conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?
+            .flatten();
+        Ok(parse_denominations(value.as_deref().unwrap_or_default()))
+    };
+    Ok(CashDenominations {
+        mxn: setting("cash_denominations_mxn")?,
+        usd: setting("cash_denominations_usd")?,
+    })
+}
+
+fn insert_count(
+    conn: &Connection,
+    session_id: i64,
+    count_type: CashCountType,
+    count: &[DenominationCount],
+) -> AppResult<()> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO cash_denomination_counts \
+            (cash_register_session_id, count_type, currency, denomination, quantity) \
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for line in count {
+        stmt.execute(params![
+            session_id,
+            count_type,
+            line.currency,
+            money::round2(line.denomination),
+            line.quantity,
+        ])?;
+    }
+    Ok(())
+}
+
+fn query_count(
+    conn: &Connection,
+    session_id: i64,
+    count_type: CashCountType,
+) -> AppResult<Vec<CashCountLine>> {
+    let mut stmt = conn.prepare(
+        "SELECT currency, denomination, quantity FROM cash_denomination_counts \
+            WHERE cash_register_session_id = ?1 AND count_type = ?2 \
+            ORDER BY currency, denomination DESC",
+    )?;
+    let lines = stmt
+        .query_map(params![session_id, count_type], |row| {
+            let denomination: f64 = row.get(1)?;
+            let quantity: i64 = row.get(2)?;
+            Ok(CashCountLine {
+                currency: row.get(0)?,
+                denomination,
+                quantity,
+                subtotal: money::mul_money(denomination, quantity as f64),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(lines)
+}
+
 struct SessionSalesBreakdown {
     total_sales: f64,
     total_transactions: i64,
@@ -223,6 +578,17 @@ struct SessionSalesBreakdown {
     account_payments_cash_mxn: f64,
     account_payments_cash_usd: f64,
     account_payments_transfer: f64,
+    stored_value_cash_mxn: f64,
+    stored_value_cash_usd: f64,
+    stored_value_transfer: f64,
+    pay_ins_mxn: f64,
+    pay_ins_usd: f64,
+    pay_outs_mxn: f64,
+    pay_outs_usd: f64,
+    safe_drops_mxn: f64,
+    safe_drops_usd: f64,
+    opening_count: Vec<CashCountLine>,
+    closing_count: Vec<CashCountLine>,
 }
 
 fn query_sales_breakdown(
@@ -251,6 +617,17 @@ fn query_sales_breakdown(
                 account_payments_cash_mxn: 0.0,
                 account_payments_cash_usd: 0.0,
                 account_payments_transfer: 0.0,
+                stored_value_cash_mxn: 0.0,
+                stored_value_cash_usd: 0.0,
+                stored_value_transfer: 0.0,
+                pay_ins_mxn: 0.0,
+                pay_ins_usd: 0.0,
+                pay_outs_mxn: 0.0,
+                pay_outs_usd: 0.0,
+                safe_drops_mxn: 0.0,
+                safe_drops_usd: 0.0,
+                opening_count: Vec::new(),
+                closing_count: Vec::new(),
             })
         },
     )?;
@@ -259,6 +636,28 @@ fn query_sales_breakdown(
         "SELECT COALESCE(SUM(payment_cash_mxn), 0), COALESCE(SUM(payment_cash_usd), 0), COALESCE(SUM(payment_transfer), 0) FROM customer_account_movements WHERE cash_register_session_id = ?1 AND movement_type = ?2",
         params![session_id, CustomerMovementType::AccountPayment], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
     )?;
+    let stored_value: (f64, f64, f64) = conn.query_row(
+        "SELECT COALESCE(SUM(payment_cash_mxn), 0), COALESCE(SUM(payment_cash_usd), 0), \
+            COALESCE(SUM(payment_transfer), 0) FROM stored_value_movements \
+            WHERE cash_register_session_id = ?1 AND movement_type IN (?2, ?3)",
+        params![
+            session_id,
+            StoredValueMovementType::Issue,
+            StoredValueMovementType::Refund
+        ],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+    )?;
+    let movement_totals = |movement_type: CashMovementType| -> rusqlite::Result<(f64, f64)> {
+        conn.query_row(
+            "SELECT COALESCE(SUM(amount_mxn), 0), COALESCE(SUM(amount_usd), 0) \
+                FROM cash_movements WHERE cash_register_session_id = ?1 AND movement_type = ?2",
+            params![session_id, movement_type],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+    };
+    let pay_ins = movement_totals(CashMovementType::PayIn)?;
+    let pay_outs = movement_totals(CashMovementType::PayOut)?;
+    let safe_drops = movement_totals(CashMovementType::SafeDrop)?;
     Ok(SessionSalesBreakdown {
         total_sales: money::round2(row.total_sales),
         total_transactions: row.total_transactions,
@@ -269,6 +668,17 @@ fn query_sales_breakdown(
         account_payments_cash_mxn: money::round2(account_payments.0),
         account_payments_cash_usd: money::round2(account_payments.1),
         account_payments_transfer: money::round2(account_payments.2),
+        stored_value_cash_mxn: money::round2(stored_value.0),
+        stored_value_cash_usd: money::round2(stored_value.1),
+        stored_value_transfer: money::round2(stored_value.2),
+        pay_ins_mxn: money::round2(pay_ins.0),
+        pay_ins_usd: money::round2(pay_ins.1),
+        pay_outs_mxn: money::round2(pay_outs.0),
+        pay_outs_usd: money::round2(pay_outs.1),
+        safe_drops_mxn: money::round2(safe_drops.0),
+        safe_drops_usd: money::round2(safe_drops.1),
+        opening_count: query_count(conn, session_id, CashCountType::Opening)?,
+        closing_count: query_count(conn, session_id, CashCountType::Closing)?,
     })
 }
 
@@ -282,16 +692,28 @@ fn build_summary(
     let expected_mxn = money::sub_money(
         money::add_money(
             session.opening_amount,
-            money::add_money(
+            money::sum_money([
                 breakdown.sales_cash_mxn,
                 breakdown.account_payments_cash_mxn,
-            ),
+                breakdown.stored_value_cash_mxn,
+                breakdown.pay_ins_mxn,
+            ]),
         ),
-        breakdown.total_change_given,
+        money::sum_money([
+            breakdown.total_change_given,
+            breakdown.pay_outs_mxn,
+            breakdown.safe_drops_mxn,
+        ]),
     );
-    let expected_usd = money::add_money(
-        breakdown.sales_cash_usd,
-        breakdown.account_payments_cash_usd,
+    let expected_usd = money::sub_money(
+        money::sum_money([
+            session.opening_cash_usd,
+            breakdown.sales_cash_usd,
+            breakdown.account_payments_cash_usd,
+            breakdown.stored_value_cash_usd,
+            breakdown.pay_ins_usd,
+        ]),
+        money::add_money(breakdown.pay_outs_usd, breakdown.safe_drops_usd),
     );
 
     CashRegisterSummary {
@@ -304,6 +726,15 @@ fn build_summary(
         account_payments_cash_mxn: breakdown.account_payments_cash_mxn,
         account_payments_cash_usd: breakdown.account_payments_cash_usd,
         account_payments_transfer: breakdown.account_payments_transfer,
+        stored_value_cash_mxn: breakdown.stored_value_cash_mxn,
+        stored_value_cash_usd: breakdown.stored_value_cash_usd,
+        stored_value_transfer: breakdown.stored_value_transfer,
+        pay_ins_mxn: breakdown.pay_ins_mxn,
+        pay_ins_usd: breakdown.pay_ins_usd,
+        pay_outs_mxn: breakdown.pay_outs_mxn,
+        pay_outs_usd: breakdown.pay_outs_usd,
+        safe_drops_mxn: breakdown.safe_drops_mxn,
+        safe_drops_usd: breakdown.safe_drops_usd,
         total_change_given: breakdown.total_change_given,
         expected_cash_mxn: expected_mxn,
         expected_cash_usd: expected_usd,
@@ -311,28 +742,35 @@ fn build_summary(
         actual_cash_usd: actual_usd,
         difference_mxn: money::sub_money(actual_mxn, expected_mxn),
         difference_usd: money::sub_money(actual_usd, expected_usd),
+        expectations_hidden: false,
+        opening_count: breakdown.opening_count,
+        closing_count: breakdown.closing_count,
     }
 }
 
 pub fn close_session(
     db: &Database,
     session_id: i64,
+    status: SessionStatus,
     closing_cash_mxn: f64,
     closing_cash_usd: f64,
+    count: &[DenominationCount],
 ) -> AppResult<CashRegisterSummary> {
-    let conn = db.conn.lock()?;
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
 
     let closing_total = money::add_money(closing_cash_mxn, closing_cash_usd);
-    conn.execute(
+    tx.execute(
         "UPDATE cash_register_sessions \
         SET status = ?1, \
             closing_amount = ?2, \
             closing_cash_mxn = ?3, \
             closing_cash_usd = ?4, \
-            closed_at = datetime('now', 'localtime') \
-        WHERE id = ?5 AND status = ?6",
+            closed_at = COALESCE(closed_at, datetime('now', 'localtime')), \
+            count_pending = 0 \
+        WHERE id = ?5 AND (status = ?6 OR count_pending = 1)",
         params![
-            SessionStatus::Closed,
+            status,
             closing_total,
             money::round2(closing_cash_mxn),
             money::round2(closing_cash_usd),
@@ -341,28 +779,1419 @@ pub fn close_session(
         ],
     )?;
 
-    if conn.changes() == 0 {
+    if tx.changes() == 0 {
         return Err(AppError::NotFound(
             "Sesión no encontrada o ya está cerrada".to_string(),
         ));
     }
 
-    let breakdown = query_sales_breakdown(&conn, session_id)?;
-    drop(conn);
+    insert_count(&tx, session_id, CashCountType::Closing, count)?;
+    let session = query_session(&tx, session_id)?
+        .ok_or_else(|| AppError::NotFound("Sesión de caja no encontrada".to_string()))?;
+    let summary = build_summary(session, query_sales_breakdown(&tx, session_id)?);
+    let snapshot =
+        serde_json::to_string(&summary).map_err(|error| AppError::Database(error.to_string()))?;
+    tx.execute(
+        "UPDATE cash_register_sessions SET closing_summary = ?1 WHERE id = ?2",
+        params![snapshot, session_id],
+    )?;
+    tx.commit()?;
+    Ok(summary)
+}
+
+pub fn get_summary(db: &Database, session_id: i64) -> AppResult<CashRegisterSummary> {
+    let conn = db.conn.lock()?;
+    query_summary(&conn, session_id)
+}
 
-    let session = find_by_id(db, session_id)?
+/// Sessions closed with a snapshot answer from it, so later edits to their sales
+/// cannot move the figures; only the session header (status, review) is current.
+fn query_summary(conn: &Connection, session_id: i64) -> AppResult<CashRegisterSummary> {
+    let session = query_session(conn, session_id)?
         .ok_or_else(|| AppError::NotFound("Sesión de caja no encontrada".to_string()))?;
+    let snapshot: Option<String> = conn.query_row(
+        "SELECT closing_summary FROM cash_register_sessions WHERE id = ?1",
+        params![session_id],
+        |row| row.get(0),
+    )?;
+    match snapshot {
+        Some(snapshot) => {
+            let mut summary: CashRegisterSummary = serde_json::from_str(&snapshot)
+                .map_err(|error| AppError::Database(error.to_string()))?;
+            summary.session = session;
+            Ok(summary)
+        }
+        None => Ok(build_summary(
+            session,
+            query_sales_breakdown(conn, session_id)?,
+        )),
+    }
+}
+
+pub fn approve_close(
+    db: &Database,
+    session_id: i64,
+    reviewer_id: i64,
+    notes: &str,
+) -> AppResult<CashRegisterSummary> {
+    let changed = db.conn.lock()?.execute(
+        "UPDATE cash_register_sessions \
+        SET status = ?1, \
+            reviewed_by = ?2, \
+            review_notes = ?3, \
+            reviewed_at = datetime('now', 'localtime') \
+        WHERE id = ?4 AND status = ?5",
+        params![
+            SessionStatus::Closed,
+            reviewer_id,
+            notes,
+            session_id,
+            SessionStatus::PendingReview,
+        ],
+    )?;
+    if changed == 0 {
+        return Err(AppError::Conflict(
+            "La sesión no tiene un cierre pendiente de revisión".to_string(),
+        ));
+    }
+    get_summary(db, session_id)
+}
+
+const MOVEMENT_SELECT: &str = "\
+    SELECT cm.id, cm.cash_register_session_id, cm.user_id, u.full_name, cm.movement_type, \
+            cm.amount_mxn, cm.amount_usd, cm.reason, cm.created_at \
+    FROM cash_movements cm JOIN users u ON cm.user_id = u.id";
 
-    Ok(build_summary(session, breakdown))
+fn row_to_movement(row: &rusqlite::Row) -> rusqlite::Result<CashMovement> {
+    Ok(CashMovement {
+        id: row.get(0)?,
+        cash_register_session_id: row.get(1)?,
+        user_id: row.get(2)?,
+        user_name: row.get(3)?,
+        movement_type: row.get(4)?,
+        amount_mxn: row.get(5)?,
+        amount_usd: row.get(6)?,
+        reason: row.get(7)?,
+        created_at: row.get(8)?,
+    })
 }
 
-pub fn get_summary(db: &Database, session_id: i64) -> AppResult<CashRegisterSummary> {
+pub fn find_movements(db: &Database, session_id: i64) -> AppResult<Vec<CashMovement>> {
+    let conn = db.conn.lock()?;
+    let query = format!(
+        "{} WHERE cm.cash_register_session_id = ?1 ORDER BY cm.id",
+        MOVEMENT_SELECT
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let movements = stmt
+        .query_map(params![session_id], row_to_movement)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(movements)
+}
+
+pub fn find_movement(db: &Database, id: i64) -> AppResult<Option<CashMovement>> {
     let conn = db.conn.lock()?;
-    let breakdown = query_sales_breakdown(&conn, session_id)?;
+    let query = format!("{} WHERE cm.id = ?1", MOVEMENT_SELECT);
+    let result = conn.query_row(&query, params![id], row_to_movement).ok();
+    Ok(result)
+}
+
+pub fn create_movement(
+    db: &Database,
+    request: &CreateCashMovementRequest,
+    reason: &str,
+) -> AppResult<CashMovement> {
+    let conn = db.conn.lock()?;
+    // Guarded on the session status so a movement cannot land on a session closed meanwhile.
+    conn.execute(
+        "INSERT INTO cash_movements \
+            (cash_register_session_id, user_id, movement_type, amount_mxn, amount_usd, reason) \
+            SELECT id, ?2, ?3, ?4, ?5, ?6 FROM cash_register_sessions WHERE id = ?1 AND status = ?7",
+        params![
+            request.session_id,
+            request.user_id,
+            request.movement_type,
+            money::round2(request.amount_mxn),
+            money::round2(request.amount_usd),
+            reason,
+            SessionStatus::Open,
+        ],
+    )?;
+    if conn.changes() == 0 {
+        return Err(AppError::Conflict(
+            "La sesión de caja no está abierta".to_string(),
+        ));
+    }
+    let id = conn.last_insert_rowid();
     drop(conn);
+    find_movement(db, id)?
+        .ok_or_else(|| AppError::NotFound("Movimiento de caja no encontrado".to_string()))
+}
+
+pub fn reopen_session(
+    db: &Database,
+    session_id: i64,
+    user_id: i64,
+    reason: &str,
+) -> AppResult<CashRegisterSession> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    let session = query_session(&tx, session_id)?
+        .ok_or_else(|| AppError::NotFound("Sesión de caja no encontrada".to_string()))?;
+    if session.status == SessionStatus::Open {
+        return Err(AppError::Conflict(
+            "La sesión de caja ya está abierta".to_string(),
+        ));
+    }
 
-    let session = find_by_id(db, session_id)?
+    let window_hours = setting(&tx, "cash_reopen_window_hours")?
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value > 0.0);
+    let Some(window_hours) = window_hours else {
+        return Err(AppError::Conflict(
+            "La reapertura de cortes está desactivada".to_string(),
+        ));
+    };
+    let within_window: bool = tx.query_row(
+        "SELECT COALESCE(closed_at >= datetime('now', 'localtime', ?2), 0) \
+            FROM cash_register_sessions WHERE id = ?1",
+        params![session_id, format!("-{} hours", window_hours)],
+        |row| row.get(0),
+    )?;
+    if !within_window {
+        return Err(AppError::Conflict(format!(
+            "Solo se pueden reabrir cortes de las últimas {} horas",
+            window_hours
+        )));
+    }
+    let newer_session: bool = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM cash_register_sessions WHERE register_id = ?1 AND id > ?2)",
+        params![session.register_id, session_id],
+        |row| row.get(0),
+    )?;
+    if newer_session {
+        return Err(AppError::Conflict(
+            "La caja ya tiene una sesión posterior; no se puede reabrir este corte".to_string(),
+        ));
+    }
+    let has_z_report: bool = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM cash_register_reports \
+            WHERE cash_register_session_id = ?1 AND report_type = ?2)",
+        params![session_id, CashReportType::Z],
+        |row| row.get(0),
+    )?;
+    if has_z_report {
+        return Err(AppError::Conflict(
+            "El corte ya tiene reporte Z y no se puede reabrir".to_string(),
+        ));
+    }
+
+    tx.execute(
+        "INSERT INTO cash_session_reopenings \
+            (cash_register_session_id, user_id, reason, previous_status, previous_closed_at, \
+            previous_summary) \
+            SELECT id, ?2, ?3, status, closed_at, closing_summary \
+            FROM cash_register_sessions WHERE id = ?1",
+        params![session_id, user_id, reason],
+    )?;
+    tx.execute(
+        "DELETE FROM cash_denomination_counts \
+            WHERE cash_register_session_id = ?1 AND count_type = ?2",
+        params![session_id, CashCountType::Closing],
+    )?;
+    tx.execute(
+        "UPDATE cash_register_sessions \
+        SET status = ?1, closing_amount = NULL, closing_cash_mxn = NULL, \
+            closing_cash_usd = NULL, closed_at = NULL, closing_summary = NULL, \
+            reviewed_by = NULL, review_notes = NULL, reviewed_at = NULL, count_pending = 0 \
+        WHERE id = ?2",
+        params![SessionStatus::Open, session_id],
+    )
+    .map_err(|error| match error.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ConstraintViolation) => register_already_open(),
+        _ => error.into(),
+    })?;
+    let session = query_session(&tx, session_id)?
+        .ok_or_else(|| AppError::NotFound("Sesión de caja no encontrada".to_string()))?;
+    tx.commit()?;
+    Ok(session)
+}
+
+pub fn find_reopenings(db: &Database, session_id: i64) -> AppResult<Vec<CashSessionReopening>> {
+    let conn = db.conn.lock()?;
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.cash_register_session_id, r.user_id, u.full_name, r.reason, \
+            r.previous_status, r.previous_closed_at, r.previous_summary, r.created_at \
+            FROM cash_session_reopenings r LEFT JOIN users u ON r.user_id = u.id \
+            WHERE r.cash_register_session_id = ?1 ORDER BY r.id",
+    )?;
+    let reopenings = stmt
+        .query_map(params![session_id], |row| {
+            let previous_summary = row
+                .get::<_, Option<String>>(7)?
+                .map(|snapshot| serde_json::from_str(&snapshot))
+                .transpose()
+                .map_err(|error| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        7,
+                        rusqlite::types::Type::Text,
+                        error.into(),
+                    )
+                })?;
+            Ok(CashSessionReopening {
+                id: row.get(0)?,
+                cash_register_session_id: row.get(1)?,
+                user_id: row.get(2)?,
+                user_name: row.get(3)?,
+                reason: row.get(4)?,
+                previous_status: row.get(5)?,
+                previous_closed_at: row.get(6)?,
+                previous_summary,
+                created_at: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(reopenings)
+}
+
+pub fn find_closed_summaries(
+    db: &Database,
+    start_date: &str,
+    end_date: &str,
+    register_id: Option<i64>,
+) -> AppResult<Vec<CashRegisterSummary>> {
+    let conn = db.conn.lock()?;
+    let ids = conn
+        .prepare(
+            "SELECT id FROM cash_register_sessions \
+                WHERE status = ?1 AND closed_at >= ?2 AND closed_at <= ?3 \
+                AND (?4 IS NULL OR register_id = ?4) ORDER BY closed_at, id",
+        )?
+        .query_map(
+            params![SessionStatus::Closed, start_date, end_date, register_id],
+            |row| row.get::<_, i64>(0),
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    ids.into_iter().map(|id| query_summary(&conn, id)).collect()
+}
+
+fn query_shortage(
+    conn: &Connection,
+    session_id: i64,
+    cashier_id: i64,
+) -> AppResult<Option<CashShortage>> {
+    Ok(conn
+        .query_row(
+            "SELECT s.id, s.cash_register_session_id, s.cashier_id, u.full_name, s.amount_mxn, \
+                s.notes, s.recorded_by, s.created_at \
+                FROM cash_shortages s LEFT JOIN users u ON s.cashier_id = u.id \
+                WHERE s.cash_register_session_id = ?1 AND s.cashier_id = ?2",
+            params![session_id, cashier_id],
+            |row| {
+                Ok(CashShortage {
+                    id: row.get(0)?,
+                    cash_register_session_id: row.get(1)?,
+                    cashier_id: row.get(2)?,
+                    cashier_name: row.get(3)?,
+                    amount_mxn: row.get(4)?,
+                    notes: row.get(5)?,
+                    recorded_by: row.get(6)?,
+                    created_at: row.get(7)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+pub fn create_shortage(
+    db: &Database,
+    request: &RecordCashShortageRequest,
+    cashier_id: i64,
+    notes: Option<&str>,
+) -> AppResult<CashShortage> {
+    let conn = db.conn.lock()?;
+    conn.execute(
+        "INSERT INTO cash_shortages \
+            (cash_register_session_id, cashier_id, amount_mxn, notes, recorded_by) \
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            request.session_id,
+            cashier_id,
+            money::round2(request.amount_mxn),
+            notes,
+            request.user_id,
+        ],
+    )
+    .map_err(|error| match error.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ConstraintViolation) => AppError::Conflict(
+            "El cajero ya tiene un faltante registrado en este corte".to_string(),
+        ),
+        _ => error.into(),
+    })?;
+    query_shortage(&conn, request.session_id, cashier_id)?
+        .ok_or_else(|| AppError::NotFound("Faltante no encontrado".to_string()))
+}
+
+const HANDOVER_SELECT: &str = "\
+    SELECT h.id, h.cash_register_session_id, rg.name, h.from_user_id, fu.full_name, \
+            h.to_user_id, tu.full_name, h.started_at, h.sales_total, h.sales_count, \
+            h.expected_cash_mxn, h.expected_cash_usd, h.counted_cash_mxn, h.counted_cash_usd, \
+            h.notes, h.created_at \
+    FROM cash_handovers h \
+    JOIN cash_register_sessions cr ON h.cash_register_session_id = cr.id \
+    LEFT JOIN registers rg ON cr.register_id = rg.id \
+    LEFT JOIN users fu ON h.from_user_id = fu.id \
+    LEFT JOIN users tu ON h.to_user_id = tu.id";
+
+fn row_to_handover(row: &rusqlite::Row) -> rusqlite::Result<CashHandover> {
+    let expected_cash_mxn: f64 = row.get(10)?;
+    let expected_cash_usd: f64 = row.get(11)?;
+    let counted_cash_mxn: f64 = row.get(12)?;
+    let counted_cash_usd: f64 = row.get(13)?;
+    Ok(CashHandover {
+        id: row.get(0)?,
+        cash_register_session_id: row.get(1)?,
+        register_name: row.get(2)?,
+        from_user_id: row.get(3)?,
+        from_user_name: row.get(4)?,
+        to_user_id: row.get(5)?,
+        to_user_name: row.get(6)?,
+        started_at: row.get(7)?,
+        sales_total: row.get(8)?,
+        sales_count: row.get(9)?,
+        expected_cash_mxn,
+        expected_cash_usd,
+        counted_cash_mxn,
+        counted_cash_usd,
+        difference_mxn: money::sub_money(counted_cash_mxn, expected_cash_mxn),
+        difference_usd: money::sub_money(counted_cash_usd, expected_cash_usd),
+        expectations_hidden: false,
+        count: Vec::new(),
+        notes: row.get(14)?,
+        created_at: row.get(15)?,
+    })
+}
+
+fn query_handover_count(conn: &Connection, handover_id: i64) -> AppResult<Vec<CashCountLine>> {
+    let mut stmt = conn.prepare(
+        "SELECT currency, denomination, quantity FROM cash_handover_counts \
+            WHERE handover_id = ?1 ORDER BY currency, denomination DESC",
+    )?;
+    let lines = stmt
+        .query_map(params![handover_id], |row| {
+            let denomination: f64 = row.get(1)?;
+            let quantity: i64 = row.get(2)?;
+            Ok(CashCountLine {
+                currency: row.get(0)?,
+                denomination,
+                quantity,
+                subtotal: money::mul_money(denomination, quantity as f64),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(lines)
+}
+
+pub fn find_handovers(db: &Database, session_id: i64) -> AppResult<Vec<CashHandover>> {
+    let conn = db.conn.lock()?;
+    let query = format!(
+        "{} WHERE h.cash_register_session_id = ?1 ORDER BY h.id",
+        HANDOVER_SELECT
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let mut handovers = stmt
+        .query_map(params![session_id], row_to_handover)?
+        .collect::<Result<Vec<_>, _>>()?;
+    for handover in &mut handovers {
+        handover.count = query_handover_count(&conn, handover.id)?;
+    }
+    Ok(handovers)
+}
+
+pub fn find_handover(db: &Database, id: i64) -> AppResult<Option<CashHandover>> {
+    let conn = db.conn.lock()?;
+    let query = format!("{} WHERE h.id = ?1", HANDOVER_SELECT);
+    let Some(mut handover) = conn
+        .query_row(&query, params![id], row_to_handover)
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    handover.count = query_handover_count(&conn, id)?;
+    Ok(Some(handover))
+}
+
+/// Snapshots the outgoing cashier's shift and reassigns the session in one transaction.
+/// The shift covers the sales rung since the previous handover, tracked by sale id.
+pub fn create_handover(
+    db: &Database,
+    handover: &NewCashHandover,
+    count: &[DenominationCount],
+) -> AppResult<CashHandover> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    let session = query_session(&tx, handover.session_id)?
         .ok_or_else(|| AppError::NotFound("Sesión de caja no encontrada".to_string()))?;
+    if session.status != SessionStatus::Open || session.user_id != handover.from_user_id {
+        return Err(AppError::Conflict(
+            "La caja ya no está abierta a nombre del cajero que entrega".to_string(),
+        ));
+    }
+
+    let (started_at, previous_sale_id): (String, i64) = tx
+        .query_row(
+            "SELECT created_at, last_sale_id FROM cash_handovers \
+                WHERE cash_register_session_id = ?1 ORDER BY id DESC LIMIT 1",
+            params![session.id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .unwrap_or_else(|| (session.opened_at.clone(), 0));
+    let (last_sale_id, sales_total, sales_count): (i64, f64, i64) = tx.query_row(
+        "SELECT COALESCE(MAX(id), ?2), \
+            COALESCE(SUM(CASE WHEN id > ?2 AND status = ?3 THEN total END), 0), \
+            COUNT(CASE WHEN id > ?2 AND status = ?3 THEN 1 END) \
+            FROM sales WHERE cash_register_session_id = ?1",
+        params![session.id, previous_sale_id, SaleStatus::Completed],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    let breakdown = query_sales_breakdown(&tx, session.id)?;
+    let summary = build_summary(session, breakdown);
+
+    tx.execute(
+        "INSERT INTO cash_handovers \
+            (cash_register_session_id, from_user_id, to_user_id, started_at, last_sale_id, \
+            sales_total, sales_count, expected_cash_mxn, expected_cash_usd, counted_cash_mxn, \
+            counted_cash_usd, notes) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            handover.session_id,
+            handover.from_user_id,
+            handover.to_user_id,
+            started_at,
+            last_sale_id,
+            money::round2(sales_total),
+            sales_count,
+            summary.expected_cash_mxn,
+            summary.expected_cash_usd,
+            money::round2(handover.counted_cash_mxn),
+            money::round2(handover.counted_cash_usd),
+            handover.notes,
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO cash_handover_counts (handover_id, currency, denomination, quantity) \
+                VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for line in count {
+            stmt.execute(params![
+                id,
+                line.currency,
+                money::round2(line.denomination),
+                line.quantity,
+            ])?;
+        }
+    }
+    tx.execute(
+        "UPDATE cash_register_sessions SET user_id = ?1 WHERE id = ?2",
+        params![handover.to_user_id, handover.session_id],
+    )?;
+    tx.commit()?;
+    drop(conn);
+    find_handover(db, id)?
+        .ok_or_else(|| AppError::NotFound("Entrega de caja no encontrada".to_string()))
+}
+
+const REPORT_SELECT: &str = "\
+    SELECT r.id, r.cash_register_session_id, r.report_type, r.z_number, r.user_id, \
+            u.full_name, r.created_at, r.payload \
+    FROM cash_register_reports r JOIN users u ON r.user_id = u.id";
+
+fn row_to_report(row: &rusqlite::Row) -> rusqlite::Result<CashRegisterReport> {
+    let payload: String = row.get(7)?;
+    let data = serde_json::from_str(&payload).map_err(|error| {
+        rusqlite::Error::FromSqlConversionFailure(7, rusqlite::types::Type::Text, error.into())
+    })?;
+    Ok(CashRegisterReport {
+        id: row.get(0)?,
+        cash_register_session_id: row.get(1)?,
+        report_type: row.get(2)?,
+        z_number: row.get(3)?,
+        user_id: row.get(4)?,
+        user_name: row.get(5)?,
+        created_at: row.get(6)?,
+        data,
+    })
+}
+
+pub fn find_reports(
+    db: &Database,
+    session_id: Option<i64>,
+    register_id: Option<i64>,
+) -> AppResult<Vec<CashRegisterReport>> {
+    let conn = db.conn.lock()?;
+    let query = format!(
+        "{} WHERE (?1 IS NULL OR r.cash_register_session_id = ?1) \
+            AND (?2 IS NULL OR r.cash_register_session_id IN \
+                (SELECT id FROM cash_register_sessions WHERE register_id = ?2)) \
+            ORDER BY r.id",
+        REPORT_SELECT
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let reports = stmt
+        .query_map(params![session_id, register_id], row_to_report)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(reports)
+}
+
+fn query_report(conn: &Connection, id: i64) -> AppResult<Option<CashRegisterReport>> {
+    let query = format!("{} WHERE r.id = ?1", REPORT_SELECT);
+    Ok(conn
+        .query_row(&query, params![id], row_to_report)
+        .optional()?)
+}
+
+/// Builds the report from the session data and stores it; Z numbers are taken inside
+/// the same transaction so two terminals can never print the same number.
+pub fn create_report(
+    db: &Database,
+    session_id: i64,
+    user_id: i64,
+    report_type: CashReportType,
+    hide_expectations: bool,
+) -> AppResult<CashRegisterReport> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    let z_number = if report_type == CashReportType::Z {
+        let exists: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM cash_register_reports \
+                WHERE cash_register_session_id = ?1 AND report_type = ?2)",
+            params![session_id, CashReportType::Z],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Err(AppError::Conflict(
+                "La sesión ya tiene un reporte Z".to_string(),
+            ));
+        }
+        let next: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(z_number), 0) + 1 FROM cash_register_reports",
+            [],
+            |row| row.get(0),
+        )?;
+        Some(next)
+    } else {
+        None
+    };
+
+    let mut summary = query_summary(&tx, session_id)?;
+    if hide_expectations {
+        summary.hide_expectations();
+    }
+    let data = query_report_data(&tx, summary)?;
+    let payload =
+        serde_json::to_string(&data).map_err(|error| AppError::Database(error.to_string()))?;
+    tx.execute(
+        "INSERT INTO cash_register_reports \
+            (cash_register_session_id, report_type, z_number, user_id, payload) \
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![session_id, report_type, z_number, user_id, payload],
+    )?;
+    let id = tx.last_insert_rowid();
+    let report = query_report(&tx, id)?
+        .ok_or_else(|| AppError::NotFound("Reporte de caja no encontrado".to_string()))?;
+    tx.commit()?;
+    Ok(report)
+}
+
+fn query_report_data(
+    conn: &Connection,
+    summary: CashRegisterSummary,
+) -> AppResult<CashRegisterReportData> {
+    let session_id = summary.session.id;
+    let tenders = conn.query_row(
+        "SELECT COALESCE(SUM(payment_cash_mxn - change_amount), 0), \
+            COALESCE(SUM(payment_cash_usd), 0), COALESCE(SUM(payment_transfer), 0), \
+            COALESCE(SUM(payment_points_mxn), 0), COALESCE(SUM(payment_stored_value), 0), \
+            COALESCE(SUM(credit_amount), 0) \
+            FROM sales WHERE cash_register_session_id = ?1 AND status = ?2",
+        params![session_id, SaleStatus::Completed],
+        |row| {
+            let tender = |tender: &str, index: usize| -> rusqlite::Result<TenderTotal> {
+                Ok(TenderTotal {
+                    tender: tender.to_string(),
+                    amount: money::round2(row.get(index)?),
+                })
+            };
+            Ok(vec![
+                tender("cash_mxn", 0)?,
+                tender("cash_usd", 1)?,
+                tender("transfer", 2)?,
+                tender("points", 3)?,
+                tender("stored_value", 4)?,
+                tender("credit", 5)?,
+            ])
+        },
+    )?;
+
+    let categories = conn
+        .prepare(
+            "SELECT p.category_id, c.name, SUM(si.quantity), SUM(si.subtotal) \
+                FROM sale_items si \
+                JOIN sales s ON si.sale_id = s.id \
+                LEFT JOIN products p ON si.product_id = p.id \
+                LEFT JOIN categories c ON p.category_id = c.id \
+                WHERE s.cash_register_session_id = ?1 AND s.status = ?2 \
+                GROUP BY p.category_id ORDER BY SUM(si.subtotal) DESC",
+        )?
+        .query_map(params![session_id, SaleStatus::Completed], |row| {
+            Ok(CategorySalesTotal {
+                category_id: row.get(0)?,
+                category_name: row.get(1)?,
+                quantity: money::round3(row.get(2)?),
+                total: money::round2(row.get(3)?),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let cashiers = conn
+        .prepare(
+            "SELECT s.user_id, u.full_name, COUNT(*), SUM(s.total) \
+                FROM sales s JOIN users u ON s.user_id = u.id \
+                WHERE s.cash_register_session_id = ?1 AND s.status = ?2 \
+                GROUP BY s.user_id ORDER BY SUM(s.total) DESC",
+        )?
+        .query_map(params![session_id, SaleStatus::Completed], |row| {
+            Ok(CashierSalesTotal {
+                user_id: row.get(0)?,
+                user_name: row.get(1)?,
+                transactions: row.get(2)?,
+                total: money::round2(row.get(3)?),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tally = |row: &rusqlite::Row| -> rusqlite::Result<ReportTally> {
+        Ok(ReportTally {
+            count: row.get(0)?,
+            total: money::round2(row.get(1)?),
+        })
+    };
+    let cancellations = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(total), 0) FROM sales \
+            WHERE cash_register_session_id = ?1 AND status = ?2",
+        params![session_id, SaleStatus::Cancelled],
+        tally,
+    )?;
+    let returns = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(amount), 0) FROM stored_value_movements \
+            WHERE cash_register_session_id = ?1 AND movement_type = ?2",
+        params![session_id, StoredValueMovementType::Refund],
+        tally,
+    )?;
+
+    let (items_total, discounts): (f64, f64) = conn.query_row(
+        "SELECT COALESCE(SUM(si.subtotal), 0), \
+            COALESCE(SUM(CASE WHEN si.price_list_id IS NOT NULL \
+                THEN MAX(si.catalog_price * si.quantity - si.subtotal, 0) ELSE 0 END), 0) \
+            FROM sale_items si \
+            JOIN sales s ON si.sale_id = s.id \
+            WHERE s.cash_register_session_id = ?1 AND s.status = ?2",
+        params![session_id, SaleStatus::Completed],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    // Same rule as the sale ticket: anything charged above the item subtotals is tax.
+    let tax = money::sub_money(summary.total_sales, items_total).max(0.0);
+
+    Ok(CashRegisterReportData {
+        summary,
+        tenders,
+        categories,
+        cashiers,
+        cancellations,
+        returns,
+        discounts: money::round2(discounts),
+        tax,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        approve_close, auto_close_session, close_session, create_handover, create_movement,
+        create_report, create_shortage, find_by_date_range_paginated, find_by_status,
+        find_reopenings, find_stale_sessions, get_summary, open_session, reopen_session,
+        stale_session_blocks_sales, SqliteCashRegisterRepository,
+    };
+    use crate::infrastructure::sqlite::Database;
+    use crate::models::cash_over_short::{CashOverShortReportRequest, RecordCashShortageRequest};
+    use crate::models::cash_register::{
+        CashCurrency, CashMovementType, CreateCashMovementRequest, DenominationCount, SessionStatus,
+    };
+    use crate::models::cash_register_report::{CashReportType, GenerateCashReportRequest};
+    use crate::modules::cash_register::application::{
+        self, NewCashHandover, NewCashRegisterSession,
+    };
+    use crate::shared::error::AppError;
+    use rusqlite::Connection;
+    use std::sync::Mutex;
+
+    fn test_database() -> Database {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (
+                id INTEGER PRIMARY KEY,
+                full_name TEXT NOT NULL
+            );
+            CREATE TABLE categories (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL
+            );
+            CREATE TABLE products (
+                id INTEGER PRIMARY KEY,
+                price REAL NOT NULL,
+                category_id INTEGER
+            );
+            CREATE TABLE registers (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL
+            );
+            CREATE TABLE cash_register_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                register_id INTEGER NOT NULL DEFAULT 1,
+                opening_amount REAL NOT NULL DEFAULT 0,
+                opening_cash_usd REAL NOT NULL DEFAULT 0,
+                closing_amount REAL,
+                closing_cash_mxn REAL,
+                closing_cash_usd REAL,
+                exchange_rate REAL,
+                status TEXT NOT NULL DEFAULT 'open',
+                opened_at TEXT DEFAULT '2026-01-01 08:00:00',
+                closed_at TEXT,
+                reviewed_by INTEGER,
+                review_notes TEXT,
+                reviewed_at TEXT,
+                closing_summary TEXT,
+                count_pending INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE UNIQUE INDEX one_open_session
+                ON cash_register_sessions(register_id) WHERE status = 'open';
+            CREATE TABLE sales (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cash_register_session_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                total REAL NOT NULL,
+                credit_amount REAL NOT NULL DEFAULT 0,
+                payment_cash_mxn REAL NOT NULL DEFAULT 0,
+                payment_cash_usd REAL NOT NULL DEFAULT 0,
+                payment_transfer REAL NOT NULL DEFAULT 0,
+                payment_points_mxn REAL NOT NULL DEFAULT 0,
+                payment_stored_value REAL NOT NULL DEFAULT 0,
+                change_amount REAL NOT NULL DEFAULT 0,
+                status TEXT NOT NULL DEFAULT 'completed'
+            );
+            CREATE TABLE sale_items (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                sale_id INTEGER NOT NULL,
+                product_id INTEGER NOT NULL,
+                quantity REAL NOT NULL,
+                price_list_id INTEGER,
+                subtotal REAL NOT NULL,
+                catalog_price REAL
+            );
+            CREATE TABLE customer_account_movements (
+                cash_register_session_id INTEGER NOT NULL,
+                movement_type TEXT NOT NULL,
+                payment_cash_mxn REAL NOT NULL DEFAULT 0,
+                payment_cash_usd REAL NOT NULL DEFAULT 0,
+                payment_transfer REAL NOT NULL DEFAULT 0
+            );
+            CREATE TABLE stored_value_movements (
+                cash_register_session_id INTEGER NOT NULL,
+                movement_type TEXT NOT NULL,
+                amount REAL NOT NULL,
+                payment_cash_mxn REAL NOT NULL DEFAULT 0,
+                payment_cash_usd REAL NOT NULL DEFAULT 0,
+                payment_transfer REAL NOT NULL DEFAULT 0
+            );
+            CREATE TABLE cash_movements (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cash_register_session_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                movement_type TEXT NOT NULL,
+                amount_mxn REAL NOT NULL DEFAULT 0,
+                amount_usd REAL NOT NULL DEFAULT 0,
+                reason TEXT NOT NULL,
+                created_at TEXT DEFAULT '2026-01-01 09:00:00'
+            );
+            CREATE TABLE cash_denomination_counts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cash_register_session_id INTEGER NOT NULL,
+                count_type TEXT NOT NULL,
+                currency TEXT NOT NULL,
+                denomination REAL NOT NULL,
+                quantity INTEGER NOT NULL
+            );
+            CREATE TABLE settings (
+                key TEXT PRIMARY KEY,
+                value TEXT
+            );
+            INSERT INTO settings (key, value) VALUES ('cash_reopen_window_hours', '12');
+            CREATE TABLE cash_session_reopenings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cash_register_session_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                reason TEXT NOT NULL,
+                previous_status TEXT NOT NULL,
+                previous_closed_at TEXT,
+                previous_summary TEXT,
+                created_at TEXT DEFAULT '2026-01-01 21:00:00'
+            );
+            CREATE TABLE cash_register_reports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cash_register_session_id INTEGER NOT NULL,
+                report_type TEXT NOT NULL,
+                z_number INTEGER UNIQUE,
+                user_id INTEGER NOT NULL,
+                payload TEXT NOT NULL,
+                created_at TEXT DEFAULT '2026-01-01 20:00:00'
+            );
+            INSERT INTO users (id, full_name) VALUES (1, 'Cajero'), (2, 'Supervisor');
+            CREATE TABLE cash_handovers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cash_register_session_id INTEGER NOT NULL,
+                from_user_id INTEGER NOT NULL,
+                to_user_id INTEGER NOT NULL,
+                started_at TEXT NOT NULL,
+                last_sale_id INTEGER NOT NULL DEFAULT 0,
+                sales_total REAL NOT NULL DEFAULT 0,
+                sales_count INTEGER NOT NULL DEFAULT 0,
+                expected_cash_mxn REAL NOT NULL,
+                expected_cash_usd REAL NOT NULL,
+                counted_cash_mxn REAL NOT NULL,
+                counted_cash_usd REAL NOT NULL,
+                notes TEXT,
+                created_at TEXT DEFAULT '2026-01-01 14:00:00'
+            );
+            CREATE TABLE cash_shortages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cash_register_session_id INTEGER NOT NULL,
+                cashier_id INTEGER NOT NULL,
+                amount_mxn REAL NOT NULL,
+                notes TEXT,
+                recorded_by INTEGER NOT NULL,
+                created_at TEXT DEFAULT '2026-01-01 21:00:00',
+                UNIQUE(cash_register_session_id, cashier_id)
+            );
+            CREATE TABLE cash_handover_counts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                handover_id INTEGER NOT NULL,
+                currency TEXT NOT NULL,
+                denomination REAL NOT NULL,
+                quantity INTEGER NOT NULL
+            );
+            INSERT INTO registers (id, name) VALUES (1, 'Caja 1'), (2, 'Caja 2');
+            INSERT INTO categories (id, name) VALUES (1, 'Frutas');
+            INSERT INTO products (id, price, category_id) VALUES (1, 10.0, 1), (2, 5.0, NULL);",
+        )
+        .unwrap();
+        Database {
+            conn: Mutex::new(conn),
+        }
+    }
+
+    fn new_session(register_id: i64, opening_amount: f64) -> NewCashRegisterSession {
+        NewCashRegisterSession {
+            user_id: 1,
+            register_id,
+            opening_amount,
+            opening_cash_usd: 0.0,
+            exchange_rate: None,
+        }
+    }
+
+    fn movement(
+        movement_type: CashMovementType,
+        amount_mxn: f64,
+        amount_usd: f64,
+    ) -> CreateCashMovementRequest {
+        CreateCashMovementRequest {
+            session_id: 1,
+            user_id: 1,
+            movement_type,
+            amount_mxn,
+            amount_usd,
+            reason: "Prueba".to_string(),
+        }
+    }
+
+    #[test]
+    fn cash_movements_adjust_the_expected_drawer() {
+        let db = test_database();
+        open_session(
+            &db,
+            &NewCashRegisterSession {
+                opening_cash_usd: 10.0,
+                ..new_session(1, 500.0)
+            },
+            &[],
+        )
+        .unwrap();
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute_batch(
+                "INSERT INTO sales (cash_register_session_id, user_id, total, payment_cash_mxn, change_amount)
+                    VALUES (1, 1, 180.0, 200.0, 20.0);",
+            )
+            .unwrap();
+        }
+        create_movement(
+            &db,
+            &movement(CashMovementType::PayIn, 100.0, 0.0),
+            "Cambio",
+        )
+        .unwrap();
+        create_movement(&db, &movement(CashMovementType::PayOut, 50.0, 0.0), "Gas").unwrap();
+        create_movement(
+            &db,
+            &movement(CashMovementType::SafeDrop, 300.0, 5.0),
+            "Caja fuerte",
+        )
+        .unwrap();
+
+        let summary = get_summary(&db, 1).unwrap();
+        assert_eq!(summary.pay_ins_mxn, 100.0);
+        assert_eq!(summary.pay_outs_mxn, 50.0);
+        assert_eq!(summary.safe_drops_usd, 5.0);
+        // 500 + 200 - 20 + 100 - 50 - 300
+        assert_eq!(summary.expected_cash_mxn, 430.0);
+        assert_eq!(summary.expected_cash_usd, 5.0);
+    }
+
+    #[test]
+    fn closing_count_is_stored_with_the_summary() {
+        let db = test_database();
+        open_session(&db, &new_session(1, 0.0), &[]).unwrap();
+        let count = [
+            DenominationCount {
+                currency: CashCurrency::Mxn,
+                denomination: 200.0,
+                quantity: 2,
+            },
+            DenominationCount {
+                currency: CashCurrency::Usd,
+                denomination: 20.0,
+                quantity: 1,
+            },
+        ];
+        let summary = close_session(&db, 1, SessionStatus::Closed, 400.0, 20.0, &count).unwrap();
+        assert_eq!(summary.closing_count.len(), 2);
+        assert_eq!(summary.closing_count[0].subtotal, 400.0);
+        assert!(summary.opening_count.is_empty());
+    }
 
-    Ok(build_summary(session, breakdown))
+    #[test]
+    fn z_reports_are_numbered_once_per_session() {
+        let db = test_database();
+        open_session(&db, &new_session(1, 0.0), &[]).unwrap();
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute_batch(
+                "INSERT INTO sales (cash_register_session_id, user_id, total, payment_cash_mxn)
+                    VALUES (1, 1, 28.0, 28.0);
+                INSERT INTO sales (cash_register_session_id, user_id, total, payment_cash_mxn, status)
+                    VALUES (1, 2, 15.0, 15.0, 'cancelled');
+                INSERT INTO sale_items
+                    (sale_id, product_id, quantity, price_list_id, subtotal, catalog_price)
+                    VALUES (1, 1, 2, 3, 18.0, 10.0), (1, 2, 2, NULL, 10.0, 5.0);
+                UPDATE products SET price = 12.0 WHERE id = 1;",
+            )
+            .unwrap();
+        }
+
+        let x = create_report(&db, 1, 1, CashReportType::X, false).unwrap();
+        assert_eq!(x.z_number, None);
+        assert_eq!(x.data.discounts, 2.0);
+        assert_eq!(x.data.cancellations.count, 1);
+        assert_eq!(x.data.cashiers.len(), 1);
+        assert_eq!(
+            x.data.categories[0].category_name.as_deref(),
+            Some("Frutas")
+        );
+
+        close_session(&db, 1, SessionStatus::Closed, 28.0, 0.0, &[]).unwrap();
+        let z = create_report(&db, 1, 2, CashReportType::Z, false).unwrap();
+        assert_eq!(z.z_number, Some(1));
+        assert!(matches!(
+            create_report(&db, 1, 2, CashReportType::Z, false),
+            Err(AppError::Conflict(_))
+        ));
+
+        open_session(&db, &new_session(1, 0.0), &[]).unwrap();
+        close_session(&db, 2, SessionStatus::Closed, 0.0, 0.0, &[]).unwrap();
+        assert_eq!(
+            create_report(&db, 2, 1, CashReportType::Z, false)
+                .unwrap()
+                .z_number,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn each_register_keeps_its_own_open_session() {
+        let db = test_database();
+        open_session(&db, &new_session(1, 0.0), &[]).unwrap();
+        let second = open_session(&db, &new_session(2, 0.0), &[]).unwrap();
+        assert_eq!(second.register_id, 2);
+        assert_eq!(second.register_name.as_deref(), Some("Caja 2"));
+        assert!(matches!(
+            open_session(&db, &new_session(1, 0.0), &[]),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn blind_close_waits_for_supervisor_approval() {
+        let db = test_database();
+        open_session(&db, &new_session(1, 100.0), &[]).unwrap();
+        let pending = close_session(&db, 1, SessionStatus::PendingReview, 90.0, 0.0, &[]).unwrap();
+        assert_eq!(pending.session.status, SessionStatus::PendingReview);
+        assert_eq!(
+            find_by_status(&db, SessionStatus::PendingReview)
+                .unwrap()
+                .len(),
+            1
+        );
+        let (listed, total) =
+            find_by_date_range_paginated(&db, "2000-01-01", "2999-12-31", None, 1, 20).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(listed[0].status, SessionStatus::PendingReview);
+
+        let approved = approve_close(&db, 1, 2, "Faltante por cambio mal dado").unwrap();
+        assert_eq!(approved.session.status, SessionStatus::Closed);
+        assert_eq!(approved.difference_mxn, -10.0);
+        let review = approved.session.review.unwrap();
+        assert_eq!(review.user_name.as_deref(), Some("Supervisor"));
+        assert_eq!(review.notes, "Faltante por cambio mal dado");
+        assert!(matches!(
+            approve_close(&db, 1, 2, "Otra vez"),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn handover_splits_sales_by_shift_and_reassigns_the_session() {
+        let db = test_database();
+        open_session(&db, &new_session(1, 100.0), &[]).unwrap();
+        let sale = |user_id: i64, total: f64| {
+            db.conn
+                .lock()
+                .unwrap()
+                .execute(
+                    "INSERT INTO sales (cash_register_session_id, user_id, total, payment_cash_mxn)
+                        VALUES (1, ?1, ?2, ?2)",
+                    rusqlite::params![user_id, total],
+                )
+                .unwrap();
+        };
+        let handover = |from_user_id: i64, to_user_id: i64, counted: f64| NewCashHandover {
+            session_id: 1,
+            from_user_id,
+            to_user_id,
+            counted_cash_mxn: counted,
+            counted_cash_usd: 0.0,
+            notes: None,
+        };
+
+        sale(1, 40.0);
+        sale(1, 10.0);
+        let count = [DenominationCount {
+            currency: CashCurrency::Mxn,
+            denomination: 50.0,
+            quantity: 3,
+        }];
+        let first = create_handover(&db, &handover(1, 2, 150.0), &count).unwrap();
+        assert_eq!(first.sales_count, 2);
+        assert_eq!(first.sales_total, 50.0);
+        assert_eq!(first.difference_mxn, 0.0);
+        assert_eq!(first.started_at, "2026-01-01 08:00:00");
+        assert_eq!(first.to_user_name.as_deref(), Some("Supervisor"));
+        assert_eq!(first.count[0].subtotal, 150.0);
+        assert_eq!(get_summary(&db, 1).unwrap().session.user_id, 2);
+
+        sale(2, 25.0);
+        assert!(matches!(
+            create_handover(&db, &handover(1, 2, 0.0), &[]),
+            Err(AppError::Conflict(_))
+        ));
+        let second = create_handover(&db, &handover(2, 1, 170.0), &[]).unwrap();
+        assert_eq!(second.sales_count, 1);
+        assert_eq!(second.sales_total, 25.0);
+        assert_eq!(second.started_at, "2026-01-01 14:00:00");
+        assert_eq!(second.difference_mxn, -5.0);
+    }
+
+    #[test]
+    fn closed_summary_is_frozen_at_close() {
+        let db = test_database();
+        open_session(&db, &new_session(1, 100.0), &[]).unwrap();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO sales (cash_register_session_id, user_id, total, payment_cash_mxn)
+                    VALUES (1, 1, 50.0, 50.0);",
+            )
+            .unwrap();
+        let closed = close_session(&db, 1, SessionStatus::Closed, 150.0, 0.0, &[]).unwrap();
+
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch("UPDATE sales SET status = 'cancelled';")
+            .unwrap();
+        let summary = get_summary(&db, 1).unwrap();
+        assert_eq!(summary.total_sales, closed.total_sales);
+        assert_eq!(summary.expected_cash_mxn, 150.0);
+        assert_eq!(summary.session.status, SessionStatus::Closed);
+    }
+
+    #[test]
+    fn reopening_keeps_the_previous_close_for_comparison() {
+        let db = test_database();
+        open_session(&db, &new_session(1, 100.0), &[]).unwrap();
+        close_session(&db, 1, SessionStatus::Closed, 90.0, 0.0, &[]).unwrap();
+
+        let reopened = reopen_session(&db, 1, 2, "Faltó registrar un abono").unwrap();
+        assert_eq!(reopened.status, SessionStatus::Open);
+        assert_eq!(reopened.closing_cash_mxn, None);
+        let audit = find_reopenings(&db, 1).unwrap();
+        assert_eq!(audit[0].previous_status, SessionStatus::Closed);
+        assert_eq!(
+            audit[0].previous_summary.as_ref().unwrap().difference_mxn,
+            -10.0
+        );
+
+        close_session(&db, 1, SessionStatus::Closed, 100.0, 0.0, &[]).unwrap();
+        open_session(&db, &new_session(1, 0.0), &[]).unwrap();
+        assert!(matches!(
+            reopen_session(&db, 1, 2, "Otra vez"),
+            Err(AppError::Conflict(_))
+        ));
+
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "UPDATE cash_register_sessions SET status = 'closed',
+                    closed_at = datetime('now', 'localtime', '-2 days') WHERE id = 2;",
+            )
+            .unwrap();
+        assert!(matches!(
+            reopen_session(&db, 2, 2, "Fuera de tiempo"),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn stale_sessions_can_block_sales_or_close_with_the_count_pending() {
+        let db = test_database();
+        open_session(&db, &new_session(1, 100.0), &[]).unwrap();
+        open_session(&db, &new_session(2, 100.0), &[]).unwrap();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "UPDATE cash_register_sessions
+                    SET opened_at = datetime('now', 'localtime', '-1 hours') WHERE id = 2;
+                INSERT INTO settings (key, value) VALUES
+                    ('cash_stale_session_hours', '18'),
+                    ('cash_stale_session_policy', 'block');",
+            )
+            .unwrap();
+
+        let stale = find_stale_sessions(&db, 18).unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].id, 1);
+        {
+            let conn = db.conn.lock().unwrap();
+            assert!(stale_session_blocks_sales(&conn, 1).unwrap());
+            assert!(!stale_session_blocks_sales(&conn, 2).unwrap());
+        }
+
+        let closed = auto_close_session(&db, 1).unwrap();
+        assert_eq!(closed.status, SessionStatus::PendingReview);
+        assert!(closed.count_pending);
+        open_session(&db, &new_session(1, 0.0), &[]).unwrap();
+
+        let counted = close_session(&db, 1, SessionStatus::PendingReview, 100.0, 0.0, &[]).unwrap();
+        assert!(!counted.session.count_pending);
+        assert_eq!(counted.session.closed_at, closed.closed_at);
+        assert_eq!(counted.difference_mxn, 0.0);
+    }
+
+    #[test]
+    fn blind_close_hides_expectations_while_the_session_is_open() {
+        let db = test_database();
+        let repository = SqliteCashRegisterRepository::new(&db);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO settings (key, value) VALUES ('cash_blind_close', 'true');
+                INSERT INTO sales (cash_register_session_id, user_id, total, payment_cash_mxn)
+                    VALUES (1, 1, 50.0, 50.0);",
+            )
+            .unwrap();
+        open_session(&db, &new_session(1, 100.0), &[]).unwrap();
+
+        let summary = application::get_summary(&repository, 1).unwrap();
+        assert!(summary.expectations_hidden);
+        assert_eq!(summary.expected_cash_mxn, 0.0);
+        assert_eq!(summary.difference_mxn, 0.0);
+        assert_eq!(summary.sales_cash_mxn, 50.0);
+
+        let x = application::generate_report(
+            &repository,
+            GenerateCashReportRequest {
+                session_id: 1,
+                user_id: 1,
+                report_type: CashReportType::X,
+            },
+        )
+        .unwrap();
+        assert!(x.data.summary.expectations_hidden);
+        assert_eq!(x.data.summary.expected_cash_mxn, 0.0);
+
+        let handover = NewCashHandover {
+            session_id: 1,
+            from_user_id: 1,
+            to_user_id: 2,
+            counted_cash_mxn: 140.0,
+            counted_cash_usd: 0.0,
+            notes: None,
+        };
+        create_handover(&db, &handover, &[]).unwrap();
+        let handovers = application::get_handovers(&repository, 1).unwrap();
+        assert!(handovers[0].expectations_hidden);
+        assert_eq!(handovers[0].difference_mxn, 0.0);
+        let printed = application::find_handover(&repository, handovers[0].id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(printed.expected_cash_mxn, 0.0);
+
+        close_session(&db, 1, SessionStatus::PendingReview, 140.0, 0.0, &[]).unwrap();
+        let summary = application::get_summary(&repository, 1).unwrap();
+        assert!(!summary.expectations_hidden);
+        assert_eq!(summary.expected_cash_mxn, 150.0);
+        assert_eq!(summary.difference_mxn, -10.0);
+        assert_eq!(
+            application::get_handovers(&repository, 1).unwrap()[0].difference_mxn,
+            -10.0
+        );
+    }
+
+    #[test]
+    fn blind_close_keeps_expectations_hidden_until_an_auto_closed_session_is_counted() {
+        let db = test_database();
+        let repository = SqliteCashRegisterRepository::new(&db);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO settings (key, value) VALUES ('cash_blind_close', 'true');
+                INSERT INTO sales (cash_register_session_id, user_id, total, payment_cash_mxn)
+                    VALUES (1, 1, 50.0, 50.0);",
+            )
+            .unwrap();
+        open_session(&db, &new_session(1, 100.0), &[]).unwrap();
+
+        let closed = auto_close_session(&db, 1).unwrap();
+        assert!(closed.count_pending);
+        let summary = application::get_summary(&repository, 1).unwrap();
+        assert!(summary.expectations_hidden);
+        assert_eq!(summary.expected_cash_mxn, 0.0);
+        let x = application::generate_report(
+            &repository,
+            GenerateCashReportRequest {
+                session_id: 1,
+                user_id: 1,
+                report_type: CashReportType::X,
+            },
+        )
+        .unwrap();
+        assert_eq!(x.data.summary.expected_cash_mxn, 0.0);
+
+        close_session(&db, 1, SessionStatus::PendingReview, 150.0, 0.0, &[]).unwrap();
+        let summary = application::get_summary(&repository, 1).unwrap();
+        assert!(!summary.expectations_hidden);
+        assert_eq!(summary.expected_cash_mxn, 150.0);
+    }
+
+    #[test]
+    fn over_short_is_charged_to_the_shift_that_caused_it() {
+        let db = test_database();
+        let repository = SqliteCashRegisterRepository::new(&db);
+        let sale = |user_id: i64, total: f64| {
+            db.conn
+                .lock()
+                .unwrap()
+                .execute(
+                    "INSERT INTO sales (cash_register_session_id, user_id, total, payment_cash_mxn)
+                        VALUES (1, ?1, ?2, ?2)",
+                    rusqlite::params![user_id, total],
+                )
+                .unwrap();
+        };
+        open_session(&db, &new_session(1, 100.0), &[]).unwrap();
+        sale(1, 50.0);
+        let handover = NewCashHandover {
+            session_id: 1,
+            from_user_id: 1,
+            to_user_id: 2,
+            counted_cash_mxn: 140.0,
+            counted_cash_usd: 0.0,
+            notes: None,
+        };
+        create_handover(&db, &handover, &[]).unwrap();
+        sale(2, 25.0);
+        close_session(&db, 1, SessionStatus::Closed, 165.0, 0.0, &[]).unwrap();
+
+        let report = application::get_over_short_report(
+            &repository,
+            CashOverShortReportRequest {
+                start_date: "2000-01-01".to_string(),
+                end_date: "2999-12-31".to_string(),
+                register_id: None,
+                cashier_id: None,
+            },
+        )
+        .unwrap();
+        let shifts: Vec<(i64, f64)> = report
+            .sessions
+            .iter()
+            .map(|shift| (shift.user_id, shift.difference_mxn))
+            .collect();
+        assert_eq!(shifts, [(1, -10.0), (2, 0.0)]);
+        assert_eq!(report.cashiers[0].short_mxn, 10.0);
+        assert_eq!(report.cashiers[1].short_mxn, 0.0);
+
+        let shortage = |cashier_id: i64| RecordCashShortageRequest {
+            session_id: 1,
+            user_id: 2,
+            cashier_id: Some(cashier_id),
+            amount_mxn: 10.0,
+            notes: None,
+        };
+        let owed = create_shortage(&db, &shortage(1), 1, None).unwrap();
+        assert_eq!(owed.cashier_name.as_deref(), Some("Cajero"));
+        create_shortage(&db, &shortage(2), 2, None).unwrap();
+        assert!(matches!(
+            create_shortage(&db, &shortage(1), 1, None),
+            Err(AppError::Conflict(_))
+        ));
+    }
 }
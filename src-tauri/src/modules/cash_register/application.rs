@@ -1,36 +1,139 @@
 use crate::constants::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::models::cash_over_short::{
+    CashOverShortReport, CashOverShortReportRequest, CashShortage, CashierOverShort,
+    RecordCashShortageRequest, SessionOverShort,
+};
 use crate::models::cash_register::{
-    CashRegisterSession, CashRegisterSummary, CloseCashRegisterRequest, DateRangeRequest,
-    OpenCashRegisterRequest,
+    ApproveCashCloseRequest, CashCurrency, CashDenominations, CashHandover, CashMovement,
+    CashRegisterSession, CashRegisterSummary, CashSessionReopening, CloseCashRegisterRequest,
+    CreateCashMovementRequest, DateRangeRequest, DenominationCount, HandoverCashRegisterRequest,
+    OpenCashRegisterRequest, ReopenCashRegisterSessionRequest, SessionStatus, StaleSessionCheck,
+    StaleSessionPolicy,
+};
+use crate::models::cash_register_report::{
+    CashRegisterReport, CashReportType, GenerateCashReportRequest,
 };
+use crate::modules::identity::application::{require_admin, UserRepository};
+use crate::modules::registers::application::{get_register, get_registers, RegisterRepository};
 use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
 use crate::shared::pagination::PaginatedResult;
 
+/// Session about to be opened, with the amounts already resolved from the count.
+pub struct NewCashRegisterSession {
+    pub user_id: i64,
+    pub register_id: i64,
+    pub opening_amount: f64,
+    pub opening_cash_usd: f64,
+    pub exchange_rate: Option<f64>,
+}
+
+/// Handover about to be recorded, with the counted amounts already resolved.
+pub struct NewCashHandover {
+    pub session_id: i64,
+    pub from_user_id: i64,
+    pub to_user_id: i64,
+    pub counted_cash_mxn: f64,
+    pub counted_cash_usd: f64,
+    pub notes: Option<String>,
+}
+
 pub trait CashRegisterRepository {
     fn find_all(&self) -> AppResult<Vec<CashRegisterSession>>;
     fn find_by_id(&self, id: i64) -> AppResult<Option<CashRegisterSession>>;
     fn find_open_by_user(&self, user_id: i64) -> AppResult<Option<CashRegisterSession>>;
     fn find_any_open(&self) -> AppResult<Option<CashRegisterSession>>;
+    fn find_open_by_register(&self, register_id: i64) -> AppResult<Option<CashRegisterSession>>;
     fn find_by_date_range_paginated(
         &self,
         start_date: &str,
         end_date: &str,
+        register_id: Option<i64>,
         page: i64,
         page_size: i64,
     ) -> AppResult<(Vec<CashRegisterSession>, i64)>;
     fn open_session(
         &self,
-        user_id: i64,
-        opening_amount: f64,
-        exchange_rate: Option<f64>,
+        session: &NewCashRegisterSession,
+        count: &[DenominationCount],
     ) -> AppResult<CashRegisterSession>;
+    /// Closes an open session into `status`: `Closed`, or `PendingReview` for a blind close.
+    /// Auto-closed sessions with their count pending are closed the same way.
     fn close_session(
         &self,
         session_id: i64,
+        status: SessionStatus,
         closing_cash_mxn: f64,
         closing_cash_usd: f64,
+        count: &[DenominationCount],
     ) -> AppResult<CashRegisterSummary>;
+    fn approve_close(
+        &self,
+        session_id: i64,
+        reviewer_id: i64,
+        notes: &str,
+    ) -> AppResult<CashRegisterSummary>;
+    fn find_by_status(&self, status: SessionStatus) -> AppResult<Vec<CashRegisterSession>>;
+    fn load_denominations(&self) -> AppResult<CashDenominations>;
+    fn blind_close_enabled(&self) -> AppResult<bool>;
+    /// Policy and cutoff in hours; a cutoff of 0 disables detection.
+    fn load_stale_session_policy(&self) -> AppResult<(StaleSessionPolicy, i64)>;
+    fn find_stale_sessions(&self, cutoff_hours: i64) -> AppResult<Vec<CashRegisterSession>>;
+    fn auto_close_session(&self, session_id: i64) -> AppResult<CashRegisterSession>;
     fn get_summary(&self, session_id: i64) -> AppResult<CashRegisterSummary>;
+    fn find_movements(&self, session_id: i64) -> AppResult<Vec<CashMovement>>;
+    fn create_handover(
+        &self,
+        handover: &NewCashHandover,
+        count: &[DenominationCount],
+    ) -> AppResult<CashHandover>;
+    fn find_handovers(&self, session_id: i64) -> AppResult<Vec<CashHandover>>;
+    fn find_handover(&self, id: i64) -> AppResult<Option<CashHandover>>;
+    /// Reopens a closed session if it is inside the reopen window, has no Z report and
+    /// no newer session on its register; the discarded close snapshot is audited.
+    fn reopen_session(
+        &self,
+        session_id: i64,
+        user_id: i64,
+        reason: &str,
+    ) -> AppResult<CashRegisterSession>;
+    fn find_reopenings(&self, session_id: i64) -> AppResult<Vec<CashSessionReopening>>;
+    /// Summaries of sessions closed between `start` and `end`.
+    fn find_closed_summaries(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+    ) -> AppResult<Vec<CashRegisterSummary>>;
+    /// Over/short tolerance as (MXN, USD).
+    fn load_over_short_tolerance(&self) -> AppResult<(f64, f64)>;
+    fn find_shortage(&self, session_id: i64, cashier_id: i64) -> AppResult<Option<CashShortage>>;
+    fn create_shortage(
+        &self,
+        request: &RecordCashShortageRequest,
+        cashier_id: i64,
+        notes: Option<&str>,
+    ) -> AppResult<CashShortage>;
+    fn find_movement(&self, id: i64) -> AppResult<Option<CashMovement>>;
+    /// `hide_expectations` stores the summary without expected cash or differences.
+    fn create_report(
+        &self,
+        session_id: i64,
+        user_id: i64,
+        report_type: CashReportType,
+        hide_expectations: bool,
+    ) -> AppResult<CashRegisterReport>;
+    fn find_reports(
+        &self,
+        session_id: Option<i64>,
+        register_id: Option<i64>,
+    ) -> AppResult<Vec<CashRegisterReport>>;
+    fn find_report(&self, id: i64) -> AppResult<Option<CashRegisterReport>>;
+    fn create_movement(
+        &self,
+        request: &CreateCashMovementRequest,
+        reason: &str,
+    ) -> AppResult<CashMovement>;
 }
 
 pub trait CashRegisterSessionLookup {
@@ -56,6 +159,7 @@ pub fn get_sessions_by_date_range(
     let (data, total) = repository.find_by_date_range_paginated(
         &request.start_date,
         &request.end_date,
+        request.register_id,
         page,
         page_size,
     )?;
@@ -78,8 +182,12 @@ pub fn get_session(
 
 pub fn get_open(
     repository: &impl CashRegisterRepository,
+    register_id: Option<i64>,
 ) -> AppResult<Option<CashRegisterSession>> {
-    repository.find_any_open()
+    match register_id {
+        Some(register_id) => repository.find_open_by_register(register_id),
+        None => repository.find_any_open(),
+    }
 }
 
 pub fn get_open_by_user(
@@ -89,11 +197,86 @@ pub fn get_open_by_user(
     repository.find_open_by_user(user_id)
 }
 
+pub fn get_denominations(repository: &impl CashRegisterRepository) -> AppResult<CashDenominations> {
+    repository.load_denominations()
+}
+
+/// Reads a comma-separated denomination setting, largest first. Entries that are not
+/// positive numbers are ignored so a typo in settings does not block the count.
+pub fn parse_denominations(value: &str) -> Vec<f64> {
+    let mut denominations: Vec<f64> = value
+        .split(',')
+        .filter_map(|entry| entry.trim().parse::<f64>().ok())
+        .filter(|denomination| denomination.is_finite() && *denomination > 0.0)
+        .map(money::round2)
+        .collect();
+    denominations.sort_by(|a, b| b.total_cmp(a));
+    denominations.dedup();
+    denominations
+}
+
+/// Totals a drawer count per currency, returned as `(mxn, usd)`.
+pub fn count_totals(
+    denominations: &CashDenominations,
+    count: &[DenominationCount],
+) -> AppResult<(f64, f64)> {
+    let mut seen: Vec<(CashCurrency, f64)> = Vec::with_capacity(count.len());
+    let mut total_mxn = 0.0;
+    let mut total_usd = 0.0;
+    for line in count {
+        if line.quantity < 0 {
+            return Err(AppError::Validation(
+                "La cantidad de piezas no puede ser negativa".to_string(),
+            ));
+        }
+        let configured = match line.currency {
+            CashCurrency::Mxn => &denominations.mxn,
+            CashCurrency::Usd => &denominations.usd,
+        };
+        // Stored rounded, so lines that round to the same value are the same denomination.
+        let denomination = money::round2(line.denomination);
+        if !configured.contains(&denomination) {
+            return Err(AppError::Validation(format!(
+                "La denominación {} {} no está configurada",
+                line.denomination,
+                line.currency.as_str()
+            )));
+        }
+        if seen.contains(&(line.currency, denomination)) {
+            return Err(AppError::Validation(format!(
+                "La denominación {} {} está repetida en el conteo",
+                denomination,
+                line.currency.as_str()
+            )));
+        }
+        seen.push((line.currency, denomination));
+        let subtotal = money::mul_money(denomination, line.quantity as f64);
+        match line.currency {
+            CashCurrency::Mxn => total_mxn = money::add_money(total_mxn, subtotal),
+            CashCurrency::Usd => total_usd = money::add_money(total_usd, subtotal),
+        }
+    }
+    Ok((total_mxn, total_usd))
+}
+
 pub fn open_session(
     repository: &impl CashRegisterRepository,
-    request: OpenCashRegisterRequest,
+    registers: &impl RegisterRepository,
+    mut request: OpenCashRegisterRequest,
 ) -> AppResult<CashRegisterSession> {
-    if request.opening_amount < 0.0 {
+    let register_id = resolve_register(registers, request.register_id)?;
+    if repository.find_open_by_register(register_id)?.is_some() {
+        return Err(AppError::Conflict(
+            "Esta caja ya tiene una sesión abierta. Debe cerrarse antes de abrir otra.".to_string(),
+        ));
+    }
+    let count = request.opening_count.take().unwrap_or_default();
+    if !count.is_empty() {
+        let (mxn, usd) = count_totals(&repository.load_denominations()?, &count)?;
+        request.opening_amount = mxn;
+        request.opening_cash_usd = usd;
+    }
+    if request.opening_amount < 0.0 || request.opening_cash_usd < 0.0 {
         return Err(AppError::Validation(
             "El monto de apertura no puede ser negativo".to_string(),
         ));
@@ -104,26 +287,662 @@ pub fn open_session(
         ));
     }
     repository.open_session(
-        request.user_id,
-        request.opening_amount,
-        request.exchange_rate,
+        &NewCashRegisterSession {
+            user_id: request.user_id,
+            register_id,
+            opening_amount: request.opening_amount,
+            opening_cash_usd: request.opening_cash_usd,
+            exchange_rate: request.exchange_rate,
+        },
+        &count,
     )
 }
 
+/// The register to open; it can be left out only when a single register is active.
+fn resolve_register(
+    registers: &impl RegisterRepository,
+    register_id: Option<i64>,
+) -> AppResult<i64> {
+    let register = match register_id {
+        Some(register_id) => get_register(registers, register_id)?,
+        None => {
+            let mut active = get_registers(registers)?
+                .into_iter()
+                .filter(|register| register.active);
+            match (active.next(), active.next()) {
+                (Some(register), None) => register,
+                (None, _) => return Err(AppError::Conflict("No hay cajas activas".to_string())),
+                (Some(_), Some(_)) => {
+                    return Err(AppError::Validation(
+                        "Selecciona la caja que se va a abrir".to_string(),
+                    ))
+                }
+            }
+        }
+    };
+    if !register.active {
+        return Err(AppError::Conflict(format!(
+            "La caja {} está desactivada",
+            register.name
+        )));
+    }
+    Ok(register.id)
+}
+
 pub fn close_session(
     repository: &impl CashRegisterRepository,
     request: CloseCashRegisterRequest,
 ) -> AppResult<CashRegisterSummary> {
+    if repository.blind_close_enabled()? {
+        return Err(AppError::Conflict(
+            "El cierre ciego está activo; el corte debe enviarse a revisión".to_string(),
+        ));
+    }
+    close_counted(repository, request, SessionStatus::Closed)
+}
+
+/// Records the cashier's count without revealing the expected cash; the session
+/// stays in `PendingReview` until a supervisor approves it.
+pub fn submit_blind_close(
+    repository: &impl CashRegisterRepository,
+    request: CloseCashRegisterRequest,
+) -> AppResult<CashRegisterSession> {
+    Ok(close_counted(repository, request, SessionStatus::PendingReview)?.session)
+}
+
+pub fn approve_close(
+    repository: &impl CashRegisterRepository,
+    users: &impl UserRepository,
+    request: ApproveCashCloseRequest,
+) -> AppResult<CashRegisterSummary> {
+    require_admin(users, request.user_id)?;
+    let notes = request.notes.trim();
+    if notes.is_empty() {
+        return Err(AppError::Validation(
+            "Explica las diferencias antes de aprobar el corte".to_string(),
+        ));
+    }
+    let session = get_session(repository, request.session_id)?;
+    if session.status != SessionStatus::PendingReview {
+        return Err(AppError::Conflict(
+            "La sesión no tiene un cierre pendiente de revisión".to_string(),
+        ));
+    }
+    if session.count_pending {
+        return Err(AppError::Conflict(
+            "La caja se cerró automáticamente; falta contar el efectivo".to_string(),
+        ));
+    }
+    repository.approve_close(session.id, request.user_id, notes)
+}
+
+pub fn get_pending_reviews(
+    repository: &impl CashRegisterRepository,
+) -> AppResult<Vec<CashRegisterSession>> {
+    repository.find_by_status(SessionStatus::PendingReview)
+}
+
+pub fn detect_stale_sessions(
+    repository: &impl CashRegisterRepository,
+) -> AppResult<StaleSessionCheck> {
+    let (policy, cutoff_hours) = repository.load_stale_session_policy()?;
+    let sessions = if cutoff_hours > 0 {
+        repository.find_stale_sessions(cutoff_hours)?
+    } else {
+        Vec::new()
+    };
+    Ok(StaleSessionCheck {
+        policy,
+        cutoff_hours,
+        sessions,
+        auto_closed: Vec::new(),
+    })
+}
+
+/// Run at startup: under `auto_close` the stale sessions are closed into review
+/// with their count pending, so the register can be opened for the new day.
+pub fn handle_stale_sessions(
+    repository: &impl CashRegisterRepository,
+) -> AppResult<StaleSessionCheck> {
+    let mut check = detect_stale_sessions(repository)?;
+    if check.policy == StaleSessionPolicy::AutoClose {
+        for session in std::mem::take(&mut check.sessions) {
+            check
+                .auto_closed
+                .push(repository.auto_close_session(session.id)?);
+        }
+    }
+    Ok(check)
+}
+
+fn close_counted(
+    repository: &impl CashRegisterRepository,
+    mut request: CloseCashRegisterRequest,
+    status: SessionStatus,
+) -> AppResult<CashRegisterSummary> {
+    let count = request.closing_count.take().unwrap_or_default();
+    if !count.is_empty() {
+        let (mxn, usd) = count_totals(&repository.load_denominations()?, &count)?;
+        request.closing_cash_mxn = mxn;
+        request.closing_cash_usd = usd;
+    }
+    if request.closing_cash_mxn < 0.0 || request.closing_cash_usd < 0.0 {
+        return Err(AppError::Validation(
+            "El efectivo contado no puede ser negativo".to_string(),
+        ));
+    }
     repository.close_session(
         request.session_id,
+        status,
         request.closing_cash_mxn,
         request.closing_cash_usd,
+        &count,
     )
 }
 
+/// Passes the open drawer to the next cashier without closing the session.
+pub fn handover_session(
+    repository: &impl CashRegisterRepository,
+    users: &impl UserRepository,
+    mut request: HandoverCashRegisterRequest,
+) -> AppResult<CashHandover> {
+    if request.from_user_id == request.to_user_id {
+        return Err(AppError::Validation(
+            "La caja debe entregarse a otro cajero".to_string(),
+        ));
+    }
+    let next_cashier = users
+        .find_by_id(request.to_user_id)?
+        .ok_or_else(|| AppError::NotFound("Cajero no encontrado".to_string()))?;
+    if !next_cashier.active {
+        return Err(AppError::Conflict(format!(
+            "El usuario {} está inactivo",
+            next_cashier.full_name
+        )));
+    }
+    let count = request.count.take().unwrap_or_default();
+    if !count.is_empty() {
+        let (mxn, usd) = count_totals(&repository.load_denominations()?, &count)?;
+        request.counted_cash_mxn = mxn;
+        request.counted_cash_usd = usd;
+    }
+    if request.counted_cash_mxn < 0.0 || request.counted_cash_usd < 0.0 {
+        return Err(AppError::Validation(
+            "El efectivo contado no puede ser negativo".to_string(),
+        ));
+    }
+    let session = get_session(repository, request.session_id)?;
+    if session.status != SessionStatus::Open {
+        return Err(AppError::Conflict(
+            "La sesión de caja no está abierta".to_string(),
+        ));
+    }
+    if session.user_id != request.from_user_id {
+        return Err(AppError::Conflict(
+            "Solo el cajero a cargo puede entregar la caja".to_string(),
+        ));
+    }
+    let notes = request
+        .notes
+        .as_deref()
+        .map(str::trim)
+        .filter(|notes| !notes.is_empty())
+        .map(str::to_string);
+    let mut handover = repository.create_handover(
+        &NewCashHandover {
+            session_id: session.id,
+            from_user_id: request.from_user_id,
+            to_user_id: request.to_user_id,
+            counted_cash_mxn: request.counted_cash_mxn,
+            counted_cash_usd: request.counted_cash_usd,
+            notes,
+        },
+        &count,
+    )?;
+    if hides_expectations(repository, &session)? {
+        handover.hide_expectations();
+    }
+    Ok(handover)
+}
+
+pub fn reopen_session(
+    repository: &impl CashRegisterRepository,
+    users: &impl UserRepository,
+    request: ReopenCashRegisterSessionRequest,
+) -> AppResult<CashRegisterSession> {
+    require_admin(users, request.user_id)?;
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::Validation(
+            "El motivo de la reapertura es obligatorio".to_string(),
+        ));
+    }
+    let session = get_session(repository, request.session_id)?;
+    if session.status == SessionStatus::Open {
+        return Err(AppError::Conflict(
+            "La sesión de caja ya está abierta".to_string(),
+        ));
+    }
+    repository.reopen_session(session.id, request.user_id, reason)
+}
+
+pub fn get_reopenings(
+    repository: &impl CashRegisterRepository,
+    session_id: i64,
+) -> AppResult<Vec<CashSessionReopening>> {
+    repository.find_reopenings(session_id)
+}
+
+pub fn get_over_short_report(
+    repository: &impl CashRegisterRepository,
+    request: CashOverShortReportRequest,
+) -> AppResult<CashOverShortReport> {
+    let (tolerance_mxn, tolerance_usd) = repository.load_over_short_tolerance()?;
+    let mut sessions = Vec::new();
+    for summary in repository.find_closed_summaries(
+        &request.start_date,
+        &request.end_date,
+        request.register_id,
+    )? {
+        let handovers = repository.find_handovers(summary.session.id)?;
+        for shift in cashier_shifts(&summary, &handovers) {
+            if request
+                .cashier_id
+                .is_some_and(|cashier_id| cashier_id != shift.user_id)
+            {
+                continue;
+            }
+            sessions.push(SessionOverShort {
+                session_id: summary.session.id,
+                register_name: summary.session.register_name.clone(),
+                user_id: shift.user_id,
+                user_name: shift.user_name,
+                closed_at: shift.ended_at,
+                expected_cash_mxn: shift.expected_cash_mxn,
+                expected_cash_usd: shift.expected_cash_usd,
+                difference_mxn: shift.difference_mxn,
+                difference_usd: shift.difference_usd,
+                flagged: shift.difference_mxn.abs() > tolerance_mxn
+                    || shift.difference_usd.abs() > tolerance_usd,
+                shortage_owed: repository.find_shortage(summary.session.id, shift.user_id)?,
+            });
+        }
+    }
+    Ok(CashOverShortReport {
+        tolerance_mxn,
+        tolerance_usd,
+        cashiers: summarize_over_short(&sessions),
+        sessions,
+    })
+}
+
+/// One cashier's stretch of a session and the cash difference it produced.
+struct CashierShift {
+    user_id: i64,
+    user_name: Option<String>,
+    ended_at: Option<String>,
+    expected_cash_mxn: f64,
+    expected_cash_usd: f64,
+    difference_mxn: f64,
+    difference_usd: f64,
+}
+
+/// Handover differences are cumulative for the session, so each shift owns the
+/// change since the previous handover and the closing cashier owns the final
+/// difference minus the last handover's.
+fn cashier_shifts(summary: &CashRegisterSummary, handovers: &[CashHandover]) -> Vec<CashierShift> {
+    let mut shifts = Vec::with_capacity(handovers.len() + 1);
+    let (mut previous_mxn, mut previous_usd) = (0.0, 0.0);
+    for handover in handovers {
+        shifts.push(CashierShift {
+            user_id: handover.from_user_id,
+            user_name: handover.from_user_name.clone(),
+            ended_at: Some(handover.created_at.clone()),
+            expected_cash_mxn: handover.expected_cash_mxn,
+            expected_cash_usd: handover.expected_cash_usd,
+            difference_mxn: money::sub_money(handover.difference_mxn, previous_mxn),
+            difference_usd: money::sub_money(handover.difference_usd, previous_usd),
+        });
+        previous_mxn = handover.difference_mxn;
+        previous_usd = handover.difference_usd;
+    }
+    shifts.push(CashierShift {
+        user_id: summary.session.user_id,
+        user_name: summary.session.user_name.clone(),
+        ended_at: summary.session.closed_at.clone(),
+        expected_cash_mxn: summary.expected_cash_mxn,
+        expected_cash_usd: summary.expected_cash_usd,
+        difference_mxn: money::sub_money(summary.difference_mxn, previous_mxn),
+        difference_usd: money::sub_money(summary.difference_usd, previous_usd),
+    });
+    shifts
+}
+
+/// Totals per cashier, in order of first appearance.
+pub fn summarize_over_short(sessions: &[SessionOverShort]) -> Vec<CashierOverShort> {
+    let mut cashiers: Vec<CashierOverShort> = Vec::new();
+    for session in sessions {
+        let index = match cashiers
+            .iter()
+            .position(|cashier| cashier.user_id == session.user_id)
+        {
+            Some(index) => index,
+            None => {
+                cashiers.push(CashierOverShort {
+                    user_id: session.user_id,
+                    user_name: session.user_name.clone(),
+                    ..Default::default()
+                });
+                cashiers.len() - 1
+            }
+        };
+        let cashier = &mut cashiers[index];
+        cashier.sessions += 1;
+        if session.flagged {
+            cashier.flagged_sessions += 1;
+        }
+        if session.difference_mxn > 0.0 {
+            cashier.over_mxn = money::add_money(cashier.over_mxn, session.difference_mxn);
+        } else {
+            cashier.short_mxn = money::sub_money(cashier.short_mxn, session.difference_mxn);
+        }
+        if session.difference_usd > 0.0 {
+            cashier.over_usd = money::add_money(cashier.over_usd, session.difference_usd);
+        } else {
+            cashier.short_usd = money::sub_money(cashier.short_usd, session.difference_usd);
+        }
+        cashier.net_mxn = money::sub_money(cashier.over_mxn, cashier.short_mxn);
+        cashier.net_usd = money::sub_money(cashier.over_usd, cashier.short_usd);
+        if let Some(shortage) = &session.shortage_owed {
+            cashier.shortage_owed_mxn =
+                money::add_money(cashier.shortage_owed_mxn, shortage.amount_mxn);
+        }
+    }
+    cashiers
+}
+
+/// Charges part or all of a cashier's MXN shortage in a closed session. The
+/// shortage is what the cashier's own shifts came up short.
+pub fn record_shortage(
+    repository: &impl CashRegisterRepository,
+    users: &impl UserRepository,
+    request: RecordCashShortageRequest,
+) -> AppResult<CashShortage> {
+    require_admin(users, request.user_id)?;
+    if request.amount_mxn <= 0.0 {
+        return Err(AppError::Validation(
+            "El monto del faltante debe ser mayor a cero".to_string(),
+        ));
+    }
+    let summary = repository.get_summary(request.session_id)?;
+    if summary.session.status != SessionStatus::Closed {
+        return Err(AppError::Conflict(
+            "Solo se registran faltantes de cortes cerrados".to_string(),
+        ));
+    }
+    let shifts = cashier_shifts(&summary, &repository.find_handovers(summary.session.id)?);
+    let cashier_id = match request.cashier_id {
+        Some(cashier_id) => cashier_id,
+        None if shifts.len() == 1 => shifts[0].user_id,
+        None => {
+            return Err(AppError::Validation(
+                "La caja cambió de manos; indica a qué cajero se carga el faltante".to_string(),
+            ))
+        }
+    };
+    let cashier_shifts: Vec<&CashierShift> = shifts
+        .iter()
+        .filter(|shift| shift.user_id == cashier_id)
+        .collect();
+    if cashier_shifts.is_empty() {
+        return Err(AppError::Validation(
+            "El cajero no tuvo la caja durante este corte".to_string(),
+        ));
+    }
+    let shortage = -money::sum_money(cashier_shifts.iter().map(|shift| shift.difference_mxn));
+    if money::round2(request.amount_mxn) > money::round2(shortage) {
+        return Err(AppError::Validation(format!(
+            "El monto excede el faltante del cajero en el corte (${:.2})",
+            shortage.max(0.0)
+        )));
+    }
+    if repository
+        .find_shortage(request.session_id, cashier_id)?
+        .is_some()
+    {
+        return Err(AppError::Conflict(
+            "El cajero ya tiene un faltante registrado en este corte".to_string(),
+        ));
+    }
+    let notes = request
+        .notes
+        .as_deref()
+        .map(str::trim)
+        .filter(|notes| !notes.is_empty());
+    repository.create_shortage(&request, cashier_id, notes)
+}
+
+pub fn get_handovers(
+    repository: &impl CashRegisterRepository,
+    session_id: i64,
+) -> AppResult<Vec<CashHandover>> {
+    let mut handovers = repository.find_handovers(session_id)?;
+    if let Some(session) = repository.find_by_id(session_id)? {
+        if hides_expectations(repository, &session)? {
+            handovers
+                .iter_mut()
+                .for_each(CashHandover::hide_expectations);
+        }
+    }
+    Ok(handovers)
+}
+
+pub fn find_handover(
+    repository: &impl CashRegisterRepository,
+    id: i64,
+) -> AppResult<Option<CashHandover>> {
+    let Some(mut handover) = repository.find_handover(id)? else {
+        return Ok(None);
+    };
+    let session = get_session(repository, handover.cash_register_session_id)?;
+    if hides_expectations(repository, &session)? {
+        handover.hide_expectations();
+    }
+    Ok(Some(handover))
+}
+
 pub fn get_summary(
     repository: &impl CashRegisterRepository,
     session_id: i64,
 ) -> AppResult<CashRegisterSummary> {
-    repository.get_summary(session_id)
+    let mut summary = repository.get_summary(session_id)?;
+    if hides_expectations(repository, &summary.session)? {
+        summary.hide_expectations();
+    }
+    Ok(summary)
+}
+
+/// Under blind close the cashier counts an open drawer without seeing what it
+/// should hold; the figures show up again once the count is submitted. An
+/// auto-closed session still waiting for its count stays hidden too.
+fn hides_expectations(
+    repository: &impl CashRegisterRepository,
+    session: &CashRegisterSession,
+) -> AppResult<bool> {
+    Ok(
+        (session.status == SessionStatus::Open || session.count_pending)
+            && repository.blind_close_enabled()?,
+    )
+}
+
+pub fn get_movements(
+    repository: &impl CashRegisterRepository,
+    session_id: i64,
+) -> AppResult<Vec<CashMovement>> {
+    repository.find_movements(session_id)
+}
+
+pub fn record_movement(
+    repository: &impl CashRegisterRepository,
+    request: CreateCashMovementRequest,
+) -> AppResult<CashMovement> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::Validation(
+            "El motivo del movimiento es obligatorio".to_string(),
+        ));
+    }
+    if request.amount_mxn < 0.0 || request.amount_usd < 0.0 {
+        return Err(AppError::Validation(
+            "Los montos del movimiento no pueden ser negativos".to_string(),
+        ));
+    }
+    if request.amount_mxn <= 0.0 && request.amount_usd <= 0.0 {
+        return Err(AppError::Validation(
+            "El movimiento debe tener un monto mayor a cero".to_string(),
+        ));
+    }
+    let session = get_session(repository, request.session_id)?;
+    if session.status != SessionStatus::Open {
+        return Err(AppError::Conflict(
+            "La sesión de caja no está abierta".to_string(),
+        ));
+    }
+    if !request.movement_type.is_inflow() {
+        let summary = repository.get_summary(session.id)?;
+        if request.amount_mxn > summary.expected_cash_mxn
+            || request.amount_usd > summary.expected_cash_usd
+        {
+            return Err(AppError::Conflict(
+                "El retiro excede el efectivo esperado en caja".to_string(),
+            ));
+        }
+    }
+    repository.create_movement(&request, reason)
+}
+
+pub fn generate_report(
+    repository: &impl CashRegisterRepository,
+    request: GenerateCashReportRequest,
+) -> AppResult<CashRegisterReport> {
+    let session = get_session(repository, request.session_id)?;
+    if request.report_type == CashReportType::Z && session.status != SessionStatus::Closed {
+        return Err(AppError::Conflict(
+            "El reporte Z solo puede generarse con la caja cerrada".to_string(),
+        ));
+    }
+    repository.create_report(
+        session.id,
+        request.user_id,
+        request.report_type,
+        hides_expectations(repository, &session)?,
+    )
+}
+
+pub fn get_reports(
+    repository: &impl CashRegisterRepository,
+    session_id: Option<i64>,
+    register_id: Option<i64>,
+) -> AppResult<Vec<CashRegisterReport>> {
+    repository.find_reports(session_id, register_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_totals, parse_denominations, summarize_over_short};
+    use crate::models::cash_over_short::SessionOverShort;
+    use crate::models::cash_register::{CashCurrency, CashDenominations, DenominationCount};
+    use crate::shared::error::AppError;
+
+    fn denominations() -> CashDenominations {
+        CashDenominations {
+            mxn: parse_denominations("500, 200,100,50,20,10,5,2,1,0.5"),
+            usd: parse_denominations("100,20,1"),
+        }
+    }
+
+    fn line(currency: CashCurrency, denomination: f64, quantity: i64) -> DenominationCount {
+        DenominationCount {
+            currency,
+            denomination,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn parses_denomination_settings_largest_first() {
+        assert_eq!(
+            parse_denominations("1, 0.5, x, 20,-5,20, 100"),
+            vec![100.0, 20.0, 1.0, 0.5]
+        );
+        assert!(parse_denominations("").is_empty());
+    }
+
+    #[test]
+    fn totals_a_drawer_count_per_currency() {
+        let count = [
+            line(CashCurrency::Mxn, 500.0, 2),
+            line(CashCurrency::Mxn, 0.5, 3),
+            line(CashCurrency::Usd, 20.0, 4),
+            line(CashCurrency::Usd, 1.0, 0),
+        ];
+        assert_eq!(
+            count_totals(&denominations(), &count).unwrap(),
+            (1001.5, 80.0)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_repeated_or_negative_denominations() {
+        for count in [
+            vec![line(CashCurrency::Usd, 50.0, 1)],
+            vec![
+                line(CashCurrency::Mxn, 10.0, 1),
+                line(CashCurrency::Mxn, 10.0, 2),
+            ],
+            vec![
+                line(CashCurrency::Mxn, 100.0, 1),
+                line(CashCurrency::Mxn, 100.001, 1),
+            ],
+            vec![line(CashCurrency::Mxn, 10.0, -1)],
+        ] {
+            assert!(matches!(
+                count_totals(&denominations(), &count),
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    fn over_short(user_id: i64, difference_mxn: f64, flagged: bool) -> SessionOverShort {
+        SessionOverShort {
+            session_id: 1,
+            register_name: None,
+            user_id,
+            user_name: None,
+            closed_at: None,
+            expected_cash_mxn: 0.0,
+            expected_cash_usd: 0.0,
+            difference_mxn,
+            difference_usd: 0.0,
+            flagged,
+            shortage_owed: None,
+        }
+    }
+
+    #[test]
+    fn aggregates_over_and_short_per_cashier() {
+        let cashiers = summarize_over_short(&[
+            over_short(1, 12.5, false),
+            over_short(2, -3.0, false),
+            over_short(1, -40.0, true),
+            over_short(1, 0.0, false),
+        ]);
+        assert_eq!(cashiers.len(), 2);
+        assert_eq!(cashiers[0].sessions, 3);
+        assert_eq!(cashiers[0].flagged_sessions, 1);
+        assert_eq!(cashiers[0].over_mxn, 12.5);
+        assert_eq!(cashiers[0].short_mxn, 40.0);
+        assert_eq!(cashiers[0].net_mxn, -27.5);
+        assert_eq!(cashiers[1].short_mxn, 3.0);
+    }
 }
@@ -1,5 +1,8 @@
 use crate::infrastructure::sqlite::Database;
-use crate::models::category::{Category, CreateCategoryRequest, UpdateCategoryRequest};
+use crate::models::category::{
+    Category, CreateCategoryRequest, MergeCategoriesRequest, MoveCategoryRequest,
+    UpdateCategoryRequest,
+};
 use crate::modules::catalog::categories::{
     adapters::outbound::sqlite::SqliteCategoryRepository, application,
 };
@@ -27,6 +30,19 @@ pub fn update_category(db: State<Database>, request: UpdateCategoryRequest) -> A
 }
 
 #[tauri::command]
-pub fn delete_category(db: State<Database>, id: i64) -> AppResult<()> {
-    application::delete_category(&SqliteCategoryRepository::new(&db), id)
+pub fn move_category(db: State<Database>, request: MoveCategoryRequest) -> AppResult<Category> {
+    application::move_category(&SqliteCategoryRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn merge_categories(
+    db: State<Database>,
+    request: MergeCategoriesRequest,
+) -> AppResult<Category> {
+    application::merge_categories(&SqliteCategoryRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn delete_category(db: State<Database>, id: i64, reassign_to: Option<i64>) -> AppResult<()> {
+    application::delete_category(&SqliteCategoryRepository::new(&db), id, reassign_to)
 }
@@ -27,8 +27,13 @@ impl CategoryRepository for SqliteCategoryRepository<'_> {
         find_by_name(self.db, name)
     }
 
-    fn create(&self, name: &str, description: Option<&str>) -> AppResult<Category> {
-        create(self.db, name, description)
+    fn create(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        parent_id: Option<i64>,
+    ) -> AppResult<Category> {
+        create(self.db, name, description, parent_id)
     }
 
     fn update(
@@ -40,25 +45,61 @@ impl CategoryRepository for SqliteCategoryRepository<'_> {
         update(self.db, id, name, description)
     }
 
-    fn delete(&self, id: i64) -> AppResult<()> {
-        delete(self.db, id)
+    fn delete(&self, id: i64, reassign_to: Option<i64>) -> AppResult<()> {
+        delete(self.db, id, reassign_to)
+    }
+
+    fn set_parent(&self, id: i64, parent_id: Option<i64>) -> AppResult<Category> {
+        self.db.conn.lock()?.execute(
+            "UPDATE categories SET parent_id = ?1 WHERE id = ?2",
+            params![parent_id, id],
+        )?;
+        find_by_id(self.db, id)?
+            .ok_or_else(|| AppError::NotFound("Categoría no encontrada".to_string()))
+    }
+
+    fn merge(&self, source_id: i64, target_id: i64) -> AppResult<Category> {
+        merge(self.db, source_id, target_id)
+    }
+
+    fn count_products(&self, id: i64) -> AppResult<i64> {
+        Ok(self.db.conn.lock()?.query_row(
+            "SELECT COUNT(*) FROM products WHERE category_id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?)
     }
 }
 
+/// Builds each category's path from its department down.
+const SELECT_QUERY: &str = "\
+    WITH RECURSIVE tree(id, path, depth) AS ( \
+        SELECT id, name, 0 FROM categories WHERE parent_id IS NULL \
+        UNION ALL \
+        SELECT c.id, tree.path || ' / ' || c.name, tree.depth + 1 \
+            FROM categories c JOIN tree ON c.parent_id = tree.id \
+    ) \
+    SELECT c.id, c.name, c.description, c.created_at, c.parent_id, tree.path, tree.depth \
+    FROM categories c JOIN tree ON tree.id = c.id";
+
+fn row_to_category(row: &rusqlite::Row) -> rusqlite::Result<Category> {
+    Ok(Category {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        created_at: row.get(3)?,
+        parent_id: row.get(4)?,
+        path: row.get(5)?,
+        depth: row.get(6)?,
+    })
+}
+
 pub fn find_all(db: &Database) -> AppResult<Vec<Category>> {
     let conn = db.conn.lock()?;
-    let mut stmt =
-        conn.prepare("SELECT id, name, description, created_at FROM categories ORDER BY name")?;
+    let mut stmt = conn.prepare(&format!("{SELECT_QUERY} ORDER BY tree.path"))?;
 
     let categories = stmt
-        .query_map([], |row| {
-            Ok(Category {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })?
+        .query_map([], row_to_category)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(categories)
@@ -68,16 +109,9 @@ pub fn find_by_id(db: &Database, id: i64) -> AppResult<Option<Category>> {
     let conn = db.conn.lock()?;
     let result = conn
         .query_row(
-            "SELECT id, name, description, created_at FROM categories WHERE id = ?1",
+            &format!("{SELECT_QUERY} WHERE c.id = ?1"),
             params![id],
-            |row| {
-                Ok(Category {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    created_at: row.get(3)?,
-                })
-            },
+            row_to_category,
         )
         .ok();
 
@@ -88,27 +122,25 @@ pub fn find_by_name(db: &Database, name: &str) -> AppResult<Option<Category>> {
     let conn = db.conn.lock()?;
     let result = conn
         .query_row(
-            "SELECT id, name, description, created_at FROM categories WHERE name = ?1",
+            &format!("{SELECT_QUERY} WHERE c.name = ?1"),
             params![name],
-            |row| {
-                Ok(Category {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    created_at: row.get(3)?,
-                })
-            },
+            row_to_category,
         )
         .ok();
 
     Ok(result)
 }
 
-pub fn create(db: &Database, name: &str, description: Option<&str>) -> AppResult<Category> {
+pub fn create(
+    db: &Database,
+    name: &str,
+    description: Option<&str>,
+    parent_id: Option<i64>,
+) -> AppResult<Category> {
     let conn = db.conn.lock()?;
     conn.execute(
-        "INSERT INTO categories (name, description) VALUES (?1, ?2)",
-        params![name, description],
+        "INSERT INTO categories (name, description, parent_id) VALUES (?1, ?2, ?3)",
+        params![name, description, parent_id],
     )?;
 
     let id = conn.last_insert_rowid();
@@ -142,8 +174,113 @@ pub fn update(
     find_by_id(db, id)?.ok_or_else(|| AppError::NotFound("Categoría no encontrada".to_string()))
 }
 
-pub fn delete(db: &Database, id: i64) -> AppResult<()> {
-    let conn = db.conn.lock()?;
-    conn.execute("DELETE FROM categories WHERE id = ?1", params![id])?;
+/// Subcategories move up to the deleted category's parent; its products move
+/// to `reassign_to`.
+pub fn delete(db: &Database, id: i64, reassign_to: Option<i64>) -> AppResult<()> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "UPDATE products SET category_id = ?1 WHERE category_id = ?2",
+        params![reassign_to, id],
+    )?;
+    tx.execute(
+        "UPDATE categories SET parent_id = (SELECT parent_id FROM categories WHERE id = ?1) \
+            WHERE parent_id = ?1",
+        params![id],
+    )?;
+    tx.execute("DELETE FROM categories WHERE id = ?1", params![id])?;
+    tx.commit()?;
     Ok(())
 }
+
+pub fn merge(db: &Database, source_id: i64, target_id: i64) -> AppResult<Category> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "UPDATE products SET category_id = ?1 WHERE category_id = ?2",
+        params![target_id, source_id],
+    )?;
+    tx.execute(
+        "UPDATE categories SET parent_id = ?1 WHERE parent_id = ?2",
+        params![target_id, source_id],
+    )?;
+    tx.execute("DELETE FROM categories WHERE id = ?1", params![source_id])?;
+    tx.commit()?;
+    drop(conn);
+    find_by_id(db, target_id)?
+        .ok_or_else(|| AppError::NotFound("Categoría no encontrada".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create, delete, find_all, merge};
+    use crate::infrastructure::sqlite::Database;
+    use rusqlite::Connection;
+    use std::sync::Mutex;
+
+    fn test_database() -> Database {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE categories (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT UNIQUE NOT NULL,
+                description TEXT,
+                created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00',
+                parent_id INTEGER REFERENCES categories(id)
+            );
+            CREATE TABLE products (
+                id INTEGER PRIMARY KEY,
+                category_id INTEGER REFERENCES categories(id) ON DELETE SET NULL
+            );",
+        )
+        .unwrap();
+        Database {
+            conn: Mutex::new(conn),
+        }
+    }
+
+    fn product_categories(db: &Database) -> Vec<Option<i64>> {
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT category_id FROM products ORDER BY id")
+            .unwrap();
+        let categories = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        categories
+    }
+
+    #[test]
+    fn merging_and_deleting_keep_products_and_subcategories() {
+        let db = test_database();
+        let groceries = create(&db, "Abarrotes", None, None).unwrap();
+        let canned = create(&db, "Enlatados", None, Some(groceries.id)).unwrap();
+        let tuna = create(&db, "Atún", None, Some(canned.id)).unwrap();
+        let preserves = create(&db, "Conservas", None, None).unwrap();
+        assert_eq!(tuna.path, "Abarrotes / Enlatados / Atún");
+        assert_eq!(tuna.depth, 2);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO products (id, category_id) VALUES (1, ?1), (2, ?2)",
+                [canned.id, preserves.id],
+            )
+            .unwrap();
+
+        let merged = merge(&db, preserves.id, canned.id).unwrap();
+        assert_eq!(merged.path, "Abarrotes / Enlatados");
+        assert_eq!(product_categories(&db), vec![Some(canned.id); 2]);
+
+        delete(&db, canned.id, Some(groceries.id)).unwrap();
+        assert_eq!(product_categories(&db), vec![Some(groceries.id); 2]);
+        let paths: Vec<String> = find_all(&db)
+            .unwrap()
+            .into_iter()
+            .map(|category| category.path)
+            .collect();
+        assert_eq!(paths, vec!["Abarrotes", "Abarrotes / Atún"]);
+    }
+}
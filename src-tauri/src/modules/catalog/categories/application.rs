@@ -1,14 +1,26 @@
-use crate::models::category::{Category, CreateCategoryRequest, UpdateCategoryRequest};
+use crate::models::category::{
+    Category, CreateCategoryRequest, MergeCategoriesRequest, MoveCategoryRequest,
+    UpdateCategoryRequest,
+};
 use crate::shared::error::{AppError, AppResult};
 
 pub trait CategoryRepository {
     fn find_all(&self) -> AppResult<Vec<Category>>;
     fn find_by_id(&self, id: i64) -> AppResult<Option<Category>>;
     fn find_by_name(&self, name: &str) -> AppResult<Option<Category>>;
-    fn create(&self, name: &str, description: Option<&str>) -> AppResult<Category>;
+    fn create(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        parent_id: Option<i64>,
+    ) -> AppResult<Category>;
     fn update(&self, id: i64, name: Option<&str>, description: Option<&str>)
         -> AppResult<Category>;
-    fn delete(&self, id: i64) -> AppResult<()>;
+    /// Moves subcategories up to the category's parent and products to `reassign_to`.
+    fn delete(&self, id: i64, reassign_to: Option<i64>) -> AppResult<()>;
+    fn set_parent(&self, id: i64, parent_id: Option<i64>) -> AppResult<Category>;
+    fn merge(&self, source_id: i64, target_id: i64) -> AppResult<Category>;
+    fn count_products(&self, id: i64) -> AppResult<i64>;
 }
 
 pub fn get_categories(repository: &impl CategoryRepository) -> AppResult<Vec<Category>> {
@@ -32,7 +44,15 @@ pub fn create_category(
         )));
     }
 
-    repository.create(&request.name, request.description.as_deref())
+    if let Some(parent_id) = request.parent_id {
+        get_category(repository, parent_id)?;
+    }
+
+    repository.create(
+        &request.name,
+        request.description.as_deref(),
+        request.parent_id,
+    )
 }
 
 pub fn update_category(
@@ -54,6 +74,115 @@ pub fn update_category(
     )
 }
 
-pub fn delete_category(repository: &impl CategoryRepository, id: i64) -> AppResult<()> {
-    repository.delete(id)
+pub fn move_category(
+    repository: &impl CategoryRepository,
+    request: MoveCategoryRequest,
+) -> AppResult<Category> {
+    get_category(repository, request.id)?;
+    if let Some(parent_id) = request.parent_id {
+        get_category(repository, parent_id)?;
+        if is_within(&repository.find_all()?, parent_id, request.id) {
+            return Err(AppError::Validation(
+                "Una categoría no puede moverse dentro de sí misma".to_string(),
+            ));
+        }
+    }
+    repository.set_parent(request.id, request.parent_id)
+}
+
+pub fn merge_categories(
+    repository: &impl CategoryRepository,
+    request: MergeCategoriesRequest,
+) -> AppResult<Category> {
+    get_category(repository, request.source_id)?;
+    get_category(repository, request.target_id)?;
+    if is_within(
+        &repository.find_all()?,
+        request.target_id,
+        request.source_id,
+    ) {
+        return Err(AppError::Validation(
+            "No se puede fusionar una categoría con una de sus subcategorías".to_string(),
+        ));
+    }
+    repository.merge(request.source_id, request.target_id)
+}
+
+/// A category with products is only deleted once the caller says where they go.
+pub fn delete_category(
+    repository: &impl CategoryRepository,
+    id: i64,
+    reassign_to: Option<i64>,
+) -> AppResult<()> {
+    get_category(repository, id)?;
+    match reassign_to {
+        Some(target) if target == id => {
+            return Err(AppError::Validation(
+                "Los productos no pueden reasignarse a la categoría que se elimina".to_string(),
+            ));
+        }
+        Some(target) => {
+            get_category(repository, target)?;
+        }
+        None => {
+            let products = repository.count_products(id)?;
+            if products > 0 {
+                return Err(AppError::Conflict(format!(
+                    "La categoría tiene {products} productos; elige a qué categoría moverlos"
+                )));
+            }
+        }
+    }
+    repository.delete(id, reassign_to)
+}
+
+/// Whether `id` is `ancestor` or one of its subcategories.
+fn is_within(categories: &[Category], id: i64, ancestor: i64) -> bool {
+    let mut current = Some(id);
+    let mut steps = 0;
+    while let Some(category_id) = current {
+        if category_id == ancestor {
+            return true;
+        }
+        steps += 1;
+        if steps > categories.len() {
+            return false;
+        }
+        current = categories
+            .iter()
+            .find(|category| category.id == category_id)
+            .and_then(|category| category.parent_id);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_within;
+    use crate::models::category::Category;
+
+    fn category(id: i64, parent_id: Option<i64>) -> Category {
+        Category {
+            id,
+            name: format!("C{id}"),
+            description: None,
+            created_at: String::new(),
+            parent_id,
+            path: String::new(),
+            depth: 0,
+        }
+    }
+
+    #[test]
+    fn detects_categories_inside_another() {
+        let categories = vec![
+            category(1, None),
+            category(2, Some(1)),
+            category(3, Some(2)),
+        ];
+        assert!(is_within(&categories, 3, 1));
+        assert!(is_within(&categories, 1, 1));
+        assert!(!is_within(&categories, 1, 3));
+        assert!(!is_within(&categories, 2, 3));
+    }
 }
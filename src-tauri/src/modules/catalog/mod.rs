@@ -1,2 +1,3 @@
 pub mod categories;
 pub mod products;
+pub mod units;
@@ -1,7 +1,21 @@
 use crate::infrastructure::sqlite::Database;
-use crate::models::product::{CreateProductRequest, Product, UpdateProductRequest};
+use crate::models::price_change::{
+    BulkRepriceRequest, RepriceLine, SchedulePriceChangesRequest, ScheduledPriceChange,
+    ScheduledPriceChangeStatus,
+};
+use crate::models::product::{
+    AddProductCodeRequest, BarcodeLookup, CreateProductRequest, Product, ProductCode,
+    ProductPriceChange, UpdateProductRequest,
+};
+use crate::models::product_import::{ProductImportReport, ProductImportRequest};
+use crate::models::product_kit::{KitComponent, SetKitComponentsRequest};
+use crate::models::product_variant::{
+    GenerateVariantsRequest, ProductAttribute, ProductGroup, ProductVariant,
+};
+use crate::modules::catalog::categories::adapters::outbound::sqlite::SqliteCategoryRepository;
 use crate::modules::catalog::products::{
-    adapters::outbound::sqlite::SqliteProductRepository, application,
+    adapters::outbound::{spreadsheet::LocalProductSheetFile, sqlite::SqliteProductRepository},
+    application, catalog_file, kits, repricing,
 };
 use crate::shared::error::AppResult;
 use tauri::State;
@@ -22,7 +36,7 @@ pub fn get_product(db: State<Database>, id: i64) -> AppResult<Product> {
 }
 
 #[tauri::command]
-pub fn find_product_by_barcode(db: State<Database>, barcode: String) -> AppResult<Product> {
+pub fn find_product_by_barcode(db: State<Database>, barcode: String) -> AppResult<BarcodeLookup> {
     application::find_product_by_barcode(&SqliteProductRepository::new(&db), barcode)
 }
 
@@ -31,6 +45,11 @@ pub fn search_products(db: State<Database>, term: String) -> AppResult<Vec<Produ
     application::search_products(&SqliteProductRepository::new(&db), term)
 }
 
+#[tauri::command]
+pub fn search_products_grouped(db: State<Database>, term: String) -> AppResult<Vec<ProductGroup>> {
+    application::search_products_grouped(&SqliteProductRepository::new(&db), term)
+}
+
 #[tauri::command]
 pub fn create_product(db: State<Database>, request: CreateProductRequest) -> AppResult<Product> {
     application::create_product(&SqliteProductRepository::new(&db), request)
@@ -45,3 +64,125 @@ pub fn update_product(db: State<Database>, request: UpdateProductRequest) -> App
 pub fn delete_product(db: State<Database>, id: i64) -> AppResult<()> {
     application::delete_product(&SqliteProductRepository::new(&db), id)
 }
+
+#[tauri::command]
+pub fn get_product_codes(db: State<Database>, product_id: i64) -> AppResult<Vec<ProductCode>> {
+    application::get_product_codes(&SqliteProductRepository::new(&db), product_id)
+}
+
+#[tauri::command]
+pub fn add_product_code(
+    db: State<Database>,
+    request: AddProductCodeRequest,
+) -> AppResult<ProductCode> {
+    application::add_product_code(&SqliteProductRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn delete_product_code(db: State<Database>, id: i64) -> AppResult<()> {
+    application::delete_product_code(&SqliteProductRepository::new(&db), id)
+}
+
+#[tauri::command]
+pub fn get_product_attributes(
+    db: State<Database>,
+    product_id: i64,
+) -> AppResult<Vec<ProductAttribute>> {
+    application::get_product_attributes(&SqliteProductRepository::new(&db), product_id)
+}
+
+#[tauri::command]
+pub fn get_product_variants(
+    db: State<Database>,
+    product_id: i64,
+) -> AppResult<Vec<ProductVariant>> {
+    application::get_product_variants(&SqliteProductRepository::new(&db), product_id)
+}
+
+#[tauri::command]
+pub fn generate_product_variants(
+    db: State<Database>,
+    request: GenerateVariantsRequest,
+) -> AppResult<Vec<ProductVariant>> {
+    application::generate_product_variants(&SqliteProductRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn get_kit_components(db: State<Database>, kit_id: i64) -> AppResult<Vec<KitComponent>> {
+    kits::get_kit_components(&SqliteProductRepository::new(&db), kit_id)
+}
+
+#[tauri::command]
+pub fn set_kit_components(
+    db: State<Database>,
+    request: SetKitComponentsRequest,
+) -> AppResult<Vec<KitComponent>> {
+    kits::set_kit_components(&SqliteProductRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn get_product_price_history(
+    db: State<Database>,
+    product_id: i64,
+) -> AppResult<Vec<ProductPriceChange>> {
+    application::get_product_price_history(&SqliteProductRepository::new(&db), product_id)
+}
+
+#[tauri::command]
+pub fn schedule_price_changes(
+    db: State<Database>,
+    request: SchedulePriceChangesRequest,
+) -> AppResult<Vec<ScheduledPriceChange>> {
+    repricing::schedule_price_changes(&SqliteProductRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn get_scheduled_price_changes(
+    db: State<Database>,
+    status: Option<ScheduledPriceChangeStatus>,
+) -> AppResult<Vec<ScheduledPriceChange>> {
+    repricing::get_scheduled_price_changes(&SqliteProductRepository::new(&db), status)
+}
+
+#[tauri::command]
+pub fn cancel_scheduled_price_change(db: State<Database>, id: i64) -> AppResult<()> {
+    repricing::cancel_scheduled_price_change(&SqliteProductRepository::new(&db), id)
+}
+
+#[tauri::command]
+pub fn preview_bulk_reprice(
+    db: State<Database>,
+    request: BulkRepriceRequest,
+) -> AppResult<Vec<RepriceLine>> {
+    repricing::preview_bulk_reprice(&SqliteProductRepository::new(&db), &request)
+}
+
+#[tauri::command]
+pub fn apply_bulk_reprice(
+    db: State<Database>,
+    request: BulkRepriceRequest,
+) -> AppResult<Vec<RepriceLine>> {
+    repricing::apply_bulk_reprice(&SqliteProductRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn import_products(
+    db: State<Database>,
+    request: ProductImportRequest,
+) -> AppResult<ProductImportReport> {
+    catalog_file::import_products(
+        &SqliteProductRepository::new(&db),
+        &SqliteCategoryRepository::new(&db),
+        &LocalProductSheetFile,
+        request,
+    )
+}
+
+#[tauri::command]
+pub fn export_products(db: State<Database>, path: String) -> AppResult<usize> {
+    catalog_file::export_products(
+        &SqliteProductRepository::new(&db),
+        &LocalProductSheetFile,
+        path,
+    )
+}
@@ -1 +1,2 @@
+pub mod spreadsheet;
 pub mod sqlite;
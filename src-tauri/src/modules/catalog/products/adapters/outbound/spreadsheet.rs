@@ -0,0 +1,150 @@
+use crate::modules::catalog::products::catalog_file::{ProductSheetFile, SheetCell};
+use crate::shared::error::{AppError, AppResult};
+use calamine::Reader;
+use std::path::Path;
+
+pub struct LocalProductSheetFile;
+
+enum SheetFormat {
+    Csv,
+    Workbook,
+}
+
+impl ProductSheetFile for LocalProductSheetFile {
+    fn read_rows(&self, path: &str) -> AppResult<Vec<Vec<String>>> {
+        match sheet_format(path)? {
+            SheetFormat::Csv => {
+                let bytes = std::fs::read(path).map_err(|error| {
+                    AppError::Database(format!("No se pudo leer el archivo: {error}"))
+                })?;
+                read_csv(&bytes)
+            }
+            SheetFormat::Workbook => read_workbook(path),
+        }
+    }
+
+    fn write_rows(&self, path: &str, rows: &[Vec<SheetCell>]) -> AppResult<()> {
+        match sheet_format(path)? {
+            SheetFormat::Csv => write_csv(path, rows),
+            SheetFormat::Workbook => write_workbook(path, rows),
+        }
+    }
+}
+
+fn sheet_format(path: &str) -> AppResult<SheetFormat> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|value| value.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    match extension.as_str() {
+        "csv" => Ok(SheetFormat::Csv),
+        "xlsx" | "xlsm" | "xls" | "ods" => Ok(SheetFormat::Workbook),
+        _ => Err(AppError::Validation(
+            "Formato no soportado; usa un archivo CSV o XLSX".to_string(),
+        )),
+    }
+}
+
+/// Spreadsheet apps in Spanish locales save CSV with `;`, so the separator is
+/// taken from whichever appears more in the header line.
+fn read_csv(bytes: &[u8]) -> AppResult<Vec<Vec<String>>> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let header = bytes
+        .split(|byte| *byte == b'\n')
+        .next()
+        .unwrap_or_default();
+    let count = |separator: u8| header.iter().filter(|byte| **byte == separator).count();
+    let delimiter = if count(b';') > count(b',') {
+        b';'
+    } else {
+        b','
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(bytes);
+    reader
+        .byte_records()
+        .map(|record| {
+            record
+                .map(|record| {
+                    record
+                        .iter()
+                        .map(|field| String::from_utf8_lossy(field).into_owned())
+                        .collect()
+                })
+                .map_err(|error| AppError::Validation(format!("CSV inválido: {error}")))
+        })
+        .collect()
+}
+
+fn read_workbook(path: &str) -> AppResult<Vec<Vec<String>>> {
+    let mut workbook = calamine::open_workbook_auto(path)
+        .map_err(|error| AppError::Validation(format!("No se pudo abrir el archivo: {error}")))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| AppError::Validation("El archivo no tiene hojas".to_string()))?
+        .map_err(|error| AppError::Validation(format!("No se pudo leer la hoja: {error}")))?;
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .collect())
+}
+
+fn write_csv(path: &str, rows: &[Vec<SheetCell>]) -> AppResult<()> {
+    let write_error =
+        |error: csv::Error| AppError::Database(format!("No se pudo escribir el archivo: {error}"));
+    let mut writer = csv::Writer::from_path(path).map_err(write_error)?;
+    for row in rows {
+        writer
+            .write_record(row.iter().map(|cell| match cell {
+                SheetCell::Text(text) => text.clone(),
+                SheetCell::Number(number) => number.to_string(),
+            }))
+            .map_err(write_error)?;
+    }
+    writer
+        .flush()
+        .map_err(|error| AppError::Database(format!("No se pudo escribir el archivo: {error}")))
+}
+
+fn write_workbook(path: &str, rows: &[Vec<SheetCell>]) -> AppResult<()> {
+    let write_error = |error: rust_xlsxwriter::XlsxError| {
+        AppError::Database(format!("No se pudo escribir el archivo: {error}"))
+    };
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let bold = rust_xlsxwriter::Format::new().set_bold();
+    for (row_index, row) in rows.iter().enumerate() {
+        let row_number = row_index as u32;
+        for (column_index, cell) in row.iter().enumerate() {
+            let column = column_index as u16;
+            match cell {
+                SheetCell::Text(text) if row_index == 0 => {
+                    worksheet.write_string_with_format(row_number, column, text, &bold)
+                }
+                SheetCell::Text(text) => worksheet.write_string(row_number, column, text),
+                SheetCell::Number(number) => worksheet.write_number(row_number, column, *number),
+            }
+            .map_err(write_error)?;
+        }
+    }
+    workbook.save(path).map_err(write_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_csv;
+
+    #[test]
+    fn reads_semicolon_csv_with_a_byte_order_mark() {
+        let rows = read_csv("\u{feff}Nombre;Precio\nCafé;45,5\n".as_bytes()).unwrap();
+        assert_eq!(rows, vec![vec!["Nombre", "Precio"], vec!["Café", "45,5"]]);
+
+        let rows = read_csv(b"Nombre,Precio\n\"Pan, dulce\",8\n").unwrap();
+        assert_eq!(rows[1], vec!["Pan, dulce", "8"]);
+    }
+}
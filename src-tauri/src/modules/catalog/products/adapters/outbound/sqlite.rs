@@ -1,8 +1,21 @@
 use crate::infrastructure::sqlite::Database;
-use crate::models::product::Product;
-use crate::modules::catalog::products::application::ProductRepository;
+use crate::models::price_change::{
+    ScheduledPriceChange, ScheduledPriceChangeStatus, ScheduledPriceInput,
+};
+use crate::models::product::{
+    AddProductCodeRequest, BelowCostPolicy, CreateProductRequest, Product, ProductCode,
+    ProductCodeType, ProductPriceChange, UpdateProductRequest,
+};
+use crate::models::product_kit::{KitComponent, KitComponentInput};
+use crate::models::product_variant::{
+    ProductAttribute, ProductAttributeValue, ProductVariant, VariantAttributeInput, VariantOption,
+};
+use crate::models::unit::Unit;
+use crate::modules::catalog::products::application::{margin_percent, ProductRepository};
+use crate::modules::catalog::products::scale_barcode::{self, ScaleBarcodeFormat};
 use crate::shared::error::{AppError, AppResult};
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeSet, HashMap};
 
 pub struct SqliteProductRepository<'db> {
     db: &'db Database,
@@ -35,40 +48,188 @@ impl ProductRepository for SqliteProductRepository<'_> {
         search(self.db, term)
     }
 
-    fn create(&self, request: &crate::models::product::CreateProductRequest) -> AppResult<Product> {
-        create(
-            self.db,
-            &request.name,
-            request.description.as_deref(),
-            request.barcode.as_deref(),
-            request.price,
-            &request.unit,
-            request.is_bulk,
-            request.category_id,
-            request.stock.unwrap_or(0.0),
-            request.min_stock.unwrap_or(0.0),
-        )
+    fn create(&self, request: &CreateProductRequest) -> AppResult<Product> {
+        create(self.db, request)
     }
 
-    fn update(&self, request: &crate::models::product::UpdateProductRequest) -> AppResult<Product> {
-        update(
-            self.db,
-            request.id,
-            request.name.as_deref(),
-            request.description.as_deref(),
-            request.barcode.as_deref(),
-            request.price,
-            request.unit.as_deref(),
-            request.is_bulk,
-            request.category_id,
-            request.min_stock,
-            request.active,
-        )
+    fn update(&self, request: &UpdateProductRequest) -> AppResult<Product> {
+        update(self.db, request)
+    }
+
+    fn update_all(&self, requests: &[UpdateProductRequest]) -> AppResult<()> {
+        let mut conn = self.db.conn.lock()?;
+        let tx = conn.transaction()?;
+        for request in requests {
+            apply_update(&tx, request)?;
+        }
+        tx.commit()?;
+        Ok(())
     }
 
     fn delete(&self, id: i64) -> AppResult<()> {
         delete(self.db, id)
     }
+
+    fn find_code(&self, code: &str) -> AppResult<Option<ProductCode>> {
+        let conn = self.db.conn.lock()?;
+        query_code(&conn, "pc.code = ?1", params![code])
+    }
+
+    fn find_code_by_id(&self, id: i64) -> AppResult<Option<ProductCode>> {
+        let conn = self.db.conn.lock()?;
+        query_code(&conn, "pc.id = ?1", params![id])
+    }
+
+    fn find_codes(&self, product_id: i64) -> AppResult<Vec<ProductCode>> {
+        find_codes(self.db, product_id)
+    }
+
+    fn add_code(&self, request: &AddProductCodeRequest) -> AppResult<ProductCode> {
+        add_code(self.db, request)
+    }
+
+    fn scale_barcode_formats(&self) -> AppResult<Vec<ScaleBarcodeFormat>> {
+        let value: Option<String> = self
+            .db
+            .conn
+            .lock()?
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'scale_barcode_formats'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(scale_barcode::parse_formats(
+            value.as_deref().unwrap_or_default(),
+        ))
+    }
+
+    fn below_cost_policy(&self) -> AppResult<BelowCostPolicy> {
+        let value: Option<String> = self
+            .db
+            .conn
+            .lock()?
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'products_below_cost_policy'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(value
+            .and_then(|value| BelowCostPolicy::parse(value.trim()))
+            .unwrap_or_default())
+    }
+
+    fn find_price_history(&self, product_id: i64) -> AppResult<Vec<ProductPriceChange>> {
+        find_price_history(self.db, product_id)
+    }
+
+    fn find_attributes(&self, product_id: i64) -> AppResult<Vec<ProductAttribute>> {
+        find_attributes(self.db, product_id)
+    }
+
+    fn find_variants(&self, parent_id: i64) -> AppResult<Vec<ProductVariant>> {
+        let conn = self.db.conn.lock()?;
+        query_variants(&conn, parent_id)
+    }
+
+    fn create_variants(
+        &self,
+        parent: &Product,
+        attributes: &[VariantAttributeInput],
+        combinations: &[Vec<VariantOption>],
+    ) -> AppResult<Vec<ProductVariant>> {
+        create_variants(self.db, parent, attributes, combinations)
+    }
+
+    fn find_in_category_tree(&self, category_id: i64) -> AppResult<Vec<Product>> {
+        find_in_category_tree(self.db, category_id)
+    }
+
+    fn create_scheduled_price_changes(
+        &self,
+        changes: &[ScheduledPriceInput],
+        effective_at: &str,
+        user_id: i64,
+    ) -> AppResult<Vec<ScheduledPriceChange>> {
+        create_scheduled_price_changes(self.db, changes, effective_at, user_id)
+    }
+
+    fn find_scheduled_price_changes(
+        &self,
+        status: Option<ScheduledPriceChangeStatus>,
+    ) -> AppResult<Vec<ScheduledPriceChange>> {
+        let conn = self.db.conn.lock()?;
+        match status {
+            Some(status) => query_scheduled_changes(
+                &conn,
+                "WHERE s.status = ?1 ORDER BY s.effective_at, s.id",
+                params![status],
+            ),
+            None => {
+                query_scheduled_changes(&conn, "ORDER BY s.effective_at DESC, s.id DESC", params![])
+            }
+        }
+    }
+
+    fn find_due_price_changes(&self) -> AppResult<Vec<ScheduledPriceChange>> {
+        let conn = self.db.conn.lock()?;
+        query_scheduled_changes(
+            &conn,
+            "WHERE s.status = 'pending' AND s.effective_at <= datetime('now', 'localtime') \
+                ORDER BY s.effective_at, s.id",
+            params![],
+        )
+    }
+
+    fn set_scheduled_price_change_status(
+        &self,
+        id: i64,
+        status: ScheduledPriceChangeStatus,
+    ) -> AppResult<bool> {
+        let updated = self.db.conn.lock()?.execute(
+            "UPDATE scheduled_price_changes SET status = ?1, \
+                applied_at = CASE WHEN ?1 = 'applied' THEN datetime('now', 'localtime') END \
+                WHERE id = ?2 AND status = 'pending'",
+            params![status, id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    fn delete_code(&self, id: i64) -> AppResult<()> {
+        self.db
+            .conn
+            .lock()?
+            .execute("DELETE FROM product_codes WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn find_kit_components(&self, kit_id: i64) -> AppResult<Vec<KitComponent>> {
+        find_kit_components(self.db, kit_id)
+    }
+
+    fn replace_kit_components(
+        &self,
+        kit_id: i64,
+        components: &[KitComponentInput],
+    ) -> AppResult<()> {
+        replace_kit_components(self.db, kit_id, components)
+    }
+
+    fn find_unit(&self, name: &str) -> AppResult<Option<Unit>> {
+        crate::modules::catalog::units::adapters::outbound::sqlite::find_by_name(self.db, name)
+    }
+
+    fn is_kit_component(&self, product_id: i64) -> AppResult<bool> {
+        let found: i64 = self.db.conn.lock()?.query_row(
+            "SELECT EXISTS(SELECT 1 FROM product_kit_components WHERE component_id = ?1)",
+            params![product_id],
+            |row| row.get(0),
+        )?;
+        Ok(found == 1)
+    }
 }
 
 fn row_to_product(row: &rusqlite::Row) -> rusqlite::Result<Product> {
@@ -78,6 +239,7 @@ fn row_to_product(row: &rusqlite::Row) -> rusqlite::Result<Product> {
         description: row.get(2)?,
         barcode: row.get(3)?,
         price: row.get(4)?,
+        cost: row.get(16)?,
         unit: row.get(5)?,
         is_bulk: row.get::<_, i32>(6)? == 1,
         category_id: row.get(7)?,
@@ -87,14 +249,20 @@ fn row_to_product(row: &rusqlite::Row) -> rusqlite::Result<Product> {
         active: row.get::<_, i32>(11)? == 1,
         created_at: row.get(12)?,
         updated_at: row.get(13)?,
+        parent_id: row.get(14)?,
+        variant_count: row.get(15)?,
+        is_kit: row.get(17)?,
     })
 }
 
 const SELECT_QUERY: &str = "\
     SELECT p.id, p.name, p.description, p.barcode, p.price, p.unit, p.is_bulk, \
-        p.category_id, c.name as category_name, p.stock, p.min_stock, \
-        p.active, p.created_at, p.updated_at \
-    FROM products p LEFT JOIN categories c ON p.category_id = c.id";
+        p.category_id, c.name as category_name, COALESCE(ka.available, p.stock), p.min_stock, \
+        p.active, p.created_at, p.updated_at, p.parent_id, \
+        (SELECT COUNT(*) FROM products v WHERE v.parent_id = p.id), p.cost, \
+        ka.kit_id IS NOT NULL \
+    FROM products p LEFT JOIN categories c ON p.category_id = c.id \
+    LEFT JOIN kit_availability ka ON ka.kit_id = p.id";
 
 pub fn find_all(db: &Database) -> AppResult<Vec<Product>> {
     let conn = db.conn.lock()?;
@@ -120,6 +288,28 @@ pub fn find_active(db: &Database) -> AppResult<Vec<Product>> {
     Ok(products)
 }
 
+pub fn find_in_category_tree(db: &Database, category_id: i64) -> AppResult<Vec<Product>> {
+    let conn = db.conn.lock()?;
+    let query = format!(
+        "{} WHERE p.category_id IN ( \
+            WITH RECURSIVE subtree(id) AS ( \
+                SELECT ?1 \
+                UNION ALL \
+                SELECT c.id FROM categories c JOIN subtree ON c.parent_id = subtree.id \
+            ) \
+            SELECT id FROM subtree) \
+        ORDER BY p.name",
+        SELECT_QUERY
+    );
+    let mut stmt = conn.prepare(&query)?;
+
+    let products = stmt
+        .query_map(params![category_id], row_to_product)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(products)
+}
+
 pub fn find_by_id(db: &Database, id: i64) -> AppResult<Option<Product>> {
     let conn = db.conn.lock()?;
     let query = format!("{} WHERE p.id = ?1", SELECT_QUERY);
@@ -129,129 +319,189 @@ pub fn find_by_id(db: &Database, id: i64) -> AppResult<Option<Product>> {
 
 pub fn find_by_barcode(db: &Database, barcode: &str) -> AppResult<Option<Product>> {
     let conn = db.conn.lock()?;
-    let query = format!("{} WHERE p.barcode = ?1 AND p.active = 1", SELECT_QUERY);
+    let query = format!(
+        "{} JOIN product_codes pc ON pc.product_id = p.id WHERE pc.code = ?1 AND p.active = 1",
+        SELECT_QUERY
+    );
     let result = conn
         .query_row(&query, params![barcode], row_to_product)
         .ok();
     Ok(result)
 }
 
+/// Ranked full-text search over name, description, codes and category. Every
+/// word must prefix-match; case and accents are ignored.
 pub fn search(db: &Database, term: &str) -> AppResult<Vec<Product>> {
+    let Some(expression) = match_expression(term) else {
+        return Ok(Vec::new());
+    };
     let conn = db.conn.lock()?;
     let query = format!(
-        "{} WHERE p.active = 1 AND (p.name LIKE ?1 OR p.barcode LIKE ?1) ORDER BY p.name LIMIT {}",
+        "{} JOIN product_search ON product_search.rowid = p.id \
+            WHERE product_search MATCH ?1 AND p.active = 1 \
+            ORDER BY bm25(product_search, {}), p.name LIMIT {}",
         SELECT_QUERY,
+        SEARCH_COLUMN_WEIGHTS,
         crate::constants::SEARCH_RESULT_LIMIT,
     );
-    let search_term = format!("%{}%", term);
     let mut stmt = conn.prepare(&query)?;
 
     let products = stmt
-        .query_map(params![search_term], row_to_product)?
+        .query_map(params![expression], row_to_product)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(products)
 }
 
-pub fn create(
-    db: &Database,
-    name: &str,
-    description: Option<&str>,
-    barcode: Option<&str>,
-    price: f64,
-    unit: &str,
-    is_bulk: bool,
-    category_id: Option<i64>,
-    stock: f64,
-    min_stock: f64,
-) -> AppResult<Product> {
-    let conn = db.conn.lock()?;
-    conn.execute(
-        "INSERT INTO products (name, description, barcode, price, unit, is_bulk, category_id, stock, min_stock) \
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-        params![name, description, barcode, price, unit, is_bulk, category_id, stock, min_stock],
-    )?;
+/// bm25 weights for name, description, codes and category.
+const SEARCH_COLUMN_WEIGHTS: &str = "10.0, 1.0, 5.0, 2.0";
 
-    let id = conn.last_insert_rowid();
+/// Turns free text into an FTS5 query: accent-folded words, each quoted so FTS
+/// syntax is taken literally, and each matched as a prefix.
+fn match_expression(term: &str) -> Option<String> {
+    let folded = deunicode::deunicode(term).to_lowercase();
+    let words: Vec<String> = folded
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\"*"))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+pub fn create(db: &Database, request: &CreateProductRequest) -> AppResult<Product> {
+    let cost = request.cost.unwrap_or(0.0);
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO products (name, description, barcode, price, cost, unit, is_bulk, category_id, stock, min_stock) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            request.name,
+            request.description,
+            request.barcode,
+            request.price,
+            cost,
+            request.unit,
+            request.is_bulk,
+            request.category_id,
+            request.stock.unwrap_or(0.0),
+            request.min_stock.unwrap_or(0.0)
+        ],
+    )
+    .map_err(map_code_conflict)?;
+
+    let id = tx.last_insert_rowid();
+    insert_price_change(&tx, id, request.price, cost, None, request.user_id)?;
+    if let Some(barcode) = request.barcode.as_deref() {
+        set_primary_code(&tx, id, Some(barcode))?;
+    }
+    tx.commit()?;
     drop(conn);
     find_by_id(db, id)?
         .ok_or_else(|| AppError::NotFound("Failed to retrieve created product".to_string()))
 }
 
-pub fn update(
-    db: &Database,
-    id: i64,
-    name: Option<&str>,
-    description: Option<&str>,
-    barcode: Option<&str>,
-    price: Option<f64>,
-    unit: Option<&str>,
-    is_bulk: Option<bool>,
-    category_id: Option<i64>,
-    min_stock: Option<f64>,
-    active: Option<bool>,
-) -> AppResult<Product> {
-    let conn = db.conn.lock()?;
+pub fn update(db: &Database, request: &UpdateProductRequest) -> AppResult<Product> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    apply_update(&tx, request)?;
+    tx.commit()?;
+    drop(conn);
+    find_by_id(db, request.id)?
+        .ok_or_else(|| AppError::NotFound("Producto no encontrado".to_string()))
+}
 
-    if let Some(val) = name {
+/// Writes the fields the request sets, recording a price history entry when the
+/// price or cost changes.
+fn apply_update(conn: &Connection, request: &UpdateProductRequest) -> AppResult<()> {
+    let id = request.id;
+    let previous: Option<(f64, f64)> = conn
+        .query_row(
+            "SELECT price, cost FROM products WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    if let Some(val) = request.name.as_deref() {
         conn.execute(
             "UPDATE products SET name = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
             params![val, id],
         )?;
     }
-    if let Some(val) = description {
+    if let Some(val) = request.description.as_deref() {
         conn.execute(
             "UPDATE products SET description = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
             params![val, id],
         )?;
     }
-    if let Some(val) = barcode {
+    if let Some(val) = request.barcode.as_deref() {
+        let val = Some(val).filter(|code| !code.is_empty());
+        set_primary_code(conn, id, val)?;
         conn.execute(
             "UPDATE products SET barcode = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
             params![val, id],
         )?;
     }
-    if let Some(val) = price {
+    if let Some(val) = request.price {
         conn.execute(
             "UPDATE products SET price = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
             params![val, id],
         )?;
     }
-    if let Some(val) = unit {
+    if let Some(val) = request.cost {
+        conn.execute(
+            "UPDATE products SET cost = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![val, id],
+        )?;
+    }
+    if let Some((previous_price, previous_cost)) = previous {
+        let new_price = request.price.unwrap_or(previous_price);
+        let new_cost = request.cost.unwrap_or(previous_cost);
+        if new_price != previous_price || new_cost != previous_cost {
+            insert_price_change(
+                conn,
+                id,
+                new_price,
+                new_cost,
+                Some((previous_price, previous_cost)),
+                request.user_id,
+            )?;
+        }
+    }
+    if let Some(val) = request.unit.as_deref() {
         conn.execute(
             "UPDATE products SET unit = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
             params![val, id],
         )?;
     }
-    if let Some(val) = is_bulk {
+    if let Some(val) = request.is_bulk {
         let is_bulk_int = if val { 1 } else { 0 };
         conn.execute(
             "UPDATE products SET is_bulk = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
             params![is_bulk_int, id],
         )?;
     }
-    if category_id.is_some() {
+    if request.category_id.is_some() {
         conn.execute(
             "UPDATE products SET category_id = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
-            params![category_id, id],
+            params![request.category_id, id],
         )?;
     }
-    if let Some(val) = min_stock {
+    if let Some(val) = request.min_stock {
         conn.execute(
             "UPDATE products SET min_stock = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
             params![val, id],
         )?;
     }
-    if let Some(val) = active {
+    if let Some(val) = request.active {
         let active_int = if val { 1 } else { 0 };
         conn.execute(
             "UPDATE products SET active = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
             params![active_int, id],
         )?;
     }
-
-    drop(conn);
-    find_by_id(db, id)?.ok_or_else(|| AppError::NotFound("Producto no encontrado".to_string()))
+    Ok(())
 }
 
 pub fn delete(db: &Database, id: i64) -> AppResult<()> {
@@ -259,13 +509,16 @@ pub fn delete(db: &Database, id: i64) -> AppResult<()> {
 
     // EXISTS short-circuits on the first matching row, making both checks optimal.
     // inventory_adjustments uses idx_inventory_product (product_id).
-    let (has_sale_items, has_inventory): (i32, i32) = conn.query_row(
-        "SELECT \
+    let (has_sale_items, has_inventory, has_variants, in_kits): (i32, i32, i32, i32) = conn
+        .query_row(
+            "SELECT \
             EXISTS(SELECT 1 FROM sale_items WHERE product_id = ?1 LIMIT 1), \
-            EXISTS(SELECT 1 FROM inventory_adjustments WHERE product_id = ?1 LIMIT 1)",
-        params![id],
-        |row| Ok((row.get(0)?, row.get(1)?)),
-    )?;
+            EXISTS(SELECT 1 FROM inventory_adjustments WHERE product_id = ?1 LIMIT 1), \
+            EXISTS(SELECT 1 FROM products WHERE parent_id = ?1 LIMIT 1), \
+            EXISTS(SELECT 1 FROM product_kit_components WHERE component_id = ?1 LIMIT 1)",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
 
     if has_sale_items == 1 {
         return Err(AppError::Conflict(
@@ -279,24 +532,487 @@ pub fn delete(db: &Database, id: i64) -> AppResult<()> {
                 .to_string(),
         ));
     }
+    if has_variants == 1 {
+        return Err(AppError::Conflict(
+            "No se puede eliminar el producto porque tiene variantes.".to_string(),
+        ));
+    }
+    if in_kits == 1 {
+        return Err(AppError::Conflict(
+            "No se puede eliminar el producto porque es componente de un kit.".to_string(),
+        ));
+    }
 
     conn.execute("DELETE FROM products WHERE id = ?1", params![id])?;
     Ok(())
 }
 
+fn insert_price_change(
+    conn: &Connection,
+    product_id: i64,
+    price: f64,
+    cost: f64,
+    previous: Option<(f64, f64)>,
+    user_id: Option<i64>,
+) -> AppResult<()> {
+    conn.execute(
+        "INSERT INTO product_price_history \
+            (product_id, price, cost, previous_price, previous_cost, changed_by) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            product_id,
+            price,
+            cost,
+            previous.map(|(price, _)| price),
+            previous.map(|(_, cost)| cost),
+            user_id,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn find_price_history(db: &Database, product_id: i64) -> AppResult<Vec<ProductPriceChange>> {
+    let conn = db.conn.lock()?;
+    let mut stmt = conn.prepare(
+        "SELECT h.id, h.product_id, h.price, h.cost, h.previous_price, h.previous_cost, \
+            h.changed_by, u.full_name, h.created_at \
+            FROM product_price_history h LEFT JOIN users u ON h.changed_by = u.id \
+            WHERE h.product_id = ?1 ORDER BY h.id DESC",
+    )?;
+    let history = stmt
+        .query_map(params![product_id], |row| {
+            let price: f64 = row.get(2)?;
+            let cost: f64 = row.get(3)?;
+            Ok(ProductPriceChange {
+                id: row.get(0)?,
+                product_id: row.get(1)?,
+                price,
+                cost,
+                previous_price: row.get(4)?,
+                previous_cost: row.get(5)?,
+                margin_percent: margin_percent(price, cost),
+                changed_by: row.get(6)?,
+                changed_by_name: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(history)
+}
+
+const SCHEDULED_SELECT: &str = "\
+    SELECT s.id, s.product_id, p.name, p.price, s.new_price, s.effective_at, s.status, \
+        s.created_by, s.created_at, s.applied_at \
+    FROM scheduled_price_changes s JOIN products p ON s.product_id = p.id";
+
+fn query_scheduled_changes(
+    conn: &Connection,
+    clause: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> AppResult<Vec<ScheduledPriceChange>> {
+    let mut stmt = conn.prepare(&format!("{SCHEDULED_SELECT} {clause}"))?;
+    let changes = stmt
+        .query_map(params, |row| {
+            Ok(ScheduledPriceChange {
+                id: row.get(0)?,
+                product_id: row.get(1)?,
+                product_name: row.get(2)?,
+                current_price: row.get(3)?,
+                new_price: row.get(4)?,
+                effective_at: row.get(5)?,
+                status: row.get(6)?,
+                created_by: row.get(7)?,
+                created_at: row.get(8)?,
+                applied_at: row.get(9)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(changes)
+}
+
+pub fn create_scheduled_price_changes(
+    db: &Database,
+    changes: &[ScheduledPriceInput],
+    effective_at: &str,
+    user_id: i64,
+) -> AppResult<Vec<ScheduledPriceChange>> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    let mut ids = Vec::with_capacity(changes.len());
+    for change in changes {
+        tx.execute(
+            "INSERT INTO scheduled_price_changes (product_id, new_price, effective_at, created_by) \
+                VALUES (?1, ?2, ?3, ?4)",
+            params![change.product_id, change.new_price, effective_at, user_id],
+        )?;
+        ids.push(tx.last_insert_rowid());
+    }
+    let mut created = Vec::with_capacity(ids.len());
+    for id in ids {
+        created.extend(query_scheduled_changes(
+            &tx,
+            "WHERE s.id = ?1",
+            params![id],
+        )?);
+    }
+    tx.commit()?;
+    Ok(created)
+}
+
+/// Replaces the product's primary code; an alternate code equal to the new
+/// barcode is promoted instead of duplicated.
+fn set_primary_code(conn: &Connection, product_id: i64, barcode: Option<&str>) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM product_codes WHERE product_id = ?1 AND (is_primary = 1 OR code = ?2)",
+        params![product_id, barcode],
+    )?;
+    if let Some(barcode) = barcode {
+        conn.execute(
+            "INSERT INTO product_codes (product_id, code, code_type, is_primary) \
+                VALUES (?1, ?2, ?3, 1)",
+            params![product_id, barcode, ProductCodeType::Barcode],
+        )
+        .map_err(map_code_conflict)?;
+    }
+    Ok(())
+}
+
+fn map_code_conflict(error: rusqlite::Error) -> AppError {
+    match error.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ConstraintViolation) => {
+            AppError::Conflict("El código ya está asignado a otro producto".to_string())
+        }
+        _ => error.into(),
+    }
+}
+
+const CODE_SELECT: &str = "\
+    SELECT pc.id, pc.product_id, pc.code, pc.code_type, pc.supplier_name, pc.is_primary, \
+        pc.created_at \
+    FROM product_codes pc";
+
+fn row_to_code(row: &rusqlite::Row) -> rusqlite::Result<ProductCode> {
+    Ok(ProductCode {
+        id: row.get(0)?,
+        product_id: row.get(1)?,
+        code: row.get(2)?,
+        code_type: row.get(3)?,
+        supplier_name: row.get(4)?,
+        is_primary: row.get::<_, i32>(5)? == 1,
+        created_at: row.get(6)?,
+    })
+}
+
+fn query_code(
+    conn: &Connection,
+    condition: &str,
+    params: impl rusqlite::Params,
+) -> AppResult<Option<ProductCode>> {
+    let query = format!("{} WHERE {}", CODE_SELECT, condition);
+    Ok(conn.query_row(&query, params, row_to_code).optional()?)
+}
+
+pub fn find_codes(db: &Database, product_id: i64) -> AppResult<Vec<ProductCode>> {
+    let conn = db.conn.lock()?;
+    let query = format!(
+        "{} WHERE pc.product_id = ?1 ORDER BY pc.is_primary DESC, pc.id",
+        CODE_SELECT
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let codes = stmt
+        .query_map(params![product_id], row_to_code)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(codes)
+}
+
+pub fn add_code(db: &Database, request: &AddProductCodeRequest) -> AppResult<ProductCode> {
+    let conn = db.conn.lock()?;
+    conn.execute(
+        "INSERT INTO product_codes (product_id, code, code_type, supplier_name) \
+            VALUES (?1, ?2, ?3, ?4)",
+        params![
+            request.product_id,
+            request.code,
+            request.code_type,
+            request.supplier_name,
+        ],
+    )
+    .map_err(map_code_conflict)?;
+    let id = conn.last_insert_rowid();
+    query_code(&conn, "pc.id = ?1", params![id])?
+        .ok_or_else(|| AppError::NotFound("Código no encontrado".to_string()))
+}
+
+pub fn find_attributes(db: &Database, product_id: i64) -> AppResult<Vec<ProductAttribute>> {
+    let conn = db.conn.lock()?;
+    let mut stmt = conn.prepare(
+        "SELECT a.id, a.name, v.id, v.value FROM product_attributes a \
+            LEFT JOIN product_attribute_values v ON v.attribute_id = a.id \
+            WHERE a.product_id = ?1 ORDER BY a.sort_order, a.id, v.sort_order, v.id",
+    )?;
+    let rows = stmt
+        .query_map(params![product_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut attributes: Vec<ProductAttribute> = Vec::new();
+    for (id, name, value_id, value) in rows {
+        if attributes.last().map(|attribute| attribute.id) != Some(id) {
+            attributes.push(ProductAttribute {
+                id,
+                product_id,
+                name,
+                values: Vec::new(),
+            });
+        }
+        if let (Some(value_id), Some(value)) = (value_id, value) {
+            if let Some(attribute) = attributes.last_mut() {
+                attribute.values.push(ProductAttributeValue {
+                    id: value_id,
+                    value,
+                });
+            }
+        }
+    }
+    Ok(attributes)
+}
+
+fn query_variants(conn: &Connection, parent_id: i64) -> AppResult<Vec<ProductVariant>> {
+    let query = format!("{} WHERE p.parent_id = ?1 ORDER BY p.id", SELECT_QUERY);
+    let products = conn
+        .prepare(&query)?
+        .query_map(params![parent_id], row_to_product)?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut options_stmt = conn.prepare(
+        "SELECT a.name, v.value FROM product_variant_values pvv \
+            JOIN product_attribute_values v ON pvv.attribute_value_id = v.id \
+            JOIN product_attributes a ON v.attribute_id = a.id \
+            WHERE pvv.variant_id = ?1 ORDER BY a.sort_order, a.id",
+    )?;
+    products
+        .into_iter()
+        .map(|product| {
+            let options = options_stmt
+                .query_map(params![product.id], |row| {
+                    Ok(VariantOption {
+                        attribute: row.get(0)?,
+                        value: row.get(1)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(ProductVariant { product, options })
+        })
+        .collect()
+}
+
+pub fn create_variants(
+    db: &Database,
+    parent: &Product,
+    attributes: &[VariantAttributeInput],
+    combinations: &[Vec<VariantOption>],
+) -> AppResult<Vec<ProductVariant>> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+
+    let mut value_ids: HashMap<(&str, &str), i64> = HashMap::new();
+    for (attribute_order, attribute) in attributes.iter().enumerate() {
+        tx.execute(
+            "INSERT OR IGNORE INTO product_attributes (product_id, name, sort_order) \
+                VALUES (?1, ?2, ?3)",
+            params![parent.id, attribute.name, attribute_order as i64],
+        )?;
+        let attribute_id: i64 = tx.query_row(
+            "SELECT id FROM product_attributes WHERE product_id = ?1 AND name = ?2",
+            params![parent.id, attribute.name],
+            |row| row.get(0),
+        )?;
+        for (value_order, value) in attribute.values.iter().enumerate() {
+            tx.execute(
+                "INSERT OR IGNORE INTO product_attribute_values (attribute_id, value, sort_order) \
+                    VALUES (?1, ?2, ?3)",
+                params![attribute_id, value, value_order as i64],
+            )?;
+            let value_id: i64 = tx.query_row(
+                "SELECT id FROM product_attribute_values WHERE attribute_id = ?1 AND value = ?2",
+                params![attribute_id, value],
+                |row| row.get(0),
+            )?;
+            value_ids.insert((attribute.name.as_str(), value.as_str()), value_id);
+        }
+    }
+
+    let mut existing: HashMap<i64, BTreeSet<i64>> = HashMap::new();
+    {
+        let mut stmt = tx.prepare(
+            "SELECT pvv.variant_id, pvv.attribute_value_id FROM product_variant_values pvv \
+                JOIN products v ON pvv.variant_id = v.id WHERE v.parent_id = ?1",
+        )?;
+        let rows = stmt.query_map(params![parent.id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        for row in rows {
+            let (variant_id, value_id) = row?;
+            existing.entry(variant_id).or_default().insert(value_id);
+        }
+    }
+    let mut existing: BTreeSet<BTreeSet<i64>> = existing.into_values().collect();
+
+    let mut created = Vec::new();
+    for combination in combinations {
+        let ids = combination
+            .iter()
+            .map(|option| {
+                value_ids
+                    .get(&(option.attribute.as_str(), option.value.as_str()))
+                    .copied()
+                    .ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "Valor {} no definido para {}",
+                            option.value, option.attribute
+                        ))
+                    })
+            })
+            .collect::<AppResult<BTreeSet<i64>>>()?;
+        if !existing.insert(ids.clone()) {
+            continue;
+        }
+        let label = combination
+            .iter()
+            .map(|option| option.value.as_str())
+            .collect::<Vec<_>>()
+            .join(" / ");
+        tx.execute(
+            "INSERT INTO products \
+                (name, description, price, cost, unit, is_bulk, category_id, stock, min_stock, \
+                parent_id) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0, ?8, ?9)",
+            params![
+                format!("{} {}", parent.name, label),
+                parent.description,
+                parent.price,
+                parent.cost,
+                parent.unit,
+                parent.is_bulk,
+                parent.category_id,
+                parent.min_stock,
+                parent.id,
+            ],
+        )?;
+        let variant_id = tx.last_insert_rowid();
+        insert_price_change(&tx, variant_id, parent.price, parent.cost, None, None)?;
+        for value_id in &ids {
+            tx.execute(
+                "INSERT INTO product_variant_values (variant_id, attribute_value_id) \
+                    VALUES (?1, ?2)",
+                params![variant_id, value_id],
+            )?;
+        }
+        created.push(variant_id);
+    }
+    tx.commit()?;
+
+    Ok(query_variants(&conn, parent.id)?
+        .into_iter()
+        .filter(|variant| created.contains(&variant.product.id))
+        .collect())
+}
+
+pub fn find_kit_components(db: &Database, kit_id: i64) -> AppResult<Vec<KitComponent>> {
+    let conn = db.conn.lock()?;
+    let mut stmt = conn.prepare(
+        "SELECT k.component_id, c.name, c.unit, k.quantity, c.stock \
+            FROM product_kit_components k JOIN products c ON k.component_id = c.id \
+            WHERE k.kit_id = ?1 ORDER BY c.name",
+    )?;
+    let components = stmt
+        .query_map(params![kit_id], |row| {
+            Ok(KitComponent {
+                component_id: row.get(0)?,
+                name: row.get(1)?,
+                unit: row.get(2)?,
+                quantity: row.get(3)?,
+                stock: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(components)
+}
+
+pub fn replace_kit_components(
+    db: &Database,
+    kit_id: i64,
+    components: &[KitComponentInput],
+) -> AppResult<()> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "DELETE FROM product_kit_components WHERE kit_id = ?1",
+        params![kit_id],
+    )?;
+    for component in components {
+        tx.execute(
+            "INSERT INTO product_kit_components (kit_id, component_id, quantity) \
+                VALUES (?1, ?2, ?3)",
+            params![kit_id, component.component_id, component.quantity],
+        )?;
+    }
+    tx.execute(
+        "UPDATE products SET updated_at = datetime('now', 'localtime') WHERE id = ?1",
+        params![kit_id],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{create, update};
+    use super::{
+        add_code, create, create_variants, delete, find_attributes, find_by_barcode, find_by_id,
+        find_codes, find_price_history, row_to_product, search, update, SqliteProductRepository,
+        SELECT_QUERY,
+    };
     use crate::infrastructure::sqlite::Database;
-    use rusqlite::Connection;
+    use crate::models::price_change::{
+        BulkRepriceRequest, SchedulePriceChangesRequest, ScheduledPriceInput,
+    };
+    use crate::models::product::{
+        AddProductCodeRequest, CreateProductRequest, ProductCodeType, UpdateProductRequest,
+    };
+    use crate::models::product_import::ProductImportRequest;
+    use crate::models::product_kit::{KitComponentInput, SetKitComponentsRequest};
+    use crate::models::product_variant::VariantAttributeInput;
+    use crate::modules::catalog::categories::adapters::outbound::sqlite::SqliteCategoryRepository;
+    use crate::modules::catalog::products::application::variant_combinations;
+    use crate::modules::catalog::products::catalog_file::{
+        export_products, import_products, ProductSheetFile, SheetCell,
+    };
+    use crate::modules::catalog::products::kits::set_kit_components;
+    use crate::modules::catalog::products::repricing::{
+        apply_bulk_reprice, apply_due_price_changes, cancel_scheduled_price_change,
+        preview_bulk_reprice, schedule_price_changes,
+    };
+    use crate::shared::error::{AppError, AppResult};
+    use rusqlite::{params, Connection};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
     use std::sync::Mutex;
+    use std::time::Instant;
 
     fn test_database() -> Database {
         let conn = Connection::open_in_memory().unwrap();
         conn.execute_batch(
             "CREATE TABLE categories (
                 id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL
+                name TEXT NOT NULL,
+                description TEXT,
+                created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00',
+                parent_id INTEGER
             );
             CREATE TABLE products (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -305,45 +1021,803 @@ mod tests {
                 barcode TEXT,
                 price REAL NOT NULL,
                 unit TEXT NOT NULL,
+                cost REAL NOT NULL DEFAULT 0,
                 is_bulk INTEGER NOT NULL DEFAULT 0,
                 category_id INTEGER,
                 stock REAL NOT NULL DEFAULT 0,
                 min_stock REAL NOT NULL DEFAULT 0,
                 active INTEGER NOT NULL DEFAULT 1,
                 created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00',
-                updated_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00'
+                updated_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00',
+                parent_id INTEGER
+            );
+            CREATE TABLE product_attributes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                product_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                sort_order INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(product_id, name)
+            );
+            CREATE TABLE product_attribute_values (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                attribute_id INTEGER NOT NULL,
+                value TEXT NOT NULL,
+                sort_order INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(attribute_id, value)
             );
+            CREATE TABLE product_variant_values (
+                variant_id INTEGER NOT NULL,
+                attribute_value_id INTEGER NOT NULL,
+                PRIMARY KEY (variant_id, attribute_value_id)
+            );
+            CREATE TABLE product_codes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                product_id INTEGER NOT NULL,
+                code TEXT NOT NULL UNIQUE,
+                code_type TEXT NOT NULL,
+                supplier_name TEXT,
+                is_primary INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00'
+            );
+            CREATE TABLE users (
+                id INTEGER PRIMARY KEY,
+                full_name TEXT NOT NULL
+            );
+            CREATE TABLE product_price_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                product_id INTEGER NOT NULL,
+                price REAL NOT NULL,
+                cost REAL NOT NULL,
+                previous_price REAL,
+                previous_cost REAL,
+                changed_by INTEGER,
+                created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00'
+            );
+            CREATE TABLE scheduled_price_changes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                product_id INTEGER NOT NULL,
+                new_price REAL NOT NULL,
+                effective_at TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                created_by INTEGER,
+                created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00',
+                applied_at TEXT
+            );
+            CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT);
+            INSERT INTO users (id, full_name) VALUES (1, 'Admin');
             CREATE TABLE sale_items (product_id INTEGER);
             CREATE TABLE inventory_adjustments (product_id INTEGER);",
         )
         .unwrap();
+        conn.execute_batch(include_str!(
+            "../../../../../infrastructure/sqlite/migrations/0025_product_search.sql"
+        ))
+        .unwrap();
+        conn.execute_batch(include_str!(
+            "../../../../../infrastructure/sqlite/migrations/0027_product_kits.sql"
+        ))
+        .unwrap();
+        conn.execute_batch(include_str!(
+            "../../../../../infrastructure/sqlite/migrations/0028_units.sql"
+        ))
+        .unwrap();
 
         Database {
             conn: Mutex::new(conn),
         }
     }
 
+    fn new_product(name: &str, price: f64, unit: &str) -> CreateProductRequest {
+        CreateProductRequest {
+            name: name.to_string(),
+            description: None,
+            barcode: None,
+            price,
+            cost: None,
+            unit: unit.to_string(),
+            is_bulk: false,
+            category_id: None,
+            stock: None,
+            min_stock: None,
+            user_id: None,
+            allow_below_cost: false,
+        }
+    }
+
+    fn product_edit(id: i64) -> UpdateProductRequest {
+        UpdateProductRequest {
+            id,
+            name: None,
+            description: None,
+            barcode: None,
+            price: None,
+            cost: None,
+            unit: None,
+            is_bulk: None,
+            category_id: None,
+            min_stock: None,
+            active: None,
+            user_id: None,
+            allow_below_cost: false,
+        }
+    }
+
     #[test]
     fn creates_and_updates_the_explicit_bulk_flag() {
         let db = test_database();
-        let created = create(&db, "Tomate", None, None, 42.50, "kg", true, None, 5.0, 0.5).unwrap();
+        let created = create(
+            &db,
+            &CreateProductRequest {
+                is_bulk: true,
+                stock: Some(5.0),
+                min_stock: Some(0.5),
+                ..new_product("Tomate", 42.50, "kg")
+            },
+        )
+        .unwrap();
         assert!(created.is_bulk);
 
         let updated = update(
             &db,
-            created.id,
-            None,
-            None,
-            None,
-            None,
-            None,
-            Some(false),
-            None,
-            None,
-            None,
+            &UpdateProductRequest {
+                is_bulk: Some(false),
+                ..product_edit(created.id)
+            },
         )
         .unwrap();
         assert!(!updated.is_bulk);
         assert_eq!(updated.unit, "kg");
     }
+
+    #[test]
+    fn every_code_finds_the_product_and_codes_stay_unique() {
+        let db = test_database();
+        let rice = create(
+            &db,
+            &CreateProductRequest {
+                barcode: Some("7501000000017".to_string()),
+                ..new_product("Arroz", 30.0, "pieza")
+            },
+        )
+        .unwrap();
+        let beans = create(&db, &new_product("Frijol", 35.0, "pieza")).unwrap();
+        let supplier_code = |code: &str, product_id| AddProductCodeRequest {
+            product_id,
+            code: code.to_string(),
+            code_type: ProductCodeType::Supplier,
+            supplier_name: Some("La Costeña".to_string()),
+        };
+        add_code(&db, &supplier_code("ARZ-01", rice.id)).unwrap();
+
+        assert_eq!(
+            find_by_barcode(&db, "7501000000017").unwrap().unwrap().id,
+            rice.id
+        );
+        assert_eq!(find_by_barcode(&db, "ARZ-01").unwrap().unwrap().id, rice.id);
+        assert_eq!(search(&db, "ARZ").unwrap()[0].id, rice.id);
+        assert!(matches!(
+            add_code(&db, &supplier_code("7501000000017", beans.id)),
+            Err(AppError::Conflict(_))
+        ));
+
+        let updated = update(
+            &db,
+            &UpdateProductRequest {
+                barcode: Some("ARZ-01".to_string()),
+                ..product_edit(rice.id)
+            },
+        )
+        .unwrap();
+        assert_eq!(updated.barcode.as_deref(), Some("ARZ-01"));
+        let codes = find_codes(&db, rice.id).unwrap();
+        assert_eq!(codes.len(), 1);
+        assert!(codes[0].is_primary);
+        assert!(find_by_barcode(&db, "7501000000017").unwrap().is_none());
+    }
+
+    #[test]
+    fn a_rejected_barcode_change_leaves_the_product_untouched() {
+        let db = test_database();
+        let rice = create(
+            &db,
+            &CreateProductRequest {
+                barcode: Some("7501000000017".to_string()),
+                ..new_product("Arroz", 30.0, "pieza")
+            },
+        )
+        .unwrap();
+        let beans = create(
+            &db,
+            &CreateProductRequest {
+                barcode: Some("7501000000024".to_string()),
+                ..new_product("Frijol", 35.0, "pieza")
+            },
+        )
+        .unwrap();
+
+        let result = update(
+            &db,
+            &UpdateProductRequest {
+                name: Some("Frijol negro".to_string()),
+                barcode: Some("7501000000017".to_string()),
+                ..product_edit(beans.id)
+            },
+        );
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        let found = find_by_barcode(&db, "7501000000024").unwrap().unwrap();
+        assert_eq!(found.id, beans.id);
+        assert_eq!(found.name, "Frijol");
+        assert_eq!(found.barcode.as_deref(), Some("7501000000024"));
+        assert_eq!(
+            find_by_barcode(&db, "7501000000017").unwrap().unwrap().id,
+            rice.id
+        );
+    }
+
+    #[test]
+    fn generates_each_missing_variant_once() {
+        let db = test_database();
+        let soda = create(
+            &db,
+            &CreateProductRequest {
+                cost: Some(12.0),
+                min_stock: Some(2.0),
+                ..new_product("Refresco", 18.0, "pieza")
+            },
+        )
+        .unwrap();
+        let attribute = |name: &str, values: &[&str]| VariantAttributeInput {
+            name: name.to_string(),
+            values: values.iter().map(|value| value.to_string()).collect(),
+        };
+
+        let attributes = vec![
+            attribute("Tamaño", &["600 ml"]),
+            attribute("Sabor", &["Cola", "Limón"]),
+        ];
+        let parent = find_by_id(&db, soda.id).unwrap().unwrap();
+        let created = create_variants(
+            &db,
+            &parent,
+            &attributes,
+            &variant_combinations(&attributes),
+        )
+        .unwrap();
+        assert_eq!(created.len(), 2);
+        assert_eq!(created[0].product.name, "Refresco 600 ml / Cola");
+        assert_eq!(created[0].product.parent_id, Some(soda.id));
+        assert_eq!(created[0].product.price, 18.0);
+        assert_eq!(created[0].product.cost, 12.0);
+        assert_eq!(created[1].options[1].value, "Limón");
+
+        let attributes = vec![
+            attribute("Tamaño", &["600 ml", "2 L"]),
+            attribute("Sabor", &["Cola", "Limón"]),
+        ];
+        let created = create_variants(
+            &db,
+            &parent,
+            &attributes,
+            &variant_combinations(&attributes),
+        )
+        .unwrap();
+        assert_eq!(created.len(), 2);
+        assert!(created
+            .iter()
+            .all(|variant| variant.options[0].value == "2 L"));
+        assert_eq!(find_by_id(&db, soda.id).unwrap().unwrap().variant_count, 4);
+        assert_eq!(find_attributes(&db, soda.id).unwrap()[0].values.len(), 2);
+    }
+
+    #[test]
+    fn price_and_cost_changes_are_recorded_with_their_author() {
+        let db = test_database();
+        let oil = create(
+            &db,
+            &CreateProductRequest {
+                cost: Some(30.0),
+                user_id: Some(1),
+                ..new_product("Aceite", 40.0, "pieza")
+            },
+        )
+        .unwrap();
+        update(
+            &db,
+            &UpdateProductRequest {
+                name: Some("Aceite 1 L".to_string()),
+                user_id: Some(1),
+                ..product_edit(oil.id)
+            },
+        )
+        .unwrap();
+        let updated = update(
+            &db,
+            &UpdateProductRequest {
+                price: Some(45.0),
+                cost: Some(32.0),
+                user_id: Some(1),
+                ..product_edit(oil.id)
+            },
+        )
+        .unwrap();
+        assert_eq!(updated.cost, 32.0);
+
+        let history = find_price_history(&db, oil.id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].price, 45.0);
+        assert_eq!(history[0].previous_price, Some(40.0));
+        assert_eq!(history[0].previous_cost, Some(30.0));
+        assert_eq!(history[0].changed_by_name.as_deref(), Some("Admin"));
+        assert_eq!(history[1].previous_price, None);
+    }
+
+    #[test]
+    fn scheduled_and_bulk_price_changes_go_through_the_history() {
+        let db = test_database();
+        let repository = SqliteProductRepository::new(&db);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO categories (id, name) VALUES (1, 'Abarrotes');
+                INSERT INTO categories (id, name, parent_id) VALUES (2, 'Granos', 1);",
+            )
+            .unwrap();
+        let rice = create(
+            &db,
+            &CreateProductRequest {
+                cost: Some(14.0),
+                category_id: Some(1),
+                ..new_product("Arroz", 19.9, "pieza")
+            },
+        )
+        .unwrap();
+        let beans = create(
+            &db,
+            &CreateProductRequest {
+                cost: Some(31.0),
+                category_id: Some(2),
+                ..new_product("Frijol", 33.0, "pieza")
+            },
+        )
+        .unwrap();
+
+        let scheduled = schedule_price_changes(
+            &repository,
+            SchedulePriceChangesRequest {
+                user_id: 1,
+                effective_at: "2000-01-01 00:00".to_string(),
+                changes: vec![ScheduledPriceInput {
+                    product_id: rice.id,
+                    new_price: 21.0,
+                }],
+                allow_below_cost: false,
+            },
+        )
+        .unwrap();
+        schedule_price_changes(
+            &repository,
+            SchedulePriceChangesRequest {
+                user_id: 1,
+                effective_at: "2999-01-01T00:00".to_string(),
+                changes: vec![ScheduledPriceInput {
+                    product_id: beans.id,
+                    new_price: 40.0,
+                }],
+                allow_below_cost: false,
+            },
+        )
+        .unwrap();
+
+        let applied = apply_due_price_changes(&repository).unwrap().applied;
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].id, scheduled[0].id);
+        assert_eq!(find_by_id(&db, rice.id).unwrap().unwrap().price, 21.0);
+        assert_eq!(find_by_id(&db, beans.id).unwrap().unwrap().price, 33.0);
+        assert!(apply_due_price_changes(&repository)
+            .unwrap()
+            .applied
+            .is_empty());
+        assert!(matches!(
+            cancel_scheduled_price_change(&repository, scheduled[0].id),
+            Err(AppError::Conflict(_))
+        ));
+
+        let request = |percent: f64, allow_below_cost: bool| BulkRepriceRequest {
+            category_id: 1,
+            percent,
+            round_to: Some(0.5),
+            user_id: 1,
+            allow_below_cost,
+        };
+        let preview = preview_bulk_reprice(&repository, &request(-10.0, false)).unwrap();
+        assert_eq!(preview.len(), 2);
+        assert!(preview.iter().any(|line| line.below_cost));
+        assert!(matches!(
+            apply_bulk_reprice(&repository, request(-10.0, false)),
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(find_by_id(&db, rice.id).unwrap().unwrap().price, 21.0);
+
+        let applied = apply_bulk_reprice(&repository, request(5.0, false)).unwrap();
+        assert_eq!(applied.len(), 2);
+        assert_eq!(find_by_id(&db, beans.id).unwrap().unwrap().price, 34.5);
+        let history = find_price_history(&db, rice.id).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].price, 22.0);
+        assert_eq!(history[1].previous_price, Some(19.9));
+        assert_eq!(history[0].changed_by, Some(1));
+    }
+
+    #[test]
+    fn a_failing_price_change_does_not_block_the_rest() {
+        let db = test_database();
+        let repository = SqliteProductRepository::new(&db);
+        let product = |name: &str| {
+            create(
+                &db,
+                &CreateProductRequest {
+                    category_id: Some(1),
+                    ..new_product(name, 20.0, "pieza")
+                },
+            )
+            .unwrap()
+        };
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO categories (id, name) VALUES (1, 'Abarrotes')",
+                [],
+            )
+            .unwrap();
+        let rice = product("Arroz");
+        let beans = product("Frijol");
+        // Bulk repricing goes by name, so the blocked product comes second.
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                &format!(
+                    "CREATE TRIGGER block_price BEFORE UPDATE OF price ON products \
+                        WHEN NEW.id = {} BEGIN SELECT RAISE(ABORT, 'precio bloqueado'); END",
+                    beans.id
+                ),
+                [],
+            )
+            .unwrap();
+
+        let schedule = |product_id: i64| {
+            schedule_price_changes(
+                &repository,
+                SchedulePriceChangesRequest {
+                    user_id: 1,
+                    effective_at: "2000-01-01 00:00".to_string(),
+                    changes: vec![ScheduledPriceInput {
+                        product_id,
+                        new_price: 25.0,
+                    }],
+                    allow_below_cost: false,
+                },
+            )
+            .unwrap()
+        };
+        let blocked = schedule(beans.id);
+        schedule(rice.id);
+        let outcome = apply_due_price_changes(&repository).unwrap();
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].0.id, blocked[0].id);
+        assert_eq!(outcome.applied.len(), 1);
+        assert_eq!(find_by_id(&db, rice.id).unwrap().unwrap().price, 25.0);
+        let outcome = apply_due_price_changes(&repository).unwrap();
+        assert!(outcome.applied.is_empty() && outcome.failed.is_empty());
+
+        let reprice = BulkRepriceRequest {
+            category_id: 1,
+            percent: 10.0,
+            round_to: None,
+            user_id: 1,
+            allow_below_cost: false,
+        };
+        assert!(apply_bulk_reprice(&repository, reprice).is_err());
+        assert_eq!(find_by_id(&db, rice.id).unwrap().unwrap().price, 25.0);
+        assert_eq!(find_price_history(&db, rice.id).unwrap().len(), 2);
+    }
+
+    struct MemorySheet {
+        rows: Vec<Vec<String>>,
+        written: RefCell<Vec<Vec<SheetCell>>>,
+    }
+
+    impl ProductSheetFile for MemorySheet {
+        fn read_rows(&self, _path: &str) -> AppResult<Vec<Vec<String>>> {
+            Ok(self.rows.clone())
+        }
+
+        fn write_rows(&self, _path: &str, rows: &[Vec<SheetCell>]) -> AppResult<()> {
+            *self.written.borrow_mut() = rows.to_vec();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn imports_upsert_by_code_and_report_rejected_rows() {
+        let db = test_database();
+        let products = SqliteProductRepository::new(&db);
+        let categories = SqliteCategoryRepository::new(&db);
+        let rice = create(
+            &db,
+            &CreateProductRequest {
+                barcode: Some("7501".to_string()),
+                cost: Some(10.0),
+                ..new_product("Arroz", 20.0, "pieza")
+            },
+        )
+        .unwrap();
+        let sheet = MemorySheet {
+            rows: [
+                [
+                    "Nombre",
+                    "Código de barras",
+                    "SKU",
+                    "Precio",
+                    "Costo",
+                    "Categoría",
+                    "Stock",
+                ],
+                ["Arroz 1 kg", "7501", "", "22", "", "", ""],
+                ["Frijol", "7502", "FRI-1", "30", "25", "abarrotes", "10"],
+                ["", "7503", "", "15", "", "", ""],
+                ["Aceite", "7504", "", "-5", "", "", ""],
+                ["Frijol negro", "7502", "", "31", "", "", ""],
+            ]
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect(),
+            written: RefCell::new(Vec::new()),
+        };
+        let request = |dry_run: bool| ProductImportRequest {
+            path: "catalogo.csv".to_string(),
+            mapping: HashMap::new(),
+            dry_run,
+            create_categories: true,
+            user_id: Some(1),
+            allow_below_cost: false,
+        };
+
+        let preview = import_products(&products, &categories, &sheet, request(true)).unwrap();
+        assert_eq!((preview.created, preview.updated), (1, 1));
+        assert_eq!(preview.created_categories, vec!["abarrotes"]);
+        let rejected: Vec<_> = preview
+            .rejected
+            .iter()
+            .map(|rejection| (rejection.row, rejection.message.as_str()))
+            .collect();
+        assert_eq!(
+            rejected,
+            vec![
+                (4, "Falta el nombre del producto"),
+                (5, "El precio no puede ser negativo"),
+                (6, "El código 7502 ya aparece en la fila 3"),
+            ]
+        );
+        assert_eq!(find_by_id(&db, rice.id).unwrap().unwrap().price, 20.0);
+        assert!(find_by_barcode(&db, "FRI-1").unwrap().is_none());
+
+        let report = import_products(&products, &categories, &sheet, request(false)).unwrap();
+        assert_eq!((report.created, report.updated), (1, 1));
+        assert_eq!(report.rejected.len(), 3);
+        let rice = find_by_id(&db, rice.id).unwrap().unwrap();
+        assert_eq!((rice.name.as_str(), rice.price), ("Arroz 1 kg", 22.0));
+        let beans = find_by_barcode(&db, "FRI-1").unwrap().unwrap();
+        assert_eq!(beans.barcode.as_deref(), Some("7502"));
+        assert_eq!(beans.category_name.as_deref(), Some("abarrotes"));
+        assert_eq!(beans.stock, 10.0);
+
+        assert_eq!(
+            export_products(&products, &sheet, "catalogo.xlsx".to_string()).unwrap(),
+            2
+        );
+        let written = sheet.written.borrow();
+        assert_eq!(written[0][3], SheetCell::Text("SKU".to_string()));
+        assert_eq!(written[2][3], SheetCell::Text("FRI-1".to_string()));
+        assert_eq!(written[2][4], SheetCell::Number(30.0));
+    }
+
+    #[test]
+    fn kit_availability_comes_from_its_components() {
+        let db = test_database();
+        let repository = SqliteProductRepository::new(&db);
+        let cheese = create(
+            &db,
+            &CreateProductRequest {
+                cost: Some(80.0),
+                is_bulk: true,
+                stock: Some(1.3),
+                ..new_product("Queso", 120.0, "kg")
+            },
+        )
+        .unwrap();
+        let wine = create(
+            &db,
+            &CreateProductRequest {
+                cost: Some(180.0),
+                stock: Some(5.0),
+                ..new_product("Vino", 250.0, "pieza")
+            },
+        )
+        .unwrap();
+        let basket = create(&db, &new_product("Canasta", 499.0, "pieza")).unwrap();
+        let request = |kit_id: i64, components: &[(i64, f64)]| SetKitComponentsRequest {
+            kit_id,
+            components: components
+                .iter()
+                .map(|&(component_id, quantity)| KitComponentInput {
+                    component_id,
+                    quantity,
+                })
+                .collect(),
+        };
+
+        let components = set_kit_components(
+            &repository,
+            request(basket.id, &[(cheese.id, 0.25), (wine.id, 2.0)]),
+        )
+        .unwrap();
+        assert_eq!(components.len(), 2);
+        let basket = find_by_id(&db, basket.id).unwrap().unwrap();
+        assert!(basket.is_kit);
+        assert_eq!(basket.stock, 2.0);
+        assert_eq!(basket.price, 499.0);
+        assert!(!find_by_id(&db, wine.id).unwrap().unwrap().is_kit);
+
+        let rejected = [
+            request(basket.id, &[(basket.id, 1.0)]),
+            request(basket.id, &[(wine.id, 1.5)]),
+            request(basket.id, &[(wine.id, 1.0), (wine.id, 2.0)]),
+            request(wine.id, &[(basket.id, 1.0)]),
+            request(cheese.id, &[(wine.id, 1.0)]),
+        ];
+        for request in rejected {
+            assert!(set_kit_components(&repository, request).is_err());
+        }
+        assert!(matches!(delete(&db, wine.id), Err(AppError::Conflict(_))));
+
+        set_kit_components(&repository, request(basket.id, &[])).unwrap();
+        let basket = find_by_id(&db, basket.id).unwrap().unwrap();
+        assert!(!basket.is_kit);
+        assert_eq!(basket.stock, 0.0);
+    }
+
+    #[test]
+    fn search_ignores_accents_matches_prefixes_and_ranks_names_first() {
+        let db = test_database();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO categories (id, name) VALUES (1, 'Salchichonería')",
+                [],
+            )
+            .unwrap();
+        let ham = create(
+            &db,
+            &CreateProductRequest {
+                barcode: Some("7501234".to_string()),
+                is_bulk: true,
+                category_id: Some(1),
+                ..new_product("Jamón de pavo", 80.0, "kg")
+            },
+        )
+        .unwrap();
+        let sandwich = create(
+            &db,
+            &CreateProductRequest {
+                description: Some("Con jamón y queso".to_string()),
+                ..new_product("Sándwich", 45.0, "pieza")
+            },
+        )
+        .unwrap();
+
+        let ids = |term: &str| -> Vec<i64> {
+            search(&db, term)
+                .unwrap()
+                .iter()
+                .map(|product| product.id)
+                .collect()
+        };
+        assert_eq!(ids("jamon"), vec![ham.id, sandwich.id]);
+        assert_eq!(ids("JAM pav"), vec![ham.id]);
+        assert_eq!(ids("salchichoneria"), vec![ham.id]);
+        assert_eq!(ids("750123"), vec![ham.id]);
+        assert_eq!(ids("sandwich \"queso"), vec![sandwich.id]);
+        assert!(ids("*").is_empty());
+
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE categories SET name = 'Carnes frías' WHERE id = 1",
+                [],
+            )
+            .unwrap();
+        assert_eq!(ids("frias"), vec![ham.id]);
+        assert!(ids("salchichoneria").is_empty());
+        add_code(
+            &db,
+            &AddProductCodeRequest {
+                product_id: sandwich.id,
+                code: "SDW-9".to_string(),
+                code_type: ProductCodeType::Sku,
+                supplier_name: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(ids("sdw"), vec![sandwich.id]);
+    }
+
+    /// Run with `cargo test search_benchmark -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn search_benchmark_over_50k_products() {
+        let db = test_database();
+        {
+            let mut conn = db.conn.lock().unwrap();
+            conn.execute_batch(
+                "CREATE INDEX idx_products_parent ON products(parent_id);
+                CREATE INDEX idx_product_codes_product ON product_codes(product_id);",
+            )
+            .unwrap();
+            let tx = conn.transaction().unwrap();
+            let words = [
+                "Jamón", "Queso", "Leche", "Café", "Azúcar", "Frijol", "Arroz", "Aceite",
+            ];
+            for index in 0..50_000 {
+                tx.execute(
+                    "INSERT INTO products (name, price, unit) VALUES (?1, 10, 'pieza')",
+                    params![format!(
+                        "{} {} marca {}",
+                        words[index % words.len()],
+                        words[(index / words.len()) % words.len()],
+                        index
+                    )],
+                )
+                .unwrap();
+                tx.execute(
+                    "INSERT INTO product_codes (product_id, code, code_type, is_primary) \
+                        VALUES (?1, ?2, 'barcode', 1)",
+                    params![tx.last_insert_rowid(), format!("75{index:011}")],
+                )
+                .unwrap();
+            }
+            tx.commit().unwrap();
+        }
+
+        let like = |term: &str| {
+            let conn = db.conn.lock().unwrap();
+            let query = format!(
+                "{} WHERE p.active = 1 AND (p.name LIKE ?1 OR EXISTS(SELECT 1 FROM product_codes pc \
+                    WHERE pc.product_id = p.id AND pc.code LIKE ?1)) ORDER BY p.name LIMIT 20",
+                SELECT_QUERY
+            );
+            let mut stmt = conn.prepare(&query).unwrap();
+            stmt.query_map(params![format!("%{term}%")], row_to_product)
+                .unwrap()
+                .count()
+        };
+        for term in ["jamon", "queso leche", "marca 4999", "7500000012"] {
+            let start = Instant::now();
+            for _ in 0..20 {
+                search(&db, term).unwrap();
+            }
+            let fts = start.elapsed() / 20;
+            let start = Instant::now();
+            for _ in 0..20 {
+                like(term);
+            }
+            let scan = start.elapsed() / 20;
+            println!("{term:>12}: fts {fts:?}, like {scan:?}");
+        }
+    }
 }
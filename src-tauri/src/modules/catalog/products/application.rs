@@ -1,17 +1,82 @@
-use crate::models::product::{CreateProductRequest, Product, UpdateProductRequest};
+use crate::models::price_change::{
+    ScheduledPriceChange, ScheduledPriceChangeStatus, ScheduledPriceInput,
+};
+use crate::models::product::{
+    AddProductCodeRequest, BarcodeLookup, BelowCostPolicy, CreateProductRequest, Product,
+    ProductCode, ProductPriceChange, ScaleLabel, UpdateProductRequest,
+};
+use crate::models::product_kit::{KitComponent, KitComponentInput};
+use crate::models::product_variant::{
+    GenerateVariantsRequest, ProductAttribute, ProductGroup, ProductVariant, VariantAttributeInput,
+    VariantOption,
+};
+use crate::models::unit::Unit;
+use crate::modules::catalog::products::scale_barcode::{self, ScaleBarcodeFormat};
 use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
 
-const BULK_UNITS: [&str; 3] = ["kg", "litro", "metro"];
+const MAX_GENERATED_VARIANTS: usize = 200;
 
 pub trait ProductRepository {
     fn find_all(&self) -> AppResult<Vec<Product>>;
     fn find_active(&self) -> AppResult<Vec<Product>>;
     fn find_by_id(&self, id: i64) -> AppResult<Option<Product>>;
+    /// Looks the code up among all of a product's codes, not only its primary barcode.
     fn find_by_barcode(&self, barcode: &str) -> AppResult<Option<Product>>;
     fn search(&self, term: &str) -> AppResult<Vec<Product>>;
     fn create(&self, request: &CreateProductRequest) -> AppResult<Product>;
     fn update(&self, request: &UpdateProductRequest) -> AppResult<Product>;
+    /// Applies every update in one transaction, or none of them.
+    fn update_all(&self, requests: &[UpdateProductRequest]) -> AppResult<()>;
     fn delete(&self, id: i64) -> AppResult<()>;
+    /// Any product's code, active or not.
+    fn find_code(&self, code: &str) -> AppResult<Option<ProductCode>>;
+    fn find_code_by_id(&self, id: i64) -> AppResult<Option<ProductCode>>;
+    fn find_codes(&self, product_id: i64) -> AppResult<Vec<ProductCode>>;
+    fn add_code(&self, request: &AddProductCodeRequest) -> AppResult<ProductCode>;
+    fn delete_code(&self, id: i64) -> AppResult<()>;
+    fn scale_barcode_formats(&self) -> AppResult<Vec<ScaleBarcodeFormat>>;
+    fn below_cost_policy(&self) -> AppResult<BelowCostPolicy>;
+    fn find_price_history(&self, product_id: i64) -> AppResult<Vec<ProductPriceChange>>;
+    fn find_attributes(&self, product_id: i64) -> AppResult<Vec<ProductAttribute>>;
+    fn find_variants(&self, parent_id: i64) -> AppResult<Vec<ProductVariant>>;
+    /// Records the attributes and creates the combinations the parent does not
+    /// have yet; returns only the new variants.
+    fn create_variants(
+        &self,
+        parent: &Product,
+        attributes: &[VariantAttributeInput],
+        combinations: &[Vec<VariantOption>],
+    ) -> AppResult<Vec<ProductVariant>>;
+    /// Products of the category and of every subcategory below it.
+    fn find_in_category_tree(&self, category_id: i64) -> AppResult<Vec<Product>>;
+    fn create_scheduled_price_changes(
+        &self,
+        changes: &[ScheduledPriceInput],
+        effective_at: &str,
+        user_id: i64,
+    ) -> AppResult<Vec<ScheduledPriceChange>>;
+    fn find_scheduled_price_changes(
+        &self,
+        status: Option<ScheduledPriceChangeStatus>,
+    ) -> AppResult<Vec<ScheduledPriceChange>>;
+    /// Pending changes whose `effective_at` has passed, oldest first.
+    fn find_due_price_changes(&self) -> AppResult<Vec<ScheduledPriceChange>>;
+    /// Moves a pending change to `status`; returns false if it was not pending.
+    fn set_scheduled_price_change_status(
+        &self,
+        id: i64,
+        status: ScheduledPriceChangeStatus,
+    ) -> AppResult<bool>;
+    fn find_kit_components(&self, kit_id: i64) -> AppResult<Vec<KitComponent>>;
+    fn replace_kit_components(
+        &self,
+        kit_id: i64,
+        components: &[KitComponentInput],
+    ) -> AppResult<()>;
+    /// Whether some kit lists the product as a component.
+    fn is_kit_component(&self, product_id: i64) -> AppResult<bool>;
+    fn find_unit(&self, name: &str) -> AppResult<Option<Unit>>;
 }
 
 pub fn get_products(repository: &impl ProductRepository) -> AppResult<Vec<Product>> {
@@ -28,12 +93,44 @@ pub fn get_product(repository: &impl ProductRepository, id: i64) -> AppResult<Pr
         .ok_or_else(|| AppError::NotFound("Producto no encontrado".to_string()))
 }
 
+/// Registered codes win; otherwise the code is tried as a scale label whose PLU
+/// must belong to a bulk product.
 pub fn find_product_by_barcode(
     repository: &impl ProductRepository,
     barcode: String,
-) -> AppResult<Product> {
-    repository.find_by_barcode(&barcode)?.ok_or_else(|| {
-        AppError::NotFound("Producto no encontrado con ese código de barras".to_string())
+) -> AppResult<BarcodeLookup> {
+    let barcode = barcode.trim();
+    if let Some(product) = repository.find_by_barcode(barcode)? {
+        return Ok(BarcodeLookup {
+            product,
+            scale: None,
+        });
+    }
+    let not_found =
+        || AppError::NotFound("Producto no encontrado con ese código de barras".to_string());
+    let reading = scale_barcode::decode(barcode, &repository.scale_barcode_formats()?)
+        .ok_or_else(not_found)?;
+    let short_plu = reading.plu.trim_start_matches('0');
+    let product = match repository.find_by_barcode(&reading.plu)? {
+        Some(product) => product,
+        None if !short_plu.is_empty() => repository
+            .find_by_barcode(short_plu)?
+            .ok_or_else(not_found)?,
+        None => return Err(not_found()),
+    };
+    if !product.is_bulk {
+        return Err(AppError::Validation(format!(
+            "El PLU {} de la etiqueta de báscula no es un producto a granel",
+            reading.plu
+        )));
+    }
+    Ok(BarcodeLookup {
+        product,
+        scale: Some(ScaleLabel {
+            plu: reading.plu,
+            input_mode: reading.input_mode,
+            input_value: reading.input_value,
+        }),
     })
 }
 
@@ -44,45 +141,296 @@ pub fn search_products(
     repository.search(&term)
 }
 
+/// Like `search_products`, with variants gathered under their parent.
+pub fn search_products_grouped(
+    repository: &impl ProductRepository,
+    term: String,
+) -> AppResult<Vec<ProductGroup>> {
+    let mut groups: Vec<ProductGroup> = Vec::new();
+    for product in repository.search(&term)? {
+        let parent_id = product.parent_id.unwrap_or(product.id);
+        if groups.iter().any(|group| group.product.id == parent_id) {
+            continue;
+        }
+        let parent = match product.parent_id {
+            Some(_) => match repository.find_by_id(parent_id)? {
+                Some(parent) => parent,
+                None => continue,
+            },
+            None => product,
+        };
+        let variants = if parent.variant_count > 0 {
+            repository
+                .find_variants(parent.id)?
+                .into_iter()
+                .filter(|variant| variant.product.active)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        groups.push(ProductGroup {
+            product: parent,
+            variants,
+        });
+    }
+    Ok(groups)
+}
+
+pub fn get_product_attributes(
+    repository: &impl ProductRepository,
+    product_id: i64,
+) -> AppResult<Vec<ProductAttribute>> {
+    get_product(repository, product_id)?;
+    repository.find_attributes(product_id)
+}
+
+pub fn get_product_variants(
+    repository: &impl ProductRepository,
+    product_id: i64,
+) -> AppResult<Vec<ProductVariant>> {
+    get_product(repository, product_id)?;
+    repository.find_variants(product_id)
+}
+
+/// Variants start with the parent's price and no stock; each one is then edited
+/// like any other product.
+pub fn generate_product_variants(
+    repository: &impl ProductRepository,
+    request: GenerateVariantsRequest,
+) -> AppResult<Vec<ProductVariant>> {
+    let parent = get_product(repository, request.product_id)?;
+    if parent.parent_id.is_some() {
+        return Err(AppError::Validation(
+            "Una variante no puede tener variantes propias".to_string(),
+        ));
+    }
+    let attributes = normalize_attributes(request.attributes)?;
+    let combinations = variant_combinations(&attributes);
+    if combinations.len() > MAX_GENERATED_VARIANTS {
+        return Err(AppError::Validation(format!(
+            "Se generarían {} variantes; el máximo es {MAX_GENERATED_VARIANTS}",
+            combinations.len()
+        )));
+    }
+    repository.create_variants(&parent, &attributes, &combinations)
+}
+
+fn normalize_attributes(
+    attributes: Vec<VariantAttributeInput>,
+) -> AppResult<Vec<VariantAttributeInput>> {
+    if attributes.is_empty() {
+        return Err(AppError::Validation(
+            "Define al menos un atributo para las variantes".to_string(),
+        ));
+    }
+    let mut normalized: Vec<VariantAttributeInput> = Vec::with_capacity(attributes.len());
+    for attribute in attributes {
+        let name = attribute.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::Validation(
+                "El nombre del atributo no puede estar vacío".to_string(),
+            ));
+        }
+        if normalized
+            .iter()
+            .any(|existing| existing.name.to_lowercase() == name.to_lowercase())
+        {
+            return Err(AppError::Validation(format!(
+                "El atributo {name} está repetido"
+            )));
+        }
+        let mut values: Vec<String> = Vec::new();
+        for value in attribute.values {
+            let value = value.trim();
+            if !value.is_empty()
+                && !values
+                    .iter()
+                    .any(|existing| existing.to_lowercase() == value.to_lowercase())
+            {
+                values.push(value.to_string());
+            }
+        }
+        if values.is_empty() {
+            return Err(AppError::Validation(format!(
+                "El atributo {name} no tiene valores"
+            )));
+        }
+        normalized.push(VariantAttributeInput { name, values });
+    }
+    Ok(normalized)
+}
+
+/// Every combination of one value per attribute, in attribute order.
+pub fn variant_combinations(attributes: &[VariantAttributeInput]) -> Vec<Vec<VariantOption>> {
+    let mut combinations: Vec<Vec<VariantOption>> = vec![Vec::new()];
+    for attribute in attributes {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                attribute.values.iter().map(move |value| {
+                    let mut next = combination.clone();
+                    next.push(VariantOption {
+                        attribute: attribute.name.clone(),
+                        value: value.clone(),
+                    });
+                    next
+                })
+            })
+            .collect();
+    }
+    combinations
+}
+
 pub fn create_product(
     repository: &impl ProductRepository,
-    request: CreateProductRequest,
+    mut request: CreateProductRequest,
 ) -> AppResult<Product> {
     validate_create_request(&request)?;
+    check_product_unit(repository, request.is_bulk, &request.unit)?;
+    check_price_against_cost(
+        request.price,
+        request.cost.unwrap_or(0.0),
+        repository.below_cost_policy()?,
+        request.allow_below_cost,
+    )?;
+    request.barcode = request
+        .barcode
+        .as_deref()
+        .map(str::trim)
+        .filter(|barcode| !barcode.is_empty())
+        .map(str::to_string);
     if let Some(barcode) = request.barcode.as_deref() {
-        if repository.find_by_barcode(barcode)?.is_some() {
-            return Err(AppError::Conflict(format!(
-                "El producto con código de barras {barcode} ya existe"
-            )));
-        }
+        ensure_code_available(repository, barcode, None)?;
     }
     repository.create(&request)
 }
 
+/// An empty `barcode` clears the primary barcode.
 pub fn update_product(
     repository: &impl ProductRepository,
-    request: UpdateProductRequest,
+    mut request: UpdateProductRequest,
 ) -> AppResult<Product> {
     let current = get_product(repository, request.id)?;
-    validate_update_request(&request, &current)?;
+    validate_update_request(&request)?;
+    if request.unit.is_some() || request.is_bulk.is_some() {
+        check_product_unit(
+            repository,
+            request.is_bulk.unwrap_or(current.is_bulk),
+            request.unit.as_deref().unwrap_or(&current.unit),
+        )?;
+    }
+    if request.price.is_some() || request.cost.is_some() {
+        check_price_against_cost(
+            request.price.unwrap_or(current.price),
+            request.cost.unwrap_or(current.cost),
+            repository.below_cost_policy()?,
+            request.allow_below_cost,
+        )?;
+    }
 
-    if let Some(barcode) = request.barcode.as_deref() {
-        if let Some(existing) = repository.find_by_barcode(barcode)? {
-            if existing.id != request.id {
-                return Err(AppError::Conflict(format!(
-                    "El producto con código de barras {barcode} ya existe"
-                )));
-            }
-        }
+    request.barcode = request
+        .barcode
+        .as_deref()
+        .map(|barcode| barcode.trim().to_string());
+    if let Some(barcode) = request.barcode.as_deref().filter(|code| !code.is_empty()) {
+        ensure_code_available(repository, barcode, Some(request.id))?;
     }
     repository.update(&request)
 }
 
+pub fn get_product_codes(
+    repository: &impl ProductRepository,
+    product_id: i64,
+) -> AppResult<Vec<ProductCode>> {
+    get_product(repository, product_id)?;
+    repository.find_codes(product_id)
+}
+
+pub fn add_product_code(
+    repository: &impl ProductRepository,
+    mut request: AddProductCodeRequest,
+) -> AppResult<ProductCode> {
+    get_product(repository, request.product_id)?;
+    request.code = request.code.trim().to_string();
+    if request.code.is_empty() {
+        return Err(AppError::Validation(
+            "El código no puede estar vacío".to_string(),
+        ));
+    }
+    request.supplier_name = request
+        .supplier_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string);
+    ensure_code_available(repository, &request.code, None)?;
+    repository.add_code(&request)
+}
+
+pub fn delete_product_code(repository: &impl ProductRepository, id: i64) -> AppResult<()> {
+    let code = repository
+        .find_code_by_id(id)?
+        .ok_or_else(|| AppError::NotFound("Código no encontrado".to_string()))?;
+    if code.is_primary {
+        return Err(AppError::Conflict(
+            "El código de barras principal se cambia desde el producto".to_string(),
+        ));
+    }
+    repository.delete_code(id)
+}
+
+/// Codes are unique across barcodes, SKUs and supplier codes of every product.
+pub fn ensure_code_available(
+    repository: &impl ProductRepository,
+    code: &str,
+    product_id: Option<i64>,
+) -> AppResult<()> {
+    match repository.find_code(code)? {
+        Some(existing) if Some(existing.product_id) != product_id => Err(AppError::Conflict(
+            format!("El código {code} ya está asignado a otro producto"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn get_product_price_history(
+    repository: &impl ProductRepository,
+    product_id: i64,
+) -> AppResult<Vec<ProductPriceChange>> {
+    get_product(repository, product_id)?;
+    repository.find_price_history(product_id)
+}
+
+/// Gross margin over price, in percent.
+pub fn margin_percent(price: f64, cost: f64) -> Option<f64> {
+    (price > 0.0).then(|| money::round2((price - cost) / price * 100.0))
+}
+
+pub fn check_price_against_cost(
+    price: f64,
+    cost: f64,
+    policy: BelowCostPolicy,
+    allow_below_cost: bool,
+) -> AppResult<()> {
+    if price >= cost {
+        return Ok(());
+    }
+    match policy {
+        BelowCostPolicy::Refuse => Err(AppError::Validation(format!(
+            "El precio {price:.2} está por debajo del costo {cost:.2}"
+        ))),
+        BelowCostPolicy::Warn if !allow_below_cost => Err(AppError::Conflict(format!(
+            "El precio {price:.2} está por debajo del costo {cost:.2}; confirma para guardarlo"
+        ))),
+        BelowCostPolicy::Warn => Ok(()),
+    }
+}
+
 pub fn delete_product(repository: &impl ProductRepository, id: i64) -> AppResult<()> {
     repository.delete(id)
 }
 
-fn validate_create_request(request: &CreateProductRequest) -> AppResult<()> {
+pub fn validate_create_request(request: &CreateProductRequest) -> AppResult<()> {
     if request.name.trim().is_empty() {
         return Err(AppError::Validation(
             "El nombre del producto no puede estar vacío".to_string(),
@@ -93,6 +441,11 @@ fn validate_create_request(request: &CreateProductRequest) -> AppResult<()> {
             "El precio no puede ser negativo".to_string(),
         ));
     }
+    if request.cost.is_some_and(|cost| cost < 0.0) {
+        return Err(AppError::Validation(
+            "El costo no puede ser negativo".to_string(),
+        ));
+    }
     if request.stock.unwrap_or(0.0) < 0.0 {
         return Err(AppError::Validation(
             "El stock inicial no puede ser negativo".to_string(),
@@ -103,10 +456,10 @@ fn validate_create_request(request: &CreateProductRequest) -> AppResult<()> {
             "El stock mínimo no puede ser negativo".to_string(),
         ));
     }
-    validate_bulk_configuration(request.is_bulk, &request.unit)
+    Ok(())
 }
 
-fn validate_update_request(request: &UpdateProductRequest, current: &Product) -> AppResult<()> {
+pub fn validate_update_request(request: &UpdateProductRequest) -> AppResult<()> {
     if request
         .name
         .as_deref()
@@ -121,46 +474,150 @@ fn validate_update_request(request: &UpdateProductRequest, current: &Product) ->
             "El precio no puede ser negativo".to_string(),
         ));
     }
+    if request.cost.is_some_and(|cost| cost < 0.0) {
+        return Err(AppError::Validation(
+            "El costo no puede ser negativo".to_string(),
+        ));
+    }
     if request.min_stock.is_some_and(|stock| stock < 0.0) {
         return Err(AppError::Validation(
             "El stock mínimo no puede ser negativo".to_string(),
         ));
     }
+    Ok(())
+}
 
-    validate_bulk_configuration(
-        request.is_bulk.unwrap_or(current.is_bulk),
-        request.unit.as_deref().unwrap_or(&current.unit),
-    )
+pub fn check_product_unit(
+    repository: &impl ProductRepository,
+    is_bulk: bool,
+    name: &str,
+) -> AppResult<()> {
+    validate_unit_configuration(is_bulk, name, repository.find_unit(name)?.as_ref())
 }
 
-fn validate_bulk_configuration(is_bulk: bool, unit: &str) -> AppResult<()> {
-    if is_bulk && !BULK_UNITS.contains(&unit) {
-        return Err(AppError::Validation(
-            "Los productos a granel deben usar kg, litro o metro como unidad base".to_string(),
-        ));
+/// Products keep stock in a configured base unit, and only fractional units
+/// can be sold a granel.
+fn validate_unit_configuration(is_bulk: bool, name: &str, unit: Option<&Unit>) -> AppResult<()> {
+    let unit =
+        unit.ok_or_else(|| AppError::Validation(format!("La unidad {name} no está configurada")))?;
+    if let Some(base_unit) = unit.base_unit.as_deref() {
+        return Err(AppError::Validation(format!(
+            "{name} es una conversión de {base_unit}; usa {base_unit} como unidad base"
+        )));
+    }
+    if is_bulk && !unit.fractional {
+        return Err(AppError::Validation(format!(
+            "Los productos a granel deben usar una unidad fraccionable; {name} no lo es"
+        )));
     }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::validate_bulk_configuration;
+    use super::{
+        check_price_against_cost, margin_percent, normalize_attributes,
+        validate_unit_configuration, variant_combinations,
+    };
+    use crate::models::product::BelowCostPolicy;
+    use crate::models::product_variant::VariantAttributeInput;
+    use crate::models::unit::Unit;
+    use crate::shared::error::AppError;
+
+    #[test]
+    fn prices_below_cost_need_confirmation_or_are_refused() {
+        assert!(check_price_against_cost(10.0, 8.0, BelowCostPolicy::Refuse, false).is_ok());
+        assert!(matches!(
+            check_price_against_cost(7.0, 8.0, BelowCostPolicy::Warn, false),
+            Err(AppError::Conflict(_))
+        ));
+        assert!(check_price_against_cost(7.0, 8.0, BelowCostPolicy::Warn, true).is_ok());
+        assert!(matches!(
+            check_price_against_cost(7.0, 8.0, BelowCostPolicy::Refuse, true),
+            Err(AppError::Validation(_))
+        ));
+    }
 
     #[test]
-    fn accepts_supported_bulk_units() {
-        for unit in ["kg", "litro", "metro"] {
-            assert!(validate_bulk_configuration(true, unit).is_ok());
+    fn margin_is_a_percentage_of_price() {
+        assert_eq!(margin_percent(40.0, 30.0), Some(25.0));
+        assert_eq!(margin_percent(0.0, 30.0), None);
+    }
+
+    fn attribute(name: &str, values: &[&str]) -> VariantAttributeInput {
+        VariantAttributeInput {
+            name: name.to_string(),
+            values: values.iter().map(|value| value.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn combines_one_value_of_each_attribute() {
+        let combinations = variant_combinations(&[
+            attribute("Tamaño", &["600 ml", "2 L"]),
+            attribute("Sabor", &["Cola", "Limón", "Naranja"]),
+        ]);
+        assert_eq!(combinations.len(), 6);
+        assert_eq!(combinations[0][0].value, "600 ml");
+        assert_eq!(combinations[0][1].value, "Cola");
+        assert_eq!(combinations[5][0].value, "2 L");
+        assert_eq!(combinations[5][1].value, "Naranja");
+    }
+
+    #[test]
+    fn normalizes_attribute_values_and_rejects_repeated_names() {
+        let attributes =
+            normalize_attributes(vec![attribute(" Color ", &["Rojo", " rojo", "", "Azul"])])
+                .unwrap();
+        assert_eq!(attributes[0].name, "Color");
+        assert_eq!(attributes[0].values, ["Rojo", "Azul"]);
+
+        assert!(normalize_attributes(vec![
+            attribute("Color", &["Rojo"]),
+            attribute("color", &["Azul"]),
+        ])
+        .is_err());
+        assert!(normalize_attributes(vec![attribute("Talla", &[" "])]).is_err());
+    }
+
+    fn unit(name: &str, base_unit: Option<&str>, fractional: bool) -> Unit {
+        Unit {
+            id: 1,
+            name: name.to_string(),
+            base_unit_id: base_unit.map(|_| 2),
+            base_unit: base_unit.map(str::to_string),
+            factor: 1.0,
+            fractional,
+            created_at: "2026-01-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn accepts_fractional_units_for_bulk_products() {
+        for name in ["kg", "litro", "metro"] {
+            let unit = unit(name, None, true);
+            assert!(validate_unit_configuration(true, name, Some(&unit)).is_ok());
         }
     }
 
     #[test]
     fn rejects_discrete_units_for_bulk_products() {
-        assert!(validate_bulk_configuration(true, "pieza").is_err());
+        let piece = unit("pieza", None, false);
+        assert!(validate_unit_configuration(true, "pieza", Some(&piece)).is_err());
+    }
+
+    #[test]
+    fn non_bulk_products_do_not_require_a_fractional_unit() {
+        let piece = unit("pieza", None, false);
+        let kilo = unit("kg", None, true);
+        assert!(validate_unit_configuration(false, "pieza", Some(&piece)).is_ok());
+        assert!(validate_unit_configuration(false, "kg", Some(&kilo)).is_ok());
     }
 
     #[test]
-    fn non_bulk_products_do_not_require_a_convertible_unit() {
-        assert!(validate_bulk_configuration(false, "pieza").is_ok());
-        assert!(validate_bulk_configuration(false, "kg").is_ok());
+    fn products_need_a_configured_base_unit() {
+        let case = unit("caja", Some("pieza"), false);
+        assert!(validate_unit_configuration(false, "caja", Some(&case)).is_err());
+        assert!(validate_unit_configuration(false, "bulto", None).is_err());
     }
 }
@@ -0,0 +1,568 @@
+//! Catalog import and export through CSV/XLSX files.
+//!
+//! Rows are matched to existing products by barcode or SKU (any of a product's
+//! codes); unmatched rows create a product. Stock is only read for new products,
+//! existing ones change stock through inventory adjustments.
+
+use crate::models::product::{
+    AddProductCodeRequest, CreateProductRequest, ProductCodeType, UpdateProductRequest,
+};
+use crate::models::product_import::{
+    ProductImportField, ProductImportRejection, ProductImportReport, ProductImportRequest,
+};
+use crate::modules::catalog::categories::application::CategoryRepository;
+use crate::modules::catalog::products::application::{
+    add_product_code, check_price_against_cost, check_product_unit, create_product,
+    ensure_code_available, get_product, update_product, validate_create_request,
+    validate_update_request, ProductRepository,
+};
+use crate::shared::error::{AppError, AppResult};
+use std::collections::HashMap;
+
+const DEFAULT_UNIT: &str = "pieza";
+
+/// Headers written by the export; importing an exported file needs no mapping.
+const EXPORT_COLUMNS: [(&str, ProductImportField); 11] = [
+    ("Nombre", ProductImportField::Name),
+    ("Descripción", ProductImportField::Description),
+    ("Código de barras", ProductImportField::Barcode),
+    ("SKU", ProductImportField::Sku),
+    ("Precio", ProductImportField::Price),
+    ("Costo", ProductImportField::Cost),
+    ("Unidad", ProductImportField::Unit),
+    ("Granel", ProductImportField::IsBulk),
+    ("Categoría", ProductImportField::Category),
+    ("Stock", ProductImportField::Stock),
+    ("Stock mínimo", ProductImportField::MinStock),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SheetCell {
+    Text(String),
+    Number(f64),
+}
+
+pub trait ProductSheetFile {
+    /// Every row of the first sheet, header included, as text.
+    fn read_rows(&self, path: &str) -> AppResult<Vec<Vec<String>>>;
+    fn write_rows(&self, path: &str, rows: &[Vec<SheetCell>]) -> AppResult<()>;
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ImportRow {
+    name: Option<String>,
+    description: Option<String>,
+    barcode: Option<String>,
+    sku: Option<String>,
+    price: Option<f64>,
+    cost: Option<f64>,
+    unit: Option<String>,
+    is_bulk: Option<bool>,
+    category: Option<String>,
+    stock: Option<f64>,
+    min_stock: Option<f64>,
+}
+
+enum RowOutcome {
+    Created,
+    Updated,
+}
+
+struct ImportContext<'a, P, C> {
+    products: &'a P,
+    categories: &'a C,
+    request: &'a ProductImportRequest,
+    category_ids: HashMap<String, Option<i64>>,
+    created_categories: Vec<String>,
+    seen_codes: HashMap<String, usize>,
+}
+
+pub fn import_products(
+    products: &impl ProductRepository,
+    categories: &impl CategoryRepository,
+    file: &impl ProductSheetFile,
+    request: ProductImportRequest,
+) -> AppResult<ProductImportReport> {
+    let mut rows = file.read_rows(&request.path)?.into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| AppError::Validation("El archivo está vacío".to_string()))?;
+    let columns = resolve_columns(&header, &request.mapping)?;
+
+    let mut context = ImportContext {
+        products,
+        categories,
+        request: &request,
+        category_ids: categories
+            .find_all()?
+            .into_iter()
+            .map(|category| (header_key(&category.name), Some(category.id)))
+            .collect(),
+        created_categories: Vec::new(),
+        seen_codes: HashMap::new(),
+    };
+    let mut report = ProductImportReport {
+        dry_run: request.dry_run,
+        total_rows: 0,
+        created: 0,
+        updated: 0,
+        created_categories: Vec::new(),
+        rejected: Vec::new(),
+    };
+    for (index, cells) in rows.enumerate() {
+        if cells.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let row_number = index + 2;
+        report.total_rows += 1;
+        let outcome =
+            parse_row(&cells, &columns).and_then(|row| import_row(&mut context, row_number, row));
+        match outcome {
+            Ok(RowOutcome::Created) => report.created += 1,
+            Ok(RowOutcome::Updated) => report.updated += 1,
+            Err(message) => report.rejected.push(ProductImportRejection {
+                row: row_number,
+                message,
+            }),
+        }
+    }
+    report.created_categories = context.created_categories;
+    Ok(report)
+}
+
+/// Writes the whole catalog, inactive products and variants included.
+pub fn export_products(
+    repository: &impl ProductRepository,
+    file: &impl ProductSheetFile,
+    path: String,
+) -> AppResult<usize> {
+    let products = repository.find_all()?;
+    let mut rows = Vec::with_capacity(products.len() + 1);
+    rows.push(
+        EXPORT_COLUMNS
+            .iter()
+            .map(|(header, _)| SheetCell::Text(header.to_string()))
+            .collect(),
+    );
+    for product in &products {
+        let sku = repository
+            .find_codes(product.id)?
+            .into_iter()
+            .find(|code| code.code_type == ProductCodeType::Sku)
+            .map(|code| code.code)
+            .unwrap_or_default();
+        rows.push(vec![
+            SheetCell::Text(product.name.clone()),
+            SheetCell::Text(product.description.clone().unwrap_or_default()),
+            SheetCell::Text(product.barcode.clone().unwrap_or_default()),
+            SheetCell::Text(sku),
+            SheetCell::Number(product.price),
+            SheetCell::Number(product.cost),
+            SheetCell::Text(product.unit.clone()),
+            SheetCell::Text(if product.is_bulk { "sí" } else { "no" }.to_string()),
+            SheetCell::Text(product.category_name.clone().unwrap_or_default()),
+            SheetCell::Number(product.stock),
+            SheetCell::Number(product.min_stock),
+        ]);
+    }
+    file.write_rows(&path, &rows)?;
+    Ok(products.len())
+}
+
+fn import_row<P: ProductRepository, C: CategoryRepository>(
+    context: &mut ImportContext<'_, P, C>,
+    row_number: usize,
+    row: ImportRow,
+) -> Result<RowOutcome, String> {
+    let codes: Vec<&str> = [row.barcode.as_deref(), row.sku.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    for code in &codes {
+        if let Some(first_row) = context.seen_codes.insert(code.to_string(), row_number) {
+            return Err(format!(
+                "El código {code} ya aparece en la fila {first_row}"
+            ));
+        }
+    }
+    let mut matched = None;
+    for code in &codes {
+        if let Some(existing) = context.products.find_code(code).map_err(message)? {
+            if matched.is_some_and(|id| id != existing.product_id) {
+                return Err(
+                    "El código de barras y el SKU pertenecen a productos distintos".to_string(),
+                );
+            }
+            matched = Some(existing.product_id);
+        }
+    }
+    match matched {
+        Some(product_id) => update_row(context, product_id, row),
+        None => create_row(context, row),
+    }
+}
+
+fn create_row<P: ProductRepository, C: CategoryRepository>(
+    context: &mut ImportContext<'_, P, C>,
+    row: ImportRow,
+) -> Result<RowOutcome, String> {
+    let name = row
+        .name
+        .ok_or_else(|| "Falta el nombre del producto".to_string())?;
+    let price = row.price.ok_or_else(|| "Falta el precio".to_string())?;
+    let mut request = CreateProductRequest {
+        name,
+        description: row.description,
+        barcode: row.barcode,
+        price,
+        cost: row.cost,
+        unit: row.unit.unwrap_or_else(|| DEFAULT_UNIT.to_string()),
+        is_bulk: row.is_bulk.unwrap_or(false),
+        category_id: None,
+        stock: row.stock,
+        min_stock: row.min_stock,
+        user_id: context.request.user_id,
+        allow_below_cost: context.request.allow_below_cost,
+    };
+    validate_create_request(&request).map_err(message)?;
+    check_product_unit(context.products, request.is_bulk, &request.unit).map_err(message)?;
+    check_price_against_cost(
+        request.price,
+        request.cost.unwrap_or(0.0),
+        context.products.below_cost_policy().map_err(message)?,
+        request.allow_below_cost,
+    )
+    .map_err(message)?;
+    if let Some(sku) = row.sku.as_deref() {
+        ensure_code_available(context.products, sku, None).map_err(message)?;
+    }
+    request.category_id = resolve_category(context, row.category.as_deref())?;
+    if context.request.dry_run {
+        return Ok(RowOutcome::Created);
+    }
+
+    let product = create_product(context.products, request).map_err(message)?;
+    if let Some(sku) = row.sku {
+        add_sku(context.products, product.id, sku)?;
+    }
+    Ok(RowOutcome::Created)
+}
+
+fn update_row<P: ProductRepository, C: CategoryRepository>(
+    context: &mut ImportContext<'_, P, C>,
+    product_id: i64,
+    row: ImportRow,
+) -> Result<RowOutcome, String> {
+    let current = get_product(context.products, product_id).map_err(message)?;
+    // A barcode the product already has must not replace its primary code.
+    let barcode = match row.barcode {
+        Some(barcode) => {
+            let known = context.products.find_code(&barcode).map_err(message)?;
+            known.is_none().then_some(barcode)
+        }
+        None => None,
+    };
+    let mut request = UpdateProductRequest {
+        id: product_id,
+        name: row.name,
+        description: row.description,
+        barcode,
+        price: row.price,
+        cost: row.cost,
+        unit: row.unit,
+        is_bulk: row.is_bulk,
+        category_id: None,
+        min_stock: row.min_stock,
+        active: None,
+        user_id: context.request.user_id,
+        allow_below_cost: context.request.allow_below_cost,
+    };
+    validate_update_request(&request).map_err(message)?;
+    if request.unit.is_some() || request.is_bulk.is_some() {
+        check_product_unit(
+            context.products,
+            request.is_bulk.unwrap_or(current.is_bulk),
+            request.unit.as_deref().unwrap_or(&current.unit),
+        )
+        .map_err(message)?;
+    }
+    if request.price.is_some() || request.cost.is_some() {
+        check_price_against_cost(
+            request.price.unwrap_or(current.price),
+            request.cost.unwrap_or(current.cost),
+            context.products.below_cost_policy().map_err(message)?,
+            request.allow_below_cost,
+        )
+        .map_err(message)?;
+    }
+    let sku = match row.sku {
+        Some(sku) => {
+            let owned = context.products.find_code(&sku).map_err(message)?;
+            if owned.is_none() {
+                ensure_code_available(context.products, &sku, Some(product_id)).map_err(message)?;
+            }
+            owned.is_none().then_some(sku)
+        }
+        None => None,
+    };
+    request.category_id = resolve_category(context, row.category.as_deref())?;
+    if context.request.dry_run {
+        return Ok(RowOutcome::Updated);
+    }
+
+    update_product(context.products, request).map_err(message)?;
+    if let Some(sku) = sku {
+        add_sku(context.products, product_id, sku)?;
+    }
+    Ok(RowOutcome::Updated)
+}
+
+fn add_sku(products: &impl ProductRepository, product_id: i64, sku: String) -> Result<(), String> {
+    add_product_code(
+        products,
+        AddProductCodeRequest {
+            product_id,
+            code: sku,
+            code_type: ProductCodeType::Sku,
+            supplier_name: None,
+        },
+    )
+    .map(|_| ())
+    .map_err(message)
+}
+
+/// Looks the category up by name, ignoring case and accents. During a dry run
+/// new categories are only reported.
+fn resolve_category<P, C: CategoryRepository>(
+    context: &mut ImportContext<'_, P, C>,
+    name: Option<&str>,
+) -> Result<Option<i64>, String> {
+    let Some(name) = name else {
+        return Ok(None);
+    };
+    let key = header_key(name);
+    if let Some(id) = context.category_ids.get(&key) {
+        return Ok(*id);
+    }
+    if !context.request.create_categories {
+        return Err(format!("La categoría {name} no existe"));
+    }
+    let id = if context.request.dry_run {
+        None
+    } else {
+        Some(
+            context
+                .categories
+                .create(name, None, None)
+                .map_err(message)?
+                .id,
+        )
+    };
+    context.category_ids.insert(key, id);
+    context.created_categories.push(name.to_string());
+    Ok(id)
+}
+
+fn resolve_columns(
+    header: &[String],
+    mapping: &HashMap<String, ProductImportField>,
+) -> AppResult<HashMap<ProductImportField, usize>> {
+    let mapping: HashMap<String, ProductImportField> = mapping
+        .iter()
+        .map(|(header, field)| (header_key(header), *field))
+        .collect();
+    let mut columns = HashMap::new();
+    for (index, text) in header.iter().enumerate() {
+        let key = header_key(text);
+        let Some(field) = mapping.get(&key).copied().or_else(|| default_field(&key)) else {
+            continue;
+        };
+        if columns.insert(field, index).is_some() {
+            return Err(AppError::Validation(format!(
+                "Dos columnas están asignadas al mismo campo ({})",
+                text.trim()
+            )));
+        }
+    }
+    if ![
+        ProductImportField::Name,
+        ProductImportField::Barcode,
+        ProductImportField::Sku,
+    ]
+    .iter()
+    .any(|field| columns.contains_key(field))
+    {
+        return Err(AppError::Validation(
+            "El archivo necesita una columna de nombre, código de barras o SKU".to_string(),
+        ));
+    }
+    Ok(columns)
+}
+
+fn default_field(key: &str) -> Option<ProductImportField> {
+    if let Some((_, field)) = EXPORT_COLUMNS
+        .iter()
+        .find(|(header, _)| header_key(header) == key)
+    {
+        return Some(*field);
+    }
+    match key {
+        "name" | "producto" => Some(ProductImportField::Name),
+        "description" => Some(ProductImportField::Description),
+        "barcode" | "codigo" | "ean" => Some(ProductImportField::Barcode),
+        "clave" => Some(ProductImportField::Sku),
+        "price" | "precio de venta" => Some(ProductImportField::Price),
+        "cost" | "precio de compra" => Some(ProductImportField::Cost),
+        "unit" => Some(ProductImportField::Unit),
+        "is_bulk" | "a granel" => Some(ProductImportField::IsBulk),
+        "category" => Some(ProductImportField::Category),
+        "existencia" | "existencias" => Some(ProductImportField::Stock),
+        "min_stock" => Some(ProductImportField::MinStock),
+        _ => None,
+    }
+}
+
+fn header_key(text: &str) -> String {
+    deunicode::deunicode(text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn parse_row(
+    cells: &[String],
+    columns: &HashMap<ProductImportField, usize>,
+) -> Result<ImportRow, String> {
+    let text = |field: ProductImportField| {
+        columns
+            .get(&field)
+            .and_then(|index| cells.get(*index))
+            .map(|cell| cell.trim())
+            .filter(|cell| !cell.is_empty())
+            .map(str::to_string)
+    };
+    let number = |field: ProductImportField, label: &str| {
+        text(field)
+            .map(|value| parse_number(&value).ok_or_else(|| format!("{label} inválido: {value}")))
+            .transpose()
+    };
+    let is_bulk = text(ProductImportField::IsBulk)
+        .map(|value| parse_flag(&value).ok_or_else(|| format!("Valor de granel inválido: {value}")))
+        .transpose()?;
+    Ok(ImportRow {
+        name: text(ProductImportField::Name),
+        description: text(ProductImportField::Description),
+        barcode: text(ProductImportField::Barcode),
+        sku: text(ProductImportField::Sku),
+        price: number(ProductImportField::Price, "Precio")?,
+        cost: number(ProductImportField::Cost, "Costo")?,
+        unit: text(ProductImportField::Unit).map(|unit| unit.to_lowercase()),
+        is_bulk,
+        category: text(ProductImportField::Category),
+        stock: number(ProductImportField::Stock, "Stock")?,
+        min_stock: number(ProductImportField::MinStock, "Stock mínimo")?,
+    })
+}
+
+/// Accepts `1234.5`, `1,234.50`, `1,250`, `$12` and a decimal comma (`12,5`).
+fn parse_number(value: &str) -> Option<f64> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '$')
+        .collect();
+    let normalized = if cleaned.contains('.') || is_thousands_grouped(&cleaned) {
+        cleaned.replace(',', "")
+    } else {
+        cleaned.replace(',', ".")
+    };
+    normalized
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+/// Commas followed by groups of exactly three digits separate thousands.
+fn is_thousands_grouped(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let is_digits = |group: &str| group.chars().all(|c| c.is_ascii_digit());
+    let mut groups = digits.split(',');
+    let first = groups.next().unwrap_or_default();
+    digits.contains(',')
+        && (1..=3).contains(&first.len())
+        && !first.starts_with('0')
+        && is_digits(first)
+        && groups.all(|group| group.len() == 3 && is_digits(group))
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match header_key(value).as_str() {
+        "si" | "s" | "x" | "1" | "true" | "yes" => Some(true),
+        "no" | "n" | "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn message(error: AppError) -> String {
+    error.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_number, parse_row, resolve_columns, ImportRow};
+    use crate::models::product_import::ProductImportField;
+    use std::collections::HashMap;
+
+    fn cells(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn resolves_export_headers_and_explicit_mappings() {
+        let header = cells(&["NOMBRE", "Codigo de barras", "P. Venta", "Notas"]);
+        let mapping = HashMap::from([("p. venta".to_string(), ProductImportField::Price)]);
+        let columns = resolve_columns(&header, &mapping).unwrap();
+        assert_eq!(columns[&ProductImportField::Name], 0);
+        assert_eq!(columns[&ProductImportField::Barcode], 1);
+        assert_eq!(columns[&ProductImportField::Price], 2);
+        assert_eq!(columns.len(), 3);
+
+        assert!(resolve_columns(&cells(&["Precio", "Notas"]), &HashMap::new()).is_err());
+        assert!(resolve_columns(&cells(&["Nombre", "Producto"]), &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn parses_numbers_written_either_way() {
+        assert_eq!(parse_number("1,234.50"), Some(1234.5));
+        assert_eq!(parse_number("$ 12"), Some(12.0));
+        assert_eq!(parse_number("12,5"), Some(12.5));
+        assert_eq!(parse_number("1,250"), Some(1250.0));
+        assert_eq!(parse_number("$1,250,000"), Some(1_250_000.0));
+        assert_eq!(parse_number("0,125"), Some(0.125));
+        assert_eq!(parse_number("doce"), None);
+    }
+
+    #[test]
+    fn reports_the_field_that_could_not_be_read() {
+        let columns = resolve_columns(
+            &cells(&["Nombre", "Precio", "Granel", "Unidad"]),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let row = parse_row(&cells(&["Azúcar", "28.5", "Sí", "KG"]), &columns).unwrap();
+        assert_eq!(
+            row,
+            ImportRow {
+                name: Some("Azúcar".to_string()),
+                price: Some(28.5),
+                is_bulk: Some(true),
+                unit: Some("kg".to_string()),
+                ..ImportRow::default()
+            }
+        );
+        assert_eq!(
+            parse_row(&cells(&["Azúcar", "caro", "", ""]), &columns),
+            Err("Precio inválido: caro".to_string())
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Kits are priced like any product but hold no stock of their own: availability
+//! comes from the components, and sales deduct and restock the components.
+
+use crate::models::product_kit::{KitComponent, KitComponentInput, SetKitComponentsRequest};
+use crate::modules::catalog::products::application::{get_product, ProductRepository};
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
+
+pub fn get_kit_components(
+    repository: &impl ProductRepository,
+    kit_id: i64,
+) -> AppResult<Vec<KitComponent>> {
+    get_product(repository, kit_id)?;
+    repository.find_kit_components(kit_id)
+}
+
+/// Components must be regular products sold by piece or by weight; kits are not
+/// nested, so a component cannot be a kit and a kit cannot be a component.
+pub fn set_kit_components(
+    repository: &impl ProductRepository,
+    request: SetKitComponentsRequest,
+) -> AppResult<Vec<KitComponent>> {
+    let kit = get_product(repository, request.kit_id)?;
+    if !request.components.is_empty() {
+        if kit.variant_count > 0 {
+            return Err(AppError::Validation(format!(
+                "'{}' tiene variantes; arma el kit sobre una variante",
+                kit.name
+            )));
+        }
+        if kit.is_bulk {
+            return Err(AppError::Validation(format!(
+                "'{}' es a granel; un kit se vende por pieza",
+                kit.name
+            )));
+        }
+        if repository.is_kit_component(kit.id)? {
+            return Err(AppError::Conflict(format!(
+                "'{}' es componente de otro kit",
+                kit.name
+            )));
+        }
+    }
+
+    let mut components: Vec<KitComponentInput> = Vec::with_capacity(request.components.len());
+    for input in request.components {
+        if input.component_id == kit.id {
+            return Err(AppError::Validation(
+                "Un kit no puede contenerse a sí mismo".to_string(),
+            ));
+        }
+        if components
+            .iter()
+            .any(|existing| existing.component_id == input.component_id)
+        {
+            return Err(AppError::Validation(
+                "Un componente está repetido en el kit".to_string(),
+            ));
+        }
+        let component = repository.find_by_id(input.component_id)?.ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Producto con ID {} no encontrado",
+                input.component_id
+            ))
+        })?;
+        if component.is_kit {
+            return Err(AppError::Validation(format!(
+                "'{}' es un kit; los kits no se anidan",
+                component.name
+            )));
+        }
+        if component.variant_count > 0 {
+            return Err(AppError::Validation(format!(
+                "'{}' tiene variantes; elige la variante que lleva el kit",
+                component.name
+            )));
+        }
+        let quantity = money::round3(input.quantity);
+        if !quantity.is_finite() || quantity <= 0.0 {
+            return Err(AppError::Validation(format!(
+                "La cantidad de '{}' debe ser mayor que cero",
+                component.name
+            )));
+        }
+        if !component.is_bulk && quantity.fract() != 0.0 {
+            return Err(AppError::Validation(format!(
+                "El producto '{}' solo acepta cantidades enteras",
+                component.name
+            )));
+        }
+        components.push(KitComponentInput {
+            component_id: component.id,
+            quantity,
+        });
+    }
+
+    repository.replace_kit_components(kit.id, &components)?;
+    repository.find_kit_components(kit.id)
+}
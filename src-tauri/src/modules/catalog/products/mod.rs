@@ -1,2 +1,6 @@
 pub mod adapters;
 pub mod application;
+pub mod catalog_file;
+pub mod kits;
+pub mod repricing;
+pub mod scale_barcode;
@@ -0,0 +1,246 @@
+//! Scheduled price changes and bulk repricing. Both go through
+//! `ProductRepository::update`/`update_all`, so every applied price lands in the
+//! price history.
+
+use crate::models::price_change::{
+    BulkRepriceRequest, RepriceLine, SchedulePriceChangesRequest, ScheduledPriceChange,
+    ScheduledPriceChangeStatus,
+};
+use crate::models::product::UpdateProductRequest;
+use crate::modules::catalog::products::application::{
+    check_price_against_cost, get_product, ProductRepository,
+};
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
+
+pub fn schedule_price_changes(
+    repository: &impl ProductRepository,
+    request: SchedulePriceChangesRequest,
+) -> AppResult<Vec<ScheduledPriceChange>> {
+    if request.changes.is_empty() {
+        return Err(AppError::Validation(
+            "Agrega al menos un producto al cambio de precio".to_string(),
+        ));
+    }
+    let effective_at = normalize_effective_at(&request.effective_at).ok_or_else(|| {
+        AppError::Validation("La fecha de aplicación debe ser AAAA-MM-DD HH:MM".to_string())
+    })?;
+    let policy = repository.below_cost_policy()?;
+    for change in &request.changes {
+        if !change.new_price.is_finite() || change.new_price < 0.0 {
+            return Err(AppError::Validation(
+                "El precio no puede ser negativo".to_string(),
+            ));
+        }
+        let product = get_product(repository, change.product_id)?;
+        check_price_against_cost(
+            change.new_price,
+            product.cost,
+            policy,
+            request.allow_below_cost,
+        )?;
+    }
+    repository.create_scheduled_price_changes(&request.changes, &effective_at, request.user_id)
+}
+
+pub fn get_scheduled_price_changes(
+    repository: &impl ProductRepository,
+    status: Option<ScheduledPriceChangeStatus>,
+) -> AppResult<Vec<ScheduledPriceChange>> {
+    repository.find_scheduled_price_changes(status)
+}
+
+pub fn cancel_scheduled_price_change(
+    repository: &impl ProductRepository,
+    id: i64,
+) -> AppResult<()> {
+    if !repository.set_scheduled_price_change_status(id, ScheduledPriceChangeStatus::Cancelled)? {
+        return Err(AppError::Conflict(
+            "El cambio de precio no existe o ya no está pendiente".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Outcome of a pass over the due scheduled price changes.
+#[derive(Debug, Default)]
+pub struct DuePriceChanges {
+    pub applied: Vec<ScheduledPriceChange>,
+    /// Changes that could not be applied, with the reason; they are cancelled.
+    pub failed: Vec<(ScheduledPriceChange, AppError)>,
+}
+
+/// Applies the pending changes whose time has come, oldest first, so the latest
+/// one for a product wins. Run at startup and periodically. A change that fails
+/// is cancelled so it does not hold back the ones after it.
+pub fn apply_due_price_changes(repository: &impl ProductRepository) -> AppResult<DuePriceChanges> {
+    let mut outcome = DuePriceChanges::default();
+    for change in repository.find_due_price_changes()? {
+        let update = repository.update(&price_update(
+            change.product_id,
+            change.new_price,
+            change.created_by,
+        ));
+        let status = match update {
+            Ok(_) => ScheduledPriceChangeStatus::Applied,
+            Err(_) => ScheduledPriceChangeStatus::Cancelled,
+        };
+        if !repository.set_scheduled_price_change_status(change.id, status)? {
+            continue;
+        }
+        match update {
+            Ok(_) => outcome.applied.push(change),
+            Err(error) => outcome.failed.push((change, error)),
+        }
+    }
+    Ok(outcome)
+}
+
+pub fn preview_bulk_reprice(
+    repository: &impl ProductRepository,
+    request: &BulkRepriceRequest,
+) -> AppResult<Vec<RepriceLine>> {
+    if !request.percent.is_finite() || request.percent <= -100.0 {
+        return Err(AppError::Validation(
+            "El porcentaje debe ser mayor a -100".to_string(),
+        ));
+    }
+    if request
+        .round_to
+        .is_some_and(|step| !step.is_finite() || step < 0.0)
+    {
+        return Err(AppError::Validation(
+            "El redondeo no puede ser negativo".to_string(),
+        ));
+    }
+    Ok(repository
+        .find_in_category_tree(request.category_id)?
+        .into_iter()
+        .filter(|product| product.active)
+        .map(|product| {
+            let new_price = reprice(product.price, request.percent, request.round_to);
+            RepriceLine {
+                product_id: product.id,
+                product_name: product.name,
+                current_price: product.price,
+                new_price,
+                cost: product.cost,
+                below_cost: new_price < product.cost,
+            }
+        })
+        .collect())
+}
+
+/// Applies exactly what `preview_bulk_reprice` shows; unchanged prices are skipped.
+pub fn apply_bulk_reprice(
+    repository: &impl ProductRepository,
+    request: BulkRepriceRequest,
+) -> AppResult<Vec<RepriceLine>> {
+    let lines: Vec<RepriceLine> = preview_bulk_reprice(repository, &request)?
+        .into_iter()
+        .filter(|line| line.new_price != line.current_price)
+        .collect();
+    let policy = repository.below_cost_policy()?;
+    for line in &lines {
+        check_price_against_cost(line.new_price, line.cost, policy, request.allow_below_cost)
+            .map_err(|error| match error {
+                AppError::Validation(message) => {
+                    AppError::Validation(format!("{}: {message}", line.product_name))
+                }
+                AppError::Conflict(message) => {
+                    AppError::Conflict(format!("{}: {message}", line.product_name))
+                }
+                other => other,
+            })?;
+    }
+    let updates: Vec<UpdateProductRequest> = lines
+        .iter()
+        .map(|line| price_update(line.product_id, line.new_price, Some(request.user_id)))
+        .collect();
+    repository.update_all(&updates)?;
+    Ok(lines)
+}
+
+/// Raises `price` by `percent` and rounds to the nearest multiple of `round_to`.
+pub fn reprice(price: f64, percent: f64, round_to: Option<f64>) -> f64 {
+    let raised = price * (1.0 + percent / 100.0);
+    match round_to.filter(|step| *step > 0.0) {
+        Some(step) => money::round2((raised / step).round() * step),
+        None => money::round2(raised),
+    }
+}
+
+/// Accepts `YYYY-MM-DD HH:MM[:SS]` (or with a `T`) and returns the SQLite form.
+pub fn normalize_effective_at(value: &str) -> Option<String> {
+    let value = value.trim().replacen('T', " ", 1);
+    let (date, time) = value.split_once(' ')?;
+    let date: Vec<&str> = date.split('-').collect();
+    let time: Vec<&str> = time.split(':').collect();
+    let [year, month, day] = date.as_slice() else {
+        return None;
+    };
+    let (hour, minute, second) = match time.as_slice() {
+        [hour, minute] => (*hour, *minute, "00"),
+        [hour, minute, second] => (*hour, *minute, *second),
+        _ => return None,
+    };
+    let field = |text: &str, len: usize, max: u32| -> Option<u32> {
+        if text.len() != len || !text.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        text.parse::<u32>().ok().filter(|number| *number <= max)
+    };
+    field(year, 4, 9999)?;
+    field(month, 2, 12).filter(|month| *month >= 1)?;
+    field(day, 2, 31).filter(|day| *day >= 1)?;
+    field(hour, 2, 23)?;
+    field(minute, 2, 59)?;
+    field(second, 2, 59)?;
+    Some(format!("{year}-{month}-{day} {hour}:{minute}:{second}"))
+}
+
+fn price_update(product_id: i64, price: f64, user_id: Option<i64>) -> UpdateProductRequest {
+    UpdateProductRequest {
+        id: product_id,
+        name: None,
+        description: None,
+        barcode: None,
+        price: Some(price),
+        cost: None,
+        unit: None,
+        is_bulk: None,
+        category_id: None,
+        min_stock: None,
+        active: None,
+        user_id,
+        allow_below_cost: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_effective_at, reprice};
+
+    #[test]
+    fn raises_prices_and_rounds_to_the_step() {
+        assert_eq!(reprice(20.0, 5.0, None), 21.0);
+        assert_eq!(reprice(19.90, 5.0, Some(0.5)), 21.0);
+        assert_eq!(reprice(33.0, 5.0, Some(0.5)), 34.5);
+        assert_eq!(reprice(40.0, -10.0, Some(1.0)), 36.0);
+    }
+
+    #[test]
+    fn normalizes_effective_dates() {
+        assert_eq!(
+            normalize_effective_at("2026-11-01 06:00").as_deref(),
+            Some("2026-11-01 06:00:00")
+        );
+        assert_eq!(
+            normalize_effective_at("2026-11-01T06:00:30").as_deref(),
+            Some("2026-11-01 06:00:30")
+        );
+        assert!(normalize_effective_at("2026-13-01 06:00").is_none());
+        assert!(normalize_effective_at("2026-11-01").is_none());
+        assert!(normalize_effective_at("1/11/2026 06:00").is_none());
+    }
+}
@@ -0,0 +1,151 @@
+//! Weight/price-embedded EAN-13 labels printed by deli scales.
+//!
+//! A label is `prefix (2) + PLU + value + check digit`; the PLU and value
+//! share the 10 digits between the prefix and the check digit.
+
+use crate::models::sale::SaleInputMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleValueKind {
+    /// Quantity in the product's base unit.
+    Weight,
+    /// Amount in pesos.
+    Price,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaleBarcodeFormat {
+    pub prefix: String,
+    pub plu_digits: usize,
+    pub kind: ScaleValueKind,
+    pub decimals: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleReading {
+    pub plu: String,
+    pub input_mode: SaleInputMode,
+    pub input_value: f64,
+}
+
+/// Parses the `scale_barcode_formats` setting: comma-separated
+/// `prefix:plu_digits:weight|price:decimals` entries. Malformed entries are skipped.
+pub fn parse_formats(value: &str) -> Vec<ScaleBarcodeFormat> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let parts: Vec<&str> = entry.trim().split(':').map(str::trim).collect();
+            let [prefix, plu_digits, kind, decimals] = parts.as_slice() else {
+                return None;
+            };
+            if prefix.len() != 2 || !prefix.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let plu_digits = plu_digits
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..10).contains(n))?;
+            let kind = match *kind {
+                "weight" => ScaleValueKind::Weight,
+                "price" => ScaleValueKind::Price,
+                _ => return None,
+            };
+            let decimals = decimals.parse::<u32>().ok().filter(|n| *n <= 3)?;
+            Some(ScaleBarcodeFormat {
+                prefix: prefix.to_string(),
+                plu_digits,
+                kind,
+                decimals,
+            })
+        })
+        .collect()
+}
+
+/// Check digit for the first 12 digits of an EAN-13.
+pub fn ean13_check_digit(digits: &str) -> Option<u32> {
+    if digits.len() != 12 {
+        return None;
+    }
+    let mut sum = 0;
+    for (index, c) in digits.chars().enumerate() {
+        let digit = c.to_digit(10)?;
+        sum += if index % 2 == 0 { digit } else { digit * 3 };
+    }
+    Some((10 - sum % 10) % 10)
+}
+
+pub fn is_valid_ean13(barcode: &str) -> bool {
+    barcode.len() == 13
+        && barcode.is_ascii()
+        && ean13_check_digit(&barcode[..12])
+            .is_some_and(|check| barcode[12..].parse::<u32>() == Ok(check))
+}
+
+/// Decodes a scale label with the first format whose prefix matches.
+pub fn decode(barcode: &str, formats: &[ScaleBarcodeFormat]) -> Option<ScaleReading> {
+    if !is_valid_ean13(barcode) {
+        return None;
+    }
+    let format = formats
+        .iter()
+        .find(|format| barcode.starts_with(&format.prefix))?;
+    let plu = &barcode[2..2 + format.plu_digits];
+    let raw_value: u64 = barcode[2 + format.plu_digits..12].parse().ok()?;
+    let input_value = raw_value as f64 / 10f64.powi(format.decimals as i32);
+    Some(ScaleReading {
+        plu: plu.to_string(),
+        input_mode: match format.kind {
+            ScaleValueKind::Weight => SaleInputMode::Base,
+            ScaleValueKind::Price => SaleInputMode::Amount,
+        },
+        input_value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, ean13_check_digit, is_valid_ean13, parse_formats, ScaleValueKind};
+    use crate::models::sale::SaleInputMode;
+
+    #[test]
+    fn computes_ean13_check_digits() {
+        assert_eq!(ean13_check_digit("750100000001"), Some(2));
+        assert_eq!(ean13_check_digit("400638133393"), Some(1));
+        assert_eq!(ean13_check_digit("200123401250"), Some(8));
+        assert_eq!(ean13_check_digit("75010000000"), None);
+        assert_eq!(ean13_check_digit("75010000000A"), None);
+    }
+
+    #[test]
+    fn rejects_labels_with_a_wrong_check_digit() {
+        assert!(is_valid_ean13("4006381333931"));
+        assert!(!is_valid_ean13("4006381333932"));
+        assert!(!is_valid_ean13("400638133393"));
+    }
+
+    #[test]
+    fn parses_configured_formats_and_skips_malformed_ones() {
+        let formats = parse_formats("20:5:weight:3, 22:5:price:2, 2:5:weight:3, 23:x:price:2");
+        assert_eq!(formats.len(), 2);
+        assert_eq!(formats[0].kind, ScaleValueKind::Weight);
+        assert_eq!(formats[1].prefix, "22");
+    }
+
+    #[test]
+    fn decodes_weight_and_price_labels() {
+        let formats = parse_formats("20:5:weight:3,22:5:price:2");
+
+        let weight = decode("2001234012508", &formats).unwrap();
+        assert_eq!(weight.plu, "01234");
+        assert_eq!(weight.input_mode, SaleInputMode::Base);
+        assert_eq!(weight.input_value, 1.25);
+
+        let price = decode("2200042045504", &formats).unwrap();
+        assert_eq!(price.plu, "00042");
+        assert_eq!(price.input_mode, SaleInputMode::Amount);
+        assert_eq!(price.input_value, 45.5);
+
+        assert!(decode("2101234012505", &formats).is_none());
+        assert!(decode("2001234012507", &formats).is_none());
+    }
+}
@@ -0,0 +1,27 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::unit::{CreateUnitRequest, Unit, UpdateUnitRequest};
+use crate::modules::catalog::units::{
+    adapters::outbound::sqlite::SqliteUnitRepository, application,
+};
+use crate::shared::error::AppResult;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_units(db: State<Database>) -> AppResult<Vec<Unit>> {
+    application::get_units(&SqliteUnitRepository::new(&db))
+}
+
+#[tauri::command]
+pub fn create_unit(db: State<Database>, request: CreateUnitRequest) -> AppResult<Unit> {
+    application::create_unit(&SqliteUnitRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn update_unit(db: State<Database>, request: UpdateUnitRequest) -> AppResult<Unit> {
+    application::update_unit(&SqliteUnitRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn delete_unit(db: State<Database>, id: i64) -> AppResult<()> {
+    application::delete_unit(&SqliteUnitRepository::new(&db), id)
+}
@@ -0,0 +1,249 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::unit::Unit;
+use crate::modules::catalog::units::application::UnitRepository;
+use crate::shared::error::{AppError, AppResult};
+use rusqlite::{params, OptionalExtension};
+
+pub struct SqliteUnitRepository<'db> {
+    db: &'db Database,
+}
+
+impl<'db> SqliteUnitRepository<'db> {
+    pub fn new(db: &'db Database) -> Self {
+        Self { db }
+    }
+}
+
+impl UnitRepository for SqliteUnitRepository<'_> {
+    fn find_all(&self) -> AppResult<Vec<Unit>> {
+        find_all(self.db)
+    }
+
+    fn find_by_id(&self, id: i64) -> AppResult<Option<Unit>> {
+        find_by_id(self.db, id)
+    }
+
+    fn find_by_name(&self, name: &str) -> AppResult<Option<Unit>> {
+        find_by_name(self.db, name)
+    }
+
+    fn create(
+        &self,
+        name: &str,
+        base_unit_id: Option<i64>,
+        factor: f64,
+        fractional: bool,
+    ) -> AppResult<Unit> {
+        create(self.db, name, base_unit_id, factor, fractional)
+    }
+
+    fn update(&self, id: i64, factor: Option<f64>, fractional: Option<bool>) -> AppResult<Unit> {
+        update(self.db, id, factor, fractional)
+    }
+
+    fn delete(&self, id: i64) -> AppResult<()> {
+        self.db
+            .conn
+            .lock()?
+            .execute("DELETE FROM units WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn count_products(&self, name: &str) -> AppResult<(i64, i64)> {
+        Ok(self.db.conn.lock()?.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(is_bulk), 0) FROM products WHERE unit = ?1",
+            params![name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?)
+    }
+
+    fn count_conversions(&self, base_unit_id: i64) -> AppResult<i64> {
+        Ok(self.db.conn.lock()?.query_row(
+            "SELECT COUNT(*) FROM units WHERE base_unit_id = ?1",
+            params![base_unit_id],
+            |row| row.get(0),
+        )?)
+    }
+}
+
+const SELECT_QUERY: &str = "\
+    SELECT u.id, u.name, u.base_unit_id, b.name, u.factor, u.fractional, u.created_at \
+    FROM units u LEFT JOIN units b ON u.base_unit_id = b.id";
+
+fn row_to_unit(row: &rusqlite::Row) -> rusqlite::Result<Unit> {
+    Ok(Unit {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        base_unit_id: row.get(2)?,
+        base_unit: row.get(3)?,
+        factor: row.get(4)?,
+        fractional: row.get::<_, i64>(5)? != 0,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Base units first, each followed by its conversions.
+pub fn find_all(db: &Database) -> AppResult<Vec<Unit>> {
+    let conn = db.conn.lock()?;
+    let mut stmt = conn.prepare(&format!(
+        "{SELECT_QUERY} ORDER BY COALESCE(b.name, u.name), u.base_unit_id IS NOT NULL, u.factor"
+    ))?;
+
+    let units = stmt
+        .query_map([], row_to_unit)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(units)
+}
+
+pub fn find_by_id(db: &Database, id: i64) -> AppResult<Option<Unit>> {
+    let conn = db.conn.lock()?;
+    conn.query_row(
+        &format!("{SELECT_QUERY} WHERE u.id = ?1"),
+        params![id],
+        row_to_unit,
+    )
+    .optional()
+    .map_err(Into::into)
+}
+
+pub fn find_by_name(db: &Database, name: &str) -> AppResult<Option<Unit>> {
+    let conn = db.conn.lock()?;
+    conn.query_row(
+        &format!("{SELECT_QUERY} WHERE u.name = ?1"),
+        params![name],
+        row_to_unit,
+    )
+    .optional()
+    .map_err(Into::into)
+}
+
+pub fn create(
+    db: &Database,
+    name: &str,
+    base_unit_id: Option<i64>,
+    factor: f64,
+    fractional: bool,
+) -> AppResult<Unit> {
+    let conn = db.conn.lock()?;
+    conn.execute(
+        "INSERT INTO units (name, base_unit_id, factor, fractional) VALUES (?1, ?2, ?3, ?4)",
+        params![name, base_unit_id, factor, fractional],
+    )?;
+
+    let id = conn.last_insert_rowid();
+    drop(conn);
+    find_by_id(db, id)?
+        .ok_or_else(|| AppError::NotFound("Failed to retrieve created unit".to_string()))
+}
+
+pub fn update(
+    db: &Database,
+    id: i64,
+    factor: Option<f64>,
+    fractional: Option<bool>,
+) -> AppResult<Unit> {
+    let conn = db.conn.lock()?;
+
+    if let Some(val) = factor {
+        conn.execute(
+            "UPDATE units SET factor = ?1 WHERE id = ?2",
+            params![val, id],
+        )?;
+    }
+    if let Some(val) = fractional {
+        conn.execute(
+            "UPDATE units SET fractional = ?1 WHERE id = ?2",
+            params![val, id],
+        )?;
+    }
+
+    drop(conn);
+    find_by_id(db, id)?.ok_or_else(|| AppError::NotFound("Unidad no encontrada".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_all, find_by_name, SqliteUnitRepository};
+    use crate::infrastructure::sqlite::Database;
+    use crate::models::unit::{CreateUnitRequest, UpdateUnitRequest};
+    use crate::modules::catalog::units::application::{create_unit, delete_unit, update_unit};
+    use crate::shared::error::AppError;
+    use rusqlite::Connection;
+    use std::sync::Mutex;
+
+    fn test_database() -> Database {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE products (
+                id INTEGER PRIMARY KEY,
+                unit TEXT NOT NULL,
+                is_bulk INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE inventory_adjustments (id INTEGER PRIMARY KEY);
+            INSERT INTO products (id, unit, is_bulk) VALUES (1, 'kg', 1), (2, ' bulto ', 0);",
+        )
+        .unwrap();
+        conn.execute_batch(include_str!(
+            "../../../../../infrastructure/sqlite/migrations/0028_units.sql"
+        ))
+        .unwrap();
+        Database {
+            conn: Mutex::new(conn),
+        }
+    }
+
+    #[test]
+    fn seeds_the_former_units_and_manages_conversions() {
+        let db = test_database();
+        let repository = SqliteUnitRepository::new(&db);
+        let names: Vec<String> = find_all(&db)
+            .unwrap()
+            .into_iter()
+            .map(|unit| unit.name)
+            .collect();
+        assert_eq!(
+            names,
+            ["bulto", "kg", "g", "litro", "ml", "metro", "cm", "pieza"]
+        );
+        let grams = find_by_name(&db, "g").unwrap().unwrap();
+        assert_eq!(grams.base_unit.as_deref(), Some("kg"));
+        assert_eq!(grams.factor, 0.001);
+
+        let piece = find_by_name(&db, "pieza").unwrap().unwrap();
+        let request =
+            |name: &str, base_unit_id: Option<i64>, factor: Option<f64>| CreateUnitRequest {
+                name: name.to_string(),
+                base_unit_id,
+                factor,
+                fractional: false,
+            };
+        let case = create_unit(&repository, request(" caja ", Some(piece.id), Some(12.0))).unwrap();
+        assert_eq!(case.name, "caja");
+        assert_eq!(case.base_unit.as_deref(), Some("pieza"));
+        assert!(create_unit(&repository, request("paquete", Some(piece.id), None)).is_err());
+        assert!(create_unit(&repository, request("tarima", Some(case.id), Some(40.0))).is_err());
+        assert!(matches!(
+            create_unit(&repository, request("caja", None, None)),
+            Err(AppError::Conflict(_))
+        ));
+
+        let kilo = find_by_name(&db, "kg").unwrap().unwrap();
+        let unfractional = UpdateUnitRequest {
+            id: kilo.id,
+            factor: None,
+            fractional: Some(false),
+        };
+        assert!(matches!(
+            update_unit(&repository, unfractional),
+            Err(AppError::Conflict(_))
+        ));
+        assert!(matches!(
+            delete_unit(&repository, piece.id),
+            Err(AppError::Conflict(_))
+        ));
+        delete_unit(&repository, case.id).unwrap();
+        delete_unit(&repository, piece.id).unwrap();
+        assert!(find_by_name(&db, "pieza").unwrap().is_none());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::models::unit::{CreateUnitRequest, Unit, UpdateUnitRequest};
+use crate::shared::error::{AppError, AppResult};
+
+pub trait UnitRepository {
+    fn find_all(&self) -> AppResult<Vec<Unit>>;
+    fn find_by_id(&self, id: i64) -> AppResult<Option<Unit>>;
+    fn find_by_name(&self, name: &str) -> AppResult<Option<Unit>>;
+    fn create(
+        &self,
+        name: &str,
+        base_unit_id: Option<i64>,
+        factor: f64,
+        fractional: bool,
+    ) -> AppResult<Unit>;
+    fn update(&self, id: i64, factor: Option<f64>, fractional: Option<bool>) -> AppResult<Unit>;
+    fn delete(&self, id: i64) -> AppResult<()>;
+    /// Products using the unit as their base unit, and how many of them are bulk.
+    fn count_products(&self, name: &str) -> AppResult<(i64, i64)>;
+    fn count_conversions(&self, base_unit_id: i64) -> AppResult<i64>;
+}
+
+pub fn get_units(repository: &impl UnitRepository) -> AppResult<Vec<Unit>> {
+    repository.find_all()
+}
+
+pub fn get_unit(repository: &impl UnitRepository, id: i64) -> AppResult<Unit> {
+    repository
+        .find_by_id(id)?
+        .ok_or_else(|| AppError::NotFound("Unidad no encontrada".to_string()))
+}
+
+/// Conversions hang from a base unit directly; they cannot be chained.
+pub fn create_unit(
+    repository: &impl UnitRepository,
+    request: CreateUnitRequest,
+) -> AppResult<Unit> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation(
+            "El nombre de la unidad no puede estar vacío".to_string(),
+        ));
+    }
+    if repository.find_by_name(name)?.is_some() {
+        return Err(AppError::Conflict(format!("La unidad {name} ya existe")));
+    }
+
+    let Some(base_unit_id) = request.base_unit_id else {
+        return repository.create(name, None, 1.0, request.fractional);
+    };
+    let base = get_unit(repository, base_unit_id)?;
+    if base.base_unit_id.is_some() {
+        return Err(AppError::Validation(format!(
+            "{} ya es una conversión; elige una unidad base",
+            base.name
+        )));
+    }
+    let factor = request.factor.ok_or_else(|| {
+        AppError::Validation(format!("Indica a cuántos {} equivale {name}", base.name))
+    })?;
+    validate_factor(factor)?;
+    repository.create(name, Some(base.id), factor, false)
+}
+
+pub fn update_unit(
+    repository: &impl UnitRepository,
+    request: UpdateUnitRequest,
+) -> AppResult<Unit> {
+    let unit = get_unit(repository, request.id)?;
+    if let Some(factor) = request.factor {
+        if unit.base_unit_id.is_none() {
+            return Err(AppError::Validation(
+                "Una unidad base no tiene factor de conversión".to_string(),
+            ));
+        }
+        validate_factor(factor)?;
+    }
+    match request.fractional {
+        Some(true) if unit.base_unit_id.is_some() => {
+            return Err(AppError::Validation(
+                "Solo las unidades base pueden ser fraccionables".to_string(),
+            ));
+        }
+        Some(false) if unit.fractional => {
+            let (_, bulk) = repository.count_products(&unit.name)?;
+            if bulk > 0 {
+                return Err(AppError::Conflict(format!(
+                    "{bulk} productos a granel usan {}",
+                    unit.name
+                )));
+            }
+        }
+        _ => {}
+    }
+    repository.update(unit.id, request.factor, request.fractional)
+}
+
+pub fn delete_unit(repository: &impl UnitRepository, id: i64) -> AppResult<()> {
+    let unit = get_unit(repository, id)?;
+    let (products, _) = repository.count_products(&unit.name)?;
+    if products > 0 {
+        return Err(AppError::Conflict(format!(
+            "{products} productos usan la unidad {}",
+            unit.name
+        )));
+    }
+    if repository.count_conversions(unit.id)? > 0 {
+        return Err(AppError::Conflict(format!(
+            "Otras unidades se convierten a {}; elimínalas primero",
+            unit.name
+        )));
+    }
+    repository.delete(unit.id)
+}
+
+/// Converts a quantity captured in `unit` into `base_unit`, or `None` when the
+/// unit is neither the base unit itself nor one of its conversions.
+pub fn to_base_quantity(unit: &Unit, base_unit: &str, value: f64) -> Option<f64> {
+    if unit.name == base_unit {
+        Some(value)
+    } else if unit.base_unit.as_deref() == Some(base_unit) {
+        Some(value * unit.factor)
+    } else {
+        None
+    }
+}
+
+fn validate_factor(factor: f64) -> AppResult<()> {
+    if !factor.is_finite() || factor <= 0.0 {
+        return Err(AppError::Validation(
+            "El factor de conversión debe ser mayor que cero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_base_quantity;
+    use crate::models::unit::Unit;
+
+    fn unit(name: &str, base_unit: Option<&str>, factor: f64) -> Unit {
+        Unit {
+            id: 1,
+            name: name.to_string(),
+            base_unit_id: base_unit.map(|_| 2),
+            base_unit: base_unit.map(str::to_string),
+            factor,
+            fractional: false,
+            created_at: "2026-01-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn converts_only_into_the_units_own_base() {
+        let grams = unit("g", Some("kg"), 0.001);
+        assert_eq!(to_base_quantity(&grams, "kg", 250.0), Some(0.25));
+        assert_eq!(to_base_quantity(&grams, "litro", 250.0), None);
+
+        let case = unit("caja", Some("pieza"), 12.0);
+        assert_eq!(to_base_quantity(&case, "pieza", 2.0), Some(24.0));
+
+        let kilo = unit("kg", None, 1.0);
+        assert_eq!(to_base_quantity(&kilo, "kg", 1.5), Some(1.5));
+        assert_eq!(to_base_quantity(&kilo, "g", 1.5), None);
+    }
+}
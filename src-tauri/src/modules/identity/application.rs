@@ -51,6 +51,17 @@ pub fn get_current_user(repository: &impl UserRepository, user_id: i64) -> AppRe
     get_user(repository, user_id)
 }
 
+/// Fails unless `user_id` belongs to an active administrator.
+pub fn require_admin(repository: &impl UserRepository, user_id: i64) -> AppResult<User> {
+    let user = get_user(repository, user_id)?;
+    if !user.active || user.role != UserRole::Admin {
+        return Err(AppError::Auth(
+            "Solo un administrador puede realizar esta operación".to_string(),
+        ));
+    }
+    Ok(user)
+}
+
 pub fn get_users(repository: &impl UserRepository) -> AppResult<Vec<User>> {
     repository.find_all()
 }
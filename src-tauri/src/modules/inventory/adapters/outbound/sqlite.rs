@@ -1,9 +1,10 @@
 use crate::infrastructure::sqlite::Database;
 use crate::models::inventory::{AdjustmentType, InventoryAdjustment};
+use crate::models::unit::Unit;
 use crate::modules::inventory::application::InventoryRepository;
 use crate::shared::error::{AppError, AppResult};
 use crate::shared::money;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 
 pub struct SqliteInventoryRepository<'db> {
     db: &'db Database,
@@ -38,6 +39,7 @@ impl InventoryRepository for SqliteInventoryRepository<'_> {
         adjustment_type: AdjustmentType,
         quantity: f64,
         reason: Option<&str>,
+        input: Option<(f64, &str)>,
     ) -> AppResult<InventoryAdjustment> {
         create(
             self.db,
@@ -46,14 +48,30 @@ impl InventoryRepository for SqliteInventoryRepository<'_> {
             adjustment_type,
             quantity,
             reason,
+            input,
         )
     }
+    fn find_product_unit(&self, product_id: i64) -> AppResult<Option<String>> {
+        Ok(self
+            .db
+            .conn
+            .lock()?
+            .query_row(
+                "SELECT unit FROM products WHERE id = ?1",
+                params![product_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+    fn find_unit(&self, name: &str) -> AppResult<Option<Unit>> {
+        crate::modules::catalog::units::adapters::outbound::sqlite::find_by_name(self.db, name)
+    }
 }
 
 const SELECT_QUERY: &str = "\
     SELECT ia.id, ia.product_id, p.name, ia.user_id, u.full_name, \
             ia.adjustment_type, ia.quantity, ia.previous_stock, ia.new_stock, \
-            ia.reason, ia.created_at \
+            ia.reason, ia.created_at, ia.input_value, ia.input_unit \
     FROM inventory_adjustments ia \
     JOIN products p ON ia.product_id = p.id \
     JOIN users u ON ia.user_id = u.id";
@@ -71,6 +89,8 @@ fn row_to_adjustment(row: &rusqlite::Row) -> rusqlite::Result<InventoryAdjustmen
         new_stock: row.get(8)?,
         reason: row.get(9)?,
         created_at: row.get(10)?,
+        input_value: row.get(11)?,
+        input_unit: row.get(12)?,
     })
 }
 
@@ -141,18 +161,25 @@ pub fn create(
     adjustment_type: AdjustmentType,
     quantity: f64,
     reason: Option<&str>,
+    input: Option<(f64, &str)>,
 ) -> AppResult<InventoryAdjustment> {
     let mut conn = db.conn.lock()?;
 
     // Read current stock before the transaction so the statement borrow does not
     // conflict with the mutable borrow required by conn.transaction().
-    let current_stock: f64 = conn
+    let (current_stock, is_kit): (f64, bool) = conn
         .query_row(
-            "SELECT stock FROM products WHERE id = ?1",
+            "SELECT stock, EXISTS(SELECT 1 FROM product_kit_components WHERE kit_id = ?1) \
+                FROM products WHERE id = ?1",
             params![product_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .map_err(|_| AppError::NotFound("Producto no encontrado".to_string()))?;
+    if is_kit {
+        return Err(AppError::Validation(
+            "Los kits no tienen stock propio; ajusta sus componentes".to_string(),
+        ));
+    }
 
     let quantity = money::round3(quantity);
     let new_stock = if adjustment_type.is_positive() {
@@ -170,9 +197,20 @@ pub fn create(
 
     tx.execute(
         "INSERT INTO inventory_adjustments \
-                (product_id, user_id, adjustment_type, quantity, previous_stock, new_stock, reason) \
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        params![product_id, user_id, adjustment_type, quantity, current_stock, new_stock, reason],
+                (product_id, user_id, adjustment_type, quantity, previous_stock, new_stock, reason, \
+                input_value, input_unit) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            product_id,
+            user_id,
+            adjustment_type,
+            quantity,
+            current_stock,
+            new_stock,
+            reason,
+            input.map(|(value, _)| value),
+            input.map(|(_, unit)| unit),
+        ],
     )?;
 
     let id = tx.last_insert_rowid();
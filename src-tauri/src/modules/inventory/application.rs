@@ -3,6 +3,8 @@ use crate::models::inventory::{
     AdjustmentType, CreateInventoryAdjustmentRequest, GetInventoryAdjustmentsByDateRangeRequest,
     InventoryAdjustment,
 };
+use crate::models::unit::Unit;
+use crate::modules::catalog::units::application::to_base_quantity;
 use crate::shared::error::{AppError, AppResult};
 use crate::shared::pagination::PaginatedResult;
 
@@ -16,6 +18,7 @@ pub trait InventoryRepository {
         page_size: i64,
     ) -> AppResult<(Vec<InventoryAdjustment>, i64)>;
     fn find_by_product(&self, product_id: i64) -> AppResult<Vec<InventoryAdjustment>>;
+    /// `input` is the captured value and unit when `quantity` was converted.
     fn create(
         &self,
         product_id: i64,
@@ -23,7 +26,10 @@ pub trait InventoryRepository {
         adjustment_type: AdjustmentType,
         quantity: f64,
         reason: Option<&str>,
+        input: Option<(f64, &str)>,
     ) -> AppResult<InventoryAdjustment>;
+    fn find_product_unit(&self, product_id: i64) -> AppResult<Option<String>>;
+    fn find_unit(&self, name: &str) -> AppResult<Option<Unit>>;
 }
 
 /// Boundary reserved for sales: its SQLite implementation will receive the
@@ -85,11 +91,44 @@ pub fn create_adjustment(
             "Tipo de ajuste inválido. Debe ser 'add', 'positive' o 'negative'".to_string(),
         )
     })?;
+    let unit = request.unit.as_deref().map(str::trim);
+    let Some(unit) = unit.filter(|unit| !unit.is_empty()) else {
+        return repository.create(
+            request.product_id,
+            request.user_id,
+            adjustment_type,
+            request.quantity,
+            request.reason.as_deref(),
+            None,
+        );
+    };
+    let product_unit = repository
+        .find_product_unit(request.product_id)?
+        .ok_or_else(|| AppError::NotFound("Producto no encontrado".to_string()))?;
+    if unit == product_unit {
+        return repository.create(
+            request.product_id,
+            request.user_id,
+            adjustment_type,
+            request.quantity,
+            request.reason.as_deref(),
+            None,
+        );
+    }
+    let quantity = repository
+        .find_unit(unit)?
+        .and_then(|configured| to_base_quantity(&configured, &product_unit, request.quantity))
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "La unidad {unit} no es compatible con {product_unit}"
+            ))
+        })?;
     repository.create(
         request.product_id,
         request.user_id,
         adjustment_type,
-        request.quantity,
+        quantity,
         request.reason.as_deref(),
+        Some((request.quantity, unit)),
     )
 }
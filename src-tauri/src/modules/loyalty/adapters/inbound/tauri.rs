@@ -0,0 +1,33 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::loyalty::{
+    CustomerPointsAccount, LoyaltyCategoryRule, SetLoyaltyCategoryRuleRequest,
+};
+use crate::modules::loyalty::{adapters::outbound::sqlite::SqliteLoyaltyRepository, application};
+use crate::shared::error::AppResult;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_customer_points(
+    db: State<Database>,
+    customer_id: i64,
+) -> AppResult<CustomerPointsAccount> {
+    application::get_customer_points(&SqliteLoyaltyRepository::new(&db), customer_id)
+}
+
+#[tauri::command]
+pub fn expire_loyalty_points(db: State<Database>) -> AppResult<i64> {
+    application::expire_loyalty_points(&SqliteLoyaltyRepository::new(&db))
+}
+
+#[tauri::command]
+pub fn get_loyalty_category_rules(db: State<Database>) -> AppResult<Vec<LoyaltyCategoryRule>> {
+    application::get_loyalty_category_rules(&SqliteLoyaltyRepository::new(&db))
+}
+
+#[tauri::command]
+pub fn set_loyalty_category_rule(
+    db: State<Database>,
+    request: SetLoyaltyCategoryRuleRequest,
+) -> AppResult<()> {
+    application::set_loyalty_category_rule(&SqliteLoyaltyRepository::new(&db), request)
+}
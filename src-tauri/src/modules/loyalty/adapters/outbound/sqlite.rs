@@ -0,0 +1,511 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::loyalty::{CustomerPointsMovement, LoyaltyCategoryRule, PointsMovementType};
+use crate::modules::loyalty::application::{LoyaltyProgram, LoyaltyRepository};
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
+use rusqlite::{params, Connection, OptionalExtension};
+
+pub struct SqliteLoyaltyRepository<'db> {
+    db: &'db Database,
+}
+
+impl<'db> SqliteLoyaltyRepository<'db> {
+    pub fn new(db: &'db Database) -> Self {
+        Self { db }
+    }
+}
+
+impl LoyaltyRepository for SqliteLoyaltyRepository<'_> {
+    fn load_program(&self) -> AppResult<LoyaltyProgram> {
+        let conn = self.db.conn.lock()?;
+        load_program(&conn)
+    }
+
+    fn customer_exists(&self, customer_id: i64) -> AppResult<bool> {
+        let conn = self.db.conn.lock()?;
+        Ok(conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM customers WHERE id = ?1)",
+            params![customer_id],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn find_movements(&self, customer_id: i64) -> AppResult<Vec<CustomerPointsMovement>> {
+        find_movements(self.db, customer_id)
+    }
+
+    fn expire_points(&self, customer_id: Option<i64>) -> AppResult<i64> {
+        let mut conn = self.db.conn.lock()?;
+        let tx = conn.transaction()?;
+        let expired = expire_points(&tx, customer_id)?;
+        tx.commit()?;
+        Ok(expired)
+    }
+
+    fn balance(&self, customer_id: i64) -> AppResult<f64> {
+        let conn = self.db.conn.lock()?;
+        balance(&conn, customer_id)
+    }
+
+    fn find_category_rules(&self) -> AppResult<Vec<LoyaltyCategoryRule>> {
+        find_category_rules(self.db)
+    }
+
+    fn set_category_rule(&self, category_id: i64, points_per_mxn: Option<f64>) -> AppResult<()> {
+        set_category_rule(self.db, category_id, points_per_mxn)
+    }
+}
+
+fn setting(conn: &Connection, key: &str) -> AppResult<Option<String>> {
+    Ok(conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+            row.get::<_, Option<String>>(0)
+        })
+        .optional()?
+        .flatten())
+}
+
+fn number_setting(conn: &Connection, key: &str) -> AppResult<f64> {
+    Ok(setting(conn, key)?
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value >= 0.0)
+        .unwrap_or(0.0))
+}
+
+pub fn load_program(conn: &Connection) -> AppResult<LoyaltyProgram> {
+    let enabled = setting(conn, "loyalty_enabled")?.as_deref() == Some("true");
+    let expiry_days = number_setting(conn, "loyalty_points_expiry_days")? as i64;
+    let category_rates = conn
+        .prepare("SELECT category_id, points_per_mxn FROM loyalty_category_rules")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    Ok(LoyaltyProgram {
+        enabled,
+        points_per_mxn: number_setting(conn, "loyalty_points_per_mxn")?,
+        point_value_mxn: number_setting(conn, "loyalty_point_value_mxn")?,
+        expiry_days: (expiry_days > 0).then_some(expiry_days),
+        category_rates,
+    })
+}
+
+pub fn balance(conn: &Connection, customer_id: i64) -> AppResult<f64> {
+    let balance: f64 = conn.query_row(
+        "SELECT COALESCE(SUM(points), 0) FROM customer_points_movements WHERE customer_id = ?1",
+        params![customer_id],
+        |row| row.get(0),
+    )?;
+    Ok(money::round2(balance))
+}
+
+/// Writes off earned points whose expiry date has passed.
+///
+/// Consumed points (redemptions and expirations) are matched against the
+/// points that expire soonest first, and a reversal is matched against the
+/// earn of the sale it undoes, so only the part of the expired points that was
+/// never used is removed. Returns the number of customers affected.
+pub fn expire_points(conn: &Connection, customer_id: Option<i64>) -> AppResult<i64> {
+    let now: String =
+        conn.query_row("SELECT datetime('now', 'localtime')", [], |row| row.get(0))?;
+    let movements: Vec<(i64, PointsEntry)> = conn
+        .prepare(
+            "SELECT customer_id, sale_id, movement_type, points, expires_at \
+            FROM customer_points_movements \
+            WHERE ?1 IS NULL OR customer_id = ?1 \
+            ORDER BY customer_id, id",
+        )?
+        .query_map(params![customer_id], |row| {
+            Ok((
+                row.get(0)?,
+                PointsEntry {
+                    sale_id: row.get(1)?,
+                    movement_type: row.get(2)?,
+                    points: row.get(3)?,
+                    expires_at: row.get(4)?,
+                },
+            ))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let overdue: Vec<(i64, f64)> = movements
+        .chunk_by(|a, b| a.0 == b.0)
+        .filter_map(|chunk| {
+            let entries: Vec<&PointsEntry> = chunk.iter().map(|(_, entry)| entry).collect();
+            let points = money::round2(unused_overdue_points(&entries, &now));
+            (points > 0.0).then_some((chunk[0].0, points))
+        })
+        .collect();
+
+    for (customer_id, points) in &overdue {
+        conn.execute(
+            "INSERT INTO customer_points_movements (customer_id, movement_type, points, notes) \
+                VALUES (?1, ?2, ?3, 'Puntos vencidos')",
+            params![
+                customer_id,
+                PointsMovementType::Expire,
+                -money::round2(*points)
+            ],
+        )?;
+    }
+    Ok(overdue.len() as i64)
+}
+
+struct PointsEntry {
+    sale_id: Option<i64>,
+    movement_type: PointsMovementType,
+    points: f64,
+    expires_at: Option<String>,
+}
+
+/// Points still held from a positive movement.
+struct PointsLot<'a> {
+    entry: &'a PointsEntry,
+    remaining: f64,
+}
+
+/// Replays a customer's movements in order and returns the unused points of
+/// lots whose expiry date is not after `now`.
+fn unused_overdue_points(movements: &[&PointsEntry], now: &str) -> f64 {
+    let mut lots: Vec<PointsLot> = Vec::new();
+    for movement in movements {
+        if movement.points > 0.0 {
+            lots.push(PointsLot {
+                entry: movement,
+                remaining: movement.points,
+            });
+            continue;
+        }
+        let mut pending = -movement.points;
+        if movement.movement_type == PointsMovementType::Reversal && movement.sale_id.is_some() {
+            if let Some(lot) = lots.iter_mut().find(|lot| {
+                lot.entry.movement_type == PointsMovementType::Earn
+                    && lot.entry.sale_id == movement.sale_id
+            }) {
+                let taken = pending.min(lot.remaining);
+                lot.remaining -= taken;
+                pending -= taken;
+            }
+        }
+        // Soonest expiry first; points that never expire go last. The sort is
+        // stable, so lots expiring together are used oldest first.
+        let mut order: Vec<usize> = (0..lots.len()).collect();
+        order.sort_by_key(|&index| {
+            let expires_at = lots[index].entry.expires_at.as_deref();
+            (expires_at.is_none(), expires_at)
+        });
+        for index in order {
+            if pending <= 0.0 {
+                break;
+            }
+            let lot = &mut lots[index];
+            let taken = pending.min(lot.remaining);
+            lot.remaining -= taken;
+            pending -= taken;
+        }
+    }
+    lots.iter()
+        .filter(|lot| {
+            lot.entry
+                .expires_at
+                .as_deref()
+                .is_some_and(|expires_at| expires_at <= now)
+        })
+        .map(|lot| lot.remaining)
+        .sum()
+}
+
+/// Points movement of a single sale, as computed by the sale draft.
+pub struct SalePoints {
+    pub sale_id: i64,
+    pub customer_id: i64,
+    pub user_id: i64,
+    pub redeemed: f64,
+    pub redeemed_mxn: f64,
+    pub earned: f64,
+    pub expiry_days: Option<i64>,
+}
+
+struct NewPointsMovement<'a> {
+    customer_id: i64,
+    sale_id: i64,
+    user_id: i64,
+    movement_type: PointsMovementType,
+    points: f64,
+    expiry_days: Option<i64>,
+    notes: Option<&'a str>,
+}
+
+/// Records the points redeemed and earned by a sale inside the sale's
+/// transaction. Fails when the customer does not have enough points.
+pub fn record_sale_points(conn: &Connection, sale: &SalePoints) -> AppResult<()> {
+    let SalePoints {
+        sale_id,
+        customer_id,
+        user_id,
+        redeemed,
+        redeemed_mxn,
+        earned,
+        expiry_days,
+    } = *sale;
+    if redeemed > 0.0 {
+        expire_points(conn, Some(customer_id))?;
+        let available = balance(conn, customer_id)?;
+        if available < redeemed {
+            return Err(AppError::Validation(format!(
+                "El cliente no tiene puntos suficientes. Disponibles: {available}"
+            )));
+        }
+        conn.execute(
+            "INSERT INTO customer_points_movements (customer_id, sale_id, user_id, movement_type, \
+                points, amount_mxn) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                customer_id,
+                sale_id,
+                user_id,
+                PointsMovementType::Redeem,
+                -redeemed,
+                money::round2(redeemed_mxn)
+            ],
+        )?;
+    }
+    if earned > 0.0 {
+        insert_with_expiry(
+            conn,
+            &NewPointsMovement {
+                customer_id,
+                sale_id,
+                user_id,
+                movement_type: PointsMovementType::Earn,
+                points: earned,
+                expiry_days,
+                notes: None,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Undoes every points movement of a sale: earned points are removed and
+/// redeemed points are returned with a fresh expiry date.
+pub fn reverse_sale_points(conn: &Connection, sale_id: i64, user_id: i64) -> AppResult<()> {
+    let movements: Vec<(i64, PointsMovementType, f64)> = conn
+        .prepare(
+            "SELECT customer_id, movement_type, points FROM customer_points_movements \
+                WHERE sale_id = ?1 AND movement_type IN (?2, ?3)",
+        )?
+        .query_map(
+            params![
+                sale_id,
+                PointsMovementType::Earn,
+                PointsMovementType::Redeem
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?
+        .collect::<Result<_, _>>()?;
+    if movements.is_empty() {
+        return Ok(());
+    }
+
+    let expiry_days = load_program(conn)?.expiry_days;
+    for (customer_id, movement_type, points) in movements {
+        let (note, expiry_days) = match movement_type {
+            PointsMovementType::Redeem => ("Devolución de puntos por cancelación", expiry_days),
+            _ => ("Reversa de puntos ganados por cancelación", None),
+        };
+        insert_with_expiry(
+            conn,
+            &NewPointsMovement {
+                customer_id,
+                sale_id,
+                user_id,
+                movement_type: PointsMovementType::Reversal,
+                points: -points,
+                expiry_days,
+                notes: Some(note),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_with_expiry(conn: &Connection, movement: &NewPointsMovement) -> AppResult<()> {
+    conn.execute(
+        "INSERT INTO customer_points_movements (customer_id, sale_id, user_id, movement_type, \
+            points, expires_at, notes) \
+            VALUES (?1, ?2, ?3, ?4, ?5, \
+                CASE WHEN ?6 IS NULL THEN NULL \
+                ELSE datetime('now', 'localtime', '+' || ?6 || ' days') END, ?7)",
+        params![
+            movement.customer_id,
+            movement.sale_id,
+            movement.user_id,
+            movement.movement_type,
+            money::round2(movement.points),
+            movement.expiry_days,
+            movement.notes
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn find_movements(db: &Database, customer_id: i64) -> AppResult<Vec<CustomerPointsMovement>> {
+    let conn = db.conn.lock()?;
+    let movements = conn
+        .prepare(
+            "SELECT m.id, m.customer_id, m.sale_id, m.user_id, u.full_name, m.movement_type, \
+                m.points, m.amount_mxn, m.expires_at, m.notes, m.created_at \
+            FROM customer_points_movements m LEFT JOIN users u ON u.id = m.user_id \
+            WHERE m.customer_id = ?1 ORDER BY m.id DESC",
+        )?
+        .query_map(params![customer_id], |row| {
+            Ok(CustomerPointsMovement {
+                id: row.get(0)?,
+                customer_id: row.get(1)?,
+                sale_id: row.get(2)?,
+                user_id: row.get(3)?,
+                user_name: row.get(4)?,
+                movement_type: row.get(5)?,
+                points: money::round2(row.get(6)?),
+                amount_mxn: money::round2(row.get(7)?),
+                expires_at: row.get(8)?,
+                notes: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(movements)
+}
+
+pub fn find_category_rules(db: &Database) -> AppResult<Vec<LoyaltyCategoryRule>> {
+    let conn = db.conn.lock()?;
+    let rules = conn
+        .prepare(
+            "SELECT r.category_id, c.name, r.points_per_mxn FROM loyalty_category_rules r \
+                JOIN categories c ON c.id = r.category_id ORDER BY c.name",
+        )?
+        .query_map([], |row| {
+            Ok(LoyaltyCategoryRule {
+                category_id: row.get(0)?,
+                category_name: row.get(1)?,
+                points_per_mxn: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rules)
+}
+
+pub fn set_category_rule(
+    db: &Database,
+    category_id: i64,
+    points_per_mxn: Option<f64>,
+) -> AppResult<()> {
+    let conn = db.conn.lock()?;
+    match points_per_mxn {
+        Some(rate) => {
+            conn.execute(
+                "INSERT INTO loyalty_category_rules (category_id, points_per_mxn) VALUES (?1, ?2) \
+                    ON CONFLICT(category_id) DO UPDATE SET points_per_mxn = excluded.points_per_mxn",
+                params![category_id, rate],
+            )?;
+        }
+        None => {
+            conn.execute(
+                "DELETE FROM loyalty_category_rules WHERE category_id = ?1",
+                params![category_id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{balance, expire_points, record_sale_points, reverse_sale_points, SalePoints};
+    use rusqlite::Connection;
+
+    fn test_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT);
+            CREATE TABLE loyalty_category_rules (category_id INTEGER PRIMARY KEY, points_per_mxn REAL NOT NULL);
+            CREATE TABLE customer_points_movements (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                customer_id INTEGER NOT NULL,
+                sale_id INTEGER,
+                user_id INTEGER,
+                movement_type TEXT NOT NULL,
+                points REAL NOT NULL,
+                amount_mxn REAL NOT NULL DEFAULT 0,
+                expires_at TEXT,
+                notes TEXT,
+                created_at TEXT DEFAULT (datetime('now', 'localtime'))
+            );
+            INSERT INTO settings (key, value) VALUES ('loyalty_points_expiry_days', '30');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn sale(sale_id: i64, redeemed: f64, earned: f64) -> SalePoints {
+        SalePoints {
+            sale_id,
+            customer_id: 5,
+            user_id: 1,
+            redeemed,
+            redeemed_mxn: redeemed / 10.0,
+            earned,
+            expiry_days: Some(30),
+        }
+    }
+
+    #[test]
+    fn rejects_redeeming_more_points_than_available() {
+        let conn = test_connection();
+        record_sale_points(&conn, &sale(1, 0.0, 40.0)).unwrap();
+        assert!(record_sale_points(&conn, &sale(2, 50.0, 0.0)).is_err());
+        record_sale_points(&conn, &sale(2, 25.0, 0.0)).unwrap();
+        assert_eq!(balance(&conn, 5).unwrap(), 15.0);
+    }
+
+    #[test]
+    fn cancellation_removes_earned_points_and_returns_redeemed_ones() {
+        let conn = test_connection();
+        record_sale_points(&conn, &sale(1, 0.0, 40.0)).unwrap();
+        record_sale_points(&conn, &sale(2, 30.0, 7.0)).unwrap();
+        assert_eq!(balance(&conn, 5).unwrap(), 17.0);
+
+        reverse_sale_points(&conn, 2, 1).unwrap();
+        assert_eq!(balance(&conn, 5).unwrap(), 40.0);
+    }
+
+    #[test]
+    fn expires_only_the_unused_part_of_overdue_points() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO customer_points_movements (customer_id, movement_type, points, expires_at)
+                VALUES (5, 'earn', 100, '2000-01-01 00:00:00'), (5, 'earn', 20, '2999-01-01 00:00:00');
+            INSERT INTO customer_points_movements (customer_id, movement_type, points)
+                VALUES (5, 'redeem', -60);",
+        )
+        .unwrap();
+
+        assert_eq!(expire_points(&conn, Some(5)).unwrap(), 1);
+        assert_eq!(balance(&conn, 5).unwrap(), 20.0);
+        assert_eq!(expire_points(&conn, None).unwrap(), 0);
+    }
+
+    #[test]
+    fn reversals_only_consume_the_earn_they_undo() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO customer_points_movements (customer_id, sale_id, movement_type, points, expires_at)
+                VALUES (5, NULL, 'earn', 100, '2000-01-01 00:00:00'),
+                    (5, 7, 'earn', 50, '2999-01-01 00:00:00');
+            INSERT INTO customer_points_movements (customer_id, sale_id, movement_type, points)
+                VALUES (5, 7, 'reversal', -50);",
+        )
+        .unwrap();
+
+        assert_eq!(expire_points(&conn, Some(5)).unwrap(), 1);
+        assert_eq!(balance(&conn, 5).unwrap(), 0.0);
+    }
+}
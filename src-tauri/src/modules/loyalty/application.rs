@@ -0,0 +1,145 @@
+use crate::models::loyalty::{
+    CustomerPointsAccount, CustomerPointsMovement, LoyaltyCategoryRule,
+    SetLoyaltyCategoryRuleRequest,
+};
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
+use std::collections::HashMap;
+
+/// Earn and redemption rules read from the `lealtad` settings group.
+#[derive(Debug, Clone, Default)]
+pub struct LoyaltyProgram {
+    pub enabled: bool,
+    pub points_per_mxn: f64,
+    pub point_value_mxn: f64,
+    /// Days until earned points expire; `None` keeps them forever.
+    pub expiry_days: Option<i64>,
+    pub category_rates: HashMap<i64, f64>,
+}
+
+impl LoyaltyProgram {
+    /// Points earned for a purchase given its `(category_id, subtotal)` lines.
+    ///
+    /// The part of the total paid with points does not earn new points, so
+    /// every line is scaled by the share of the sale paid with other tenders.
+    pub fn points_for_purchase(&self, lines: &[(Option<i64>, f64)], redeemed_mxn: f64) -> f64 {
+        if !self.enabled {
+            return 0.0;
+        }
+        let total = money::sum_money(lines.iter().map(|(_, subtotal)| *subtotal));
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let earning_share = (money::sub_money(total, redeemed_mxn) / total).clamp(0.0, 1.0);
+        let raw: f64 = lines
+            .iter()
+            .map(|(category_id, subtotal)| {
+                let rate = category_id
+                    .and_then(|id| self.category_rates.get(&id).copied())
+                    .unwrap_or(self.points_per_mxn);
+                subtotal * rate
+            })
+            .sum();
+        (raw * earning_share + 0.000_001).floor().max(0.0)
+    }
+
+    /// Monetary value of `points` when used as a tender.
+    pub fn redemption_value(&self, points: f64) -> f64 {
+        money::mul_money(points, self.point_value_mxn)
+    }
+}
+
+pub trait LoyaltyRepository {
+    fn load_program(&self) -> AppResult<LoyaltyProgram>;
+    fn customer_exists(&self, customer_id: i64) -> AppResult<bool>;
+    fn find_movements(&self, customer_id: i64) -> AppResult<Vec<CustomerPointsMovement>>;
+    fn expire_points(&self, customer_id: Option<i64>) -> AppResult<i64>;
+    fn balance(&self, customer_id: i64) -> AppResult<f64>;
+    fn find_category_rules(&self) -> AppResult<Vec<LoyaltyCategoryRule>>;
+    fn set_category_rule(&self, category_id: i64, points_per_mxn: Option<f64>) -> AppResult<()>;
+}
+
+pub fn get_customer_points(
+    repository: &impl LoyaltyRepository,
+    customer_id: i64,
+) -> AppResult<CustomerPointsAccount> {
+    if !repository.customer_exists(customer_id)? {
+        return Err(AppError::NotFound("Cliente no encontrado".to_string()));
+    }
+    repository.expire_points(Some(customer_id))?;
+    let program = repository.load_program()?;
+    let balance = repository.balance(customer_id)?;
+    Ok(CustomerPointsAccount {
+        customer_id,
+        balance,
+        balance_value_mxn: program.redemption_value(balance.max(0.0)),
+        movements: repository.find_movements(customer_id)?,
+    })
+}
+
+/// Writes off expired points for every customer and returns how many
+/// customers were affected.
+pub fn expire_loyalty_points(repository: &impl LoyaltyRepository) -> AppResult<i64> {
+    repository.expire_points(None)
+}
+
+pub fn get_loyalty_category_rules(
+    repository: &impl LoyaltyRepository,
+) -> AppResult<Vec<LoyaltyCategoryRule>> {
+    repository.find_category_rules()
+}
+
+pub fn set_loyalty_category_rule(
+    repository: &impl LoyaltyRepository,
+    request: SetLoyaltyCategoryRuleRequest,
+) -> AppResult<()> {
+    if request
+        .points_per_mxn
+        .is_some_and(|rate| !rate.is_finite() || rate < 0.0)
+    {
+        return Err(AppError::Validation(
+            "Los puntos por peso no pueden ser negativos".to_string(),
+        ));
+    }
+    repository.set_category_rule(request.category_id, request.points_per_mxn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoyaltyProgram;
+    use std::collections::HashMap;
+
+    fn program() -> LoyaltyProgram {
+        LoyaltyProgram {
+            enabled: true,
+            points_per_mxn: 0.1,
+            point_value_mxn: 0.5,
+            expiry_days: Some(365),
+            category_rates: HashMap::from([(4, 0.2)]),
+        }
+    }
+
+    #[test]
+    fn earns_whole_points_using_category_overrides() {
+        let points = program().points_for_purchase(&[(None, 105.0), (Some(4), 50.0)], 0.0);
+        assert_eq!(points, 20.0);
+    }
+
+    #[test]
+    fn does_not_earn_on_the_share_paid_with_points() {
+        let points = program().points_for_purchase(&[(None, 200.0)], 100.0);
+        assert_eq!(points, 10.0);
+    }
+
+    #[test]
+    fn disabled_program_never_earns() {
+        let mut program = program();
+        program.enabled = false;
+        assert_eq!(program.points_for_purchase(&[(None, 500.0)], 0.0), 0.0);
+    }
+
+    #[test]
+    fn values_redeemed_points_in_pesos() {
+        assert_eq!(program().redemption_value(37.0), 18.5);
+    }
+}
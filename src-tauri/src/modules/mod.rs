@@ -3,6 +3,10 @@ pub mod cash_register;
 pub mod catalog;
 pub mod identity;
 pub mod inventory;
+pub mod loyalty;
+pub mod pricing;
 pub mod printing;
+pub mod registers;
 pub mod sales;
 pub mod settings;
+pub mod stored_value;
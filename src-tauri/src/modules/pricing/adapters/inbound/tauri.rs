@@ -0,0 +1,46 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::price_list::{
+    AssignCustomerPriceListRequest, CreatePriceListRequest, PriceList, UpdatePriceListRequest,
+};
+use crate::modules::pricing::{adapters::outbound::sqlite::SqlitePriceListRepository, application};
+use crate::shared::error::AppResult;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_price_lists(db: State<Database>) -> AppResult<Vec<PriceList>> {
+    application::get_price_lists(&SqlitePriceListRepository::new(&db))
+}
+
+#[tauri::command]
+pub fn get_price_list(db: State<Database>, id: i64) -> AppResult<PriceList> {
+    application::get_price_list(&SqlitePriceListRepository::new(&db), id)
+}
+
+#[tauri::command]
+pub fn create_price_list(
+    db: State<Database>,
+    request: CreatePriceListRequest,
+) -> AppResult<PriceList> {
+    application::create_price_list(&SqlitePriceListRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn update_price_list(
+    db: State<Database>,
+    request: UpdatePriceListRequest,
+) -> AppResult<PriceList> {
+    application::update_price_list(&SqlitePriceListRepository::new(&db), request)
+}
+
+#[tauri::command]
+pub fn delete_price_list(db: State<Database>, id: i64) -> AppResult<()> {
+    application::delete_price_list(&SqlitePriceListRepository::new(&db), id)
+}
+
+#[tauri::command]
+pub fn assign_customer_price_list(
+    db: State<Database>,
+    request: AssignCustomerPriceListRequest,
+) -> AppResult<()> {
+    application::assign_customer_price_list(&SqlitePriceListRepository::new(&db), request)
+}
@@ -0,0 +1,265 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::price_list::{
+    CreatePriceListRequest, PriceList, PriceListRule, PriceListRuleRequest, UpdatePriceListRequest,
+};
+use crate::modules::pricing::application::PriceListRepository;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
+use rusqlite::{params, Connection, OptionalExtension};
+
+pub struct SqlitePriceListRepository<'db> {
+    db: &'db Database,
+}
+
+impl<'db> SqlitePriceListRepository<'db> {
+    pub fn new(db: &'db Database) -> Self {
+        Self { db }
+    }
+}
+
+impl PriceListRepository for SqlitePriceListRepository<'_> {
+    fn find_all(&self) -> AppResult<Vec<PriceList>> {
+        find_all(self.db)
+    }
+
+    fn find_by_id(&self, id: i64) -> AppResult<Option<PriceList>> {
+        find_by_id(self.db, id)
+    }
+
+    fn find_by_name(&self, name: &str) -> AppResult<Option<PriceList>> {
+        find_by_name(self.db, name)
+    }
+
+    fn create(&self, request: &CreatePriceListRequest) -> AppResult<PriceList> {
+        create(self.db, request)
+    }
+
+    fn update(&self, request: &UpdatePriceListRequest) -> AppResult<PriceList> {
+        update(self.db, request)
+    }
+
+    fn delete(&self, id: i64) -> AppResult<()> {
+        delete(self.db, id)
+    }
+
+    fn assign_to_customer(&self, customer_id: i64, price_list_id: Option<i64>) -> AppResult<()> {
+        assign_to_customer(self.db, customer_id, price_list_id)
+    }
+}
+
+const SELECT_QUERY: &str = "\
+    SELECT id, name, description, default_adjustment_percent, active, created_at, updated_at \
+    FROM price_lists";
+
+fn row_to_price_list(row: &rusqlite::Row) -> rusqlite::Result<PriceList> {
+    Ok(PriceList {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        default_adjustment_percent: row.get(3)?,
+        active: row.get::<_, i64>(4)? != 0,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        rules: Vec::new(),
+    })
+}
+
+fn find_rules(conn: &Connection, price_list_id: i64) -> AppResult<Vec<PriceListRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.price_list_id, r.product_id, p.name, r.category_id, c.name, \
+                r.min_quantity, r.fixed_price, r.percent_adjustment \
+            FROM price_list_rules r \
+            LEFT JOIN products p ON r.product_id = p.id \
+            LEFT JOIN categories c ON r.category_id = c.id \
+            WHERE r.price_list_id = ?1 \
+            ORDER BY r.product_id IS NULL, r.category_id IS NULL, r.min_quantity",
+    )?;
+    let rules = stmt
+        .query_map(params![price_list_id], |row| {
+            Ok(PriceListRule {
+                id: row.get(0)?,
+                price_list_id: row.get(1)?,
+                product_id: row.get(2)?,
+                product_name: row.get(3)?,
+                category_id: row.get(4)?,
+                category_name: row.get(5)?,
+                min_quantity: money::round3(row.get(6)?),
+                fixed_price: row.get(7)?,
+                percent_adjustment: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rules)
+}
+
+fn with_rules(conn: &Connection, price_list: Option<PriceList>) -> AppResult<Option<PriceList>> {
+    match price_list {
+        Some(mut price_list) => {
+            price_list.rules = find_rules(conn, price_list.id)?;
+            Ok(Some(price_list))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn find_all(db: &Database) -> AppResult<Vec<PriceList>> {
+    let conn = db.conn.lock()?;
+    let query = format!("{} ORDER BY name", SELECT_QUERY);
+    let lists = conn
+        .prepare(&query)?
+        .query_map([], row_to_price_list)?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut result = Vec::with_capacity(lists.len());
+    for mut list in lists {
+        list.rules = find_rules(&conn, list.id)?;
+        result.push(list);
+    }
+    Ok(result)
+}
+
+pub fn find_by_id(db: &Database, id: i64) -> AppResult<Option<PriceList>> {
+    let conn = db.conn.lock()?;
+    let query = format!("{} WHERE id = ?1", SELECT_QUERY);
+    let price_list = conn
+        .query_row(&query, params![id], row_to_price_list)
+        .optional()?;
+    with_rules(&conn, price_list)
+}
+
+pub fn find_by_name(db: &Database, name: &str) -> AppResult<Option<PriceList>> {
+    let conn = db.conn.lock()?;
+    let query = format!("{} WHERE name = ?1", SELECT_QUERY);
+    let price_list = conn
+        .query_row(&query, params![name], row_to_price_list)
+        .optional()?;
+    with_rules(&conn, price_list)
+}
+
+/// Active price list assigned to a customer, with its rules loaded.
+pub fn find_customer_price_list(db: &Database, customer_id: i64) -> AppResult<Option<PriceList>> {
+    let conn = db.conn.lock()?;
+    let query = format!(
+        "{} WHERE active = 1 AND id = (SELECT price_list_id FROM customers WHERE id = ?1)",
+        SELECT_QUERY
+    );
+    let price_list = conn
+        .query_row(&query, params![customer_id], row_to_price_list)
+        .optional()?;
+    with_rules(&conn, price_list)
+}
+
+fn replace_rules(
+    tx: &Connection,
+    price_list_id: i64,
+    rules: &[PriceListRuleRequest],
+) -> AppResult<()> {
+    tx.execute(
+        "DELETE FROM price_list_rules WHERE price_list_id = ?1",
+        params![price_list_id],
+    )?;
+    for rule in rules {
+        tx.execute(
+            "INSERT INTO price_list_rules (price_list_id, product_id, category_id, min_quantity, \
+                fixed_price, percent_adjustment) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                price_list_id,
+                rule.product_id,
+                rule.category_id,
+                money::round3(rule.min_quantity.unwrap_or(1.0)),
+                rule.fixed_price.map(money::round2),
+                rule.percent_adjustment,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn create(db: &Database, request: &CreatePriceListRequest) -> AppResult<PriceList> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO price_lists (name, description, default_adjustment_percent) VALUES (?1, ?2, ?3)",
+        params![
+            request.name.trim(),
+            request.description,
+            request.default_adjustment_percent.unwrap_or(0.0)
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    replace_rules(&tx, id, &request.rules)?;
+    tx.commit()?;
+    drop(conn);
+    find_by_id(db, id)?
+        .ok_or_else(|| AppError::NotFound("Failed to retrieve created price list".to_string()))
+}
+
+pub fn update(db: &Database, request: &UpdatePriceListRequest) -> AppResult<PriceList> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+
+    if let Some(val) = request.name.as_deref() {
+        tx.execute(
+            "UPDATE price_lists SET name = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![val.trim(), request.id],
+        )?;
+    }
+    if let Some(val) = request.description.as_deref() {
+        tx.execute(
+            "UPDATE price_lists SET description = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![val, request.id],
+        )?;
+    }
+    if let Some(val) = request.default_adjustment_percent {
+        tx.execute(
+            "UPDATE price_lists SET default_adjustment_percent = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![val, request.id],
+        )?;
+    }
+    if let Some(val) = request.active {
+        tx.execute(
+            "UPDATE price_lists SET active = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![val, request.id],
+        )?;
+    }
+    if let Some(rules) = request.rules.as_deref() {
+        replace_rules(&tx, request.id, rules)?;
+    }
+
+    tx.commit()?;
+    drop(conn);
+    find_by_id(db, request.id)?
+        .ok_or_else(|| AppError::NotFound("Lista de precios no encontrada".to_string()))
+}
+
+pub fn delete(db: &Database, id: i64) -> AppResult<()> {
+    let conn = db.conn.lock()?;
+    let used_in_sales: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sale_items WHERE price_list_id = ?1 LIMIT 1)",
+        params![id],
+        |row| row.get(0),
+    )?;
+    if used_in_sales {
+        return Err(AppError::Conflict(
+            "No se puede eliminar la lista de precios porque se aplicó en ventas registradas. Desactívala en su lugar."
+                .to_string(),
+        ));
+    }
+    conn.execute("DELETE FROM price_lists WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn assign_to_customer(
+    db: &Database,
+    customer_id: i64,
+    price_list_id: Option<i64>,
+) -> AppResult<()> {
+    let conn = db.conn.lock()?;
+    let rows = conn.execute(
+        "UPDATE customers SET price_list_id = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+        params![price_list_id, customer_id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound("Cliente no encontrado".to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,345 @@
+use crate::models::price_list::{
+    AssignCustomerPriceListRequest, CreatePriceListRequest, PriceList, PriceListRule,
+    PriceListRuleRequest, UpdatePriceListRequest,
+};
+use crate::models::product::Product;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
+
+/// Unit price chosen for a sale line and the list that produced it, if any.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolvedPrice {
+    pub unit_price: f64,
+    pub price_list_id: Option<i64>,
+}
+
+pub trait PriceListRepository {
+    fn find_all(&self) -> AppResult<Vec<PriceList>>;
+    fn find_by_id(&self, id: i64) -> AppResult<Option<PriceList>>;
+    fn find_by_name(&self, name: &str) -> AppResult<Option<PriceList>>;
+    fn create(&self, request: &CreatePriceListRequest) -> AppResult<PriceList>;
+    fn update(&self, request: &UpdatePriceListRequest) -> AppResult<PriceList>;
+    fn delete(&self, id: i64) -> AppResult<()>;
+    fn assign_to_customer(&self, customer_id: i64, price_list_id: Option<i64>) -> AppResult<()>;
+}
+
+pub fn get_price_lists(repository: &impl PriceListRepository) -> AppResult<Vec<PriceList>> {
+    repository.find_all()
+}
+
+pub fn get_price_list(repository: &impl PriceListRepository, id: i64) -> AppResult<PriceList> {
+    repository
+        .find_by_id(id)?
+        .ok_or_else(|| AppError::NotFound("Lista de precios no encontrada".to_string()))
+}
+
+pub fn create_price_list(
+    repository: &impl PriceListRepository,
+    request: CreatePriceListRequest,
+) -> AppResult<PriceList> {
+    if request.name.trim().is_empty() {
+        return Err(AppError::Validation(
+            "El nombre de la lista de precios no puede estar vacío".to_string(),
+        ));
+    }
+    if repository.find_by_name(request.name.trim())?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "La lista de precios {} ya existe",
+            request.name.trim()
+        )));
+    }
+    validate_percent(request.default_adjustment_percent.unwrap_or(0.0))?;
+    validate_rules(&request.rules)?;
+    repository.create(&request)
+}
+
+pub fn update_price_list(
+    repository: &impl PriceListRepository,
+    request: UpdatePriceListRequest,
+) -> AppResult<PriceList> {
+    get_price_list(repository, request.id)?;
+    if let Some(name) = request.name.as_deref() {
+        if name.trim().is_empty() {
+            return Err(AppError::Validation(
+                "El nombre de la lista de precios no puede estar vacío".to_string(),
+            ));
+        }
+        if let Some(existing) = repository.find_by_name(name.trim())? {
+            if existing.id != request.id {
+                return Err(AppError::Conflict(format!(
+                    "La lista de precios {} ya existe",
+                    name.trim()
+                )));
+            }
+        }
+    }
+    if let Some(percent) = request.default_adjustment_percent {
+        validate_percent(percent)?;
+    }
+    if let Some(rules) = request.rules.as_deref() {
+        validate_rules(rules)?;
+    }
+    repository.update(&request)
+}
+
+pub fn delete_price_list(repository: &impl PriceListRepository, id: i64) -> AppResult<()> {
+    get_price_list(repository, id)?;
+    repository.delete(id)
+}
+
+pub fn assign_customer_price_list(
+    repository: &impl PriceListRepository,
+    request: AssignCustomerPriceListRequest,
+) -> AppResult<()> {
+    if let Some(price_list_id) = request.price_list_id {
+        let price_list = get_price_list(repository, price_list_id)?;
+        if !price_list.active {
+            return Err(AppError::Conflict(format!(
+                "La lista de precios {} está desactivada",
+                price_list.name
+            )));
+        }
+    }
+    repository.assign_to_customer(request.customer_id, request.price_list_id)
+}
+
+/// Picks the unit price for `quantity` units of `product` under `price_list`.
+///
+/// Product rules win over category rules, which win over list-wide rules.
+/// Within the same scope the rule with the highest `min_quantity` not above
+/// the requested quantity applies. Without a matching rule the list's default
+/// adjustment is used; a list without rules nor adjustment keeps the catalog
+/// price and is not recorded on the line.
+pub fn resolve_unit_price(
+    product: &Product,
+    quantity: f64,
+    price_list: Option<&PriceList>,
+) -> ResolvedPrice {
+    let catalog = ResolvedPrice {
+        unit_price: product.price,
+        price_list_id: None,
+    };
+    let Some(price_list) = price_list.filter(|list| list.active) else {
+        return catalog;
+    };
+
+    let rule = price_list
+        .rules
+        .iter()
+        .filter(|rule| rule.min_quantity <= quantity + 0.000_001)
+        .filter_map(|rule| rule_scope(rule, product).map(|scope| (scope, rule)))
+        .max_by(|(scope_a, a), (scope_b, b)| {
+            scope_a
+                .cmp(scope_b)
+                .then(a.min_quantity.total_cmp(&b.min_quantity))
+        })
+        .map(|(_, rule)| rule);
+
+    let unit_price = match rule {
+        Some(rule) => match (rule.fixed_price, rule.percent_adjustment) {
+            (Some(fixed_price), _) => money::round2(fixed_price),
+            (None, Some(percent)) => adjust(product.price, percent),
+            (None, None) => return catalog,
+        },
+        None if price_list.default_adjustment_percent != 0.0 => {
+            adjust(product.price, price_list.default_adjustment_percent)
+        }
+        None => return catalog,
+    };
+    ResolvedPrice {
+        unit_price,
+        price_list_id: Some(price_list.id),
+    }
+}
+
+/// Specificity of `rule` for `product`; `None` when the rule does not apply.
+fn rule_scope(rule: &PriceListRule, product: &Product) -> Option<u8> {
+    match (rule.product_id, rule.category_id) {
+        (Some(product_id), _) => (product_id == product.id).then_some(2),
+        (None, Some(category_id)) => (Some(category_id) == product.category_id).then_some(1),
+        (None, None) => Some(0),
+    }
+}
+
+fn adjust(price: f64, percent: f64) -> f64 {
+    money::round2((price * (1.0 + percent / 100.0)).max(0.0))
+}
+
+fn validate_percent(percent: f64) -> AppResult<()> {
+    if !percent.is_finite() || percent <= -100.0 {
+        return Err(AppError::Validation(
+            "El ajuste porcentual debe ser mayor a -100%".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_rules(rules: &[PriceListRuleRequest]) -> AppResult<()> {
+    for rule in rules {
+        if rule.product_id.is_some() && rule.category_id.is_some() {
+            return Err(AppError::Validation(
+                "Una regla de precio aplica a un producto o a una categoría, no a ambos"
+                    .to_string(),
+            ));
+        }
+        if rule
+            .min_quantity
+            .is_some_and(|quantity| !quantity.is_finite() || quantity <= 0.0)
+        {
+            return Err(AppError::Validation(
+                "La cantidad mínima de una regla debe ser mayor que cero".to_string(),
+            ));
+        }
+        match (rule.fixed_price, rule.percent_adjustment) {
+            (Some(price), None) if price.is_finite() && price >= 0.0 => {}
+            (Some(_), None) => {
+                return Err(AppError::Validation(
+                    "El precio fijo de una regla no puede ser negativo".to_string(),
+                ))
+            }
+            (None, Some(percent)) => validate_percent(percent)?,
+            _ => {
+                return Err(AppError::Validation(
+                    "Cada regla debe tener un precio fijo o un ajuste porcentual".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_unit_price, ResolvedPrice};
+    use crate::models::price_list::{PriceList, PriceListRule};
+    use crate::models::product::Product;
+
+    fn product() -> Product {
+        Product {
+            id: 1,
+            name: "Arroz".to_string(),
+            description: None,
+            barcode: None,
+            price: 30.0,
+            cost: 0.0,
+            unit: "pieza".to_string(),
+            is_bulk: false,
+            category_id: Some(7),
+            category_name: Some("Abarrotes".to_string()),
+            stock: 100.0,
+            min_stock: 0.0,
+            active: true,
+            created_at: "2026-01-01".to_string(),
+            updated_at: "2026-01-01".to_string(),
+            parent_id: None,
+            variant_count: 0,
+            is_kit: false,
+        }
+    }
+
+    fn rule(
+        product_id: Option<i64>,
+        category_id: Option<i64>,
+        min_quantity: f64,
+        fixed_price: Option<f64>,
+        percent_adjustment: Option<f64>,
+    ) -> PriceListRule {
+        PriceListRule {
+            id: 0,
+            price_list_id: 3,
+            product_id,
+            product_name: None,
+            category_id,
+            category_name: None,
+            min_quantity,
+            fixed_price,
+            percent_adjustment,
+        }
+    }
+
+    fn mayoreo(rules: Vec<PriceListRule>) -> PriceList {
+        PriceList {
+            id: 3,
+            name: "Mayoreo".to_string(),
+            description: None,
+            default_adjustment_percent: 0.0,
+            active: true,
+            created_at: "2026-01-01".to_string(),
+            updated_at: "2026-01-01".to_string(),
+            rules,
+        }
+    }
+
+    #[test]
+    fn keeps_the_catalog_price_without_a_price_list() {
+        assert_eq!(
+            resolve_unit_price(&product(), 1.0, None),
+            ResolvedPrice {
+                unit_price: 30.0,
+                price_list_id: None
+            }
+        );
+    }
+
+    #[test]
+    fn applies_the_highest_quantity_break_reached() {
+        let list = mayoreo(vec![
+            rule(Some(1), None, 1.0, Some(28.0), None),
+            rule(Some(1), None, 12.0, Some(25.0), None),
+        ]);
+        assert_eq!(
+            resolve_unit_price(&product(), 5.0, Some(&list)).unit_price,
+            28.0
+        );
+        let resolved = resolve_unit_price(&product(), 12.0, Some(&list));
+        assert_eq!(resolved.unit_price, 25.0);
+        assert_eq!(resolved.price_list_id, Some(3));
+    }
+
+    #[test]
+    fn product_rules_take_precedence_over_category_and_list_rules() {
+        let list = mayoreo(vec![
+            rule(None, None, 1.0, None, Some(-5.0)),
+            rule(None, Some(7), 1.0, None, Some(-10.0)),
+            rule(Some(1), None, 1.0, Some(29.0), None),
+        ]);
+        assert_eq!(
+            resolve_unit_price(&product(), 1.0, Some(&list)).unit_price,
+            29.0
+        );
+
+        let mut other = product();
+        other.id = 2;
+        assert_eq!(
+            resolve_unit_price(&other, 1.0, Some(&list)).unit_price,
+            27.0
+        );
+
+        other.category_id = None;
+        assert_eq!(
+            resolve_unit_price(&other, 1.0, Some(&list)).unit_price,
+            28.5
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_default_adjustment_and_ignores_inactive_lists() {
+        let mut list = mayoreo(Vec::new());
+        assert_eq!(
+            resolve_unit_price(&product(), 1.0, Some(&list)).price_list_id,
+            None
+        );
+
+        list.default_adjustment_percent = -10.0;
+        assert_eq!(
+            resolve_unit_price(&product(), 1.0, Some(&list)).unit_price,
+            27.0
+        );
+
+        list.active = false;
+        assert_eq!(
+            resolve_unit_price(&product(), 1.0, Some(&list)).unit_price,
+            30.0
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use crate::infrastructure::sqlite::Database;
+use crate::modules::cash_register::adapters::outbound::sqlite::SqliteCashRegisterRepository;
 use crate::modules::printing::adapters::outbound::{
     escpos::EscposPrinterPort, sqlite::SqlitePrintingSettingsRepository,
 };
 use crate::modules::printing::application;
+use crate::modules::registers::adapters::outbound::sqlite::SqliteRegisterRepository;
 use crate::modules::sales::adapters::outbound::sqlite::SqliteSalesRepository;
 use crate::printer::models::{PrinterInfo, PrinterSettings};
 use crate::shared::error::AppResult;
@@ -36,9 +38,52 @@ pub async fn test_printer(db: State<'_, Database>) -> AppResult<()> {
 pub async fn print_sale_ticket(db: State<'_, Database>, sale_id: i64) -> AppResult<()> {
     application::print_sale_ticket(
         &SqlitePrintingSettingsRepository::new(&db),
+        &SqliteRegisterRepository::new(&db),
         &SqliteSalesRepository::new(&db),
         &EscposPrinterPort,
         sale_id,
     )
     .await
 }
+
+#[tauri::command]
+pub async fn print_cash_movement_voucher(
+    db: State<'_, Database>,
+    movement_id: i64,
+) -> AppResult<()> {
+    application::print_cash_movement_voucher(
+        &SqlitePrintingSettingsRepository::new(&db),
+        &SqliteRegisterRepository::new(&db),
+        &SqliteCashRegisterRepository::new(&db),
+        &EscposPrinterPort,
+        movement_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn print_cash_register_report(db: State<'_, Database>, report_id: i64) -> AppResult<()> {
+    application::print_cash_register_report(
+        &SqlitePrintingSettingsRepository::new(&db),
+        &SqliteRegisterRepository::new(&db),
+        &SqliteCashRegisterRepository::new(&db),
+        &EscposPrinterPort,
+        report_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn print_cash_handover_report(
+    db: State<'_, Database>,
+    handover_id: i64,
+) -> AppResult<()> {
+    application::print_cash_handover_report(
+        &SqlitePrintingSettingsRepository::new(&db),
+        &SqliteRegisterRepository::new(&db),
+        &SqliteCashRegisterRepository::new(&db),
+        &EscposPrinterPort,
+        handover_id,
+    )
+    .await
+}
@@ -1,7 +1,17 @@
 use crate::infrastructure::sqlite::Database;
-use crate::modules::printing::application::{PrintingSettingsRepository, SaleReader};
+use crate::models::cash_register::{CashHandover, CashMovement};
+use crate::models::cash_register_report::CashRegisterReport;
+use crate::modules::cash_register::adapters::outbound::sqlite::SqliteCashRegisterRepository;
+use crate::modules::cash_register::application::{self as cash_register, CashRegisterRepository};
+use crate::modules::printing::application::{
+    CashHandoverReader, CashMovementReader, CashReportReader, PrintingSettingsRepository,
+    RegisterPrinterReader, SaleReader,
+};
+use crate::modules::registers::adapters::outbound::sqlite::SqliteRegisterRepository;
+use crate::modules::registers::application::RegisterRepository;
 use crate::modules::sales::adapters::outbound::sqlite::SqliteSalesRepository;
 use crate::modules::sales::application::SalesQueryPort;
+use crate::printer::models::PrinterSettings;
 use crate::shared::error::AppResult;
 use std::collections::HashMap;
 
@@ -48,3 +58,28 @@ impl SaleReader for SqliteSalesRepository<'_> {
         SalesQueryPort::find_sale(self, id)
     }
 }
+
+impl CashMovementReader for SqliteCashRegisterRepository<'_> {
+    fn find_cash_movement(&self, id: i64) -> AppResult<Option<CashMovement>> {
+        CashRegisterRepository::find_movement(self, id)
+    }
+}
+
+impl CashHandoverReader for SqliteCashRegisterRepository<'_> {
+    fn find_cash_handover(&self, id: i64) -> AppResult<Option<CashHandover>> {
+        cash_register::find_handover(self, id)
+    }
+}
+
+impl CashReportReader for SqliteCashRegisterRepository<'_> {
+    fn find_cash_report(&self, id: i64) -> AppResult<Option<CashRegisterReport>> {
+        CashRegisterRepository::find_report(self, id)
+    }
+}
+
+impl RegisterPrinterReader for SqliteRegisterRepository<'_> {
+    fn find_session_printer(&self, session_id: i64) -> AppResult<Option<PrinterSettings>> {
+        Ok(RegisterRepository::find_by_session(self, session_id)?
+            .and_then(|register| register.printer))
+    }
+}
@@ -1,7 +1,12 @@
+use crate::models::cash_register::{CashHandover, CashMovement};
+use crate::models::cash_register_report::CashRegisterReport;
 use crate::models::sale::Sale;
 use crate::printer::config::{runtime_config_from_settings, settings_from_map};
 use crate::printer::models::{PrinterConfig, PrinterInfo, PrinterSettings, TicketData};
-use crate::printer::ticket_builder::build_sale_ticket;
+use crate::printer::ticket_builder::{
+    build_cash_handover_report, build_cash_movement_voucher, build_cash_register_report,
+    build_sale_ticket,
+};
 use crate::shared::error::{AppError, AppResult};
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -35,6 +40,23 @@ pub trait SaleReader {
     fn find_sale(&self, id: i64) -> AppResult<Option<Sale>>;
 }
 
+pub trait CashMovementReader {
+    fn find_cash_movement(&self, id: i64) -> AppResult<Option<CashMovement>>;
+}
+
+pub trait CashReportReader {
+    fn find_cash_report(&self, id: i64) -> AppResult<Option<CashRegisterReport>>;
+}
+
+pub trait CashHandoverReader {
+    fn find_cash_handover(&self, id: i64) -> AppResult<Option<CashHandover>>;
+}
+
+pub trait RegisterPrinterReader {
+    /// Printer configured on the register that ran the session, if any.
+    fn find_session_printer(&self, session_id: i64) -> AppResult<Option<PrinterSettings>>;
+}
+
 #[async_trait]
 pub trait PrinterPort {
     async fn detect_printers(&self) -> AppResult<Vec<PrinterInfo>>;
@@ -72,15 +94,15 @@ pub async fn test_printer(
 
 pub async fn print_sale_ticket(
     repository: &impl PrintingSettingsRepository,
+    registers: &impl RegisterPrinterReader,
     sales: &impl SaleReader,
     port: &impl PrinterPort,
     sale_id: i64,
 ) -> AppResult<()> {
-    let config = runtime_config_from_settings(&get_printer_config(repository)?, true)?
-        .ok_or_else(|| AppError::Conflict("La impresora esta deshabilitada".to_string()))?;
     let sale = sales
         .find_sale(sale_id)?
         .ok_or_else(|| AppError::NotFound("Venta no encontrada".to_string()))?;
+    let config = session_printer_config(repository, registers, sale.cash_register_session_id)?;
     let settings = repository.find_values(TICKET_SETTING_KEYS)?;
     let ticket = build_sale_ticket(
         &sale,
@@ -92,6 +114,81 @@ pub async fn print_sale_ticket(
     port.print_ticket(config, ticket).await
 }
 
+pub async fn print_cash_movement_voucher(
+    repository: &impl PrintingSettingsRepository,
+    registers: &impl RegisterPrinterReader,
+    movements: &impl CashMovementReader,
+    port: &impl PrinterPort,
+    movement_id: i64,
+) -> AppResult<()> {
+    let movement = movements
+        .find_cash_movement(movement_id)?
+        .ok_or_else(|| AppError::NotFound("Movimiento de caja no encontrado".to_string()))?;
+    let config = session_printer_config(repository, registers, movement.cash_register_session_id)?;
+    let settings = repository.find_values(TICKET_SETTING_KEYS)?;
+    let voucher = build_cash_movement_voucher(
+        &movement,
+        setting_value(&settings, "business_name"),
+        setting_value(&settings, "ticket_header"),
+    );
+    port.print_ticket(config, voucher).await
+}
+
+/// Prints a stored X or Z report; reprints show exactly what was generated.
+pub async fn print_cash_register_report(
+    repository: &impl PrintingSettingsRepository,
+    registers: &impl RegisterPrinterReader,
+    reports: &impl CashReportReader,
+    port: &impl PrinterPort,
+    report_id: i64,
+) -> AppResult<()> {
+    let report = reports
+        .find_cash_report(report_id)?
+        .ok_or_else(|| AppError::NotFound("Reporte de caja no encontrado".to_string()))?;
+    let config = session_printer_config(repository, registers, report.cash_register_session_id)?;
+    let settings = repository.find_values(TICKET_SETTING_KEYS)?;
+    let ticket = build_cash_register_report(
+        &report,
+        setting_value(&settings, "business_name"),
+        setting_value(&settings, "ticket_header"),
+    );
+    port.print_ticket(config, ticket).await
+}
+
+pub async fn print_cash_handover_report(
+    repository: &impl PrintingSettingsRepository,
+    registers: &impl RegisterPrinterReader,
+    handovers: &impl CashHandoverReader,
+    port: &impl PrinterPort,
+    handover_id: i64,
+) -> AppResult<()> {
+    let handover = handovers
+        .find_cash_handover(handover_id)?
+        .ok_or_else(|| AppError::NotFound("Entrega de caja no encontrada".to_string()))?;
+    let config = session_printer_config(repository, registers, handover.cash_register_session_id)?;
+    let settings = repository.find_values(TICKET_SETTING_KEYS)?;
+    let ticket = build_cash_handover_report(
+        &handover,
+        setting_value(&settings, "business_name"),
+        setting_value(&settings, "ticket_header"),
+    );
+    port.print_ticket(config, ticket).await
+}
+
+/// Uses the register's own printer when it has one, otherwise the global settings.
+fn session_printer_config(
+    repository: &impl PrintingSettingsRepository,
+    registers: &impl RegisterPrinterReader,
+    session_id: i64,
+) -> AppResult<PrinterConfig> {
+    let settings = match registers.find_session_printer(session_id)? {
+        Some(printer) => printer,
+        None => get_printer_config(repository)?,
+    };
+    runtime_config_from_settings(&settings, true)?
+        .ok_or_else(|| AppError::Conflict("La impresora esta deshabilitada".to_string()))
+}
+
 fn persist_printer_settings(
     repository: &impl PrintingSettingsRepository,
     config: &PrinterSettings,
@@ -0,0 +1,36 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::register::{CreateRegisterRequest, Register, UpdateRegisterRequest};
+use crate::modules::identity::adapters::outbound::sqlite::SqliteUserRepository;
+use crate::modules::registers::{
+    adapters::outbound::sqlite::SqliteRegisterRepository, application,
+};
+use crate::shared::error::AppResult;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_registers(db: State<Database>) -> AppResult<Vec<Register>> {
+    application::get_registers(&SqliteRegisterRepository::new(&db))
+}
+
+#[tauri::command]
+pub fn get_register(db: State<Database>, id: i64) -> AppResult<Register> {
+    application::get_register(&SqliteRegisterRepository::new(&db), id)
+}
+
+#[tauri::command]
+pub fn create_register(db: State<Database>, request: CreateRegisterRequest) -> AppResult<Register> {
+    application::create_register(
+        &SqliteRegisterRepository::new(&db),
+        &SqliteUserRepository::new(&db),
+        request,
+    )
+}
+
+#[tauri::command]
+pub fn update_register(db: State<Database>, request: UpdateRegisterRequest) -> AppResult<Register> {
+    application::update_register(
+        &SqliteRegisterRepository::new(&db),
+        &SqliteUserRepository::new(&db),
+        request,
+    )
+}
@@ -0,0 +1,166 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::register::{CreateRegisterRequest, Register, UpdateRegisterRequest};
+use crate::modules::registers::application::RegisterRepository;
+use crate::printer::models::PrinterSettings;
+use crate::shared::error::{AppError, AppResult};
+use rusqlite::{params, Connection, OptionalExtension};
+
+pub struct SqliteRegisterRepository<'db> {
+    db: &'db Database,
+}
+
+impl<'db> SqliteRegisterRepository<'db> {
+    pub fn new(db: &'db Database) -> Self {
+        Self { db }
+    }
+}
+
+impl RegisterRepository for SqliteRegisterRepository<'_> {
+    fn find_all(&self) -> AppResult<Vec<Register>> {
+        find_all(self.db)
+    }
+
+    fn find_by_id(&self, id: i64) -> AppResult<Option<Register>> {
+        let conn = self.db.conn.lock()?;
+        query_register(&conn, id)
+    }
+
+    fn find_by_name(&self, name: &str) -> AppResult<Option<Register>> {
+        find_by_name(self.db, name)
+    }
+
+    fn find_by_session(&self, session_id: i64) -> AppResult<Option<Register>> {
+        find_by_session(self.db, session_id)
+    }
+
+    fn create(&self, request: &CreateRegisterRequest) -> AppResult<Register> {
+        create(self.db, request)
+    }
+
+    fn update(&self, request: &UpdateRegisterRequest) -> AppResult<Register> {
+        update(self.db, request)
+    }
+}
+
+const SELECT_QUERY: &str = "\
+    SELECT id, name, location, printer_settings, active, created_at, updated_at \
+    FROM registers";
+
+fn row_to_register(row: &rusqlite::Row) -> rusqlite::Result<Register> {
+    let printer: Option<String> = row.get(3)?;
+    let printer = printer
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|error| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, error.into())
+        })?;
+    Ok(Register {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        location: row.get(2)?,
+        printer,
+        active: row.get::<_, i64>(4)? != 0,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+fn printer_json(printer: &PrinterSettings) -> AppResult<String> {
+    serde_json::to_string(printer).map_err(|error| AppError::Database(error.to_string()))
+}
+
+pub fn find_all(db: &Database) -> AppResult<Vec<Register>> {
+    let conn = db.conn.lock()?;
+    let query = format!("{} ORDER BY name", SELECT_QUERY);
+    let mut stmt = conn.prepare(&query)?;
+    let registers = stmt
+        .query_map([], row_to_register)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(registers)
+}
+
+pub fn query_register(conn: &Connection, id: i64) -> AppResult<Option<Register>> {
+    let query = format!("{} WHERE id = ?1", SELECT_QUERY);
+    Ok(conn
+        .query_row(&query, params![id], row_to_register)
+        .optional()?)
+}
+
+pub fn find_by_session(db: &Database, session_id: i64) -> AppResult<Option<Register>> {
+    let conn = db.conn.lock()?;
+    let query = format!(
+        "{} WHERE id = (SELECT register_id FROM cash_register_sessions WHERE id = ?1)",
+        SELECT_QUERY
+    );
+    Ok(conn
+        .query_row(&query, params![session_id], row_to_register)
+        .optional()?)
+}
+
+pub fn find_by_name(db: &Database, name: &str) -> AppResult<Option<Register>> {
+    let conn = db.conn.lock()?;
+    let query = format!("{} WHERE name = ?1", SELECT_QUERY);
+    Ok(conn
+        .query_row(&query, params![name], row_to_register)
+        .optional()?)
+}
+
+pub fn create(db: &Database, request: &CreateRegisterRequest) -> AppResult<Register> {
+    let printer = request.printer.as_ref().map(printer_json).transpose()?;
+    let conn = db.conn.lock()?;
+    conn.execute(
+        "INSERT INTO registers (name, location, printer_settings) VALUES (?1, ?2, ?3)",
+        params![
+            request.name.trim(),
+            request
+                .location
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty()),
+            printer
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    query_register(&conn, id)?.ok_or_else(|| AppError::NotFound("Caja no encontrada".to_string()))
+}
+
+pub fn update(db: &Database, request: &UpdateRegisterRequest) -> AppResult<Register> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+
+    if let Some(val) = request.name.as_deref() {
+        tx.execute(
+            "UPDATE registers SET name = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![val.trim(), request.id],
+        )?;
+    }
+    if let Some(val) = request.location.as_deref() {
+        let location = Some(val.trim()).filter(|value| !value.is_empty());
+        tx.execute(
+            "UPDATE registers SET location = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![location, request.id],
+        )?;
+    }
+    if request.use_default_printer {
+        tx.execute(
+            "UPDATE registers SET printer_settings = NULL, updated_at = datetime('now', 'localtime') WHERE id = ?1",
+            params![request.id],
+        )?;
+    } else if let Some(printer) = &request.printer {
+        tx.execute(
+            "UPDATE registers SET printer_settings = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![printer_json(printer)?, request.id],
+        )?;
+    }
+    if let Some(val) = request.active {
+        tx.execute(
+            "UPDATE registers SET active = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+            params![val, request.id],
+        )?;
+    }
+
+    let register = query_register(&tx, request.id)?
+        .ok_or_else(|| AppError::NotFound("Caja no encontrada".to_string()))?;
+    tx.commit()?;
+    Ok(register)
+}
@@ -0,0 +1,76 @@
+use crate::models::register::{CreateRegisterRequest, Register, UpdateRegisterRequest};
+use crate::modules::identity::application::{require_admin, UserRepository};
+use crate::printer::config::runtime_config_from_settings;
+use crate::printer::models::PrinterSettings;
+use crate::shared::error::{AppError, AppResult};
+
+pub trait RegisterRepository {
+    fn find_all(&self) -> AppResult<Vec<Register>>;
+    fn find_by_id(&self, id: i64) -> AppResult<Option<Register>>;
+    fn find_by_name(&self, name: &str) -> AppResult<Option<Register>>;
+    fn find_by_session(&self, session_id: i64) -> AppResult<Option<Register>>;
+    fn create(&self, request: &CreateRegisterRequest) -> AppResult<Register>;
+    fn update(&self, request: &UpdateRegisterRequest) -> AppResult<Register>;
+}
+
+pub fn get_registers(repository: &impl RegisterRepository) -> AppResult<Vec<Register>> {
+    repository.find_all()
+}
+
+pub fn get_register(repository: &impl RegisterRepository, id: i64) -> AppResult<Register> {
+    repository
+        .find_by_id(id)?
+        .ok_or_else(|| AppError::NotFound("Caja no encontrada".to_string()))
+}
+
+pub fn create_register(
+    repository: &impl RegisterRepository,
+    users: &impl UserRepository,
+    request: CreateRegisterRequest,
+) -> AppResult<Register> {
+    require_admin(users, request.user_id)?;
+    validate_name(repository, request.name.trim(), None)?;
+    if let Some(printer) = &request.printer {
+        validate_printer(printer)?;
+    }
+    repository.create(&request)
+}
+
+pub fn update_register(
+    repository: &impl RegisterRepository,
+    users: &impl UserRepository,
+    request: UpdateRegisterRequest,
+) -> AppResult<Register> {
+    require_admin(users, request.user_id)?;
+    get_register(repository, request.id)?;
+    if let Some(name) = request.name.as_deref() {
+        validate_name(repository, name.trim(), Some(request.id))?;
+    }
+    if let Some(printer) = &request.printer {
+        validate_printer(printer)?;
+    }
+    repository.update(&request)
+}
+
+fn validate_name(
+    repository: &impl RegisterRepository,
+    name: &str,
+    current_id: Option<i64>,
+) -> AppResult<()> {
+    if name.is_empty() {
+        return Err(AppError::Validation(
+            "El nombre de la caja no puede estar vacío".to_string(),
+        ));
+    }
+    if let Some(existing) = repository.find_by_name(name)? {
+        if Some(existing.id) != current_id {
+            return Err(AppError::Conflict(format!("La caja {} ya existe", name)));
+        }
+    }
+    Ok(())
+}
+
+/// A lane printer must be complete enough to connect even while it is disabled.
+fn validate_printer(printer: &PrinterSettings) -> AppResult<()> {
+    runtime_config_from_settings(printer, false).map(|_| ())
+}
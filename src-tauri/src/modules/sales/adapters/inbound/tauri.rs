@@ -1,5 +1,10 @@
 use crate::infrastructure::sqlite::Database;
-use crate::models::sale::{CreateSaleRequest, DateRangeRequest, Sale, SalesReport, TopProduct};
+use crate::models::sale::{
+    AmendSaleRequest, CategorySales, CreateSaleRequest, DateRangeRequest, Sale, SaleAmendment,
+    SaleRevision, SaleSearchCriteria, SalesReport, TopProduct,
+};
+use crate::modules::cash_register::adapters::outbound::sqlite::SqliteCashRegisterRepository;
+use crate::modules::identity::adapters::outbound::sqlite::SqliteUserRepository;
 use crate::modules::sales::adapters::outbound::sqlite::SqliteSalesRepository;
 use crate::modules::sales::application;
 use crate::shared::error::AppResult;
@@ -46,6 +51,16 @@ pub fn get_sales_by_date_range(
     application::get_sales_by_date_range(&SqliteSalesRepository::new(&db), request, page, page_size)
 }
 
+#[tauri::command]
+pub fn search_sales(
+    db: State<Database>,
+    criteria: SaleSearchCriteria,
+    page: Option<i64>,
+    page_size: Option<i64>,
+) -> AppResult<PaginatedResult<Sale>> {
+    application::search_sales(&SqliteSalesRepository::new(&db), criteria, page, page_size)
+}
+
 #[tauri::command]
 pub fn get_sales_report(db: State<Database>, request: DateRangeRequest) -> AppResult<SalesReport> {
     application::get_sales_report(&SqliteSalesRepository::new(&db), request)
@@ -56,25 +71,70 @@ pub fn get_top_products(
     db: State<Database>,
     start_date: String,
     end_date: String,
+    register_id: Option<i64>,
+    category_id: Option<i64>,
     limit: Option<i64>,
 ) -> AppResult<Vec<TopProduct>> {
     application::get_top_products(
         &SqliteSalesRepository::new(&db),
         start_date,
         end_date,
+        register_id,
+        category_id,
         limit,
     )
 }
 
 #[tauri::command]
-pub fn cancel_sale(db: State<Database>, sale_id: i64) -> AppResult<()> {
-    application::cancel_sale(&SqliteSalesRepository::new(&db), sale_id)
+pub fn get_sales_by_category(
+    db: State<Database>,
+    start_date: String,
+    end_date: String,
+    register_id: Option<i64>,
+    depth: Option<i64>,
+) -> AppResult<Vec<CategorySales>> {
+    application::get_sales_by_category(
+        &SqliteSalesRepository::new(&db),
+        start_date,
+        end_date,
+        register_id,
+        depth,
+    )
+}
+
+#[tauri::command]
+pub fn cancel_sale(
+    db: State<Database>,
+    sale_id: i64,
+    refund_to_store_credit: Option<bool>,
+) -> AppResult<()> {
+    application::cancel_sale(
+        &SqliteSalesRepository::new(&db),
+        sale_id,
+        refund_to_store_credit.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+pub fn amend_sale(db: State<Database>, request: AmendSaleRequest) -> AppResult<SaleAmendment> {
+    application::amend_sale(
+        &SqliteSalesRepository::new(&db),
+        &SqliteUserRepository::new(&db),
+        &SqliteCashRegisterRepository::new(&db),
+        request,
+    )
+}
+
+#[tauri::command]
+pub fn get_sale_revisions(db: State<Database>, sale_id: i64) -> AppResult<Vec<SaleRevision>> {
+    application::get_sale_revisions(&SqliteSalesRepository::new(&db), sale_id)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::models::product::Product;
     use crate::models::sale::{CreateSaleItemRequest, SaleInputMode};
+    use crate::models::unit::Unit;
     use crate::modules::sales::application::validate_sale_input;
 
     fn bulk_product() -> Product {
@@ -84,6 +144,7 @@ mod tests {
             description: None,
             barcode: None,
             price: 100.0,
+            cost: 0.0,
             unit: "kg".to_string(),
             is_bulk: true,
             category_id: None,
@@ -93,6 +154,21 @@ mod tests {
             active: true,
             created_at: "2026-01-01".to_string(),
             updated_at: "2026-01-01".to_string(),
+            parent_id: None,
+            variant_count: 0,
+            is_kit: false,
+        }
+    }
+
+    fn conversion(name: &str, base_unit: &str, factor: f64) -> Unit {
+        Unit {
+            id: 2,
+            name: name.to_string(),
+            base_unit_id: Some(1),
+            base_unit: Some(base_unit.to_string()),
+            factor,
+            fractional: false,
+            created_at: "2026-01-01".to_string(),
         }
     }
 
@@ -113,8 +189,31 @@ mod tests {
             input_value: 20.0,
             input_unit: "MXN".to_string(),
         };
-        assert!(validate_sale_input(&product, &subunit, 0.2).is_ok());
-        assert!(validate_sale_input(&product, &amount, 0.2).is_ok());
+        let grams = conversion("g", "kg", 0.001);
+        assert!(validate_sale_input(&product, &subunit, 0.2, Some(&grams)).is_ok());
+        assert!(validate_sale_input(&product, &amount, 0.2, None).is_ok());
+    }
+
+    #[test]
+    fn converts_any_configured_unit_of_the_product() {
+        let product = Product {
+            unit: "pieza".to_string(),
+            is_bulk: false,
+            ..bulk_product()
+        };
+        let cases = CreateSaleItemRequest {
+            product_id: 1,
+            quantity: 24.0,
+            input_mode: SaleInputMode::Sub,
+            input_value: 2.0,
+            input_unit: "caja".to_string(),
+        };
+        let case = conversion("caja", "pieza", 12.0);
+        assert!(validate_sale_input(&product, &cases, 24.0, Some(&case)).is_ok());
+        assert!(validate_sale_input(&product, &cases, 24.0, None).is_err());
+
+        let grams = conversion("g", "kg", 0.001);
+        assert!(validate_sale_input(&product, &cases, 24.0, Some(&grams)).is_err());
     }
 
     #[test]
@@ -127,6 +226,7 @@ mod tests {
             input_value: 500.0,
             input_unit: "g".to_string(),
         };
-        assert!(validate_sale_input(&product, &request, 0.2).is_err());
+        let grams = conversion("g", "kg", 0.001);
+        assert!(validate_sale_input(&product, &request, 0.2, Some(&grams)).is_err());
     }
 }
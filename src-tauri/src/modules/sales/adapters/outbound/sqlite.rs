@@ -1,14 +1,25 @@
 use crate::infrastructure::sqlite::Database;
 use crate::models::cash_register::{CashRegisterSession, SessionStatus};
 use crate::models::customer::{Customer, CustomerMovementType};
+use crate::models::price_list::PriceList;
 use crate::models::product::Product;
-use crate::models::sale::{Sale, SaleInputMode, SaleItem, SaleStatus, TopProduct};
+use crate::models::sale::{
+    CategorySales, Sale, SaleInputMode, SaleItem, SaleRevision, SaleRevisionSnapshot,
+    SaleSearchCriteria, SaleSortField, SaleStatus, SortDirection, TopProduct,
+};
+use crate::models::stored_value::StoredValueMovementType;
+use crate::models::unit::Unit;
+use crate::modules::cash_register::adapters::outbound::sqlite as cash_register;
+use crate::modules::loyalty::adapters::outbound::sqlite as loyalty;
+use crate::modules::loyalty::application::LoyaltyProgram;
 use crate::modules::sales::application::{
     SaleDraft, SaleDraftDependencies, SalesQueryPort, SalesReportMetrics, SalesUnitOfWork,
 };
+use crate::modules::stored_value::adapters::outbound::sqlite as stored_value;
 use crate::shared::error::{AppError, AppResult};
 use crate::shared::money;
-use rusqlite::{params, OptionalExtension};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, OptionalExtension};
 
 pub struct SqliteSalesRepository<'db> {
     db: &'db Database,
@@ -34,29 +45,51 @@ impl SalesUnitOfWork for SqliteSalesRepository<'_> {
                 input_value: item.input_value,
                 input_unit: item.input_unit,
                 unit_price: item.unit_price,
+                catalog_price: item.catalog_price,
+                price_list_id: item.price_list_id,
                 subtotal: item.subtotal,
+                unit_cost: item.unit_cost,
             })
             .collect();
         create(
             self.db,
-            draft.cash_register_session_id,
-            draft.user_id,
-            draft.total,
-            &draft.payment_method,
-            draft.payment_amount,
-            draft.payment_cash_mxn,
-            draft.payment_cash_usd,
-            draft.payment_transfer,
-            draft.exchange_rate,
-            draft.change_amount,
-            draft.customer_id,
-            draft.credit_amount,
-            &items,
+            &PreparedSale {
+                cash_register_session_id: draft.cash_register_session_id,
+                user_id: draft.user_id,
+                total: draft.total,
+                payment_method: draft.payment_method,
+                payment_amount: draft.payment_amount,
+                payment_cash_mxn: draft.payment_cash_mxn,
+                payment_cash_usd: draft.payment_cash_usd,
+                payment_transfer: draft.payment_transfer,
+                payment_points: draft.payment_points,
+                payment_points_mxn: draft.payment_points_mxn,
+                payment_stored_value: draft.payment_stored_value,
+                stored_value_code: draft.stored_value_code,
+                exchange_rate: draft.exchange_rate,
+                change_amount: draft.change_amount,
+                customer_id: draft.customer_id,
+                credit_amount: draft.credit_amount,
+                points_earned: draft.points_earned,
+                points_expiry_days: draft.points_expiry_days,
+                items,
+            },
         )
     }
 
-    fn cancel_sale_atomically(&self, sale_id: i64) -> AppResult<()> {
-        cancel_sale(self.db, sale_id)
+    fn cancel_sale_atomically(&self, sale_id: i64, refund_to_store_credit: bool) -> AppResult<()> {
+        cancel_sale(self.db, sale_id, refund_to_store_credit)
+    }
+
+    fn amend_sale_atomically(
+        &self,
+        sale_id: i64,
+        user_id: i64,
+        reason: &str,
+        before: &SaleRevisionSnapshot,
+        after: &SaleRevisionSnapshot,
+    ) -> AppResult<SaleRevision> {
+        amend_sale(self.db, sale_id, user_id, reason, before, after)
     }
 }
 
@@ -68,6 +101,27 @@ impl SaleDraftDependencies for SqliteSalesRepository<'_> {
     fn find_product(&self, id: i64) -> AppResult<Option<Product>> {
         find_product(self.db, id)
     }
+
+    fn find_customer_price_list(&self, customer_id: i64) -> AppResult<Option<PriceList>> {
+        crate::modules::pricing::adapters::outbound::sqlite::find_customer_price_list(
+            self.db,
+            customer_id,
+        )
+    }
+
+    fn loyalty_program(&self) -> AppResult<LoyaltyProgram> {
+        let conn = self.db.conn.lock()?;
+        loyalty::load_program(&conn)
+    }
+
+    fn stale_session_blocks_sales(&self, session_id: i64) -> AppResult<bool> {
+        let conn = self.db.conn.lock()?;
+        cash_register::stale_session_blocks_sales(&conn, session_id)
+    }
+
+    fn find_unit(&self, name: &str) -> AppResult<Option<Unit>> {
+        crate::modules::catalog::units::adapters::outbound::sqlite::find_by_name(self.db, name)
+    }
 }
 
 impl SalesQueryPort for SqliteSalesRepository<'_> {
@@ -89,19 +143,57 @@ impl SalesQueryPort for SqliteSalesRepository<'_> {
         &self,
         start: &str,
         end: &str,
+        register_id: Option<i64>,
         page: i64,
         page_size: i64,
     ) -> AppResult<(Vec<Sale>, i64)> {
-        find_by_date_range_paginated(self.db, start, end, page, page_size)
+        find_by_date_range_paginated(self.db, start, end, register_id, page, page_size)
+    }
+    fn search_sales(
+        &self,
+        criteria: &SaleSearchCriteria,
+        page: i64,
+        page_size: i64,
+    ) -> AppResult<(Vec<Sale>, i64)> {
+        search(self.db, criteria, page, page_size)
+    }
+    fn find_sales_for_report(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+    ) -> AppResult<Vec<Sale>> {
+        find_by_date_range(self.db, start, end, register_id)
+    }
+    fn find_revisions(&self, sale_id: i64) -> AppResult<Vec<SaleRevision>> {
+        find_revisions(self.db, sale_id)
     }
-    fn find_sales_for_report(&self, start: &str, end: &str) -> AppResult<Vec<Sale>> {
-        find_by_date_range(self.db, start, end)
+    fn report_metrics(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+    ) -> AppResult<SalesReportMetrics> {
+        report_metrics(self.db, start, end, register_id)
     }
-    fn report_metrics(&self, start: &str, end: &str) -> AppResult<SalesReportMetrics> {
-        report_metrics(self.db, start, end)
+    fn top_products(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+        category_id: Option<i64>,
+        limit: i64,
+    ) -> AppResult<Vec<TopProduct>> {
+        get_top_products(self.db, start, end, register_id, category_id, limit)
     }
-    fn top_products(&self, start: &str, end: &str, limit: i64) -> AppResult<Vec<TopProduct>> {
-        get_top_products(self.db, start, end, limit)
+    fn sales_by_category(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+        depth: i64,
+    ) -> AppResult<Vec<CategorySales>> {
+        get_sales_by_category(self.db, start, end, register_id, depth)
     }
 }
 
@@ -110,15 +202,20 @@ fn find_session(db: &Database, id: i64) -> AppResult<Option<CashRegisterSession>
     conn.query_row(
         "SELECT cr.id, cr.user_id, u.full_name, cr.opening_amount, cr.closing_amount, \
             cr.closing_cash_mxn, cr.closing_cash_usd, cr.exchange_rate, cr.status, \
-            cr.opened_at, cr.closed_at \
-            FROM cash_register_sessions cr JOIN users u ON cr.user_id = u.id WHERE cr.id = ?1",
+            cr.opened_at, cr.closed_at, cr.opening_cash_usd, cr.register_id, rg.name, \
+            cr.count_pending \
+            FROM cash_register_sessions cr JOIN users u ON cr.user_id = u.id \
+            LEFT JOIN registers rg ON cr.register_id = rg.id WHERE cr.id = ?1",
         params![id],
         |row| {
             Ok(CashRegisterSession {
                 id: row.get(0)?,
+                register_id: row.get(12)?,
+                register_name: row.get(13)?,
                 user_id: row.get(1)?,
                 user_name: row.get(2)?,
                 opening_amount: row.get(3)?,
+                opening_cash_usd: row.get(11)?,
                 closing_amount: row.get(4)?,
                 closing_cash_mxn: row.get(5)?,
                 closing_cash_usd: row.get(6)?,
@@ -126,6 +223,8 @@ fn find_session(db: &Database, id: i64) -> AppResult<Option<CashRegisterSession>
                 status: row.get::<_, SessionStatus>(8)?,
                 opened_at: row.get(9)?,
                 closed_at: row.get(10)?,
+                review: None,
+                count_pending: row.get(14)?,
                 total_sales: None,
                 total_transactions: None,
             })
@@ -139,8 +238,12 @@ fn find_product(db: &Database, id: i64) -> AppResult<Option<Product>> {
     let conn = db.conn.lock()?;
     conn.query_row(
         "SELECT p.id, p.name, p.description, p.barcode, p.price, p.unit, p.is_bulk, \
-            p.category_id, c.name, p.stock, p.min_stock, p.active, p.created_at, p.updated_at \
-            FROM products p LEFT JOIN categories c ON p.category_id = c.id WHERE p.id = ?1",
+            p.category_id, c.name, COALESCE(ka.available, p.stock), p.min_stock, p.active, \
+            p.created_at, p.updated_at, p.parent_id, \
+            (SELECT COUNT(*) FROM products v WHERE v.parent_id = p.id), p.cost, \
+            ka.kit_id IS NOT NULL \
+            FROM products p LEFT JOIN categories c ON p.category_id = c.id \
+            LEFT JOIN kit_availability ka ON ka.kit_id = p.id WHERE p.id = ?1",
         params![id],
         |row| {
             Ok(Product {
@@ -149,6 +252,7 @@ fn find_product(db: &Database, id: i64) -> AppResult<Option<Product>> {
                 description: row.get(2)?,
                 barcode: row.get(3)?,
                 price: row.get(4)?,
+                cost: row.get(16)?,
                 unit: row.get(5)?,
                 is_bulk: row.get::<_, i64>(6)? != 0,
                 category_id: row.get(7)?,
@@ -158,6 +262,9 @@ fn find_product(db: &Database, id: i64) -> AppResult<Option<Product>> {
                 active: row.get::<_, i64>(11)? != 0,
                 created_at: row.get(12)?,
                 updated_at: row.get(13)?,
+                parent_id: row.get(14)?,
+                variant_count: row.get(15)?,
+                is_kit: row.get(17)?,
             })
         },
     )
@@ -169,9 +276,35 @@ const SALE_SELECT: &str = "\
     SELECT s.id, s.cash_register_session_id, s.user_id, u.full_name, \
             s.total, s.customer_id, c.name, s.credit_amount, s.payment_method, s.payment_amount, \
             s.payment_cash_mxn, s.payment_cash_usd, s.payment_transfer, \
-            s.exchange_rate, s.change_amount, s.status, s.created_at \
+            s.exchange_rate, s.change_amount, s.status, s.created_at, \
+            s.payment_points, s.payment_points_mxn, s.points_earned, \
+            s.payment_stored_value, s.stored_value_account_id, \
+            (SELECT sva.code FROM stored_value_accounts sva WHERE sva.id = s.stored_value_account_id), \
+            s.stored_value_balance, s.register_id \
     FROM sales s JOIN users u ON s.user_id = u.id LEFT JOIN customers c ON s.customer_id = c.id";
 
+pub struct PreparedSale {
+    pub cash_register_session_id: i64,
+    pub user_id: i64,
+    pub total: f64,
+    pub payment_method: String,
+    pub payment_amount: f64,
+    pub payment_cash_mxn: f64,
+    pub payment_cash_usd: f64,
+    pub payment_transfer: f64,
+    pub payment_points: f64,
+    pub payment_points_mxn: f64,
+    pub payment_stored_value: f64,
+    pub stored_value_code: Option<String>,
+    pub exchange_rate: Option<f64>,
+    pub change_amount: f64,
+    pub customer_id: Option<i64>,
+    pub credit_amount: f64,
+    pub points_earned: f64,
+    pub points_expiry_days: Option<i64>,
+    pub items: Vec<PreparedSaleItem>,
+}
+
 pub struct PreparedSaleItem {
     pub product_id: i64,
     pub product_name: String,
@@ -181,13 +314,17 @@ pub struct PreparedSaleItem {
     pub input_value: f64,
     pub input_unit: String,
     pub unit_price: f64,
+    pub catalog_price: f64,
+    pub price_list_id: Option<i64>,
     pub subtotal: f64,
+    pub unit_cost: f64,
 }
 
 fn row_to_sale(row: &rusqlite::Row) -> rusqlite::Result<Sale> {
     Ok(Sale {
         id: row.get(0)?,
         cash_register_session_id: row.get(1)?,
+        register_id: row.get(24)?,
         user_id: row.get(2)?,
         user_name: row.get(3)?,
         total: row.get(4)?,
@@ -199,6 +336,13 @@ fn row_to_sale(row: &rusqlite::Row) -> rusqlite::Result<Sale> {
         payment_cash_mxn: row.get(10)?,
         payment_cash_usd: row.get(11)?,
         payment_transfer: row.get(12)?,
+        payment_points: row.get(17)?,
+        payment_points_mxn: row.get(18)?,
+        points_earned: row.get(19)?,
+        payment_stored_value: row.get(20)?,
+        stored_value_account_id: row.get(21)?,
+        stored_value_code: row.get(22)?,
+        stored_value_balance: row.get(23)?,
         exchange_rate: row.get(13)?,
         change_amount: row.get(14)?,
         status: row.get(15)?,
@@ -213,7 +357,7 @@ fn find_sale_items_by_sale_id(
 ) -> AppResult<Vec<SaleItem>> {
     let mut stmt = conn.prepare(
         "SELECT id, sale_id, product_id, product_name, quantity, base_unit, input_mode, \
-                input_value, input_unit, unit_price, subtotal \
+                input_value, input_unit, unit_price, price_list_id, subtotal, unit_cost \
             FROM sale_items WHERE sale_id = ?1",
     )?;
 
@@ -230,7 +374,9 @@ fn find_sale_items_by_sale_id(
                 input_value: row.get(7)?,
                 input_unit: row.get(8)?,
                 unit_price: row.get(9)?,
-                subtotal: row.get(10)?,
+                price_list_id: row.get(10)?,
+                subtotal: row.get(11)?,
+                unit_cost: row.get(12)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -247,24 +393,16 @@ fn load_items_for_sales(conn: &rusqlite::Connection, sales: Vec<Sale>) -> AppRes
     Ok(result)
 }
 
-pub fn create(
-    db: &Database,
-    cash_register_session_id: i64,
-    user_id: i64,
-    total: f64,
-    payment_method: &str,
-    payment_amount: f64,
-    payment_cash_mxn: f64,
-    payment_cash_usd: f64,
-    payment_transfer: f64,
-    exchange_rate: Option<f64>,
-    change_amount: f64,
-    customer_id: Option<i64>,
-    credit_amount: f64,
-    items: &[PreparedSaleItem],
-) -> AppResult<Sale> {
+pub fn create(db: &Database, sale: &PreparedSale) -> AppResult<Sale> {
     let mut conn = db.conn.lock()?;
     let tx = conn.transaction()?;
+    let PreparedSale {
+        cash_register_session_id,
+        user_id,
+        customer_id,
+        credit_amount,
+        ..
+    } = *sale;
 
     if credit_amount > 0.0 {
         let customer_id = customer_id.ok_or_else(|| {
@@ -293,29 +431,84 @@ pub fn create(
         }
     }
 
+    let redemption = if sale.payment_stored_value > 0.0 {
+        Some(stored_value::reserve_redemption(
+            &tx,
+            sale.stored_value_code.as_deref(),
+            customer_id,
+            sale.payment_stored_value,
+        )?)
+    } else {
+        None
+    };
+
     tx.execute(
         "INSERT INTO sales (cash_register_session_id, user_id, total, customer_id, credit_amount, payment_method, \
             payment_amount, payment_cash_mxn, payment_cash_usd, payment_transfer, \
-            exchange_rate, change_amount) \
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            exchange_rate, change_amount, payment_points, payment_points_mxn, points_earned, \
+            payment_stored_value, stored_value_account_id, stored_value_balance, register_id) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, \
+            (SELECT register_id FROM cash_register_sessions WHERE id = ?1))",
         params![
             cash_register_session_id,
             user_id,
-            total,
+            sale.total,
             customer_id,
             money::round2(credit_amount),
-            payment_method,
-            payment_amount,
-            payment_cash_mxn,
-            payment_cash_usd,
-            payment_transfer,
-            exchange_rate,
-            change_amount
+            sale.payment_method,
+            sale.payment_amount,
+            sale.payment_cash_mxn,
+            sale.payment_cash_usd,
+            sale.payment_transfer,
+            sale.exchange_rate,
+            sale.change_amount,
+            sale.payment_points,
+            money::round2(sale.payment_points_mxn),
+            sale.points_earned,
+            money::round2(sale.payment_stored_value),
+            redemption.map(|(account_id, _)| account_id),
+            redemption.map(|(_, balance)| balance)
         ],
     )?;
 
     let sale_id = tx.last_insert_rowid();
 
+    if let Some((account_id, _)) = redemption {
+        stored_value::record_movement(
+            &tx,
+            &stored_value::NewStoredValueMovement {
+                account_id,
+                sale_id: Some(sale_id),
+                cash_register_session_id,
+                user_id,
+                movement_type: StoredValueMovementType::Redeem,
+                amount: -sale.payment_stored_value,
+                payment_cash_mxn: 0.0,
+                payment_cash_usd: 0.0,
+                payment_transfer: 0.0,
+                exchange_rate: None,
+                notes: None,
+            },
+        )?;
+    }
+
+    if let Some(customer_id) = customer_id {
+        if sale.payment_points > 0.0 || sale.points_earned > 0.0 {
+            loyalty::record_sale_points(
+                &tx,
+                &loyalty::SalePoints {
+                    sale_id,
+                    customer_id,
+                    user_id,
+                    redeemed: sale.payment_points,
+                    redeemed_mxn: sale.payment_points_mxn,
+                    earned: sale.points_earned,
+                    expiry_days: sale.points_expiry_days,
+                },
+            )?;
+        }
+    }
+
     if credit_amount > 0.0 {
         tx.execute(
             "INSERT INTO customer_account_movements (customer_id, sale_id, cash_register_session_id, user_id, movement_type, amount) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
@@ -323,12 +516,24 @@ pub fn create(
         )?;
     }
 
-    for item in items {
+    for item in &sale.items {
         let quantity = money::round3(item.quantity);
+        let components = kit_components(&tx, item.product_id)?;
+        // A kit costs what the components it takes out of stock cost.
+        let unit_cost = if components.is_empty() {
+            item.unit_cost
+        } else {
+            money::sum_money(
+                components
+                    .iter()
+                    .map(|(_, _, per_kit, cost)| money::mul_money(*cost, *per_kit)),
+            )
+        };
         tx.execute(
             "INSERT INTO sale_items (sale_id, product_id, product_name, quantity, base_unit, \
-                input_mode, input_value, input_unit, unit_price, subtotal) \
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                input_mode, input_value, input_unit, unit_price, price_list_id, subtotal, unit_cost, \
+                catalog_price) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 sale_id,
                 item.product_id,
@@ -339,33 +544,28 @@ pub fn create(
                 item.input_value,
                 item.input_unit,
                 item.unit_price,
+                item.price_list_id,
                 item.subtotal,
+                unit_cost,
+                item.catalog_price,
             ],
         )?;
+        let sale_item_id = tx.last_insert_rowid();
 
-        let current_stock: f64 = tx
-            .query_row(
-                "SELECT stock FROM products WHERE id = ?1",
-                params![item.product_id],
-                |row| row.get(0),
-            )
-            .map_err(|_| {
-                AppError::NotFound(format!("Producto '{}' no encontrado", item.product_name))
-            })?;
-
-        let available_stock = money::round3(current_stock);
-        if available_stock < quantity {
-            return Err(AppError::Validation(format!(
-                "Stock insuficiente para '{}'. Disponible: {}, Solicitado: {}",
-                item.product_name, available_stock, quantity
-            )));
+        if components.is_empty() {
+            deduct_stock(&tx, item.product_id, &item.product_name, quantity)?;
+        }
+        // Recorded per sale so a cancellation restocks what was actually taken,
+        // even if the kit is rearmed later.
+        for (component_id, component_name, per_kit, _) in components {
+            let used = money::round3(per_kit * quantity);
+            deduct_stock(&tx, component_id, &component_name, used)?;
+            tx.execute(
+                "INSERT INTO sale_item_components (sale_item_id, product_id, quantity) \
+                    VALUES (?1, ?2, ?3)",
+                params![sale_item_id, component_id, used],
+            )?;
         }
-
-        let new_stock = money::sub_stock(available_stock, quantity);
-        tx.execute(
-            "UPDATE products SET stock = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
-            params![new_stock, item.product_id],
-        )?;
     }
 
     tx.commit()?;
@@ -374,6 +574,70 @@ pub fn create(
         .ok_or_else(|| AppError::NotFound("Failed to retrieve created sale".to_string()))
 }
 
+/// Component id, name, quantity per kit and cost; empty for regular products.
+fn kit_components(
+    conn: &rusqlite::Connection,
+    product_id: i64,
+) -> AppResult<Vec<(i64, String, f64, f64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT k.component_id, c.name, k.quantity, c.cost FROM product_kit_components k \
+            JOIN products c ON k.component_id = c.id WHERE k.kit_id = ?1",
+    )?;
+    let components = stmt
+        .query_map(params![product_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(components)
+}
+
+fn deduct_stock(
+    conn: &rusqlite::Connection,
+    product_id: i64,
+    name: &str,
+    quantity: f64,
+) -> AppResult<()> {
+    let current_stock: f64 = conn
+        .query_row(
+            "SELECT stock FROM products WHERE id = ?1",
+            params![product_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| AppError::NotFound(format!("Producto '{}' no encontrado", name)))?;
+
+    let available_stock = money::round3(current_stock);
+    if available_stock < quantity {
+        return Err(AppError::Validation(format!(
+            "Stock insuficiente para '{}'. Disponible: {}, Solicitado: {}",
+            name, available_stock, quantity
+        )));
+    }
+
+    let new_stock = money::sub_stock(available_stock, quantity);
+    conn.execute(
+        "UPDATE products SET stock = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+        params![new_stock, product_id],
+    )?;
+    Ok(())
+}
+
+fn restock(conn: &rusqlite::Connection, product_id: i64, quantity: f64) -> AppResult<()> {
+    let current_stock: f64 = conn
+        .query_row(
+            "SELECT stock FROM products WHERE id = ?1",
+            params![product_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| AppError::NotFound(format!("Producto con ID {} no encontrado", product_id)))?;
+
+    let new_stock = money::add_stock(money::round3(current_stock), money::round3(quantity));
+    conn.execute(
+        "UPDATE products SET stock = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
+        params![new_stock, product_id],
+    )?;
+    Ok(())
+}
+
 pub fn find_by_id(db: &Database, id: i64) -> AppResult<Option<Sale>> {
     let conn = db.conn.lock()?;
     let query = format!("{} WHERE s.id = ?1", SALE_SELECT);
@@ -432,19 +696,22 @@ pub fn find_by_date_range_paginated(
     db: &Database,
     start_date: &str,
     end_date: &str,
+    register_id: Option<i64>,
     page: i64,
     page_size: i64,
 ) -> AppResult<(Vec<Sale>, i64)> {
     let conn = db.conn.lock()?;
 
     let total: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM sales s WHERE s.created_at >= ?1 AND s.created_at <= ?2",
-        params![start_date, end_date],
+        "SELECT COUNT(*) FROM sales s WHERE s.created_at >= ?1 AND s.created_at <= ?2 \
+            AND (?3 IS NULL OR s.register_id = ?3)",
+        params![start_date, end_date, register_id],
         |row| row.get(0),
     )?;
 
     let query = format!(
-        "{} WHERE s.created_at >= ?1 AND s.created_at <= ?2 ORDER BY s.id DESC LIMIT ?3 OFFSET ?4",
+        "{} WHERE s.created_at >= ?1 AND s.created_at <= ?2 AND (?5 IS NULL OR s.register_id = ?5) \
+            ORDER BY s.id DESC LIMIT ?3 OFFSET ?4",
         SALE_SELECT
     );
     let offset = (page - 1) * page_size;
@@ -452,7 +719,7 @@ pub fn find_by_date_range_paginated(
 
     let sales = stmt
         .query_map(
-            params![start_date, end_date, page_size, offset],
+            params![start_date, end_date, page_size, offset, register_id],
             row_to_sale,
         )?
         .collect::<Result<Vec<_>, _>>()?;
@@ -461,25 +728,181 @@ pub fn find_by_date_range_paginated(
     Ok((sales, total))
 }
 
-pub fn find_by_date_range(db: &Database, start_date: &str, end_date: &str) -> AppResult<Vec<Sale>> {
+pub fn find_by_date_range(
+    db: &Database,
+    start_date: &str,
+    end_date: &str,
+    register_id: Option<i64>,
+) -> AppResult<Vec<Sale>> {
     let conn = db.conn.lock()?;
     let query = format!(
-        "{} WHERE s.created_at >= ?1 AND s.created_at <= ?2 ORDER BY s.id DESC",
+        "{} WHERE s.created_at >= ?1 AND s.created_at <= ?2 AND (?3 IS NULL OR s.register_id = ?3) \
+            ORDER BY s.id DESC",
         SALE_SELECT
     );
     let mut stmt = conn.prepare(&query)?;
 
     let sales = stmt
-        .query_map(params![start_date, end_date], row_to_sale)?
+        .query_map(params![start_date, end_date, register_id], row_to_sale)?
         .collect::<Result<Vec<_>, _>>()?;
 
     load_items_for_sales(&conn, sales)
 }
 
+/// WHERE clause of a sale search. User input only ever reaches SQLite as a
+/// bound value; the SQL text is assembled from fixed fragments.
+#[derive(Default)]
+struct SearchFilter {
+    clauses: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl SearchFilter {
+    fn bind(&mut self, value: impl Into<Value>) -> String {
+        self.values.push(value.into());
+        format!("?{}", self.values.len())
+    }
+
+    fn sql(&self) -> String {
+        if self.clauses.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.clauses.join(" AND "))
+        }
+    }
+}
+
+/// Escapes LIKE wildcards so a search term always matches literally.
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+fn search_filter(criteria: &SaleSearchCriteria) -> SearchFilter {
+    let mut filter = SearchFilter::default();
+    if let Some(query) = criteria.query.as_deref() {
+        let pattern = filter.bind(like_pattern(query));
+        let mut matches = vec![
+            format!("c.name LIKE {pattern} ESCAPE '\\'"),
+            format!("u.full_name LIKE {pattern} ESCAPE '\\'"),
+            format!(
+                "EXISTS (SELECT 1 FROM sale_items si WHERE si.sale_id = s.id \
+                    AND si.product_name LIKE {pattern} ESCAPE '\\')"
+            ),
+        ];
+        if let Ok(folio) = query.trim_start_matches('#').parse::<i64>() {
+            let folio = filter.bind(folio);
+            matches.push(format!("s.id = {folio}"));
+        }
+        filter.clauses.push(format!("({})", matches.join(" OR ")));
+    }
+    if let Some(folio) = criteria.folio {
+        let folio = filter.bind(folio);
+        filter.clauses.push(format!("s.id = {folio}"));
+    }
+    if let Some(product_id) = criteria.product_id {
+        let product_id = filter.bind(product_id);
+        filter.clauses.push(format!(
+            "EXISTS (SELECT 1 FROM sale_items si WHERE si.sale_id = s.id AND si.product_id = {product_id})"
+        ));
+    }
+    if let Some(customer_id) = criteria.customer_id {
+        let customer_id = filter.bind(customer_id);
+        filter
+            .clauses
+            .push(format!("s.customer_id = {customer_id}"));
+    }
+    if let Some(user_id) = criteria.user_id {
+        let user_id = filter.bind(user_id);
+        filter.clauses.push(format!("s.user_id = {user_id}"));
+    }
+    if let Some(register_id) = criteria.register_id {
+        let register_id = filter.bind(register_id);
+        filter
+            .clauses
+            .push(format!("s.register_id = {register_id}"));
+    }
+    if let Some(method) = criteria.payment_method.as_deref() {
+        let method = filter.bind(method.to_string());
+        filter.clauses.push(format!("s.payment_method = {method}"));
+    }
+    if let Some(status) = &criteria.status {
+        let status = filter.bind(status.as_str().to_string());
+        filter.clauses.push(format!("s.status = {status}"));
+    }
+    if let Some(min_total) = criteria.min_total {
+        let min_total = filter.bind(min_total);
+        filter.clauses.push(format!("s.total >= {min_total}"));
+    }
+    if let Some(max_total) = criteria.max_total {
+        let max_total = filter.bind(max_total);
+        filter.clauses.push(format!("s.total <= {max_total}"));
+    }
+    if let Some(start) = criteria.start_date.as_deref() {
+        let start = filter.bind(start.to_string());
+        filter.clauses.push(format!("s.created_at >= {start}"));
+    }
+    if let Some(end) = criteria.end_date.as_deref() {
+        let end = filter.bind(end.to_string());
+        filter.clauses.push(format!("s.created_at <= {end}"));
+    }
+    filter
+}
+
+pub fn search(
+    db: &Database,
+    criteria: &SaleSearchCriteria,
+    page: i64,
+    page_size: i64,
+) -> AppResult<(Vec<Sale>, i64)> {
+    let conn = db.conn.lock()?;
+    let mut filter = search_filter(criteria);
+    let where_sql = filter.sql();
+
+    let total: i64 = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM sales s JOIN users u ON s.user_id = u.id \
+                LEFT JOIN customers c ON s.customer_id = c.id{}",
+            where_sql
+        ),
+        params_from_iter(filter.values.iter()),
+        |row| row.get(0),
+    )?;
+
+    let order_column = match criteria.sort_by {
+        SaleSortField::CreatedAt => "s.created_at",
+        SaleSortField::Total => "s.total",
+        SaleSortField::Folio => "s.id",
+    };
+    let direction = match criteria.sort_direction {
+        SortDirection::Asc => "ASC",
+        SortDirection::Desc => "DESC",
+    };
+    let limit = filter.bind(page_size);
+    let offset = filter.bind((page - 1) * page_size);
+    let query = format!(
+        "{}{} ORDER BY {} {}, s.id {} LIMIT {} OFFSET {}",
+        SALE_SELECT, where_sql, order_column, direction, direction, limit, offset
+    );
+    let sales = conn
+        .prepare(&query)?
+        .query_map(params_from_iter(filter.values.iter()), row_to_sale)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let sales = load_items_for_sales(&conn, sales)?;
+    Ok((sales, total))
+}
+
+/// `category_id` keeps products in that category or any of its subcategories.
 pub fn get_top_products(
     db: &Database,
     start_date: &str,
     end_date: &str,
+    register_id: Option<i64>,
+    category_id: Option<i64>,
     limit: i64,
 ) -> AppResult<Vec<TopProduct>> {
     let conn = db.conn.lock()?;
@@ -488,13 +911,28 @@ pub fn get_top_products(
                 SUM(si.quantity) as total_qty, SUM(si.subtotal) as total_rev \
             FROM sale_items si JOIN sales s ON si.sale_id = s.id \
             WHERE s.created_at >= ?1 AND s.created_at <= ?2 AND s.status = ?3 \
+            AND (?5 IS NULL OR s.register_id = ?5) \
+            AND (?6 IS NULL OR si.product_id IN ( \
+                WITH RECURSIVE subtree(id) AS ( \
+                    SELECT ?6 \
+                    UNION ALL \
+                    SELECT c.id FROM categories c JOIN subtree ON c.parent_id = subtree.id \
+                ) \
+                SELECT p.id FROM products p WHERE p.category_id IN (SELECT id FROM subtree))) \
             GROUP BY si.product_id, si.product_name \
             ORDER BY total_qty DESC LIMIT ?4",
     )?;
 
     let products = stmt
         .query_map(
-            params![start_date, end_date, SaleStatus::Completed, limit],
+            params![
+                start_date,
+                end_date,
+                SaleStatus::Completed,
+                limit,
+                register_id,
+                category_id
+            ],
             |row| {
                 Ok(TopProduct {
                     product_id: row.get(0)?,
@@ -509,6 +947,57 @@ pub fn get_top_products(
     Ok(products)
 }
 
+pub fn get_sales_by_category(
+    db: &Database,
+    start_date: &str,
+    end_date: &str,
+    register_id: Option<i64>,
+    depth: i64,
+) -> AppResult<Vec<CategorySales>> {
+    let conn = db.conn.lock()?;
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE category_tree(id, rollup_id, depth) AS ( \
+                SELECT id, id, 0 FROM categories WHERE parent_id IS NULL \
+                UNION ALL \
+                SELECT c.id, CASE WHEN t.depth < ?5 THEN c.id ELSE t.rollup_id END, t.depth + 1 \
+                    FROM categories c JOIN category_tree t ON c.parent_id = t.id \
+            ) \
+            SELECT t.rollup_id, rc.name, SUM(si.quantity), SUM(si.subtotal), \
+                COUNT(DISTINCT s.id) \
+            FROM sale_items si JOIN sales s ON si.sale_id = s.id \
+            LEFT JOIN products p ON p.id = si.product_id \
+            LEFT JOIN category_tree t ON t.id = p.category_id \
+            LEFT JOIN categories rc ON rc.id = t.rollup_id \
+            WHERE s.created_at >= ?1 AND s.created_at <= ?2 AND s.status = ?3 \
+            AND (?4 IS NULL OR s.register_id = ?4) \
+            GROUP BY t.rollup_id, rc.name \
+            ORDER BY SUM(si.subtotal) DESC",
+    )?;
+
+    let categories = stmt
+        .query_map(
+            params![
+                start_date,
+                end_date,
+                SaleStatus::Completed,
+                register_id,
+                depth
+            ],
+            |row| {
+                Ok(CategorySales {
+                    category_id: row.get(0)?,
+                    category_name: row.get(1)?,
+                    total_quantity: money::round3(row.get::<_, f64>(2)?),
+                    total_revenue: money::round2(row.get::<_, f64>(3)?),
+                    total_transactions: row.get(4)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(categories)
+}
+
 fn row_to_customer(row: &rusqlite::Row) -> rusqlite::Result<Customer> {
     Ok(Customer {
         id: row.get(0)?,
@@ -516,6 +1005,8 @@ fn row_to_customer(row: &rusqlite::Row) -> rusqlite::Result<Customer> {
         phone: row.get(2)?,
         notes: row.get(3)?,
         credit_limit: money::round2(row.get(4)?),
+        price_list_id: row.get(9)?,
+        price_list_name: row.get(10)?,
         active: row.get::<_, i64>(5)? != 0,
         balance: money::round2(row.get(6)?),
         created_at: row.get(7)?,
@@ -523,11 +1014,16 @@ fn row_to_customer(row: &rusqlite::Row) -> rusqlite::Result<Customer> {
     })
 }
 
-fn report_metrics(db: &Database, start: &str, end: &str) -> AppResult<SalesReportMetrics> {
+fn report_metrics(
+    db: &Database,
+    start: &str,
+    end: &str,
+    register_id: Option<i64>,
+) -> AppResult<SalesReportMetrics> {
     let conn = db.conn.lock()?;
     let total_credit_sold: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(credit_amount), 0) FROM sales WHERE status = 'completed' AND created_at >= ?1 AND created_at <= ?2",
-        params![start, end], |row| row.get(0),
+        "SELECT COALESCE(SUM(credit_amount), 0) FROM sales WHERE status = 'completed' AND created_at >= ?1 AND created_at <= ?2 AND (?3 IS NULL OR register_id = ?3)",
+        params![start, end, register_id], |row| row.get(0),
     )?;
     let total_account_collected: f64 = conn.query_row(
         "SELECT COALESCE(-SUM(amount), 0) FROM customer_account_movements WHERE movement_type = ?1 AND created_at >= ?2 AND created_at <= ?3",
@@ -540,7 +1036,9 @@ fn report_metrics(db: &Database, start: &str, end: &str) -> AppResult<SalesRepor
     )?;
     let debtors_sql = "SELECT c.id, c.name, c.phone, c.notes, c.credit_limit, c.active, \
         COALESCE(SUM(CASE WHEN m.created_at <= ?1 THEN m.amount ELSE 0 END), 0), \
-        c.created_at, c.updated_at FROM customers c LEFT JOIN customer_account_movements m \
+        c.created_at, c.updated_at, c.price_list_id, \
+        (SELECT pl.name FROM price_lists pl WHERE pl.id = c.price_list_id) \
+        FROM customers c LEFT JOIN customer_account_movements m \
         ON m.customer_id = c.id GROUP BY c.id HAVING COALESCE(SUM(CASE WHEN m.created_at <= ?1 \
         THEN m.amount ELSE 0 END), 0) > 0 ORDER BY 7 DESC LIMIT 5";
     let top_debtors = conn
@@ -555,15 +1053,15 @@ fn report_metrics(db: &Database, start: &str, end: &str) -> AppResult<SalesRepor
     })
 }
 
-pub fn cancel_sale(db: &Database, sale_id: i64) -> AppResult<()> {
+pub fn cancel_sale(db: &Database, sale_id: i64, refund_to_store_credit: bool) -> AppResult<()> {
     let mut conn = db.conn.lock()?;
     let tx = conn.transaction()?;
 
-    let (status, credit_amount): (SaleStatus, f64) = tx
+    let (status, credit_amount, user_id): (SaleStatus, f64, i64) = tx
         .query_row(
-            "SELECT status, credit_amount FROM sales WHERE id = ?1",
+            "SELECT status, credit_amount, user_id FROM sales WHERE id = ?1",
             params![sale_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
         .map_err(|_| AppError::NotFound("Venta no encontrada".to_string()))?;
 
@@ -576,31 +1074,28 @@ pub fn cancel_sale(db: &Database, sale_id: i64) -> AppResult<()> {
         ));
     }
 
-    let items: Vec<(i64, f64)> = {
-        let mut stmt =
-            tx.prepare("SELECT product_id, quantity FROM sale_items WHERE sale_id = ?1")?;
+    // Kit lines give back the components they deducted instead of the kit.
+    let movements: Vec<(i64, f64)> = {
+        let mut stmt = tx.prepare(
+            "SELECT COALESCE(sic.product_id, si.product_id), COALESCE(sic.quantity, si.quantity) \
+                FROM sale_items si \
+                LEFT JOIN sale_item_components sic ON sic.sale_item_id = si.id \
+                WHERE si.sale_id = ?1",
+        )?;
         let rows = stmt
             .query_map(params![sale_id], |row| Ok((row.get(0)?, row.get(1)?)))?
             .collect::<Result<Vec<_>, _>>()?;
         rows
     };
 
-    for (product_id, quantity) in items {
-        let current_stock: f64 = tx
-            .query_row(
-                "SELECT stock FROM products WHERE id = ?1",
-                params![product_id],
-                |row| row.get(0),
-            )
-            .map_err(|_| {
-                AppError::NotFound(format!("Producto con ID {} no encontrado", product_id))
-            })?;
+    for (product_id, quantity) in movements {
+        restock(&tx, product_id, quantity)?;
+    }
 
-        let new_stock = money::add_stock(money::round3(current_stock), money::round3(quantity));
-        tx.execute(
-            "UPDATE products SET stock = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
-            params![new_stock, product_id],
-        )?;
+    loyalty::reverse_sale_points(&tx, sale_id, user_id)?;
+    stored_value::reverse_sale_redemption(&tx, sale_id, user_id)?;
+    if refund_to_store_credit {
+        refund_sale_to_store_credit(&tx, sale_id, user_id)?;
     }
 
     tx.execute(
@@ -612,11 +1107,210 @@ pub fn cancel_sale(db: &Database, sale_id: i64) -> AppResult<()> {
     Ok(())
 }
 
+const REVISION_SELECT: &str = "\
+    SELECT r.id, r.sale_id, r.user_id, u.full_name, r.reason, r.before_snapshot, \
+            r.after_snapshot, r.created_at \
+    FROM sale_revisions r JOIN users u ON r.user_id = u.id";
+
+fn snapshot_column(row: &rusqlite::Row, index: usize) -> rusqlite::Result<SaleRevisionSnapshot> {
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json).map_err(|error| {
+        rusqlite::Error::FromSqlConversionFailure(
+            index,
+            rusqlite::types::Type::Text,
+            Box::new(error),
+        )
+    })
+}
+
+fn row_to_revision(row: &rusqlite::Row) -> rusqlite::Result<SaleRevision> {
+    Ok(SaleRevision {
+        id: row.get(0)?,
+        sale_id: row.get(1)?,
+        user_id: row.get(2)?,
+        user_name: row.get(3)?,
+        reason: row.get(4)?,
+        before: snapshot_column(row, 5)?,
+        after: snapshot_column(row, 6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+pub fn find_revisions(db: &Database, sale_id: i64) -> AppResult<Vec<SaleRevision>> {
+    let conn = db.conn.lock()?;
+    let revisions = conn
+        .prepare(&format!(
+            "{} WHERE r.sale_id = ?1 ORDER BY r.id DESC",
+            REVISION_SELECT
+        ))?
+        .query_map(params![sale_id], row_to_revision)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(revisions)
+}
+
+/// Applies an amendment and records its before/after snapshot. The update is
+/// guarded by the `before` values so a concurrent change is not overwritten.
+pub fn amend_sale(
+    db: &Database,
+    sale_id: i64,
+    user_id: i64,
+    reason: &str,
+    before: &SaleRevisionSnapshot,
+    after: &SaleRevisionSnapshot,
+) -> AppResult<SaleRevision> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+
+    if after.customer_id != before.customer_id {
+        if let Some(customer_id) = after.customer_id {
+            let active: bool = tx
+                .query_row(
+                    "SELECT active FROM customers WHERE id = ?1",
+                    params![customer_id],
+                    |row| row.get::<_, i64>(0).map(|value| value != 0),
+                )
+                .optional()?
+                .ok_or_else(|| AppError::NotFound("Cliente no encontrado".to_string()))?;
+            if !active {
+                return Err(AppError::Conflict(
+                    "El cliente seleccionado está inactivo".to_string(),
+                ));
+            }
+        }
+    }
+
+    let changed = tx.execute(
+        "UPDATE sales SET customer_id = ?1, payment_method = ?2, payment_amount = ?3, \
+            payment_cash_mxn = ?4, payment_cash_usd = ?5, payment_transfer = ?6, \
+            change_amount = ?7 \
+            WHERE id = ?8 AND status = ?9 AND customer_id IS ?10 AND payment_method = ?11 \
+            AND payment_cash_mxn = ?12 AND payment_cash_usd = ?13 AND payment_transfer = ?14",
+        params![
+            after.customer_id,
+            after.payment_method,
+            after.payment_amount,
+            after.payment_cash_mxn,
+            after.payment_cash_usd,
+            after.payment_transfer,
+            after.change_amount,
+            sale_id,
+            SaleStatus::Completed,
+            before.customer_id,
+            before.payment_method,
+            before.payment_cash_mxn,
+            before.payment_cash_usd,
+            before.payment_transfer
+        ],
+    )?;
+    if changed == 0 {
+        return Err(AppError::Conflict(
+            "La venta cambió mientras se corregía; vuelve a intentarlo".to_string(),
+        ));
+    }
+
+    let to_json = |snapshot: &SaleRevisionSnapshot| {
+        serde_json::to_string(snapshot).map_err(|error| AppError::Database(error.to_string()))
+    };
+    tx.execute(
+        "INSERT INTO sale_revisions (sale_id, user_id, reason, before_snapshot, after_snapshot) \
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![sale_id, user_id, reason, to_json(before)?, to_json(after)?],
+    )?;
+    let revision_id = tx.last_insert_rowid();
+    let revision = tx.query_row(
+        &format!("{} WHERE r.id = ?1", REVISION_SELECT),
+        params![revision_id],
+        row_to_revision,
+    )?;
+    tx.commit()?;
+    Ok(revision)
+}
+
+/// Keeps the money tendered for a cancelled sale as store credit of its
+/// customer instead of handing it back. The tenders stay on the movement so
+/// the sale's cash session still expects them in the drawer, which is why the
+/// session must still be open.
+fn refund_sale_to_store_credit(
+    conn: &rusqlite::Connection,
+    sale_id: i64,
+    user_id: i64,
+) -> AppResult<()> {
+    let (customer_id, session_id, cash_mxn, cash_usd, transfer, exchange_rate, change, status): (
+        Option<i64>,
+        i64,
+        f64,
+        f64,
+        f64,
+        Option<f64>,
+        f64,
+        SessionStatus,
+    ) = conn.query_row(
+        "SELECT s.customer_id, s.cash_register_session_id, s.payment_cash_mxn, \
+            s.payment_cash_usd, s.payment_transfer, s.exchange_rate, s.change_amount, cr.status \
+            FROM sales s JOIN cash_register_sessions cr ON cr.id = s.cash_register_session_id \
+            WHERE s.id = ?1",
+        params![sale_id],
+        |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+                row.get(7)?,
+            ))
+        },
+    )?;
+    if status != SessionStatus::Open {
+        return Err(AppError::Conflict(
+            "La sesión de caja de la venta ya está cerrada; no se puede reembolsar como saldo a favor"
+                .to_string(),
+        ));
+    }
+    let customer_id = customer_id.ok_or_else(|| {
+        AppError::Validation(
+            "Solo las ventas con cliente se pueden reembolsar como saldo a favor".to_string(),
+        )
+    })?;
+    let cash_mxn = money::sub_money(cash_mxn, change);
+    let amount = money::total_paid_mxn(cash_mxn, cash_usd, transfer, exchange_rate.unwrap_or(1.0));
+    if amount <= 0.0 {
+        return Ok(());
+    }
+    let account_id = stored_value::store_credit_account(conn, customer_id)?;
+    stored_value::record_movement(
+        conn,
+        &stored_value::NewStoredValueMovement {
+            account_id,
+            sale_id: Some(sale_id),
+            cash_register_session_id: session_id,
+            user_id,
+            movement_type: StoredValueMovementType::Refund,
+            amount,
+            payment_cash_mxn: cash_mxn,
+            payment_cash_usd: cash_usd,
+            payment_transfer: transfer,
+            exchange_rate,
+            notes: Some("Reembolso de venta cancelada"),
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{cancel_sale, create, PreparedSaleItem};
+    use super::{
+        amend_sale, cancel_sale, create, find_by_date_range, find_by_date_range_paginated,
+        find_revisions, get_sales_by_category, get_top_products, like_pattern, search,
+        PreparedSale, PreparedSaleItem,
+    };
     use crate::infrastructure::sqlite::Database;
-    use crate::models::sale::SaleInputMode;
+    use crate::models::sale::{
+        SaleInputMode, SaleRevisionSnapshot, SaleSearchCriteria, SaleSortField, SaleStatus,
+        SortDirection,
+    };
+    use crate::shared::error::AppError;
     use rusqlite::Connection;
     use std::sync::Mutex;
 
@@ -627,10 +1321,18 @@ mod tests {
                 id INTEGER PRIMARY KEY,
                 full_name TEXT NOT NULL
             );
+            CREATE TABLE categories (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                parent_id INTEGER
+            );
             CREATE TABLE products (
                 id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL DEFAULT 'Producto',
                 stock REAL NOT NULL,
-                updated_at TEXT
+                cost REAL NOT NULL DEFAULT 0,
+                updated_at TEXT,
+                category_id INTEGER
             );
             CREATE TABLE sales (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -646,6 +1348,13 @@ mod tests {
                 payment_transfer REAL NOT NULL,
                 exchange_rate REAL,
                 change_amount REAL NOT NULL,
+                payment_points REAL NOT NULL DEFAULT 0,
+                payment_points_mxn REAL NOT NULL DEFAULT 0,
+                points_earned REAL NOT NULL DEFAULT 0,
+                payment_stored_value REAL NOT NULL DEFAULT 0,
+                stored_value_account_id INTEGER,
+                stored_value_balance REAL,
+                register_id INTEGER,
                 status TEXT NOT NULL DEFAULT 'completed',
                 created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00'
             );
@@ -660,7 +1369,10 @@ mod tests {
                 input_value REAL,
                 input_unit TEXT,
                 unit_price REAL NOT NULL,
-                subtotal REAL NOT NULL
+                price_list_id INTEGER,
+                subtotal REAL NOT NULL,
+                unit_cost REAL,
+                catalog_price REAL
             );
             CREATE TABLE customers (
                 id INTEGER PRIMARY KEY,
@@ -687,9 +1399,72 @@ mod tests {
                 notes TEXT,
                 created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00'
             );
-            INSERT INTO users (id, full_name) VALUES (1, 'Test User');",
+            CREATE TABLE customer_points_movements (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                customer_id INTEGER NOT NULL,
+                sale_id INTEGER,
+                user_id INTEGER,
+                movement_type TEXT NOT NULL,
+                points REAL NOT NULL,
+                amount_mxn REAL NOT NULL DEFAULT 0,
+                expires_at TEXT,
+                notes TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime'))
+            );
+            CREATE TABLE loyalty_category_rules (
+                category_id INTEGER PRIMARY KEY,
+                points_per_mxn REAL NOT NULL
+            );
+            CREATE TABLE stored_value_accounts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                code TEXT UNIQUE COLLATE NOCASE,
+                customer_id INTEGER,
+                active INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT DEFAULT '2026-01-01 00:00:00',
+                updated_at TEXT DEFAULT '2026-01-01 00:00:00'
+            );
+            CREATE TABLE stored_value_movements (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                account_id INTEGER NOT NULL,
+                sale_id INTEGER,
+                cash_register_session_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                movement_type TEXT NOT NULL,
+                amount REAL NOT NULL,
+                payment_cash_mxn REAL NOT NULL DEFAULT 0,
+                payment_cash_usd REAL NOT NULL DEFAULT 0,
+                payment_transfer REAL NOT NULL DEFAULT 0,
+                exchange_rate REAL,
+                notes TEXT,
+                created_at TEXT DEFAULT '2026-01-01 00:00:00'
+            );
+            CREATE TABLE sale_revisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                sale_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                reason TEXT NOT NULL,
+                before_snapshot TEXT NOT NULL,
+                after_snapshot TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT '2026-01-01 00:00:00'
+            );
+            CREATE TABLE settings (
+                key TEXT PRIMARY KEY,
+                value TEXT
+            );
+            CREATE TABLE cash_register_sessions (
+                id INTEGER PRIMARY KEY,
+                register_id INTEGER NOT NULL,
+                status TEXT NOT NULL DEFAULT 'open'
+            );
+            INSERT INTO users (id, full_name) VALUES (1, 'Test User');
+            INSERT INTO cash_register_sessions (id, register_id) VALUES (1, 1), (2, 2);",
         )
         .unwrap();
+        conn.execute_batch(include_str!(
+            "../../../../infrastructure/sqlite/migrations/0027_product_kits.sql"
+        ))
+        .unwrap();
         conn.execute("INSERT INTO products (id, stock) VALUES (1, ?1)", [stock])
             .unwrap();
 
@@ -708,34 +1483,81 @@ mod tests {
             .unwrap()
     }
 
+    fn item(product_name: &str, quantity: f64, unit_price: f64) -> PreparedSaleItem {
+        PreparedSaleItem {
+            product_id: 1,
+            product_name: product_name.to_string(),
+            quantity,
+            base_unit: "pieza".to_string(),
+            input_mode: SaleInputMode::Base,
+            input_value: quantity,
+            input_unit: "pieza".to_string(),
+            unit_price,
+            catalog_price: unit_price,
+            price_list_id: None,
+            subtotal: quantity * unit_price,
+            unit_cost: 0.0,
+        }
+    }
+
+    fn cash_sale(total: f64, items: Vec<PreparedSaleItem>) -> PreparedSale {
+        PreparedSale {
+            cash_register_session_id: 1,
+            user_id: 1,
+            total,
+            payment_method: "cash_mxn".to_string(),
+            payment_amount: total,
+            payment_cash_mxn: total,
+            payment_cash_usd: 0.0,
+            payment_transfer: 0.0,
+            payment_points: 0.0,
+            payment_points_mxn: 0.0,
+            payment_stored_value: 0.0,
+            stored_value_code: None,
+            exchange_rate: None,
+            change_amount: 0.0,
+            customer_id: None,
+            credit_amount: 0.0,
+            points_earned: 0.0,
+            points_expiry_days: None,
+            items,
+        }
+    }
+
+    fn points_balance(db: &Database) -> f64 {
+        db.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COALESCE(SUM(points), 0) FROM customer_points_movements WHERE customer_id = 2",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
     #[test]
     fn bulk_sale_and_cancellation_keep_stock_rounded_without_double_return() {
         let db = test_database(1.0);
         let sale = create(
             &db,
-            1,
-            1,
-            33.30,
-            "cash_mxn",
-            33.30,
-            33.30,
-            0.0,
-            0.0,
-            None,
-            0.0,
-            None,
-            0.0,
-            &[PreparedSaleItem {
-                product_id: 1,
-                product_name: "Producto a granel".to_string(),
-                quantity: 0.333,
-                base_unit: "kg".to_string(),
-                input_mode: SaleInputMode::Sub,
-                input_value: 333.0,
-                input_unit: "g".to_string(),
-                unit_price: 100.0,
-                subtotal: 33.30,
-            }],
+            &cash_sale(
+                33.30,
+                vec![PreparedSaleItem {
+                    product_id: 1,
+                    product_name: "Producto a granel".to_string(),
+                    quantity: 0.333,
+                    base_unit: "kg".to_string(),
+                    input_mode: SaleInputMode::Sub,
+                    input_value: 333.0,
+                    input_unit: "g".to_string(),
+                    unit_price: 100.0,
+                    catalog_price: 100.0,
+                    price_list_id: None,
+                    subtotal: 33.30,
+                    unit_cost: 0.0,
+                }],
+            ),
         )
         .unwrap();
 
@@ -746,10 +1568,10 @@ mod tests {
         assert_eq!(sale.items[0].input_unit.as_deref(), Some("g"));
         assert_eq!(product_stock(&db), 0.667);
 
-        cancel_sale(&db, sale.id).unwrap();
+        cancel_sale(&db, sale.id, false).unwrap();
         assert_eq!(product_stock(&db), 1.0);
 
-        assert!(cancel_sale(&db, sale.id).is_err());
+        assert!(cancel_sale(&db, sale.id, false).is_err());
         assert_eq!(product_stock(&db), 1.0);
     }
 
@@ -767,29 +1589,13 @@ mod tests {
 
         let sale = create(
             &db,
-            1,
-            1,
-            20.0,
-            "cash_mxn",
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            None,
-            0.0,
-            Some(2),
-            20.0,
-            &[PreparedSaleItem {
-                product_id: 1,
-                product_name: "Producto".to_string(),
-                quantity: 1.0,
-                base_unit: "pieza".to_string(),
-                input_mode: SaleInputMode::Base,
-                input_value: 1.0,
-                input_unit: "pieza".to_string(),
-                unit_price: 20.0,
-                subtotal: 20.0,
-            }],
+            &PreparedSale {
+                payment_amount: 0.0,
+                payment_cash_mxn: 0.0,
+                customer_id: Some(2),
+                credit_amount: 20.0,
+                ..cash_sale(20.0, vec![item("Producto", 1.0, 20.0)])
+            },
         )
         .unwrap();
 
@@ -805,7 +1611,396 @@ mod tests {
             )
             .unwrap();
         assert_eq!(balance, 20.0);
-        assert!(cancel_sale(&db, sale.id).is_err());
+        assert!(cancel_sale(&db, sale.id, false).is_err());
         assert_eq!(product_stock(&db), 4.0);
     }
+
+    #[test]
+    fn points_are_recorded_with_the_sale_and_reversed_on_cancel() {
+        let db = test_database(5.0);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO customers (id, name) VALUES (2, 'Cliente frecuente');
+                INSERT INTO customer_points_movements (customer_id, movement_type, points)
+                    VALUES (2, 'earn', 50);",
+            )
+            .unwrap();
+
+        let sale = create(
+            &db,
+            &PreparedSale {
+                payment_method: "mixed".to_string(),
+                payment_cash_mxn: 15.0,
+                payment_points: 50.0,
+                payment_points_mxn: 5.0,
+                customer_id: Some(2),
+                points_earned: 1.0,
+                points_expiry_days: Some(365),
+                ..cash_sale(20.0, vec![item("Producto", 1.0, 20.0)])
+            },
+        )
+        .unwrap();
+        assert_eq!(sale.payment_points, 50.0);
+        assert_eq!(points_balance(&db), 1.0);
+
+        cancel_sale(&db, sale.id, false).unwrap();
+        assert_eq!(points_balance(&db), 50.0);
+    }
+
+    fn stored_value_balance(db: &Database, account_id: i64) -> f64 {
+        db.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COALESCE(SUM(amount), 0) FROM stored_value_movements WHERE account_id = ?1",
+                [account_id],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn gift_card_payment_is_checked_against_the_balance_and_refunds_go_to_store_credit() {
+        let db = test_database(5.0);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO customers (id, name) VALUES (2, 'Ana');
+                INSERT INTO stored_value_accounts (id, kind, code) VALUES (1, 'gift_card', 'GC-1');
+                INSERT INTO stored_value_movements (account_id, cash_register_session_id, user_id,
+                    movement_type, amount) VALUES (1, 1, 1, 'issue', 30);",
+            )
+            .unwrap();
+        let gift_card_sale = |stored_value: f64| PreparedSale {
+            payment_method: "mixed".to_string(),
+            payment_cash_mxn: 20.0 - stored_value,
+            payment_stored_value: stored_value,
+            stored_value_code: Some("GC-1".to_string()),
+            customer_id: Some(2),
+            ..cash_sale(20.0, vec![item("Producto", 1.0, 20.0)])
+        };
+
+        let sale = create(&db, &gift_card_sale(15.0)).unwrap();
+        assert_eq!(sale.stored_value_code.as_deref(), Some("GC-1"));
+        assert_eq!(sale.stored_value_balance, Some(15.0));
+        assert!(create(&db, &gift_card_sale(20.0)).is_err());
+        assert_eq!(product_stock(&db), 4.0);
+
+        cancel_sale(&db, sale.id, true).unwrap();
+        assert_eq!(stored_value_balance(&db, 1), 30.0);
+        let store_credit: f64 = db
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT SUM(m.amount) FROM stored_value_movements m \
+                    JOIN stored_value_accounts a ON a.id = m.account_id \
+                    WHERE a.kind = 'store_credit' AND a.customer_id = 2",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(store_credit, 5.0);
+    }
+
+    #[test]
+    fn refuses_store_credit_refunds_once_the_sale_session_is_closed() {
+        let db = test_database(5.0);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute("INSERT INTO customers (id, name) VALUES (2, 'Ana')", [])
+            .unwrap();
+        let sale = create(
+            &db,
+            &PreparedSale {
+                customer_id: Some(2),
+                ..cash_sale(20.0, vec![item("Producto", 1.0, 20.0)])
+            },
+        )
+        .unwrap();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE cash_register_sessions SET status = 'closed' WHERE id = 1",
+                [],
+            )
+            .unwrap();
+
+        assert!(matches!(
+            cancel_sale(&db, sale.id, true),
+            Err(AppError::Conflict(_))
+        ));
+        let (status, refunds): (SaleStatus, i64) = db
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT status, (SELECT COUNT(*) FROM stored_value_movements) \
+                    FROM sales WHERE id = ?1",
+                [sale.id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(status, SaleStatus::Completed);
+        assert_eq!(refunds, 0);
+        assert_eq!(product_stock(&db), 4.0);
+    }
+
+    #[test]
+    fn escapes_like_wildcards_in_search_terms() {
+        assert_eq!(like_pattern("50%_off"), "%50\\%\\_off%");
+    }
+
+    #[test]
+    fn searches_sales_by_combined_filters_with_sorting_and_pagination() {
+        let db = test_database(10.0);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO customers (id, name) VALUES (2, 'Ana López')",
+                [],
+            )
+            .unwrap();
+        for (total, customer_id) in [(10.0, None), (35.0, Some(2)), (60.0, Some(2))] {
+            create(
+                &db,
+                &PreparedSale {
+                    customer_id,
+                    ..cash_sale(total, vec![item("Jitomate", 1.0, total)])
+                },
+            )
+            .unwrap();
+        }
+        cancel_sale(&db, 1, false).unwrap();
+
+        let by_customer = SaleSearchCriteria {
+            query: Some("lópez".to_string()),
+            min_total: Some(30.0),
+            sort_by: SaleSortField::Total,
+            sort_direction: SortDirection::Asc,
+            ..Default::default()
+        };
+        let (sales, total) = search(&db, &by_customer, 1, 1).unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(sales.len(), 1);
+        assert_eq!(sales[0].total, 35.0);
+
+        let cancelled = SaleSearchCriteria {
+            query: Some("#1".to_string()),
+            status: Some(SaleStatus::Cancelled),
+            ..Default::default()
+        };
+        let (sales, _) = search(&db, &cancelled, 1, 10).unwrap();
+        assert_eq!(sales.iter().map(|sale| sale.id).collect::<Vec<_>>(), [1]);
+
+        let injection = SaleSearchCriteria {
+            payment_method: Some("cash_mxn' OR '1'='1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(search(&db, &injection, 1, 10).unwrap().1, 0);
+    }
+
+    #[test]
+    fn rolls_sales_up_to_departments_and_filters_top_products_by_subtree() {
+        let db = test_database(10.0);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO categories (id, name, parent_id) VALUES
+                    (1, 'Abarrotes', NULL), (2, 'Enlatados', 1), (3, 'Bebidas', NULL);
+                UPDATE products SET category_id = 2 WHERE id = 1;
+                INSERT INTO products (id, stock, category_id) VALUES (2, 10, 3), (3, 10, NULL);",
+            )
+            .unwrap();
+        let product = |product_id: i64, name: &str, quantity: f64, price: f64| PreparedSaleItem {
+            product_id,
+            ..item(name, quantity, price)
+        };
+        create(
+            &db,
+            &cash_sale(
+                50.0,
+                vec![product(1, "Atún", 2.0, 20.0), product(2, "Agua", 1.0, 10.0)],
+            ),
+        )
+        .unwrap();
+        create(
+            &db,
+            &cash_sale(
+                25.0,
+                vec![product(1, "Atún", 1.0, 20.0), product(3, "Bolsa", 1.0, 5.0)],
+            ),
+        )
+        .unwrap();
+
+        let departments = get_sales_by_category(&db, "2000-01-01", "2100-01-01", None, 0).unwrap();
+        let summary: Vec<_> = departments
+            .iter()
+            .map(|row| {
+                (
+                    row.category_name.as_deref(),
+                    row.total_revenue,
+                    row.total_transactions,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("Abarrotes"), 60.0, 2),
+                (Some("Bebidas"), 10.0, 1),
+                (None, 5.0, 1)
+            ]
+        );
+        let subcategories =
+            get_sales_by_category(&db, "2000-01-01", "2100-01-01", None, 1).unwrap();
+        assert_eq!(subcategories[0].category_id, Some(2));
+
+        let groceries =
+            get_top_products(&db, "2000-01-01", "2100-01-01", None, Some(1), 10).unwrap();
+        assert_eq!(groceries.len(), 1);
+        assert_eq!(groceries[0].product_name, "Atún");
+        assert_eq!(groceries[0].total_quantity, 3.0);
+        assert_eq!(
+            get_top_products(&db, "2000-01-01", "2100-01-01", None, None, 10)
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn sales_take_the_register_of_their_session() {
+        let db = test_database(10.0);
+        create(&db, &cash_sale(10.0, vec![item("Jitomate", 1.0, 10.0)])).unwrap();
+        create(
+            &db,
+            &PreparedSale {
+                cash_register_session_id: 2,
+                ..cash_sale(25.0, vec![item("Jitomate", 1.0, 25.0)])
+            },
+        )
+        .unwrap();
+
+        let (sales, total) =
+            find_by_date_range_paginated(&db, "2000-01-01", "2100-01-01", Some(2), 1, 10).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(sales[0].register_id, Some(2));
+        assert_eq!(sales[0].total, 25.0);
+
+        let by_register = SaleSearchCriteria {
+            register_id: Some(1),
+            ..Default::default()
+        };
+        let (sales, _) = search(&db, &by_register, 1, 10).unwrap();
+        assert_eq!(
+            sales.iter().map(|sale| sale.total).collect::<Vec<_>>(),
+            [10.0]
+        );
+        assert_eq!(
+            find_by_date_range(&db, "2000-01-01", "2100-01-01", None)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn amendments_update_tenders_and_keep_a_revision_history() {
+        let db = test_database(5.0);
+        let sale = create(&db, &cash_sale(20.0, vec![item("Producto", 1.0, 20.0)])).unwrap();
+        let before = SaleRevisionSnapshot {
+            customer_id: None,
+            payment_method: "cash_mxn".to_string(),
+            payment_amount: 20.0,
+            payment_cash_mxn: 20.0,
+            payment_cash_usd: 0.0,
+            payment_transfer: 0.0,
+            change_amount: 0.0,
+        };
+        let after = SaleRevisionSnapshot {
+            payment_method: "transfer".to_string(),
+            payment_cash_mxn: 0.0,
+            payment_transfer: 20.0,
+            ..before.clone()
+        };
+
+        let revision =
+            amend_sale(&db, sale.id, 1, "Pagó por transferencia", &before, &after).unwrap();
+        assert_eq!(revision.before, before);
+        assert_eq!(revision.after.payment_transfer, 20.0);
+        assert!(amend_sale(&db, sale.id, 1, "Repetida", &before, &after).is_err());
+
+        let revisions = find_revisions(&db, sale.id).unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].user_name.as_deref(), Some("Test User"));
+    }
+
+    #[test]
+    fn kit_sales_deduct_components_and_cancellation_restocks_them() {
+        let db = test_database(10.0);
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "UPDATE products SET cost = 40 WHERE id = 1;
+                INSERT INTO products (id, name, stock, cost) VALUES (2, 'Vino', 3, 150), (3, 'Canasta', 0, 0);
+                INSERT INTO product_kit_components (kit_id, component_id, quantity)
+                    VALUES (3, 1, 2), (3, 2, 1);",
+            )
+            .unwrap();
+        let stock = |id: i64| -> f64 {
+            db.conn
+                .lock()
+                .unwrap()
+                .query_row("SELECT stock FROM products WHERE id = ?1", [id], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        let available = || -> i64 {
+            db.conn
+                .lock()
+                .unwrap()
+                .query_row(
+                    "SELECT available FROM kit_availability WHERE kit_id = 3",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        let basket = |quantity: f64| PreparedSaleItem {
+            product_id: 3,
+            ..item("Canasta", quantity, 500.0)
+        };
+        assert_eq!(available(), 3);
+
+        let sale = create(&db, &cash_sale(1000.0, vec![basket(2.0)])).unwrap();
+        assert_eq!((stock(1), stock(2), stock(3)), (6.0, 1.0, 0.0));
+        assert_eq!(sale.items[0].unit_cost, Some(230.0));
+        assert_eq!(available(), 1);
+
+        let error = create(&db, &cash_sale(1000.0, vec![basket(2.0)])).unwrap_err();
+        assert!(error.to_string().contains("Vino"));
+        assert_eq!((stock(1), stock(2)), (6.0, 1.0));
+
+        // Rearming the kit does not change what the sale gives back.
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "DELETE FROM product_kit_components WHERE component_id = 2",
+                [],
+            )
+            .unwrap();
+        cancel_sale(&db, sale.id, false).unwrap();
+        assert_eq!((stock(1), stock(2), stock(3)), (10.0, 3.0, 0.0));
+    }
 }
@@ -1,11 +1,22 @@
 use crate::constants::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
 use crate::models::cash_register::{CashRegisterSession, SessionStatus};
 use crate::models::customer::Customer;
+use crate::models::price_list::PriceList;
 use crate::models::product::Product;
 use crate::models::sale::{
-    CreateSaleItemRequest, CreateSaleRequest, DateRangeRequest, Sale, SaleInputMode, SaleStatus,
-    SalesReport, TopProduct,
+    AmendSaleRequest, CategorySales, CreateSaleItemRequest, CreateSaleRequest, DateRangeRequest,
+    Sale, SaleAmendment, SaleInputMode, SaleRevision, SaleRevisionSnapshot, SaleSearchCriteria,
+    SaleStatus, SalesDay, SalesReport, TopProduct,
 };
+use crate::models::unit::Unit;
+use crate::modules::cash_register::application::{
+    self as cash_register_application, CashRegisterRepository,
+};
+use crate::modules::catalog::units::application::to_base_quantity;
+use crate::modules::identity::application::{require_admin, UserRepository};
+use crate::modules::loyalty::application::LoyaltyProgram;
+use crate::modules::pricing::application::resolve_unit_price;
+use crate::modules::stored_value::application::normalize_code;
 use crate::shared::error::{AppError, AppResult};
 use crate::shared::money;
 use crate::shared::pagination::PaginatedResult;
@@ -20,7 +31,11 @@ pub struct SaleDraftItem {
     pub input_value: f64,
     pub input_unit: String,
     pub unit_price: f64,
+    /// Catalog price before the price list applied; reports derive discounts from it.
+    pub catalog_price: f64,
+    pub price_list_id: Option<i64>,
     pub subtotal: f64,
+    pub unit_cost: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -33,10 +48,16 @@ pub struct SaleDraft {
     pub payment_cash_mxn: f64,
     pub payment_cash_usd: f64,
     pub payment_transfer: f64,
+    pub payment_points: f64,
+    pub payment_points_mxn: f64,
+    pub payment_stored_value: f64,
+    pub stored_value_code: Option<String>,
     pub exchange_rate: Option<f64>,
     pub change_amount: f64,
     pub customer_id: Option<i64>,
     pub credit_amount: f64,
+    pub points_earned: f64,
+    pub points_expiry_days: Option<i64>,
     pub items: Vec<SaleDraftItem>,
 }
 
@@ -51,11 +72,23 @@ pub struct SalesReportMetrics {
 pub trait SaleDraftDependencies {
     fn find_session(&self, id: i64) -> AppResult<Option<CashRegisterSession>>;
     fn find_product(&self, id: i64) -> AppResult<Option<Product>>;
+    fn find_customer_price_list(&self, customer_id: i64) -> AppResult<Option<PriceList>>;
+    fn loyalty_program(&self) -> AppResult<LoyaltyProgram>;
+    fn stale_session_blocks_sales(&self, session_id: i64) -> AppResult<bool>;
+    fn find_unit(&self, name: &str) -> AppResult<Option<Unit>>;
 }
 
 pub trait SalesUnitOfWork {
     fn create_sale_atomically(&self, draft: SaleDraft) -> AppResult<Sale>;
-    fn cancel_sale_atomically(&self, sale_id: i64) -> AppResult<()>;
+    fn cancel_sale_atomically(&self, sale_id: i64, refund_to_store_credit: bool) -> AppResult<()>;
+    fn amend_sale_atomically(
+        &self,
+        sale_id: i64,
+        user_id: i64,
+        reason: &str,
+        before: &SaleRevisionSnapshot,
+        after: &SaleRevisionSnapshot,
+    ) -> AppResult<SaleRevision>;
 }
 
 pub trait SalesQueryPort {
@@ -71,12 +104,48 @@ pub trait SalesQueryPort {
         &self,
         start: &str,
         end: &str,
+        register_id: Option<i64>,
+        page: i64,
+        page_size: i64,
+    ) -> AppResult<(Vec<Sale>, i64)>;
+    fn search_sales(
+        &self,
+        criteria: &SaleSearchCriteria,
         page: i64,
         page_size: i64,
     ) -> AppResult<(Vec<Sale>, i64)>;
-    fn find_sales_for_report(&self, start: &str, end: &str) -> AppResult<Vec<Sale>>;
-    fn report_metrics(&self, start: &str, end: &str) -> AppResult<SalesReportMetrics>;
-    fn top_products(&self, start: &str, end: &str, limit: i64) -> AppResult<Vec<TopProduct>>;
+    fn find_sales_for_report(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+    ) -> AppResult<Vec<Sale>>;
+    fn find_revisions(&self, sale_id: i64) -> AppResult<Vec<SaleRevision>>;
+    /// Credit sold follows the register filter; account collections and balances
+    /// belong to the customer ledger and are always store-wide.
+    fn report_metrics(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+    ) -> AppResult<SalesReportMetrics>;
+    fn top_products(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+        category_id: Option<i64>,
+        limit: i64,
+    ) -> AppResult<Vec<TopProduct>>;
+    /// Each item counts toward its category's ancestor at `depth`, or the
+    /// category itself when it sits higher.
+    fn sales_by_category(
+        &self,
+        start: &str,
+        end: &str,
+        register_id: Option<i64>,
+        depth: i64,
+    ) -> AppResult<Vec<CategorySales>>;
 }
 
 pub fn create_sale<T>(ports: &T, request: CreateSaleRequest) -> AppResult<Sale>
@@ -104,9 +173,17 @@ fn prepare_sale_draft(
             "La sesión de caja no está abierta".to_string(),
         ));
     }
+    if dependencies.stale_session_blocks_sales(session.id)? {
+        return Err(AppError::Conflict(
+            "La sesión de caja sigue abierta desde otro día; ciérrala antes de seguir vendiendo"
+                .to_string(),
+        ));
+    }
     if request.payment_cash_mxn < 0.0
         || request.payment_cash_usd < 0.0
         || request.payment_transfer < 0.0
+        || request.payment_points < 0.0
+        || request.payment_stored_value < 0.0
     {
         return Err(AppError::Validation(
             "Los montos de pago no pueden ser negativos".to_string(),
@@ -115,16 +192,55 @@ fn prepare_sale_draft(
     let cash_mxn = money::round2(request.payment_cash_mxn);
     let cash_usd = money::round2(request.payment_cash_usd);
     let transfer = money::round2(request.payment_transfer);
+    let stored_value = money::round2(request.payment_stored_value);
+    let stored_value_code = request
+        .stored_value_code
+        .as_deref()
+        .and_then(normalize_code);
+    if stored_value > 0.0 && stored_value_code.is_none() && request.customer_id.is_none() {
+        return Err(AppError::Validation(
+            "Indica la tarjeta de regalo o selecciona un cliente con saldo a favor".to_string(),
+        ));
+    }
+    let points = request.payment_points;
+    if !points.is_finite() || points.fract() != 0.0 {
+        return Err(AppError::Validation(
+            "Los puntos se canjean en cantidades enteras".to_string(),
+        ));
+    }
     if cash_usd > 0.0 && session.exchange_rate.is_none() {
         return Err(AppError::Validation(
             "No se puede pagar con USD sin tipo de cambio configurado en la caja".to_string(),
         ));
     }
 
+    let price_list = request
+        .customer_id
+        .map(|customer_id| dependencies.find_customer_price_list(customer_id))
+        .transpose()?
+        .flatten();
+    let program = match request.customer_id {
+        Some(_) => dependencies.loyalty_program()?,
+        None => LoyaltyProgram::default(),
+    };
+    if points > 0.0 {
+        if request.customer_id.is_none() {
+            return Err(AppError::Validation(
+                "Selecciona un cliente para canjear puntos".to_string(),
+            ));
+        }
+        if !program.enabled {
+            return Err(AppError::Conflict(
+                "El programa de puntos está desactivado".to_string(),
+            ));
+        }
+    }
+
     let mut items = Vec::with_capacity(request.items.len());
     let mut total = 0.0;
+    let mut earning_lines = Vec::with_capacity(request.items.len());
     for item_request in &request.items {
-        let product = dependencies
+        let mut product = dependencies
             .find_product(item_request.product_id)?
             .ok_or_else(|| {
                 AppError::NotFound(format!(
@@ -138,6 +254,12 @@ fn prepare_sale_draft(
                 product.name
             )));
         }
+        if product.variant_count > 0 {
+            return Err(AppError::Validation(format!(
+                "'{}' tiene variantes; elige la variante a vender",
+                product.name
+            )));
+        }
         let quantity = money::round3(item_request.quantity);
         if !quantity.is_finite() || quantity <= 0.0 {
             return Err(AppError::Validation(format!(
@@ -151,7 +273,16 @@ fn prepare_sale_draft(
                 product.name
             )));
         }
-        validate_sale_input(&product, item_request, quantity)?;
+        let input_unit = match item_request.input_mode {
+            SaleInputMode::Sub => dependencies.find_unit(&item_request.input_unit)?,
+            _ => None,
+        };
+        // Amount captures are converted with the catalog price the cart shows;
+        // the customer's list price only applies once the quantity is known.
+        validate_sale_input(&product, item_request, quantity, input_unit.as_ref())?;
+        let catalog_price = product.price;
+        let resolved = resolve_unit_price(&product, quantity, price_list.as_ref());
+        product.price = resolved.unit_price;
         if product.stock < quantity {
             return Err(AppError::Validation(format!(
                 "Stock insuficiente para '{}'. Disponible: {}, Solicitado: {}",
@@ -160,6 +291,7 @@ fn prepare_sale_draft(
         }
         let subtotal = money::mul_money(product.price, quantity);
         total = money::add_money(total, subtotal);
+        earning_lines.push((product.category_id, subtotal));
         items.push(SaleDraftItem {
             product_id: product.id,
             product_name: product.name,
@@ -169,12 +301,24 @@ fn prepare_sale_draft(
             input_value: item_request.input_value,
             input_unit: item_request.input_unit.clone(),
             unit_price: product.price,
+            catalog_price,
+            price_list_id: resolved.price_list_id,
             subtotal,
+            unit_cost: product.cost,
         });
     }
 
     let exchange_rate = session.exchange_rate.unwrap_or(1.0);
-    let total_paid = money::total_paid_mxn(cash_mxn, cash_usd, transfer, exchange_rate);
+    let points_mxn = program.redemption_value(points);
+    if money::add_money(points_mxn, stored_value) > total {
+        return Err(AppError::Validation(
+            "Los pagos con puntos y saldo exceden el total de la venta".to_string(),
+        ));
+    }
+    let tendered = money::total_paid_mxn(cash_mxn, cash_usd, transfer, exchange_rate);
+    let total_paid = money::sum_money([tendered, points_mxn, stored_value]);
+    let payment_method =
+        payment_method(tendered, cash_mxn, cash_usd, transfer, points, stored_value);
     let credit_amount = money::round2((total - total_paid).max(0.0));
     if credit_amount > 0.0 && request.customer_id.is_none() {
         return Err(AppError::Validation(
@@ -185,19 +329,50 @@ fn prepare_sale_draft(
         cash_register_session_id: request.cash_register_session_id,
         user_id: request.user_id,
         total,
-        payment_method: money::derive_payment_method(cash_mxn, cash_usd, transfer),
+        payment_method,
         payment_amount: total_paid,
         payment_cash_mxn: cash_mxn,
         payment_cash_usd: cash_usd,
         payment_transfer: transfer,
+        payment_points: points,
+        payment_points_mxn: points_mxn,
+        payment_stored_value: stored_value,
+        stored_value_code,
         exchange_rate: session.exchange_rate,
         change_amount: money::calc_change(total, total_paid).max(0.0),
         customer_id: request.customer_id,
         credit_amount,
+        points_earned: program.points_for_purchase(&earning_lines, points_mxn),
+        points_expiry_days: program.expiry_days,
         items,
     })
 }
 
+/// Cash tenders keep their own method names; balance-based tenders are
+/// `points` or `stored_value` when used alone and `mixed` otherwise.
+fn payment_method(
+    tendered: f64,
+    cash_mxn: f64,
+    cash_usd: f64,
+    transfer: f64,
+    points: f64,
+    stored_value: f64,
+) -> String {
+    let balances: Vec<&str> = [
+        (points > 0.0, "points"),
+        (stored_value > 0.0, "stored_value"),
+    ]
+    .into_iter()
+    .filter(|(used, _)| *used)
+    .map(|(_, name)| name)
+    .collect();
+    match (balances.as_slice(), tendered > 0.0) {
+        ([], _) => money::derive_payment_method(cash_mxn, cash_usd, transfer),
+        ([method], false) => method.to_string(),
+        _ => "mixed".to_string(),
+    }
+}
+
 pub fn get_sale(port: &impl SalesQueryPort, id: i64) -> AppResult<Sale> {
     port.find_sale(id)?
         .ok_or_else(|| AppError::NotFound("Venta no encontrada".to_string()))
@@ -230,8 +405,13 @@ pub fn get_sales_by_date_range(
     page_size: Option<i64>,
 ) -> AppResult<PaginatedResult<Sale>> {
     let (page, page_size) = page_bounds(page, page_size);
-    let (data, total) =
-        port.find_sales_by_date_range(&request.start_date, &request.end_date, page, page_size)?;
+    let (data, total) = port.find_sales_by_date_range(
+        &request.start_date,
+        &request.end_date,
+        request.register_id,
+        page,
+        page_size,
+    )?;
     Ok(PaginatedResult {
         data,
         total,
@@ -240,18 +420,69 @@ pub fn get_sales_by_date_range(
     })
 }
 
+pub fn search_sales(
+    port: &impl SalesQueryPort,
+    criteria: SaleSearchCriteria,
+    page: Option<i64>,
+    page_size: Option<i64>,
+) -> AppResult<PaginatedResult<Sale>> {
+    let criteria = normalize_search_criteria(criteria)?;
+    let (page, page_size) = page_bounds(page, page_size);
+    let (data, total) = port.search_sales(&criteria, page, page_size)?;
+    Ok(PaginatedResult {
+        data,
+        total,
+        page,
+        page_size,
+    })
+}
+
+fn normalize_search_criteria(mut criteria: SaleSearchCriteria) -> AppResult<SaleSearchCriteria> {
+    let amounts = [criteria.min_total, criteria.max_total];
+    if amounts
+        .iter()
+        .flatten()
+        .any(|amount| !amount.is_finite() || *amount < 0.0)
+    {
+        return Err(AppError::Validation(
+            "Los montos de búsqueda deben ser mayores o iguales a cero".to_string(),
+        ));
+    }
+    if let (Some(min), Some(max)) = (criteria.min_total, criteria.max_total) {
+        if min > max {
+            return Err(AppError::Validation(
+                "El monto mínimo no puede ser mayor que el máximo".to_string(),
+            ));
+        }
+    }
+    for text in [
+        &mut criteria.query,
+        &mut criteria.payment_method,
+        &mut criteria.start_date,
+        &mut criteria.end_date,
+    ] {
+        *text = text
+            .take()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+    }
+    Ok(criteria)
+}
+
 pub fn get_sales_report(
     port: &impl SalesQueryPort,
     request: DateRangeRequest,
 ) -> AppResult<SalesReport> {
-    let sales = port.find_sales_for_report(&request.start_date, &request.end_date)?;
+    let sales =
+        port.find_sales_for_report(&request.start_date, &request.end_date, request.register_id)?;
     let completed: Vec<&Sale> = sales
         .iter()
         .filter(|sale| sale.status == SaleStatus::Completed)
         .collect();
     let total_sales = money::sum_money(completed.iter().map(|sale| sale.total));
     let total_transactions = completed.len() as i64;
-    let metrics = port.report_metrics(&request.start_date, &request.end_date)?;
+    let metrics =
+        port.report_metrics(&request.start_date, &request.end_date, request.register_id)?;
     Ok(SalesReport {
         total_sales,
         total_transactions,
@@ -264,21 +495,202 @@ pub fn get_sales_report(
         total_account_collected: metrics.total_account_collected,
         outstanding_balance: metrics.outstanding_balance,
         top_debtors: metrics.top_debtors,
+        days: sales_by_day(&completed),
+        departments: port.sales_by_category(
+            &request.start_date,
+            &request.end_date,
+            request.register_id,
+            0,
+        )?,
         sales,
     })
 }
 
+fn sales_by_day(completed: &[&Sale]) -> Vec<SalesDay> {
+    let mut days: Vec<SalesDay> = Vec::new();
+    for sale in completed {
+        let date = sale.created_at.get(..10).unwrap_or(&sale.created_at);
+        let index = match days.iter().position(|day| day.date == date) {
+            Some(index) => index,
+            None => {
+                days.push(SalesDay {
+                    date: date.to_string(),
+                    total_sales: 0.0,
+                    total_transactions: 0,
+                });
+                days.len() - 1
+            }
+        };
+        let day = &mut days[index];
+        day.total_sales = money::add_money(day.total_sales, sale.total);
+        day.total_transactions += 1;
+    }
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+    days
+}
+
 pub fn get_top_products(
     port: &impl SalesQueryPort,
     start: String,
     end: String,
+    register_id: Option<i64>,
+    category_id: Option<i64>,
     limit: Option<i64>,
 ) -> AppResult<Vec<TopProduct>> {
-    port.top_products(&start, &end, limit.unwrap_or(10))
+    port.top_products(&start, &end, register_id, category_id, limit.unwrap_or(10))
+}
+
+pub fn get_sales_by_category(
+    port: &impl SalesQueryPort,
+    start: String,
+    end: String,
+    register_id: Option<i64>,
+    depth: Option<i64>,
+) -> AppResult<Vec<CategorySales>> {
+    let depth = depth.unwrap_or(0);
+    if depth < 0 {
+        return Err(AppError::Validation(
+            "El nivel de categoría no puede ser negativo".to_string(),
+        ));
+    }
+    port.sales_by_category(&start, &end, register_id, depth)
+}
+
+pub fn cancel_sale(
+    port: &impl SalesUnitOfWork,
+    sale_id: i64,
+    refund_to_store_credit: bool,
+) -> AppResult<()> {
+    port.cancel_sale_atomically(sale_id, refund_to_store_credit)
+}
+
+pub fn amend_sale<T>(
+    ports: &T,
+    users: &impl UserRepository,
+    cash_register: &impl CashRegisterRepository,
+    request: AmendSaleRequest,
+) -> AppResult<SaleAmendment>
+where
+    T: SaleDraftDependencies + SalesUnitOfWork + SalesQueryPort,
+{
+    require_admin(users, request.user_id)?;
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::Validation(
+            "Indica el motivo de la corrección".to_string(),
+        ));
+    }
+    let sale = get_sale(ports, request.sale_id)?;
+    if sale.status != SaleStatus::Completed {
+        return Err(AppError::Conflict(
+            "Solo se pueden corregir ventas completadas".to_string(),
+        ));
+    }
+    let session = ports
+        .find_session(sale.cash_register_session_id)?
+        .ok_or_else(|| AppError::NotFound("Sesión de caja no encontrada".to_string()))?;
+    let session_open = session.status == SessionStatus::Open;
+
+    let before = SaleRevisionSnapshot {
+        customer_id: sale.customer_id,
+        payment_method: sale.payment_method.clone(),
+        payment_amount: sale.payment_amount,
+        payment_cash_mxn: sale.payment_cash_mxn,
+        payment_cash_usd: sale.payment_cash_usd,
+        payment_transfer: sale.payment_transfer,
+        change_amount: sale.change_amount,
+    };
+    let mut after = before.clone();
+
+    if let Some(customer_id) = request.customer_id {
+        let bound_to_customer = sale.credit_amount > 0.0
+            || sale.payment_points > 0.0
+            || sale.points_earned > 0.0
+            || (sale.payment_stored_value > 0.0 && sale.stored_value_code.is_none());
+        if bound_to_customer && sale.customer_id != Some(customer_id) {
+            return Err(AppError::Conflict(
+                "La venta tiene adeudo, puntos o saldo a favor del cliente actual".to_string(),
+            ));
+        }
+        after.customer_id = Some(customer_id);
+    }
+
+    let tenders_changed = request.payment_cash_mxn.is_some()
+        || request.payment_cash_usd.is_some()
+        || request.payment_transfer.is_some();
+    if tenders_changed {
+        if !session_open {
+            return Err(AppError::Conflict(
+                "La sesión de caja ya está cerrada; solo se puede cambiar el cliente".to_string(),
+            ));
+        }
+        let cash_mxn = money::round2(request.payment_cash_mxn.unwrap_or(sale.payment_cash_mxn));
+        let cash_usd = money::round2(request.payment_cash_usd.unwrap_or(sale.payment_cash_usd));
+        let transfer = money::round2(request.payment_transfer.unwrap_or(sale.payment_transfer));
+        if cash_mxn < 0.0 || cash_usd < 0.0 || transfer < 0.0 {
+            return Err(AppError::Validation(
+                "Los montos de pago no pueden ser negativos".to_string(),
+            ));
+        }
+        if cash_usd > 0.0 && session.exchange_rate.is_none() {
+            return Err(AppError::Validation(
+                "No se puede pagar con USD sin tipo de cambio configurado en la caja".to_string(),
+            ));
+        }
+        let tendered = money::total_paid_mxn(
+            cash_mxn,
+            cash_usd,
+            transfer,
+            session.exchange_rate.unwrap_or(1.0),
+        );
+        let total_paid =
+            money::sum_money([tendered, sale.payment_points_mxn, sale.payment_stored_value]);
+        if total_paid < money::sub_money(sale.total, sale.credit_amount) {
+            return Err(AppError::Validation(
+                "Los pagos no cubren el total de la venta".to_string(),
+            ));
+        }
+        after.payment_method = payment_method(
+            tendered,
+            cash_mxn,
+            cash_usd,
+            transfer,
+            sale.payment_points,
+            sale.payment_stored_value,
+        );
+        after.payment_amount = total_paid;
+        after.payment_cash_mxn = cash_mxn;
+        after.payment_cash_usd = cash_usd;
+        after.payment_transfer = transfer;
+        after.change_amount = money::calc_change(sale.total, total_paid).max(0.0);
+    }
+
+    if after == before {
+        return Err(AppError::Validation(
+            "La corrección no cambia ningún dato de la venta".to_string(),
+        ));
+    }
+    let revision =
+        ports.amend_sale_atomically(sale.id, request.user_id, reason, &before, &after)?;
+    Ok(SaleAmendment {
+        sale: get_sale(ports, sale.id)?,
+        revision,
+        session_summary: if session_open {
+            Some(cash_register_application::get_summary(
+                cash_register,
+                session.id,
+            )?)
+        } else {
+            None
+        },
+    })
 }
 
-pub fn cancel_sale(port: &impl SalesUnitOfWork, sale_id: i64) -> AppResult<()> {
-    port.cancel_sale_atomically(sale_id)
+pub fn get_sale_revisions(
+    port: &impl SalesQueryPort,
+    sale_id: i64,
+) -> AppResult<Vec<SaleRevision>> {
+    port.find_revisions(sale_id)
 }
 
 fn page_bounds(page: Option<i64>, page_size: Option<i64>) -> (i64, i64) {
@@ -290,10 +702,13 @@ fn page_bounds(page: Option<i64>, page_size: Option<i64>) -> (i64, i64) {
     )
 }
 
+/// `input_unit` is the configured unit named by `item.input_unit`, looked up
+/// for `Sub` captures.
 pub fn validate_sale_input(
     product: &Product,
     item: &CreateSaleItemRequest,
     quantity: f64,
+    input_unit: Option<&Unit>,
 ) -> AppResult<()> {
     if !item.input_value.is_finite() || item.input_value <= 0.0 {
         return Err(AppError::Validation(
@@ -308,16 +723,11 @@ pub fn validate_sale_input(
                 product.unit, product.name
             )))
         }
-        SaleInputMode::Sub if !product.is_bulk => {
-            return Err(AppError::Validation(format!(
-                "El producto '{}' no admite subunidades",
-                product.name
-            )))
-        }
-        SaleInputMode::Sub => match (product.unit.as_str(), item.input_unit.as_str()) {
-            ("kg", "g") | ("litro", "ml") => item.input_value / 1000.0,
-            ("metro", "cm") => item.input_value / 100.0,
-            _ => {
+        SaleInputMode::Sub => match input_unit
+            .and_then(|unit| to_base_quantity(unit, &product.unit, item.input_value))
+        {
+            Some(converted) => converted,
+            None => {
                 return Err(AppError::Validation(format!(
                     "La unidad {} no es compatible con {}",
                     item.input_unit, product.unit
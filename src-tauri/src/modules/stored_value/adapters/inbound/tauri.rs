@@ -0,0 +1,41 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::stored_value::{IssueGiftCardRequest, StoredValueAccount, StoredValueMovement};
+use crate::modules::{
+    cash_register::adapters::outbound::sqlite::SqliteCashRegisterRepository,
+    stored_value::{adapters::outbound::sqlite::SqliteStoredValueRepository, application},
+};
+use crate::shared::error::AppResult;
+use tauri::State;
+
+#[tauri::command]
+pub fn find_gift_card(db: State<Database>, code: String) -> AppResult<StoredValueAccount> {
+    application::find_gift_card(&SqliteStoredValueRepository::new(&db), &code)
+}
+
+#[tauri::command]
+pub fn get_customer_store_credit(
+    db: State<Database>,
+    customer_id: i64,
+) -> AppResult<Option<StoredValueAccount>> {
+    application::get_customer_store_credit(&SqliteStoredValueRepository::new(&db), customer_id)
+}
+
+#[tauri::command]
+pub fn get_stored_value_movements(
+    db: State<Database>,
+    account_id: i64,
+) -> AppResult<Vec<StoredValueMovement>> {
+    application::get_stored_value_movements(&SqliteStoredValueRepository::new(&db), account_id)
+}
+
+#[tauri::command]
+pub fn issue_gift_card(
+    db: State<Database>,
+    request: IssueGiftCardRequest,
+) -> AppResult<StoredValueAccount> {
+    application::issue_gift_card(
+        &SqliteStoredValueRepository::new(&db),
+        &SqliteCashRegisterRepository::new(&db),
+        request,
+    )
+}
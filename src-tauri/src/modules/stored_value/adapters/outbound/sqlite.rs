@@ -0,0 +1,427 @@
+use crate::infrastructure::sqlite::Database;
+use crate::models::stored_value::{
+    IssueGiftCardRequest, StoredValueAccount, StoredValueKind, StoredValueMovement,
+    StoredValueMovementType,
+};
+use crate::modules::stored_value::application::StoredValueRepository;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
+use rusqlite::{params, Connection, OptionalExtension};
+
+pub struct SqliteStoredValueRepository<'db> {
+    db: &'db Database,
+}
+
+impl<'db> SqliteStoredValueRepository<'db> {
+    pub fn new(db: &'db Database) -> Self {
+        Self { db }
+    }
+}
+
+impl StoredValueRepository for SqliteStoredValueRepository<'_> {
+    fn find_by_code(&self, code: &str) -> AppResult<Option<StoredValueAccount>> {
+        let conn = self.db.conn.lock()?;
+        find_account(
+            &conn,
+            "a.kind = ?1 AND a.code = ?2",
+            params![StoredValueKind::GiftCard, code],
+        )
+    }
+
+    fn find_store_credit(&self, customer_id: i64) -> AppResult<Option<StoredValueAccount>> {
+        let conn = self.db.conn.lock()?;
+        find_account(
+            &conn,
+            "a.kind = ?1 AND a.customer_id = ?2",
+            params![StoredValueKind::StoreCredit, customer_id],
+        )
+    }
+
+    fn find_movements(&self, account_id: i64) -> AppResult<Vec<StoredValueMovement>> {
+        find_movements(self.db, account_id)
+    }
+
+    fn issue_gift_card(
+        &self,
+        request: &IssueGiftCardRequest,
+        code: &str,
+        exchange_rate: Option<f64>,
+        notes: Option<&str>,
+    ) -> AppResult<StoredValueAccount> {
+        issue_gift_card(self.db, request, code, exchange_rate, notes)
+    }
+}
+
+const ACCOUNT_SELECT: &str = "SELECT a.id, a.kind, a.code, a.customer_id, c.name, \
+    COALESCE((SELECT SUM(m.amount) FROM stored_value_movements m WHERE m.account_id = a.id), 0), \
+    a.active, a.created_at, a.updated_at \
+    FROM stored_value_accounts a LEFT JOIN customers c ON c.id = a.customer_id";
+
+fn row_to_account(row: &rusqlite::Row) -> rusqlite::Result<StoredValueAccount> {
+    Ok(StoredValueAccount {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        code: row.get(2)?,
+        customer_id: row.get(3)?,
+        customer_name: row.get(4)?,
+        balance: money::round2(row.get(5)?),
+        active: row.get::<_, i64>(6)? != 0,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+fn find_account(
+    conn: &Connection,
+    condition: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> AppResult<Option<StoredValueAccount>> {
+    Ok(conn
+        .query_row(
+            &format!("{} WHERE {}", ACCOUNT_SELECT, condition),
+            params,
+            row_to_account,
+        )
+        .optional()?)
+}
+
+pub fn find_movements(db: &Database, account_id: i64) -> AppResult<Vec<StoredValueMovement>> {
+    let conn = db.conn.lock()?;
+    let movements = conn
+        .prepare(
+            "SELECT m.id, m.account_id, m.sale_id, m.cash_register_session_id, m.user_id, \
+                u.full_name, m.movement_type, m.amount, m.payment_cash_mxn, m.payment_cash_usd, \
+                m.payment_transfer, m.exchange_rate, m.notes, m.created_at \
+            FROM stored_value_movements m JOIN users u ON u.id = m.user_id \
+            WHERE m.account_id = ?1 ORDER BY m.id DESC",
+        )?
+        .query_map(params![account_id], |row| {
+            Ok(StoredValueMovement {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                sale_id: row.get(2)?,
+                cash_register_session_id: row.get(3)?,
+                user_id: row.get(4)?,
+                user_name: row.get(5)?,
+                movement_type: row.get(6)?,
+                amount: money::round2(row.get(7)?),
+                payment_cash_mxn: money::round2(row.get(8)?),
+                payment_cash_usd: money::round2(row.get(9)?),
+                payment_transfer: money::round2(row.get(10)?),
+                exchange_rate: row.get(11)?,
+                notes: row.get(12)?,
+                created_at: row.get(13)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(movements)
+}
+
+pub fn issue_gift_card(
+    db: &Database,
+    request: &IssueGiftCardRequest,
+    code: &str,
+    exchange_rate: Option<f64>,
+    notes: Option<&str>,
+) -> AppResult<StoredValueAccount> {
+    let mut conn = db.conn.lock()?;
+    let tx = conn.transaction()?;
+    let exists: bool = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM stored_value_accounts WHERE code = ?1)",
+        params![code],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Err(AppError::Conflict(
+            "Ya existe una tarjeta con ese código".to_string(),
+        ));
+    }
+    tx.execute(
+        "INSERT INTO stored_value_accounts (kind, code, customer_id) VALUES (?1, ?2, ?3)",
+        params![StoredValueKind::GiftCard, code, request.customer_id],
+    )?;
+    let account_id = tx.last_insert_rowid();
+    let cash_mxn = money::round2(request.payment_cash_mxn);
+    let cash_usd = money::round2(request.payment_cash_usd);
+    let transfer = money::round2(request.payment_transfer);
+    record_movement(
+        &tx,
+        &NewStoredValueMovement {
+            account_id,
+            sale_id: None,
+            cash_register_session_id: request.cash_register_session_id,
+            user_id: request.user_id,
+            movement_type: StoredValueMovementType::Issue,
+            amount: money::total_paid_mxn(
+                cash_mxn,
+                cash_usd,
+                transfer,
+                exchange_rate.unwrap_or(1.0),
+            ),
+            payment_cash_mxn: cash_mxn,
+            payment_cash_usd: cash_usd,
+            payment_transfer: transfer,
+            exchange_rate,
+            notes,
+        },
+    )?;
+    let account = find_account(&tx, "a.id = ?1", params![account_id])?;
+    tx.commit()?;
+    account.ok_or_else(|| AppError::Database("No se pudo recuperar la tarjeta creada".to_string()))
+}
+
+pub struct NewStoredValueMovement<'a> {
+    pub account_id: i64,
+    pub sale_id: Option<i64>,
+    pub cash_register_session_id: i64,
+    pub user_id: i64,
+    pub movement_type: StoredValueMovementType,
+    pub amount: f64,
+    pub payment_cash_mxn: f64,
+    pub payment_cash_usd: f64,
+    pub payment_transfer: f64,
+    pub exchange_rate: Option<f64>,
+    pub notes: Option<&'a str>,
+}
+
+pub fn record_movement(conn: &Connection, movement: &NewStoredValueMovement) -> AppResult<()> {
+    conn.execute(
+        "INSERT INTO stored_value_movements (account_id, sale_id, cash_register_session_id, \
+            user_id, movement_type, amount, payment_cash_mxn, payment_cash_usd, payment_transfer, \
+            exchange_rate, notes) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            movement.account_id,
+            movement.sale_id,
+            movement.cash_register_session_id,
+            movement.user_id,
+            movement.movement_type,
+            money::round2(movement.amount),
+            money::round2(movement.payment_cash_mxn),
+            money::round2(movement.payment_cash_usd),
+            money::round2(movement.payment_transfer),
+            movement.exchange_rate,
+            movement.notes
+        ],
+    )?;
+    Ok(())
+}
+
+/// Finds the instrument used to pay a sale and checks, inside the sale's
+/// transaction, that it can cover `amount`. A gift card is used when a code
+/// is given; otherwise the customer's store credit is used. Returns the
+/// account id and the balance left after the payment.
+pub fn reserve_redemption(
+    conn: &Connection,
+    code: Option<&str>,
+    customer_id: Option<i64>,
+    amount: f64,
+) -> AppResult<(i64, f64)> {
+    let account = match (code, customer_id) {
+        (Some(code), _) => find_account(
+            conn,
+            "a.kind = ?1 AND a.code = ?2",
+            params![StoredValueKind::GiftCard, code],
+        )?
+        .ok_or_else(|| AppError::NotFound("Tarjeta de regalo no encontrada".to_string()))?,
+        (None, Some(customer_id)) => find_account(
+            conn,
+            "a.kind = ?1 AND a.customer_id = ?2",
+            params![StoredValueKind::StoreCredit, customer_id],
+        )?
+        .ok_or_else(|| AppError::Validation("El cliente no tiene saldo a favor".to_string()))?,
+        (None, None) => {
+            return Err(AppError::Validation(
+                "Indica la tarjeta de regalo o el cliente con saldo a favor".to_string(),
+            ))
+        }
+    };
+    if !account.active {
+        return Err(AppError::Conflict(match account.kind {
+            StoredValueKind::GiftCard => "La tarjeta de regalo está desactivada".to_string(),
+            StoredValueKind::StoreCredit => "El saldo a favor está desactivado".to_string(),
+        }));
+    }
+    if money::round2(amount) > account.balance {
+        return Err(AppError::Validation(format!(
+            "Saldo insuficiente. Disponible: ${:.2}",
+            account.balance
+        )));
+    }
+    Ok((account.id, money::sub_money(account.balance, amount)))
+}
+
+/// Gives back to its instrument the balance a cancelled sale consumed.
+pub fn reverse_sale_redemption(conn: &Connection, sale_id: i64, user_id: i64) -> AppResult<()> {
+    let redemptions: Vec<(i64, i64, f64)> = conn
+        .prepare(
+            "SELECT account_id, cash_register_session_id, amount FROM stored_value_movements \
+                WHERE sale_id = ?1 AND movement_type = ?2",
+        )?
+        .query_map(params![sale_id, StoredValueMovementType::Redeem], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<Result<_, _>>()?;
+    for (account_id, cash_register_session_id, amount) in redemptions {
+        record_movement(
+            conn,
+            &NewStoredValueMovement {
+                account_id,
+                sale_id: Some(sale_id),
+                cash_register_session_id,
+                user_id,
+                movement_type: StoredValueMovementType::Reversal,
+                amount: -amount,
+                payment_cash_mxn: 0.0,
+                payment_cash_usd: 0.0,
+                payment_transfer: 0.0,
+                exchange_rate: None,
+                notes: Some("Devolución de saldo por cancelación"),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the store credit account of a customer, opening it on first use.
+pub fn store_credit_account(conn: &Connection, customer_id: i64) -> AppResult<i64> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM stored_value_accounts WHERE kind = ?1 AND customer_id = ?2",
+            params![StoredValueKind::StoreCredit, customer_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    conn.execute(
+        "INSERT INTO stored_value_accounts (kind, customer_id) VALUES (?1, ?2)",
+        params![StoredValueKind::StoreCredit, customer_id],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        record_movement, reserve_redemption, reverse_sale_redemption, store_credit_account,
+        NewStoredValueMovement,
+    };
+    use crate::models::stored_value::StoredValueMovementType;
+    use crate::shared::error::AppError;
+    use rusqlite::Connection;
+
+    fn test_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE customers (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+            CREATE TABLE stored_value_accounts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                code TEXT UNIQUE COLLATE NOCASE,
+                customer_id INTEGER,
+                active INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT DEFAULT '2026-01-01 00:00:00',
+                updated_at TEXT DEFAULT '2026-01-01 00:00:00'
+            );
+            CREATE TABLE stored_value_movements (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                account_id INTEGER NOT NULL,
+                sale_id INTEGER,
+                cash_register_session_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                movement_type TEXT NOT NULL,
+                amount REAL NOT NULL,
+                payment_cash_mxn REAL NOT NULL DEFAULT 0,
+                payment_cash_usd REAL NOT NULL DEFAULT 0,
+                payment_transfer REAL NOT NULL DEFAULT 0,
+                exchange_rate REAL,
+                notes TEXT,
+                created_at TEXT DEFAULT '2026-01-01 00:00:00'
+            );
+            INSERT INTO customers (id, name) VALUES (3, 'Ana');
+            INSERT INTO stored_value_accounts (id, kind, code) VALUES (1, 'gift_card', 'GC-100');
+            INSERT INTO stored_value_movements (account_id, cash_register_session_id, user_id,
+                movement_type, amount, payment_cash_mxn) VALUES (1, 1, 1, 'issue', 100, 100);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn redeem(conn: &Connection, account_id: i64, sale_id: i64, amount: f64) {
+        record_movement(
+            conn,
+            &NewStoredValueMovement {
+                account_id,
+                sale_id: Some(sale_id),
+                cash_register_session_id: 1,
+                user_id: 1,
+                movement_type: StoredValueMovementType::Redeem,
+                amount: -amount,
+                payment_cash_mxn: 0.0,
+                payment_cash_usd: 0.0,
+                payment_transfer: 0.0,
+                exchange_rate: None,
+                notes: None,
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn gift_card_redemptions_cannot_exceed_the_balance() {
+        let conn = test_connection();
+        assert_eq!(
+            reserve_redemption(&conn, Some("gc-100"), None, 60.0).unwrap(),
+            (1, 40.0)
+        );
+        redeem(&conn, 1, 7, 60.0);
+        assert!(reserve_redemption(&conn, Some("GC-100"), None, 60.0).is_err());
+
+        reverse_sale_redemption(&conn, 7, 1).unwrap();
+        assert_eq!(
+            reserve_redemption(&conn, Some("GC-100"), None, 100.0).unwrap(),
+            (1, 0.0)
+        );
+    }
+
+    #[test]
+    fn store_credit_is_opened_once_per_customer() {
+        let conn = test_connection();
+        assert!(reserve_redemption(&conn, None, Some(3), 10.0).is_err());
+        let account_id = store_credit_account(&conn, 3).unwrap();
+        assert_eq!(store_credit_account(&conn, 3).unwrap(), account_id);
+        record_movement(
+            &conn,
+            &NewStoredValueMovement {
+                account_id,
+                sale_id: Some(9),
+                cash_register_session_id: 1,
+                user_id: 1,
+                movement_type: StoredValueMovementType::Refund,
+                amount: 25.0,
+                payment_cash_mxn: 25.0,
+                payment_cash_usd: 0.0,
+                payment_transfer: 0.0,
+                exchange_rate: None,
+                notes: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            reserve_redemption(&conn, None, Some(3), 10.0).unwrap(),
+            (account_id, 15.0)
+        );
+        conn.execute(
+            "UPDATE stored_value_accounts SET active = 0 WHERE id = ?1",
+            [account_id],
+        )
+        .unwrap();
+        match reserve_redemption(&conn, None, Some(3), 10.0) {
+            Err(AppError::Conflict(message)) => {
+                assert_eq!(message, "El saldo a favor está desactivado")
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::models::cash_register::SessionStatus;
+use crate::models::stored_value::{IssueGiftCardRequest, StoredValueAccount, StoredValueMovement};
+use crate::modules::cash_register::application::CashRegisterSessionLookup;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::money;
+
+pub trait StoredValueRepository {
+    fn find_by_code(&self, code: &str) -> AppResult<Option<StoredValueAccount>>;
+    fn find_store_credit(&self, customer_id: i64) -> AppResult<Option<StoredValueAccount>>;
+    fn find_movements(&self, account_id: i64) -> AppResult<Vec<StoredValueMovement>>;
+    fn issue_gift_card(
+        &self,
+        request: &IssueGiftCardRequest,
+        code: &str,
+        exchange_rate: Option<f64>,
+        notes: Option<&str>,
+    ) -> AppResult<StoredValueAccount>;
+}
+
+/// Trims a gift card code typed or scanned at the counter; blank codes are `None`.
+pub fn normalize_code(code: &str) -> Option<String> {
+    let code = code.trim();
+    (!code.is_empty()).then(|| code.to_uppercase())
+}
+
+pub fn find_gift_card(
+    repository: &impl StoredValueRepository,
+    code: &str,
+) -> AppResult<StoredValueAccount> {
+    let code = normalize_code(code).ok_or_else(|| {
+        AppError::Validation("El código de la tarjeta es obligatorio".to_string())
+    })?;
+    repository
+        .find_by_code(&code)?
+        .ok_or_else(|| AppError::NotFound("Tarjeta de regalo no encontrada".to_string()))
+}
+
+pub fn get_customer_store_credit(
+    repository: &impl StoredValueRepository,
+    customer_id: i64,
+) -> AppResult<Option<StoredValueAccount>> {
+    repository.find_store_credit(customer_id)
+}
+
+pub fn get_stored_value_movements(
+    repository: &impl StoredValueRepository,
+    account_id: i64,
+) -> AppResult<Vec<StoredValueMovement>> {
+    repository.find_movements(account_id)
+}
+
+pub fn issue_gift_card(
+    repository: &impl StoredValueRepository,
+    sessions: &impl CashRegisterSessionLookup,
+    request: IssueGiftCardRequest,
+) -> AppResult<StoredValueAccount> {
+    let code = normalize_code(&request.code).ok_or_else(|| {
+        AppError::Validation("El código de la tarjeta es obligatorio".to_string())
+    })?;
+    let session = sessions
+        .find_by_id(request.cash_register_session_id)?
+        .ok_or_else(|| AppError::NotFound("Sesión de caja no encontrada".to_string()))?;
+    if session.status != SessionStatus::Open {
+        return Err(AppError::Conflict(
+            "La sesión de caja no está abierta".to_string(),
+        ));
+    }
+    if request.payment_cash_mxn < 0.0
+        || request.payment_cash_usd < 0.0
+        || request.payment_transfer < 0.0
+    {
+        return Err(AppError::Validation(
+            "Los montos de pago no pueden ser negativos".to_string(),
+        ));
+    }
+    if request.payment_cash_usd > 0.0 && session.exchange_rate.is_none() {
+        return Err(AppError::Validation(
+            "No se puede recibir USD sin tipo de cambio configurado en la caja".to_string(),
+        ));
+    }
+    let paid = money::total_paid_mxn(
+        request.payment_cash_mxn,
+        request.payment_cash_usd,
+        request.payment_transfer,
+        session.exchange_rate.unwrap_or(1.0),
+    );
+    if paid <= 0.0 {
+        return Err(AppError::Validation(
+            "El saldo de la tarjeta debe ser mayor que cero".to_string(),
+        ));
+    }
+    let notes = request
+        .notes
+        .as_deref()
+        .filter(|value| !value.trim().is_empty());
+    repository.issue_gift_card(&request, &code, session.exchange_rate, notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{issue_gift_card, normalize_code, StoredValueRepository};
+    use crate::models::cash_register::{CashRegisterSession, SessionStatus};
+    use crate::models::stored_value::{
+        IssueGiftCardRequest, StoredValueAccount, StoredValueKind, StoredValueMovement,
+    };
+    use crate::modules::cash_register::application::CashRegisterSessionLookup;
+    use crate::shared::error::{AppError, AppResult};
+
+    struct FakeRepository;
+
+    impl StoredValueRepository for FakeRepository {
+        fn find_by_code(&self, _code: &str) -> AppResult<Option<StoredValueAccount>> {
+            Ok(None)
+        }
+        fn find_store_credit(&self, _customer_id: i64) -> AppResult<Option<StoredValueAccount>> {
+            Ok(None)
+        }
+        fn find_movements(&self, _account_id: i64) -> AppResult<Vec<StoredValueMovement>> {
+            Ok(Vec::new())
+        }
+        fn issue_gift_card(
+            &self,
+            request: &IssueGiftCardRequest,
+            code: &str,
+            _exchange_rate: Option<f64>,
+            _notes: Option<&str>,
+        ) -> AppResult<StoredValueAccount> {
+            Ok(StoredValueAccount {
+                id: 1,
+                kind: StoredValueKind::GiftCard,
+                code: Some(code.to_string()),
+                customer_id: request.customer_id,
+                customer_name: None,
+                balance: request.payment_cash_mxn,
+                active: true,
+                created_at: "2026-01-01".to_string(),
+                updated_at: "2026-01-01".to_string(),
+            })
+        }
+    }
+
+    struct FakeSessions(SessionStatus);
+
+    impl CashRegisterSessionLookup for FakeSessions {
+        fn find_by_id(&self, id: i64) -> AppResult<Option<CashRegisterSession>> {
+            Ok(Some(CashRegisterSession {
+                id,
+                register_id: 1,
+                register_name: None,
+                user_id: 1,
+                user_name: None,
+                opening_amount: 0.0,
+                opening_cash_usd: 0.0,
+                closing_amount: None,
+                closing_cash_mxn: None,
+                closing_cash_usd: None,
+                exchange_rate: None,
+                status: self.0.clone(),
+                opened_at: "2026-01-01".to_string(),
+                closed_at: None,
+                review: None,
+                count_pending: false,
+                total_sales: None,
+                total_transactions: None,
+            }))
+        }
+    }
+
+    fn request(code: &str, cash: f64) -> IssueGiftCardRequest {
+        IssueGiftCardRequest {
+            code: code.to_string(),
+            customer_id: None,
+            cash_register_session_id: 1,
+            user_id: 1,
+            payment_cash_mxn: cash,
+            payment_cash_usd: 0.0,
+            payment_transfer: 0.0,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn normalizes_scanned_codes() {
+        assert_eq!(normalize_code("  gc-001 ").as_deref(), Some("GC-001"));
+        assert_eq!(normalize_code("   "), None);
+    }
+
+    #[test]
+    fn issues_cards_only_with_a_positive_payment_in_an_open_session() {
+        let open = FakeSessions(SessionStatus::Open);
+        let card = issue_gift_card(&FakeRepository, &open, request(" gc-1 ", 500.0)).unwrap();
+        assert_eq!(card.code.as_deref(), Some("GC-1"));
+        assert!(matches!(
+            issue_gift_card(&FakeRepository, &open, request("GC-2", 0.0)),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            issue_gift_card(
+                &FakeRepository,
+                &FakeSessions(SessionStatus::Closed),
+                request("GC-3", 100.0)
+            ),
+            Err(AppError::Conflict(_))
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::models::cash_register::{CashCountLine, CashHandover, CashMovement, CashMovementType};
+use crate::models::cash_register_report::{CashRegisterReport, CashReportType};
 use crate::models::sale::Sale;
 use crate::shared::money;
 
@@ -47,6 +49,25 @@ pub fn build_sale_ticket(
         }
         footer_lines.push(format!("Adeudo pendiente: ${:.2}", sale.credit_amount));
     }
+    if sale.payment_points > 0.0 {
+        footer_lines.push(format!(
+            "Puntos canjeados: {} (${:.2})",
+            sale.payment_points, sale.payment_points_mxn
+        ));
+    }
+    if sale.points_earned > 0.0 {
+        footer_lines.push(format!("Puntos ganados: {}", sale.points_earned));
+    }
+    if sale.payment_stored_value > 0.0 {
+        let instrument = match sale.stored_value_code.as_deref() {
+            Some(code) => format!("Tarjeta de regalo {}", mask_code(code)),
+            None => "Saldo a favor".to_string(),
+        };
+        footer_lines.push(format!("{}: ${:.2}", instrument, sale.payment_stored_value));
+        if let Some(balance) = sale.stored_value_balance {
+            footer_lines.push(format!("Saldo restante: ${:.2}", balance));
+        }
+    }
     if let Some(extra) = ticket_footer.filter(|value| !value.trim().is_empty()) {
         footer_lines.push(extra.trim().to_string());
     }
@@ -76,19 +97,311 @@ pub fn build_sale_ticket(
     }
 }
 
+/// Voucher for a pay-in, pay-out or safe drop; the amount goes in the totals block and
+/// the footer leaves room for the signature of whoever handed over the cash.
+pub fn build_cash_movement_voucher(
+    movement: &CashMovement,
+    business_name: Option<&str>,
+    ticket_header: Option<&str>,
+) -> TicketData {
+    let mut header_lines = Vec::new();
+    if let Some(name) = business_name.filter(|value| !value.trim().is_empty()) {
+        header_lines.push(name.trim().to_string());
+    }
+    if let Some(extra) = ticket_header.filter(|value| !value.trim().is_empty()) {
+        header_lines.push(extra.trim().to_string());
+    }
+    header_lines.push(format!(
+        "{} #{}",
+        cash_movement_label(movement.movement_type),
+        movement.id
+    ));
+    header_lines.push(format!(
+        "Caja: sesion #{}",
+        movement.cash_register_session_id
+    ));
+    header_lines.push(format!("Fecha: {}", movement.created_at));
+    if let Some(cashier) = movement
+        .user_name
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+    {
+        header_lines.push(format!("Cajero: {}", cashier.trim()));
+    }
+
+    let mut footer_lines = Vec::new();
+    if movement.amount_usd > 0.0 {
+        footer_lines.push(format!("Efectivo USD: ${:.2}", movement.amount_usd));
+    }
+    footer_lines.push(format!("Motivo: {}", movement.reason));
+    footer_lines.push(String::new());
+    footer_lines.push("Firma: ______________________".to_string());
+
+    TicketData {
+        items: Vec::new(),
+        total: movement.amount_mxn,
+        subtotal: movement.amount_mxn,
+        tax: 0.0,
+        barcode: None,
+        qr_code: None,
+        footer: Some(footer_lines.join("\n")),
+        header: Some(header_lines.join("\n")),
+    }
+}
+
+/// X/Z report laid out for the thermal printer; totals go in the totals block and the
+/// breakdowns in the footer.
+pub fn build_cash_register_report(
+    report: &CashRegisterReport,
+    business_name: Option<&str>,
+    ticket_header: Option<&str>,
+) -> TicketData {
+    let data = &report.data;
+    let summary = &data.summary;
+    let session = &summary.session;
+
+    let mut header_lines = Vec::new();
+    if let Some(name) = business_name.filter(|value| !value.trim().is_empty()) {
+        header_lines.push(name.trim().to_string());
+    }
+    if let Some(extra) = ticket_header.filter(|value| !value.trim().is_empty()) {
+        header_lines.push(extra.trim().to_string());
+    }
+    header_lines.push(match (report.report_type, report.z_number) {
+        (CashReportType::Z, Some(number)) => format!("REPORTE Z #{:06}", number),
+        _ => "REPORTE X (INFORMATIVO)".to_string(),
+    });
+    if let Some(register) = session.register_name.as_deref() {
+        header_lines.push(format!("Caja: {}", register));
+    }
+    header_lines.push(format!("Sesion #{}", session.id));
+    header_lines.push(format!("Apertura: {}", session.opened_at));
+    if let Some(closed_at) = &session.closed_at {
+        header_lines.push(format!("Cierre: {}", closed_at));
+    }
+    if let Some(cashier) = session.user_name.as_deref() {
+        header_lines.push(format!("Cajero: {}", cashier));
+    }
+    header_lines.push(format!("Generado: {}", report.created_at));
+
+    let mut footer_lines = vec![format!("Transacciones: {}", summary.total_transactions)];
+    footer_lines.push("-- FORMAS DE PAGO --".to_string());
+    for tender in &data.tenders {
+        footer_lines.push(format!(
+            "{}: ${:.2}",
+            payment_method_label(&tender.tender),
+            tender.amount
+        ));
+    }
+    if !data.categories.is_empty() {
+        footer_lines.push("-- CATEGORIAS --".to_string());
+        for category in &data.categories {
+            footer_lines.push(format!(
+                "{}: {} / ${:.2}",
+                category.category_name.as_deref().unwrap_or("Sin categoria"),
+                category.quantity,
+                category.total
+            ));
+        }
+    }
+    if !data.cashiers.is_empty() {
+        footer_lines.push("-- CAJEROS --".to_string());
+        for cashier in &data.cashiers {
+            footer_lines.push(format!(
+                "{}: {} ventas / ${:.2}",
+                cashier.user_name.as_deref().unwrap_or("Sin nombre"),
+                cashier.transactions,
+                cashier.total
+            ));
+        }
+    }
+    footer_lines.push(format!(
+        "Cancelaciones: {} (${:.2})",
+        data.cancellations.count, data.cancellations.total
+    ));
+    footer_lines.push(format!(
+        "Devoluciones: {} (${:.2})",
+        data.returns.count, data.returns.total
+    ));
+    footer_lines.push(format!("Descuentos: ${:.2}", data.discounts));
+
+    footer_lines.push("-- EFECTIVO --".to_string());
+    footer_lines.push(format!("Fondo inicial: ${:.2}", session.opening_amount));
+    footer_lines.push(format!(
+        "Entradas: ${:.2} / USD ${:.2}",
+        summary.pay_ins_mxn, summary.pay_ins_usd
+    ));
+    footer_lines.push(format!(
+        "Salidas: ${:.2} / USD ${:.2}",
+        summary.pay_outs_mxn, summary.pay_outs_usd
+    ));
+    footer_lines.push(format!(
+        "Retiros a caja fuerte: ${:.2} / USD ${:.2}",
+        summary.safe_drops_mxn, summary.safe_drops_usd
+    ));
+    footer_lines.push(format!(
+        "Cambio entregado: ${:.2}",
+        summary.total_change_given
+    ));
+    if !summary.expectations_hidden {
+        footer_lines.push(format!(
+            "Esperado: ${:.2} / USD ${:.2}",
+            summary.expected_cash_mxn, summary.expected_cash_usd
+        ));
+    }
+    if session.closed_at.is_some() {
+        footer_lines.push(format!(
+            "Contado: ${:.2} / USD ${:.2}",
+            summary.actual_cash_mxn, summary.actual_cash_usd
+        ));
+        footer_lines.push(format!(
+            "Diferencia: ${:.2} / USD ${:.2}",
+            summary.difference_mxn, summary.difference_usd
+        ));
+    }
+    if !summary.closing_count.is_empty() {
+        footer_lines.push("-- ARQUEO --".to_string());
+        footer_lines.extend(summary.closing_count.iter().map(count_line_label));
+    }
+
+    TicketData {
+        items: Vec::new(),
+        total: summary.total_sales,
+        subtotal: money::sub_money(summary.total_sales, data.tax),
+        tax: data.tax,
+        barcode: None,
+        qr_code: None,
+        footer: Some(footer_lines.join("\n")),
+        header: Some(header_lines.join("\n")),
+    }
+}
+
+/// Shift handover slip signed by both cashiers; the outgoing shift's sales are the total.
+pub fn build_cash_handover_report(
+    handover: &CashHandover,
+    business_name: Option<&str>,
+    ticket_header: Option<&str>,
+) -> TicketData {
+    let mut header_lines = Vec::new();
+    if let Some(name) = business_name.filter(|value| !value.trim().is_empty()) {
+        header_lines.push(name.trim().to_string());
+    }
+    if let Some(extra) = ticket_header.filter(|value| !value.trim().is_empty()) {
+        header_lines.push(extra.trim().to_string());
+    }
+    header_lines.push(format!("ENTREGA DE CAJA #{}", handover.id));
+    if let Some(register) = handover.register_name.as_deref() {
+        header_lines.push(format!("Caja: {}", register));
+    }
+    header_lines.push(format!("Sesion: #{}", handover.cash_register_session_id));
+    header_lines.push(format!(
+        "Turno: {} a {}",
+        handover.started_at, handover.created_at
+    ));
+    header_lines.push(format!(
+        "Entrega: {}",
+        handover.from_user_name.as_deref().unwrap_or("-")
+    ));
+    header_lines.push(format!(
+        "Recibe: {}",
+        handover.to_user_name.as_deref().unwrap_or("-")
+    ));
+
+    let mut footer_lines = vec![format!("Ventas del turno: {}", handover.sales_count)];
+    if !handover.expectations_hidden {
+        footer_lines.push(format!(
+            "Esperado: ${:.2} / USD ${:.2}",
+            handover.expected_cash_mxn, handover.expected_cash_usd
+        ));
+    }
+    footer_lines.push(format!(
+        "Contado: ${:.2} / USD ${:.2}",
+        handover.counted_cash_mxn, handover.counted_cash_usd
+    ));
+    if !handover.expectations_hidden {
+        footer_lines.push(format!(
+            "Diferencia: ${:.2} / USD ${:.2}",
+            handover.difference_mxn, handover.difference_usd
+        ));
+    }
+    if !handover.count.is_empty() {
+        footer_lines.push("-- ARQUEO --".to_string());
+        footer_lines.extend(handover.count.iter().map(count_line_label));
+    }
+    if let Some(notes) = handover.notes.as_deref() {
+        footer_lines.push(format!("Notas: {}", notes));
+    }
+    footer_lines.push(String::new());
+    footer_lines.push("Entrega: ______________________".to_string());
+    footer_lines.push(String::new());
+    footer_lines.push("Recibe: _______________________".to_string());
+
+    TicketData {
+        items: Vec::new(),
+        total: handover.sales_total,
+        subtotal: handover.sales_total,
+        tax: 0.0,
+        barcode: None,
+        qr_code: None,
+        footer: Some(footer_lines.join("\n")),
+        header: Some(header_lines.join("\n")),
+    }
+}
+
+fn count_line_label(line: &CashCountLine) -> String {
+    format!(
+        "{} {} x {} = ${:.2}",
+        line.currency.as_str(),
+        line.denomination,
+        line.quantity,
+        line.subtotal
+    )
+}
+
+fn cash_movement_label(movement_type: CashMovementType) -> &'static str {
+    match movement_type {
+        CashMovementType::PayIn => "ENTRADA DE EFECTIVO",
+        CashMovementType::PayOut => "SALIDA DE EFECTIVO",
+        CashMovementType::SafeDrop => "RETIRO A CAJA FUERTE",
+    }
+}
+
 fn payment_method_label(method: &str) -> &'static str {
     match method {
         "cash_mxn" => "Efectivo MXN",
         "cash_usd" => "Efectivo USD",
         "transfer" => "Transferencia",
+        "points" => "Puntos",
+        "stored_value" => "Tarjeta de regalo / saldo a favor",
+        "credit" => "Credito",
         "mixed" => "Mixto",
         _ => "Otro",
     }
 }
 
+/// Prints only the last four characters so a ticket cannot be used to spend the card.
+fn mask_code(code: &str) -> String {
+    let visible: String = code
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("****{}", visible)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::build_sale_ticket;
+    use super::{build_cash_movement_voucher, build_cash_register_report, build_sale_ticket};
+    use crate::models::cash_register::{
+        CashCountLine, CashCurrency, CashMovement, CashMovementType, CashRegisterSession,
+        CashRegisterSummary, SessionStatus,
+    };
+    use crate::models::cash_register_report::{
+        CashRegisterReport, CashRegisterReportData, CashReportType, ReportTally, TenderTotal,
+    };
     use crate::models::sale::{Sale, SaleInputMode, SaleItem, SaleStatus};
 
     #[test]
@@ -96,6 +409,7 @@ mod tests {
         let sale = Sale {
             id: 1,
             cash_register_session_id: 1,
+            register_id: Some(1),
             user_id: 1,
             user_name: Some("Cajero".to_string()),
             total: 20.0,
@@ -107,6 +421,13 @@ mod tests {
             payment_cash_mxn: 20.0,
             payment_cash_usd: 0.0,
             payment_transfer: 0.0,
+            payment_points: 0.0,
+            payment_points_mxn: 0.0,
+            points_earned: 0.0,
+            payment_stored_value: 0.0,
+            stored_value_account_id: None,
+            stored_value_code: None,
+            stored_value_balance: None,
             exchange_rate: None,
             change_amount: 0.0,
             status: SaleStatus::Completed,
@@ -122,7 +443,9 @@ mod tests {
                 input_value: Some(200.0),
                 input_unit: Some("g".to_string()),
                 unit_price: 100.0,
+                price_list_id: None,
                 subtotal: 20.0,
+                unit_cost: None,
             }],
         };
 
@@ -139,6 +462,7 @@ mod tests {
         let mut sale = Sale {
             id: 2,
             cash_register_session_id: 1,
+            register_id: Some(1),
             user_id: 1,
             user_name: None,
             total: 100.0,
@@ -150,6 +474,13 @@ mod tests {
             payment_cash_mxn: 60.0,
             payment_cash_usd: 0.0,
             payment_transfer: 0.0,
+            payment_points: 0.0,
+            payment_points_mxn: 0.0,
+            points_earned: 0.0,
+            payment_stored_value: 0.0,
+            stored_value_account_id: None,
+            stored_value_code: None,
+            stored_value_balance: None,
             exchange_rate: None,
             change_amount: 0.0,
             status: SaleStatus::Completed,
@@ -163,5 +494,137 @@ mod tests {
             .footer
             .unwrap()
             .contains("Adeudo pendiente"));
+        sale.payment_points = 30.0;
+        sale.payment_points_mxn = 3.0;
+        sale.points_earned = 6.0;
+        let footer = build_sale_ticket(&sale, None, None, None, None)
+            .footer
+            .unwrap();
+        assert!(footer.contains("Puntos canjeados: 30 ($3.00)"));
+        assert!(footer.contains("Puntos ganados: 6"));
+
+        sale.payment_stored_value = 25.0;
+        sale.stored_value_code = Some("GC-2026-0042".to_string());
+        sale.stored_value_balance = Some(75.0);
+        let footer = build_sale_ticket(&sale, None, None, None, None)
+            .footer
+            .unwrap();
+        assert!(footer.contains("Tarjeta de regalo ****0042: $25.00"));
+        assert!(footer.contains("Saldo restante: $75.00"));
+        assert!(!footer.contains("GC-2026"));
+    }
+
+    #[test]
+    fn prints_cash_movement_vouchers_with_reason_and_signature() {
+        let movement = CashMovement {
+            id: 7,
+            cash_register_session_id: 3,
+            user_id: 1,
+            user_name: Some("Cajero".to_string()),
+            movement_type: CashMovementType::SafeDrop,
+            amount_mxn: 1500.0,
+            amount_usd: 20.0,
+            reason: "Exceso de efectivo".to_string(),
+            created_at: "2026-01-01".to_string(),
+        };
+        let voucher = build_cash_movement_voucher(&movement, Some("Tienda"), None);
+        assert!(voucher.items.is_empty());
+        assert_eq!(voucher.total, 1500.0);
+        assert!(voucher.header.unwrap().contains("RETIRO A CAJA FUERTE #7"));
+        let footer = voucher.footer.unwrap();
+        assert!(footer.contains("Efectivo USD: $20.00"));
+        assert!(footer.contains("Motivo: Exceso de efectivo"));
+        assert!(footer.contains("Firma:"));
+    }
+
+    #[test]
+    fn prints_z_reports_with_number_and_drawer_count() {
+        let session = CashRegisterSession {
+            id: 4,
+            register_id: 1,
+            register_name: Some("Caja 1".to_string()),
+            user_id: 1,
+            user_name: Some("Cajero".to_string()),
+            opening_amount: 500.0,
+            opening_cash_usd: 0.0,
+            closing_amount: Some(900.0),
+            closing_cash_mxn: Some(900.0),
+            closing_cash_usd: Some(0.0),
+            exchange_rate: None,
+            status: SessionStatus::Closed,
+            opened_at: "2026-01-01 08:00:00".to_string(),
+            closed_at: Some("2026-01-01 20:00:00".to_string()),
+            review: None,
+            count_pending: false,
+            total_sales: None,
+            total_transactions: None,
+        };
+        let summary = CashRegisterSummary {
+            session,
+            total_sales: 400.0,
+            total_transactions: 3,
+            sales_cash_mxn: 400.0,
+            sales_cash_usd: 0.0,
+            sales_transfer: 0.0,
+            account_payments_cash_mxn: 0.0,
+            account_payments_cash_usd: 0.0,
+            account_payments_transfer: 0.0,
+            stored_value_cash_mxn: 0.0,
+            stored_value_cash_usd: 0.0,
+            stored_value_transfer: 0.0,
+            pay_ins_mxn: 0.0,
+            pay_ins_usd: 0.0,
+            pay_outs_mxn: 0.0,
+            pay_outs_usd: 0.0,
+            safe_drops_mxn: 0.0,
+            safe_drops_usd: 0.0,
+            total_change_given: 0.0,
+            expected_cash_mxn: 900.0,
+            expected_cash_usd: 0.0,
+            actual_cash_mxn: 900.0,
+            actual_cash_usd: 0.0,
+            difference_mxn: 0.0,
+            difference_usd: 0.0,
+            expectations_hidden: false,
+            opening_count: Vec::new(),
+            closing_count: vec![CashCountLine {
+                currency: CashCurrency::Mxn,
+                denomination: 500.0,
+                quantity: 1,
+                subtotal: 500.0,
+            }],
+        };
+        let report = CashRegisterReport {
+            id: 1,
+            cash_register_session_id: 4,
+            report_type: CashReportType::Z,
+            z_number: Some(12),
+            user_id: 1,
+            user_name: None,
+            created_at: "2026-01-01 20:05:00".to_string(),
+            data: CashRegisterReportData {
+                summary,
+                tenders: vec![TenderTotal {
+                    tender: "cash_mxn".to_string(),
+                    amount: 400.0,
+                }],
+                categories: Vec::new(),
+                cashiers: Vec::new(),
+                cancellations: ReportTally::default(),
+                returns: ReportTally::default(),
+                discounts: 0.0,
+                tax: 0.0,
+            },
+        };
+
+        let ticket = build_cash_register_report(&report, None, None);
+        let header = ticket.header.unwrap();
+        assert!(header.contains("REPORTE Z #000012"));
+        assert!(header.contains("Caja: Caja 1"));
+        assert_eq!(ticket.total, 400.0);
+        let footer = ticket.footer.unwrap();
+        assert!(footer.contains("Efectivo MXN: $400.00"));
+        assert!(footer.contains("Diferencia: $0.00"));
+        assert!(footer.contains("MXN 500 x 1 = $500.00"));
     }
 }